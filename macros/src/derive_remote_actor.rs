@@ -1,6 +1,6 @@
 use quote::{ToTokens, quote};
 use syn::{
-    DeriveInput, Expr, ExprAssign, ExprLit, Generics, Ident, Lit, LitStr,
+    DeriveInput, Generics, Ident, LitStr, Token,
     parse::{Parse, ParseStream},
     spanned::Spanned,
 };
@@ -26,6 +26,11 @@ impl ToTokens for DeriveRemoteActor {
                 ::std::concat!(::std::module_path!(), "::", ::std::stringify!(#ident))
             },
         };
+        let spawn_fn = if attrs.spawnable {
+            quote! { spawn }
+        } else {
+            quote! { spawn_unsupported }
+        };
 
         tokens.extend(quote! {
             #[automatically_derived]
@@ -84,6 +89,23 @@ impl ToTokens for DeriveRemoteActor {
                                     stop_reason,
                                 ))
                             }) as ::kameo::remote::_internal::RemoteSignalLinkDiedFn,
+                        spawn: (
+                            |
+                              args: ::std::vec::Vec<u8>,
+                              codec: ::kameo::remote::codec::CodecId,
+                              link: ::std::option::Option<(
+                                  ::kameo::actor::ActorId,
+                                  ::std::borrow::Cow<'static, str>,
+                              )>,
+                            | {
+                                ::std::boxed::Box::pin(::kameo::remote::_internal::#spawn_fn::<
+                                    #ident #ty_generics,
+                                >(
+                                    args,
+                                    codec,
+                                    link,
+                                ))
+                            }) as ::kameo::remote::_internal::RemoteSpawnFn,
                     },
                 );
             };
@@ -119,30 +141,37 @@ impl Parse for DeriveRemoteActor {
 #[derive(Default)]
 struct DeriveRemoteActorAttrs {
     id: Option<LitStr>,
+    spawnable: bool,
 }
 
 impl Parse for DeriveRemoteActorAttrs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let expr: ExprAssign = input
-            .parse()
-            .map_err(|_| syn::Error::new(input.span(), "expected id = \"...\" expression"))?;
-        let Expr::Path(left_path) = expr.left.as_ref() else {
-            return Err(syn::Error::new(expr.left.span(), "expected `id`"));
-        };
-        if !left_path.path.is_ident("id") {
-            return Err(syn::Error::new(expr.left.span(), "expected `id`"));
+        let mut attrs = DeriveRemoteActorAttrs::default();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key == "id" {
+                if attrs.id.is_some() {
+                    return Err(syn::Error::new(key.span(), "id already specified"));
+                }
+                input.parse::<Token![=]>()?;
+                attrs.id = Some(
+                    input
+                        .parse()
+                        .map_err(|err| syn::Error::new(err.span(), "expected a string literal"))?,
+                );
+            } else if key == "spawnable" {
+                if attrs.spawnable {
+                    return Err(syn::Error::new(key.span(), "spawnable already specified"));
+                }
+                attrs.spawnable = true;
+            } else {
+                return Err(syn::Error::new(key.span(), "expected `id` or `spawnable`"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
-        let Expr::Lit(ExprLit {
-            lit: Lit::Str(lit_str),
-            ..
-        }) = *expr.right
-        else {
-            return Err(syn::Error::new(
-                expr.right.span(),
-                "expected a string literal",
-            ));
-        };
 
-        Ok(DeriveRemoteActorAttrs { id: Some(lit_str) })
+        Ok(attrs)
     }
 }
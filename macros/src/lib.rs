@@ -175,6 +175,9 @@ pub fn derive_reply(input: TokenStream) -> TokenStream {
 ///
 /// The `#[remote_actor(id = "...")]` attribute can be specified to change the default remote actor ID.
 ///
+/// Actors can only be spawned by other peers with `remote::spawn` if they opt in with
+/// `#[remote_actor(spawnable)]`, which requires the actor's `Args` to implement `Deserialize`.
+///
 /// # Example
 ///
 /// ```ignore
@@ -184,6 +187,10 @@ pub fn derive_reply(input: TokenStream) -> TokenStream {
 /// struct MyActor { }
 ///
 /// assert_eq!(MyActor::REMOTE_ID, "my_crate::module::MyActor");
+///
+/// #[derive(Actor, RemoteActor, Serialize, Deserialize)]
+/// #[remote_actor(id = "worker", spawnable)]
+/// struct Worker { }
 /// ```
 #[proc_macro_derive(RemoteActor, attributes(remote_actor))]
 pub fn derive_remote_actor(input: TokenStream) -> TokenStream {
@@ -206,6 +213,34 @@ pub fn derive_remote_actor(input: TokenStream) -> TokenStream {
 ///     // implementation here
 /// }
 /// ```
+///
+/// A custom remote ID can be given as a string literal, and the codec used to encode the message
+/// can be pinned with `codec = ...`, overriding the codec configured for the swarm.
+///
+/// ```ignore
+/// #[remote_message("my_message", codec = kameo::remote::codec::Json)]
+/// impl Message<MyMessage> for MyActor {
+///     // implementation here
+/// }
+/// ```
+///
+/// Messages can be versioned with `version = ...`, which defaults to `1`. Payloads sent by older
+/// versions of the message are rejected, unless an upcaster is registered with `upcast(...)`,
+/// mapping an older version to a type which is decoded and converted with `From`.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct MyMessageV1 { }
+///
+/// impl From<MyMessageV1> for MyMessage {
+///     // conversion here
+/// }
+///
+/// #[remote_message("my_message", version = 2, upcast(1 = MyMessageV1))]
+/// impl Message<MyMessage> for MyActor {
+///     // implementation here
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn remote_message(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let remote_actor_attrs = parse_macro_input!(attrs as RemoteMessageAttrs);
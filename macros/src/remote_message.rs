@@ -1,8 +1,8 @@
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use syn::{
-    AngleBracketedGenericArguments, GenericArgument, Generics, ItemImpl, LitStr, PathArguments,
-    PathSegment, Token, Type,
+    AngleBracketedGenericArguments, GenericArgument, Generics, Ident, ItemImpl, LitInt, LitStr,
+    Path, PathArguments, PathSegment, Token, Type, parenthesized,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
@@ -10,17 +10,98 @@ use syn::{
 
 pub struct RemoteMessageAttrs {
     id: Option<LitStr>,
+    codec: Option<Path>,
+    version: Option<LitInt>,
+    upcasts: Vec<Upcast>,
+}
+
+struct Upcast {
+    version: LitInt,
+    ty: Type,
+}
+
+impl Parse for Upcast {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let version = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+        Ok(Upcast { version, ty })
+    }
 }
 
 impl Parse for RemoteMessageAttrs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.is_empty() {
-            Ok(RemoteMessageAttrs { id: None })
-        } else {
-            Ok(RemoteMessageAttrs {
-                id: Some(input.parse()?),
-            })
+        let mut attrs = RemoteMessageAttrs {
+            id: None,
+            codec: None,
+            version: None,
+            upcasts: Vec::new(),
+        };
+        if input.peek(LitStr) {
+            attrs.id = Some(input.parse()?);
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key == "codec" {
+                if attrs.codec.is_some() {
+                    return Err(syn::Error::new(key.span(), "codec already specified"));
+                }
+                input.parse::<Token![=]>()?;
+                attrs.codec = Some(input.parse()?);
+            } else if key == "version" {
+                if attrs.version.is_some() {
+                    return Err(syn::Error::new(key.span(), "version already specified"));
+                }
+                input.parse::<Token![=]>()?;
+                attrs.version = Some(input.parse()?);
+            } else if key == "upcast" {
+                if !attrs.upcasts.is_empty() {
+                    return Err(syn::Error::new(key.span(), "upcast already specified"));
+                }
+                let content;
+                parenthesized!(content in input);
+                attrs.upcasts = Punctuated::<Upcast, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .collect();
+            } else {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "expected `codec`, `version` or `upcast`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        let version = match &attrs.version {
+            Some(version) => {
+                let value: u32 = version.base10_parse()?;
+                if value == 0 {
+                    return Err(syn::Error::new(
+                        version.span(),
+                        "version must be greater than 0",
+                    ));
+                }
+                value
+            }
+            None => 1,
+        };
+        for upcast in &attrs.upcasts {
+            let upcast_version: u32 = upcast.version.base10_parse()?;
+            if upcast_version == 0 || upcast_version >= version {
+                return Err(syn::Error::new(
+                    upcast.version.span(),
+                    format!("upcast version must be between 1 and {}", version - 1),
+                ));
+            }
+        }
+
+        Ok(attrs)
     }
 }
 
@@ -40,7 +121,51 @@ impl RemoteMessage {
             message_generics,
         } = self;
 
-        let RemoteMessageAttrs { id } = attrs;
+        let RemoteMessageAttrs {
+            id,
+            codec,
+            version,
+            upcasts,
+        } = attrs;
+        let codec = codec.map(|codec| {
+            quote! {
+                const CODEC: ::std::option::Option<::kameo::remote::codec::CodecId> =
+                    ::std::option::Option::Some(<#codec as ::kameo::remote::codec::Codec>::ID);
+            }
+        });
+        let version = version.map(|version| {
+            quote! {
+                const VERSION: u32 = #version;
+            }
+        });
+        let upcast = (!upcasts.is_empty()).then(|| {
+            let versions = upcasts.iter().map(|upcast| &upcast.version);
+            let arms = upcasts.iter().map(|Upcast { version, ty }| {
+                quote! {
+                    #version => ::std::option::Option::Some(
+                        codec
+                            .decode::<#ty>(payload)
+                            .map(<#message_generics as ::std::convert::From<#ty>>::from),
+                    ),
+                }
+            });
+            quote! {
+                const UPCAST_VERSIONS: &'static [u32] = &[#( #versions ),*];
+
+                fn upcast(
+                    version: u32,
+                    payload: &[u8],
+                    codec: ::kameo::remote::codec::CodecId,
+                ) -> ::std::option::Option<
+                    ::std::result::Result<#message_generics, ::kameo::remote::codec::CodecError>,
+                > {
+                    match version {
+                        #( #arms )*
+                        _ => ::std::option::Option::None,
+                    }
+                }
+            }
+        });
         let id = id.map(|id| id.into_token_stream()).unwrap_or_else(|| {
             let actor_ty = actor_ty.to_token_stream().to_string().replace(' ', "");
             let message_generics = message_generics
@@ -78,6 +203,9 @@ impl RemoteMessage {
             #[automatically_derived]
             impl #impl_generics ::kameo::remote::RemoteMessage<#message_generics> for #actor_ty #ty_generics #where_clause {
                 const REMOTE_ID: &'static str = #id;
+                #codec
+                #version
+                #upcast
             }
 
             const _: () = {
@@ -94,8 +222,13 @@ impl RemoteMessage {
                         message_remote_id: <#actor_ty #ty_generics as ::kameo::remote::RemoteMessage<#message_generics>>::REMOTE_ID,
                     },
                     ::kameo::remote::_internal::RemoteMessageFns {
+                        version: <#actor_ty #ty_generics as ::kameo::remote::RemoteMessage<#message_generics>>::VERSION,
+                        upcast_versions: <#actor_ty #ty_generics as ::kameo::remote::RemoteMessage<#message_generics>>::UPCAST_VERSIONS,
                         ask: (|actor_id: ::kameo::actor::ActorId,
+                              sender: ::kameo::remote::RemoteSender,
                               msg: ::std::vec::Vec<u8>,
+                              codec: ::kameo::remote::codec::CodecId,
+                              version: u32,
                               mailbox_timeout: ::std::option::Option<::std::time::Duration>,
                               reply_timeout: ::std::option::Option<::std::time::Duration>| {
                                 ::std::boxed::Box::pin(::kameo::remote::_internal::ask::<
@@ -103,43 +236,64 @@ impl RemoteMessage {
                                     #message_generics,
                                 >(
                                     actor_id,
+                                    sender,
                                     msg,
+                                    codec,
+                                    version,
                                     mailbox_timeout,
                                     reply_timeout,
                                 ))
                             }) as ::kameo::remote::_internal::RemoteAskFn,
                         try_ask: (|actor_id: ::kameo::actor::ActorId,
+                              sender: ::kameo::remote::RemoteSender,
                               msg: ::std::vec::Vec<u8>,
+                              codec: ::kameo::remote::codec::CodecId,
+                              version: u32,
                               reply_timeout: ::std::option::Option<::std::time::Duration>| {
                                 ::std::boxed::Box::pin(::kameo::remote::_internal::try_ask::<
                                     #actor_ty,
                                     #message_generics,
                                 >(
                                     actor_id,
+                                    sender,
                                     msg,
+                                    codec,
+                                    version,
                                     reply_timeout,
                                 ))
                             }) as ::kameo::remote::_internal::RemoteTryAskFn,
                         tell: (|actor_id: ::kameo::actor::ActorId,
+                              sender: ::kameo::remote::RemoteSender,
                               msg: ::std::vec::Vec<u8>,
+                              codec: ::kameo::remote::codec::CodecId,
+                              version: u32,
                               mailbox_timeout: ::std::option::Option<::std::time::Duration>| {
                                 ::std::boxed::Box::pin(::kameo::remote::_internal::tell::<
                                     #actor_ty,
                                     #message_generics,
                                 >(
                                     actor_id,
+                                    sender,
                                     msg,
+                                    codec,
+                                    version,
                                     mailbox_timeout,
                                 ))
                             }) as ::kameo::remote::_internal::RemoteTellFn,
                         try_tell: (|actor_id: ::kameo::actor::ActorId,
-                              msg: ::std::vec::Vec<u8>| {
+                              sender: ::kameo::remote::RemoteSender,
+                              msg: ::std::vec::Vec<u8>,
+                              codec: ::kameo::remote::codec::CodecId,
+                              version: u32| {
                                 ::std::boxed::Box::pin(::kameo::remote::_internal::try_tell::<
                                     #actor_ty,
                                     #message_generics,
                                 >(
                                     actor_id,
+                                    sender,
                                     msg,
+                                    codec,
+                                    version,
                                 ))
                             }) as ::kameo::remote::_internal::RemoteTryTellFn,
                     },
@@ -24,6 +24,10 @@
 mod actor_ref;
 mod id;
 mod kind;
+#[cfg(feature = "remote")]
+mod remote_recipient;
+#[cfg(feature = "remote")]
+mod resilient_actor_ref;
 mod spawn;
 
 use std::{any, ops::ControlFlow};
@@ -39,6 +43,10 @@ use crate::{
 
 pub use actor_ref::*;
 pub use id::*;
+#[cfg(feature = "remote")]
+pub use remote_recipient::*;
+#[cfg(feature = "remote")]
+pub use resilient_actor_ref::*;
 pub use spawn::*;
 
 const DEFAULT_MAILBOX_CAPACITY: usize = 64;
@@ -262,6 +270,10 @@ pub trait Actor: Sized + Send + 'static {
                 ActorStopReason::PeerDisconnected => {
                     Ok(ControlFlow::Break(ActorStopReason::PeerDisconnected))
                 }
+                #[cfg(feature = "remote")]
+                ActorStopReason::PeerUnreachable => {
+                    Ok(ControlFlow::Break(ActorStopReason::PeerUnreachable))
+                }
             }
         }
     }
@@ -18,7 +18,7 @@ use tokio::{
 };
 
 #[cfg(feature = "remote")]
-use std::marker::PhantomData;
+use std::{collections::BTreeMap, marker::PhantomData};
 
 #[cfg(feature = "remote")]
 use crate::remote;
@@ -42,6 +42,12 @@ use super::id::ActorId;
 task_local! {
     pub(crate) static CURRENT_ACTOR_ID: ActorId;
 }
+/// Returns the ID of the actor whose task is currently running, if any.
+#[cfg(feature = "remote")]
+pub(crate) fn current_actor_id() -> Option<ActorId> {
+    CURRENT_ACTOR_ID.try_with(Clone::clone).ok()
+}
+
 thread_local! {
     pub(crate) static CURRENT_THREAD_ACTOR_ID: Cell<Option<ActorId>> = const { Cell::new(None) };
 }
@@ -123,7 +129,111 @@ where
     {
         remote::ActorSwarm::get()
             .ok_or(error::RegistryError::SwarmNotBootstrapped)?
-            .register(self.clone(), name.into())
+            .register(self.clone(), name.into(), BTreeMap::new())
+            .await
+    }
+
+    /// Registers the actor under a given name within the actor swarm, with tags attached.
+    ///
+    /// Tags are arbitrary key/value pairs such as a region, version or role, which are
+    /// stored with the registration and returned by lookups. They can be used to choose
+    /// between multiple actors registered under the same name with
+    /// [`RemoteActorRef::lookup_where`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[derive(kameo::Actor, kameo::RemoteActor)]
+    /// # struct MyActor;
+    /// #
+    /// # tokio_test::block_on(async {
+    /// use kameo::actor::Spawn;
+    ///
+    /// let actor_ref = MyActor::spawn(MyActor);
+    /// actor_ref
+    ///     .register_with_tags("my-service", [("region", "eu-west"), ("version", "2")])
+    ///     .await?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    #[cfg(feature = "remote")]
+    pub async fn register_with_tags<K, V>(
+        &self,
+        name: impl Into<Arc<str>>,
+        tags: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<(), error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let tags = tags
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        remote::ActorSwarm::get()
+            .ok_or(error::RegistryError::SwarmNotBootstrapped)?
+            .register(self.clone(), name.into(), tags)
+            .await
+    }
+
+    /// Registers the actor under a given name within a specific actor swarm.
+    ///
+    /// This behaves like [`ActorRef::register`], but registers the actor in the swarm of the
+    /// handle instead of the global actor swarm. An actor can be registered in multiple swarms.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[derive(kameo::Actor, kameo::RemoteActor)]
+    /// # struct MyActor;
+    /// #
+    /// # tokio_test::block_on(async {
+    /// use kameo::{actor::Spawn, remote};
+    ///
+    /// let node = remote::Bootstrap::new().global(false).start()?;
+    /// let actor_ref = MyActor::spawn(MyActor);
+    /// actor_ref.register_in(&node.swarm(), "my_actor").await?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    #[cfg(feature = "remote")]
+    pub async fn register_in(
+        &self,
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+    ) -> Result<(), error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        swarm
+            .actor_swarm()
+            .register(self.clone(), name.into(), BTreeMap::new())
+            .await
+    }
+
+    /// Registers the actor under a given name within a specific actor swarm, with tags attached.
+    ///
+    /// See [`ActorRef::register_with_tags`] and [`ActorRef::register_in`].
+    #[cfg(feature = "remote")]
+    pub async fn register_with_tags_in<K, V>(
+        &self,
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+        tags: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<(), error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let tags = tags
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        swarm
+            .actor_swarm()
+            .register(self.clone(), name.into(), tags)
             .await
     }
 
@@ -153,6 +263,20 @@ where
             .await
     }
 
+    /// Looks up an actor registered locally by its name within a specific actor swarm.
+    ///
+    /// See [`ActorRef::lookup`].
+    #[cfg(feature = "remote")]
+    pub async fn lookup_in(
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+    ) -> Result<Option<Self>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        swarm.actor_swarm().lookup_local(name.into()).await
+    }
+
     /// Creates a message-specific recipient for this actor.
     ///
     /// This allows creating a more specific reference that hides the concrete
@@ -769,18 +893,16 @@ where
 
         remote::REMOTE_REGISTRY
             .lock()
-            .await
+            .unwrap()
             .entry(self.id)
             .or_insert_with(|| remote::RemoteRegistryActorRef::new(self.clone(), None));
 
+        let swarm = sibbling_ref.swarm_tx.swarm();
         self.links.lock().await.insert(
             sibbling_ref.id,
-            Link::Remote(std::borrow::Cow::Borrowed(B::REMOTE_ID)),
+            Link::Remote(std::borrow::Cow::Borrowed(B::REMOTE_ID), swarm.clone()),
         );
-        remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
-            .link::<A, B>(self.id, sibbling_ref.id)
-            .await
+        swarm.link::<A, B>(self.id, sibbling_ref.id).await
     }
 
     /// Unlinks two previously linked sibling actors.
@@ -909,8 +1031,9 @@ where
         }
 
         self.links.lock().await.remove(&sibbling_ref.id);
-        remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
+        sibbling_ref
+            .swarm_tx
+            .swarm()
             .unlink::<B>(self.id, sibbling_ref.id)
             .await
     }
@@ -1023,18 +1146,31 @@ where
 
         remote::REMOTE_REGISTRY
             .lock()
-            .await
+            .unwrap()
             .entry(self.id())
             .or_insert_with(|| remote::RemoteRegistryActorRef::new_weak(self.downgrade(), None));
 
         remote_ref
     }
 
+    /// Weakly registers the actor to receive remote messages.
+    ///
+    /// The registry is only ever locked briefly, so this is called synchronously when
+    /// serializing, and the actor is registered before the serialized message is sent.
+    #[cfg(feature = "remote")]
+    fn register_remote_weak(&self) {
+        remote::REMOTE_REGISTRY
+            .lock()
+            .unwrap()
+            .entry(self.id)
+            .or_insert_with(|| remote::RemoteRegistryActorRef::new_weak(self.downgrade(), None));
+    }
+
     /// Blocking version of `into_remote_ref` for use in synchronous contexts.
     ///
     /// # Panics
     ///
-    /// This function panics if called within an asynchronous execution context.
+    /// This function panics if the actor swarm hasn't been bootstrapped.
     #[cfg(feature = "remote")]
     pub fn into_remote_ref_blocking(&self) -> RemoteActorRef<A>
     where
@@ -1046,7 +1182,8 @@ where
         );
 
         remote::REMOTE_REGISTRY
-            .blocking_lock()
+            .lock()
+            .unwrap()
             .entry(self.id())
             .or_insert_with(|| remote::RemoteRegistryActorRef::new_weak(self.downgrade(), None));
 
@@ -1114,6 +1251,30 @@ impl<A: Actor> Hash for ActorRef<A> {
     }
 }
 
+/// Serializes the actor ref in the same format as a [`RemoteActorRef`], so it can be embedded in
+/// a remote message and deserialized as a `RemoteActorRef<A>` bound to this peer.
+///
+/// The actor is weakly registered to receive remote messages while it's running, as with
+/// [`ActorRef::into_remote_ref`]. The actor is identified by the peer of the swarm the message is
+/// sent through, or the global actor swarm when serialized outside of a remote message.
+/// Serialization fails if there is no such swarm.
+#[cfg(feature = "remote")]
+impl<A: Actor + remote::RemoteActor> serde::Serialize for ActorRef<A> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let swarm_tx = remote::SwarmSender::current()
+            .ok_or_else(|| serde::ser::Error::custom("actor swarm not bootstrapped"))?;
+        if self.is_alive() {
+            self.register_remote_weak();
+        }
+
+        let id = self.id.with_local_peer_id(swarm_tx.local_peer_id());
+        RemoteActorRef::<A>::new(id, swarm_tx).serialize(serializer)
+    }
+}
+
 /// A type-erased actor reference for bidirectional communication with a single message type.
 ///
 /// Supports both `tell` and `ask` operations, with response types determined by the
@@ -1451,6 +1612,7 @@ impl<M: Send + 'static> Hash for Recipient<M> {
 pub struct RemoteActorRef<A: Actor> {
     id: ActorId,
     swarm_tx: remote::SwarmSender,
+    tags: Option<Arc<BTreeMap<String, String>>>,
     phantom: PhantomData<fn(&mut A)>,
 }
 
@@ -1463,15 +1625,31 @@ where
         RemoteActorRef {
             id,
             swarm_tx,
+            tags: None,
             phantom: PhantomData,
         }
     }
 
+    pub(crate) fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        if !tags.is_empty() {
+            self.tags = Some(Arc::new(tags));
+        }
+        self
+    }
+
     /// Returns the unique identifier of the remote actor.
     pub fn id(&self) -> ActorId {
         self.id
     }
 
+    /// Returns the tags the actor was registered with.
+    ///
+    /// Tags are only available on actor refs returned by lookups, and are empty otherwise.
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        static EMPTY: BTreeMap<String, String> = BTreeMap::new();
+        self.tags.as_deref().unwrap_or(&EMPTY)
+    }
+
     /// Looks up a single actor registered by name across the distributed network.
     ///
     /// If multiple actors are registered under the same name, returns one of them.
@@ -1509,6 +1687,136 @@ where
         }
     }
 
+    /// Looks up a single actor registered by name within a specific actor swarm.
+    ///
+    /// This behaves like [`RemoteActorRef::lookup`], but looks up the actor in the swarm of the
+    /// handle instead of the global actor swarm. Messages sent to the returned actor ref are
+    /// sent through the same swarm.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kameo::{actor::RemoteActorRef, remote};
+    ///
+    /// # #[derive(kameo::Actor, kameo::RemoteActor)]
+    /// # struct MyActor;
+    /// #
+    /// # tokio_test::block_on(async {
+    /// let node = remote::Bootstrap::new().global(false).start()?;
+    /// let actor_ref = RemoteActorRef::<MyActor>::lookup_in(&node.swarm(), "my_actor").await?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn lookup_in(
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+    ) -> Result<Option<Self>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        swarm.actor_swarm().lookup(name.into()).await
+    }
+
+    /// Looks up all actors registered by name within a specific actor swarm.
+    ///
+    /// See [`RemoteActorRef::lookup_all`] and [`RemoteActorRef::lookup_in`].
+    pub fn lookup_all_in(
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+    ) -> remote::LookupStream<A>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        swarm.actor_swarm().lookup_all(name.into())
+    }
+
+    /// Looks up the first actor registered by name within a specific actor swarm whose tags
+    /// match a predicate.
+    ///
+    /// See [`RemoteActorRef::lookup_where`] and [`RemoteActorRef::lookup_in`].
+    pub async fn lookup_where_in(
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+        predicate: impl FnMut(&BTreeMap<String, String>) -> bool,
+    ) -> Result<Option<Self>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        swarm
+            .actor_swarm()
+            .lookup_where(name.into(), predicate)
+            .await
+    }
+
+    /// Looks up an actor registered by name, returning a reference which re-resolves the
+    /// name when the actor can no longer be reached.
+    ///
+    /// Unlike [`RemoteActorRef::lookup`], the returned reference survives the actor being
+    /// restarted or re-registered on another node. The name doesn't need to be registered
+    /// yet, as it's resolved again on the first send.
+    ///
+    /// See [`ResilientRemoteActorRef`](super::ResilientRemoteActorRef) for details.
+    pub async fn lookup_resilient(
+        name: impl Into<Arc<str>>,
+    ) -> Result<super::ResilientRemoteActorRef<A>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        let swarm =
+            remote::SwarmHandle::global().ok_or(error::RegistryError::SwarmNotBootstrapped)?;
+        super::ResilientRemoteActorRef::new(swarm, name.into()).await
+    }
+
+    /// Looks up an actor registered by name within a specific actor swarm, returning a reference
+    /// which re-resolves the name in the same swarm when the actor can no longer be reached.
+    ///
+    /// See [`RemoteActorRef::lookup_resilient`] and [`RemoteActorRef::lookup_in`].
+    pub async fn lookup_resilient_in(
+        swarm: &remote::SwarmHandle,
+        name: impl Into<Arc<str>>,
+    ) -> Result<super::ResilientRemoteActorRef<A>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        super::ResilientRemoteActorRef::new(swarm.clone(), name.into()).await
+    }
+
+    /// Looks up the first actor registered by name whose tags match a predicate.
+    ///
+    /// Tags are attached at registration with [`ActorRef::register_with_tags`], and
+    /// can be used to pick between replicas, such as one in the same region.
+    ///
+    /// Returns `None` if no actor with matching tags is found.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kameo::actor::RemoteActorRef;
+    ///
+    /// # #[derive(kameo::Actor, kameo::RemoteActor)]
+    /// # struct MyActor;
+    /// #
+    /// # tokio_test::block_on(async {
+    /// let actor_ref = RemoteActorRef::<MyActor>::lookup_where("my-service", |tags| {
+    ///     tags.get("region").map(String::as_str) == Some("eu-west")
+    /// })
+    /// .await?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn lookup_where(
+        name: impl Into<Arc<str>>,
+        predicate: impl FnMut(&BTreeMap<String, String>) -> bool,
+    ) -> Result<Option<Self>, error::RegistryError>
+    where
+        A: remote::RemoteActor + 'static,
+    {
+        remote::ActorSwarm::get()
+            .ok_or(error::RegistryError::SwarmNotBootstrapped)?
+            .lookup_where(name.into(), predicate)
+            .await
+    }
+
     /// Sends a message to the remote actor and waits for a reply.
     ///
     /// The `ask` pattern is used when a response is expected from the remote actor. This method
@@ -1638,12 +1946,9 @@ where
             return Ok(());
         }
 
-        let fut_a = remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
-            .link::<A, B>(self.id, sibbling_ref.id);
-        let fut_b = remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
-            .link::<B, A>(sibbling_ref.id, self.id);
+        let swarm = self.swarm_tx.swarm();
+        let fut_a = swarm.link::<A, B>(self.id, sibbling_ref.id);
+        let fut_b = swarm.link::<B, A>(sibbling_ref.id, self.id);
 
         tokio::try_join!(fut_a, fut_b)?;
 
@@ -1683,20 +1988,116 @@ where
             return Ok(());
         }
 
-        let fut_a = remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
-            .unlink::<B>(self.id, sibbling_ref.id);
-        let fut_b = remote::ActorSwarm::get()
-            .ok_or(error::RemoteSendError::SwarmNotBootstrapped)?
-            .unlink::<A>(sibbling_ref.id, self.id);
+        let swarm = self.swarm_tx.swarm();
+        let fut_a = swarm.unlink::<B>(self.id, sibbling_ref.id);
+        let fut_b = swarm.unlink::<A>(sibbling_ref.id, self.id);
 
         tokio::try_join!(fut_a, fut_b)?;
 
         Ok(())
     }
 
-    pub(crate) fn send_to_swarm(&self, msg: remote::SwarmCommand) {
-        self.swarm_tx.send(msg)
+    /// Attaches a stream of messages to the remote actor, forwarding each item in the stream.
+    ///
+    /// This is the remote counterpart of [`ActorRef::attach_stream`]. Rather than sending a tell
+    /// request per item, items are sent in batches over a dedicated streaming protocol with flow
+    /// control, so a slow remote actor applies backpressure to the stream. See the
+    /// [`streaming`](remote::streaming) module for more information.
+    ///
+    /// The stream will continue until it is completed or an item fails to be delivered, such as when
+    /// the remote actor is stopped. A `JoinHandle` is returned, which can be used to cancel the stream.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kameo::actor::RemoteActorRef;
+    /// use kameo::message::{Context, Message, StreamMessage};
+    ///
+    /// #[derive(kameo::Actor, kameo::RemoteActor)]
+    /// struct MyActor;
+    ///
+    /// #[kameo::remote_message("my_stream")]
+    /// impl Message<StreamMessage<u32, (), ()>> for MyActor {
+    ///     type Reply = ();
+    ///
+    ///     async fn handle(&mut self, msg: StreamMessage<u32, (), ()>, ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+    ///         if let StreamMessage::Next(num) = msg {
+    ///             println!("Received item: {num}");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let stream = futures::stream::iter(vec![17, 19, 24]);
+    ///
+    /// let remote_actor_ref = RemoteActorRef::<MyActor>::lookup("my_actor").await?.unwrap();
+    /// remote_actor_ref.attach_stream(stream, (), ()).await??;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn attach_stream<M, S, T, F>(
+        &self,
+        stream: S,
+        start_value: T,
+        finish_value: F,
+    ) -> JoinHandle<Result<S, error::RemoteSendError>>
+    where
+        A: Message<StreamMessage<M, T, F>> + remote::RemoteMessage<StreamMessage<M, T, F>>,
+        S: Stream<Item = M> + Send + Unpin + 'static,
+        M: serde::Serialize + Send + 'static,
+        T: serde::Serialize + Send + 'static,
+        F: serde::Serialize + Send + 'static,
+    {
+        let codec = self.message_codec::<StreamMessage<M, T, F>>();
+        let batch_size = self.swarm_tx.stream_config().batch_size();
+        let mut sender = remote::streaming::StreamSender::new(
+            self.swarm_tx.clone(),
+            self.id,
+            <A as remote::RemoteActor>::REMOTE_ID,
+            <A as remote::RemoteMessage<StreamMessage<M, T, F>>>::REMOTE_ID,
+            <A as remote::RemoteMessage<StreamMessage<M, T, F>>>::VERSION,
+            codec,
+        );
+        let swarm_tx = self.swarm_tx.clone();
+        let encode = move |msg: StreamMessage<M, T, F>| {
+            swarm_tx
+                .enter(|| codec.encode(&msg))
+                .map_err(|err| error::RemoteSendError::SerializeMessage(err.to_string()))
+        };
+
+        tokio::spawn(async move {
+            sender
+                .send(vec![encode(StreamMessage::Started(start_value))?], false)
+                .await?;
+
+            let mut chunks = stream.ready_chunks(batch_size);
+            while let Some(items) = chunks.next().await {
+                let payloads = items
+                    .into_iter()
+                    .map(|item| encode(StreamMessage::Next(item)))
+                    .collect::<Result<_, _>>()?;
+                sender.send(payloads, false).await?;
+            }
+
+            sender
+                .send(vec![encode(StreamMessage::Finished(finish_value))?], true)
+                .await?;
+            sender.flush().await?;
+
+            Ok(chunks.into_inner())
+        })
+    }
+
+    pub(crate) fn swarm_tx(&self) -> &remote::SwarmSender {
+        &self.swarm_tx
+    }
+
+    /// Returns the codec used to encode message `M` for this actor.
+    pub(crate) fn message_codec<M>(&self) -> remote::codec::CodecId
+    where
+        A: remote::RemoteMessage<M>,
+    {
+        <A as remote::RemoteMessage<M>>::CODEC.unwrap_or(self.swarm_tx.codec())
     }
 }
 
@@ -1706,6 +2107,7 @@ impl<A: Actor> Clone for RemoteActorRef<A> {
         RemoteActorRef {
             id: self.id,
             swarm_tx: self.swarm_tx.clone(),
+            tags: self.tags.clone(),
             phantom: PhantomData,
         }
     }
@@ -1799,12 +2201,13 @@ impl<'de, A: Actor> serde::Deserialize<'de> for RemoteActorRef<A> {
                     }
                 }
                 let id = id.ok_or_else(|| serde::de::Error::missing_field("id"))?;
-                let swarm = remote::ActorSwarm::get()
+                let swarm_tx = remote::SwarmSender::current()
                     .ok_or_else(|| serde::de::Error::custom("actor swarm not bootstrapped"))?;
 
                 Ok(RemoteActorRef {
                     id,
-                    swarm_tx: swarm.sender().clone(),
+                    swarm_tx,
+                    tags: None,
                     phantom: PhantomData,
                 })
             }
@@ -1816,12 +2219,13 @@ impl<'de, A: Actor> serde::Deserialize<'de> for RemoteActorRef<A> {
                 let id: Option<ActorId> = seq.next_element()?;
                 let id = id.ok_or_else(|| serde::de::Error::missing_field("id"))?;
 
-                let swarm = remote::ActorSwarm::get()
+                let swarm_tx = remote::SwarmSender::current()
                     .ok_or_else(|| serde::de::Error::custom("actor swarm not bootstrapped"))?;
 
                 Ok(RemoteActorRef {
                     id,
-                    swarm_tx: swarm.sender().clone(),
+                    swarm_tx,
+                    tags: None,
                     phantom: PhantomData,
                 })
             }
@@ -2127,10 +2531,12 @@ impl ops::Deref for Links {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum Link {
     Local(Box<dyn SignalMailbox>),
+    /// A link with a remote actor, notified through the swarm the link was made in.
     #[cfg(feature = "remote")]
-    Remote(std::borrow::Cow<'static, str>),
+    Remote(std::borrow::Cow<'static, str>, remote::ActorSwarm),
 }
 
 pub(crate) trait MessageHandler<M: Send + 'static>:
@@ -8,7 +8,7 @@ use std::{fmt, sync::atomic::AtomicUsize};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "remote")]
-use crate::remote::ActorSwarm;
+use crate::remote::{ActorSwarm, SwarmSender};
 
 static ACTOR_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
@@ -100,6 +100,28 @@ impl ActorId {
         self.peer_id.peer_id()
     }
 
+    /// Returns the ID with the local peer replaced by `local_peer_id`, leaving IDs of actors
+    /// on other peers unchanged.
+    ///
+    /// Local actors can be registered in swarms other than the global one, whose peer ID must be
+    /// used when the ID is shared with other peers.
+    #[cfg(feature = "remote")]
+    pub(crate) fn with_local_peer_id(self, local_peer_id: libp2p::PeerId) -> Self {
+        match self.peer_id {
+            PeerIdKind::Local => ActorId::new_with_peer_id(self.sequence_id, local_peer_id),
+            PeerIdKind::PeerId(_) => self,
+        }
+    }
+
+    /// Returns the ID of the local actor with the same sequence ID.
+    ///
+    /// Requests received by any swarm in the process are addressed to local actors, which are
+    /// stored in the remote registry under their local IDs.
+    #[cfg(feature = "remote")]
+    pub(crate) fn into_local(self) -> Self {
+        ActorId::new(self.sequence_id)
+    }
+
     /// Serializes the `ActorId` into a byte vector.
     ///
     /// The resulting vector contains the `sequence_id` followed by the `peer_id` (if present).
@@ -113,10 +135,13 @@ impl ActorId {
 
         #[cfg(feature = "remote")]
         {
-            let peer_id_bytes = self
-                .peer_id()
-                .map(|peer_id| peer_id.to_bytes())
-                .or_else(|| ActorSwarm::get().map(|swarm| swarm.local_peer_id().to_bytes()));
+            // Local actors are identified by the peer of the swarm the ID is being sent through
+            let peer_id_bytes = match self.peer_id {
+                PeerIdKind::Local => {
+                    SwarmSender::current().map(|swarm| swarm.local_peer_id().to_bytes())
+                }
+                PeerIdKind::PeerId(peer_id) => Some(peer_id.to_bytes()),
+            };
 
             if let Some(peer_id_bytes) = peer_id_bytes {
                 bytes.extend(peer_id_bytes);
@@ -276,7 +301,7 @@ enum PeerIdKind {
 impl PeerIdKind {
     fn peer_id(&self) -> Option<&libp2p::PeerId> {
         match self {
-            PeerIdKind::Local => ActorSwarm::get().map(ActorSwarm::local_peer_id),
+            PeerIdKind::Local => ActorSwarm::global_peer_id(),
             PeerIdKind::PeerId(peer_id) => Some(peer_id),
         }
     }
@@ -322,6 +347,9 @@ mod tests {
     #[cfg(feature = "remote")]
     use libp2p::PeerId;
 
+    #[cfg(feature = "remote")]
+    use crate::remote::{codec::CodecId, streaming};
+
     use super::*;
 
     #[cfg(feature = "remote")]
@@ -383,7 +411,13 @@ mod tests {
 
         // Bootstrapped
         let local_peer_id = local_peer_id();
-        let _ = ActorSwarm::set(mpsc::unbounded_channel().0, local_peer_id);
+        let _ = ActorSwarm::set(ActorSwarm::new(
+            mpsc::unbounded_channel().0,
+            local_peer_id,
+            CodecId::default(),
+            streaming::Config::default(),
+            None,
+        ));
         assert_eq!(id1.peer_id(), Some(&local_peer_id));
         assert_eq!(id2.peer_id(), Some(&local_peer_id));
 
@@ -486,7 +520,13 @@ mod tests {
 
         // Bootstrapped
         let local_peer_id = local_peer_id();
-        let _ = ActorSwarm::set(mpsc::unbounded_channel().0, local_peer_id);
+        let _ = ActorSwarm::set(ActorSwarm::new(
+            mpsc::unbounded_channel().0,
+            local_peer_id,
+            CodecId::default(),
+            streaming::Config::default(),
+            None,
+        ));
         assert_eq!(id1.peer_id(), Some(&local_peer_id));
         assert_eq!(id2.peer_id(), Some(&local_peer_id));
 
@@ -5,7 +5,7 @@ use futures::FutureExt;
 use crate::{
     actor::{Actor, ActorRef, WeakActorRef},
     error::{ActorStopReason, PanicError, PanicReason},
-    mailbox::{MailboxReceiver, Signal},
+    mailbox::{MailboxReceiver, MessageMetadata, Signal},
     message::BoxMessage,
     reply::BoxReplySender,
 };
@@ -46,8 +46,9 @@ where
                     actor_ref,
                     reply,
                     sent_within_actor,
+                    metadata,
                 } => {
-                    self.handle_message(message, actor_ref, reply, sent_within_actor)
+                    self.handle_message(message, actor_ref, reply, sent_within_actor, metadata)
                         .await?;
                 }
                 _ => unreachable!(),
@@ -63,6 +64,7 @@ where
         actor_ref: ActorRef<A>,
         reply: Option<BoxReplySender>,
         sent_within_actor: bool,
+        metadata: MessageMetadata,
     ) -> ControlFlow<ActorStopReason> {
         if !sent_within_actor && !self.finished_startup {
             // The actor is still starting up, so we'll push this message to a buffer to be processed upon startup
@@ -71,14 +73,17 @@ where
                 actor_ref,
                 reply,
                 sent_within_actor,
+                metadata,
             });
             return ControlFlow::Continue(());
         }
 
         let mut stop = false;
-        let res = AssertUnwindSafe(self.state.on_message(message, actor_ref, reply, &mut stop))
-            .catch_unwind()
-            .await;
+        let fut = self.state.on_message(message, actor_ref, reply, &mut stop);
+        #[cfg(feature = "remote")]
+        let fut =
+            crate::remote::REMOTE_SENDER.scope(metadata.remote_sender.map(|sender| *sender), fut);
+        let res = AssertUnwindSafe(fut).catch_unwind().await;
         match res {
             Ok(Ok(())) => {
                 if stop {
@@ -153,6 +158,10 @@ where
             ActorStopReason::PeerDisconnected => {
                 ControlFlow::Break(ActorStopReason::PeerDisconnected)
             }
+            #[cfg(feature = "remote")]
+            ActorStopReason::PeerUnreachable => {
+                ControlFlow::Break(ActorStopReason::PeerUnreachable)
+            }
         }
     }
 
@@ -0,0 +1,467 @@
+use std::{borrow::Cow, cmp, fmt, hash, marker::PhantomData};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    Actor, Reply,
+    error::{Infallible, RemoteSendError},
+    message::Message,
+    remote::{self, MessageTarget, RemoteActor, RemoteMessage, codec::CodecId},
+};
+
+use super::{ActorId, ActorRef, RemoteActorRef};
+
+/// A type-erased reference to a remote actor for sending a single message type.
+///
+/// This is the remote counterpart of [`Recipient`](super::Recipient). It hides the concrete
+/// actor type, and can be serialized and sent to other peers, such as to pass a "reply-to"
+/// address inside a remote message. Messages are delivered through the actor's
+/// [`RemoteActor`] and [`RemoteMessage`] registrations, so the receiving side doesn't need
+/// to know the actor type.
+///
+/// Created with [`RemoteActorRef::recipient`] or [`ActorRef::remote_recipient`].
+///
+/// # Example
+///
+/// ```no_run
+/// use kameo::actor::{RemoteActorRef, RemoteRecipient};
+///
+/// # #[derive(kameo::Actor, kameo::RemoteActor)]
+/// # struct MyActor;
+/// #
+/// # #[derive(serde::Serialize, serde::Deserialize)]
+/// # struct Notify;
+/// #
+/// # #[kameo::remote_message("notify")]
+/// # impl kameo::message::Message<Notify> for MyActor {
+/// #     type Reply = ();
+/// #     async fn handle(&mut self, msg: Notify, ctx: &mut kameo::message::Context<Self, Self::Reply>) -> Self::Reply { }
+/// # }
+/// #
+/// # tokio_test::block_on(async {
+/// let actor_ref = RemoteActorRef::<MyActor>::lookup("my_actor").await?.unwrap();
+/// let recipient: RemoteRecipient<Notify> = actor_ref.recipient();
+///
+/// recipient.tell_ack(&Notify).await?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub struct RemoteRecipient<M> {
+    target: Target,
+    phantom: PhantomData<fn(M)>,
+}
+
+/// A type-erased reference to a remote actor for sending a single message type and receiving
+/// its reply.
+///
+/// This is the remote counterpart of [`ReplyRecipient`](super::ReplyRecipient), supporting both
+/// `tell` and `ask`. Like [`RemoteRecipient`], it can be serialized and sent to other peers.
+///
+/// Created with [`RemoteActorRef::reply_recipient`] or [`ActorRef::remote_reply_recipient`].
+pub struct RemoteReplyRecipient<M, Ok, Err = Infallible> {
+    target: Target,
+    phantom: PhantomData<fn(M) -> Result<Ok, Err>>,
+}
+
+/// The registration a remote recipient delivers messages through.
+#[derive(Clone)]
+struct Target {
+    actor_id: ActorId,
+    actor_remote_id: Cow<'static, str>,
+    message_remote_id: Cow<'static, str>,
+    version: u32,
+    codec: CodecId,
+    swarm_tx: remote::SwarmSender,
+}
+
+/// The serialized form of a remote recipient.
+#[derive(Serialize, Deserialize)]
+struct TargetData<'a> {
+    actor_id: ActorId,
+    actor_remote_id: Cow<'a, str>,
+    message_remote_id: Cow<'a, str>,
+    version: u32,
+    codec: CodecId,
+}
+
+impl<A> RemoteActorRef<A>
+where
+    A: Actor + RemoteActor,
+{
+    /// Creates a message-specific recipient for this actor, hiding its concrete type.
+    ///
+    /// For bidirectional communication that supports `ask` requests,
+    /// see [`RemoteActorRef::reply_recipient`].
+    pub fn recipient<M>(self) -> RemoteRecipient<M>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: Serialize + Send + 'static,
+    {
+        RemoteRecipient {
+            target: Target::new::<A, M>(&self),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a message-specific recipient for this actor with bidirectional communication,
+    /// hiding its concrete type.
+    ///
+    /// The `Ok` and `Err` types are determined by the message's `Reply` implementation.
+    pub fn reply_recipient<M>(
+        self,
+    ) -> RemoteReplyRecipient<M, <A::Reply as Reply>::Ok, <A::Reply as Reply>::Error>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: Serialize + Send + 'static,
+    {
+        RemoteReplyRecipient {
+            target: Target::new::<A, M>(&self),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<A> ActorRef<A>
+where
+    A: Actor + RemoteActor,
+{
+    /// Creates a remote recipient for this actor, which can be sent to other peers.
+    ///
+    /// Like [`ActorRef::into_remote_ref`], the actor is registered so it can receive remote
+    /// messages while it's running.
+    pub async fn remote_recipient<M>(&self) -> RemoteRecipient<M>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: Serialize + Send + 'static,
+    {
+        self.into_remote_ref().await.recipient()
+    }
+
+    /// Creates a remote recipient for this actor with bidirectional communication, which can
+    /// be sent to other peers.
+    ///
+    /// Like [`ActorRef::into_remote_ref`], the actor is registered so it can receive remote
+    /// messages while it's running.
+    pub async fn remote_reply_recipient<M>(
+        &self,
+    ) -> RemoteReplyRecipient<M, <A::Reply as Reply>::Ok, <A::Reply as Reply>::Error>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: Serialize + Send + 'static,
+    {
+        self.into_remote_ref().await.reply_recipient()
+    }
+}
+
+impl<M: Serialize> RemoteRecipient<M> {
+    /// Returns the unique identifier of the remote actor.
+    pub fn id(&self) -> ActorId {
+        self.target.actor_id
+    }
+
+    /// Sends a message to the actor without waiting for delivery acknowledgment.
+    pub fn tell(&self, msg: &M) -> Result<(), RemoteSendError> {
+        self.target.message_target().tell(msg, None, false)
+    }
+
+    /// Sends a message to the actor and waits for delivery acknowledgment.
+    pub async fn tell_ack(&self, msg: &M) -> Result<(), RemoteSendError> {
+        self.target
+            .message_target()
+            .tell_ack(msg, None, false)
+            .await
+    }
+}
+
+impl<M, Ok, Err> RemoteReplyRecipient<M, Ok, Err>
+where
+    M: Serialize,
+    Ok: DeserializeOwned,
+    Err: DeserializeOwned,
+{
+    /// Returns the unique identifier of the remote actor.
+    pub fn id(&self) -> ActorId {
+        self.target.actor_id
+    }
+
+    /// Converts this reply recipient into a regular recipient, losing `ask` capability.
+    pub fn erase_reply(self) -> RemoteRecipient<M> {
+        RemoteRecipient {
+            target: self.target,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sends a message to the actor without waiting for delivery acknowledgment.
+    pub fn tell(&self, msg: &M) -> Result<(), RemoteSendError> {
+        self.target.message_target().tell(msg, None, false)
+    }
+
+    /// Sends a message to the actor and waits for delivery acknowledgment.
+    pub async fn tell_ack(&self, msg: &M) -> Result<(), RemoteSendError> {
+        self.target
+            .message_target()
+            .tell_ack(msg, None, false)
+            .await
+    }
+
+    /// Sends a message to the actor and waits for a reply.
+    pub async fn ask(&self, msg: &M) -> Result<Ok, RemoteSendError<Err>> {
+        self.target
+            .message_target()
+            .ask(msg, None, None, false)
+            .await
+    }
+}
+
+impl Target {
+    fn new<A, M>(actor_ref: &RemoteActorRef<A>) -> Self
+    where
+        A: Actor + RemoteActor + RemoteMessage<M>,
+    {
+        Target {
+            actor_id: actor_ref.id(),
+            actor_remote_id: Cow::Borrowed(<A as RemoteActor>::REMOTE_ID),
+            message_remote_id: Cow::Borrowed(<A as RemoteMessage<M>>::REMOTE_ID),
+            version: <A as RemoteMessage<M>>::VERSION,
+            codec: actor_ref.message_codec::<M>(),
+            swarm_tx: actor_ref.swarm_tx().clone(),
+        }
+    }
+
+    /// Returns the target messages are sent through.
+    fn message_target(&self) -> MessageTarget<'_> {
+        MessageTarget {
+            swarm_tx: &self.swarm_tx,
+            actor_id: self.actor_id,
+            actor_remote_id: self.actor_remote_id.clone(),
+            message_remote_id: self.message_remote_id.clone(),
+            version: self.version,
+            codec: self.codec,
+        }
+    }
+
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TargetData {
+            actor_id: self.actor_id,
+            actor_remote_id: Cow::Borrowed(&self.actor_remote_id),
+            message_remote_id: Cow::Borrowed(&self.message_remote_id),
+            version: self.version,
+            codec: self.codec,
+        }
+        .serialize(serializer)
+    }
+
+    fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = TargetData::deserialize(deserializer)?;
+        let swarm_tx = remote::SwarmSender::current()
+            .ok_or_else(|| serde::de::Error::custom("actor swarm not bootstrapped"))?;
+
+        Ok(Target {
+            actor_id: data.actor_id,
+            actor_remote_id: Cow::Owned(data.actor_remote_id.into_owned()),
+            message_remote_id: Cow::Owned(data.message_remote_id.into_owned()),
+            version: data.version,
+            codec: data.codec,
+            swarm_tx,
+        })
+    }
+}
+
+impl<M> Clone for RemoteRecipient<M> {
+    fn clone(&self) -> Self {
+        RemoteRecipient {
+            target: self.target.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M> fmt::Debug for RemoteRecipient<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteRecipient")
+            .field("id", &self.target.actor_id)
+            .field("message_remote_id", &self.target.message_remote_id)
+            .finish()
+    }
+}
+
+impl<M> PartialEq for RemoteRecipient<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.target.actor_id == other.target.actor_id
+    }
+}
+
+impl<M> Eq for RemoteRecipient<M> {}
+
+impl<M> PartialOrd for RemoteRecipient<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M> Ord for RemoteRecipient<M> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.target.actor_id.cmp(&other.target.actor_id)
+    }
+}
+
+impl<M> hash::Hash for RemoteRecipient<M> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.target.actor_id.hash(state);
+    }
+}
+
+impl<M> Serialize for RemoteRecipient<M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.target.serialize(serializer)
+    }
+}
+
+impl<'de, M> Deserialize<'de> for RemoteRecipient<M> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(RemoteRecipient {
+            target: Target::deserialize(deserializer)?,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<M, Ok, Err> Clone for RemoteReplyRecipient<M, Ok, Err> {
+    fn clone(&self) -> Self {
+        RemoteReplyRecipient {
+            target: self.target.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<M, Ok, Err> fmt::Debug for RemoteReplyRecipient<M, Ok, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteReplyRecipient")
+            .field("id", &self.target.actor_id)
+            .field("message_remote_id", &self.target.message_remote_id)
+            .finish()
+    }
+}
+
+impl<M, Ok, Err> PartialEq for RemoteReplyRecipient<M, Ok, Err> {
+    fn eq(&self, other: &Self) -> bool {
+        self.target.actor_id == other.target.actor_id
+    }
+}
+
+impl<M, Ok, Err> Eq for RemoteReplyRecipient<M, Ok, Err> {}
+
+impl<M, Ok, Err> PartialOrd for RemoteReplyRecipient<M, Ok, Err> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M, Ok, Err> Ord for RemoteReplyRecipient<M, Ok, Err> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.target.actor_id.cmp(&other.target.actor_id)
+    }
+}
+
+impl<M, Ok, Err> hash::Hash for RemoteReplyRecipient<M, Ok, Err> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.target.actor_id.hash(state);
+    }
+}
+
+impl<M, Ok, Err> Serialize for RemoteReplyRecipient<M, Ok, Err> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.target.serialize(serializer)
+    }
+}
+
+impl<'de, M, Ok, Err> Deserialize<'de> for RemoteReplyRecipient<M, Ok, Err> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(RemoteReplyRecipient {
+            target: Target::deserialize(deserializer)?,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, error};
+
+    use crate::{
+        actor::Spawn,
+        message::Context,
+        remote::testing::{
+            Cluster,
+            fixtures::{Get, Spawned},
+            register_remote_actor, register_remote_message,
+        },
+    };
+
+    use super::*;
+
+    struct Asker;
+
+    impl Actor for Asker {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Asker);
+
+    /// Asks a recipient on another node for its value.
+    #[derive(Serialize, Deserialize)]
+    struct AskVia(RemoteReplyRecipient<Get, u32>);
+
+    impl Message<AskVia> for Asker {
+        type Reply = Option<(u32, String)>;
+
+        async fn handle(
+            &mut self,
+            AskVia(recipient): AskVia,
+            _ctx: &mut Context<Self, Self::Reply>,
+        ) -> Option<(u32, String)> {
+            let value = recipient.ask(&Get).await.ok()?;
+            let peer_id = recipient.target.swarm_tx.local_peer_id();
+            Some((value, peer_id.to_string()))
+        }
+    }
+
+    register_remote_message!(Asker, AskVia);
+
+    #[tokio::test]
+    async fn reply_recipients_in_messages_are_bound_to_the_receiving_swarm()
+    -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let asker = Asker::spawn(Asker);
+        cluster.node(0).register(&asker, "asker").await?;
+        let remote_asker = cluster
+            .wait_for_lookup::<Asker>(1, "asker", 1)
+            .await?
+            .remove(0);
+
+        let spawned = Spawned::spawn(Spawned { value: 7 });
+        cluster.node(1).register(&spawned, "spawned").await?;
+        let recipient = cluster
+            .wait_for_lookup::<Spawned>(1, "spawned", 1)
+            .await?
+            .remove(0)
+            .reply_recipient::<Get>();
+        assert_eq!(recipient.id(), cluster.node(1).actor_id(&spawned));
+
+        let reply = remote_asker.ask(&AskVia(recipient)).await?;
+        assert_eq!(reply, Some((7, cluster.node(0).peer_id().to_string())));
+
+        Ok(())
+    }
+}
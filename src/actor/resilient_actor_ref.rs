@@ -0,0 +1,324 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::Future;
+
+use crate::{
+    Actor, Reply,
+    error::{RegistryError, RemoteSendError},
+    message::Message,
+    remote::{self, RetryPolicy, send_with_retry},
+};
+
+use super::{ActorId, RemoteActorRef};
+
+/// A reference to a remote actor registered by name, which re-resolves the name when its
+/// current target can no longer be reached.
+///
+/// A [`RemoteActorRef`] is pinned to a single [`ActorId`], so if the actor stops or the node
+/// hosting it restarts, the ref can no longer be used. A `ResilientRemoteActorRef` instead
+/// looks the name up again when a send fails with [`RemoteSendError::ActorNotRunning`],
+/// [`RemoteSendError::DialFailure`] or [`RemoteSendError::ConnectionClosed`], and retries
+/// the send against the newly registered actor.
+///
+/// Retried asks may be processed more than once, since a closed connection doesn't tell
+/// whether the original request was received.
+///
+/// Created with [`RemoteActorRef::lookup_resilient`] or [`RemoteActorRef::lookup_resilient_in`].
+/// Clones share the same target.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use kameo::actor::RemoteActorRef;
+///
+/// # #[derive(kameo::Actor, kameo::RemoteActor)]
+/// # struct MyActor;
+/// #
+/// # #[derive(serde::Serialize, serde::Deserialize)]
+/// # struct Msg;
+/// #
+/// # #[kameo::remote_message("id")]
+/// # impl kameo::message::Message<Msg> for MyActor {
+/// #     type Reply = ();
+/// #     async fn handle(&mut self, msg: Msg, ctx: &mut kameo::message::Context<Self, Self::Reply>) -> Self::Reply { }
+/// # }
+/// #
+/// # tokio_test::block_on(async {
+/// let actor_ref = RemoteActorRef::<MyActor>::lookup_resilient("my_actor")
+///     .await?
+///     .with_max_retries(5)
+///     .with_retry_interval(Duration::from_secs(1))
+///     .on_target_changed(|change| {
+///         println!("{} moved to {}", change.name, change.current);
+///     });
+///
+/// actor_ref.ask(&Msg).await?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub struct ResilientRemoteActorRef<A: Actor> {
+    swarm: remote::SwarmHandle,
+    name: Arc<str>,
+    max_retries: usize,
+    retry_interval: Duration,
+    target: Arc<Mutex<Target<A>>>,
+    on_target_changed: Option<TargetChangedHook>,
+}
+
+type TargetChangedHook = Arc<dyn Fn(&TargetChanged) + Send + Sync>;
+
+struct Target<A: Actor> {
+    actor_ref: Option<RemoteActorRef<A>>,
+    /// The last actor targeted, kept after it's cleared to report target changes.
+    actor_id: Option<ActorId>,
+}
+
+/// Describes a [`ResilientRemoteActorRef`] resolving its name to a different actor.
+#[derive(Clone, Debug)]
+pub struct TargetChanged {
+    /// The name the actor is registered under.
+    pub name: Arc<str>,
+    /// The actor previously targeted, if the name had been resolved before.
+    pub previous: Option<ActorId>,
+    /// The actor now targeted.
+    pub current: ActorId,
+}
+
+impl<A> ResilientRemoteActorRef<A>
+where
+    A: Actor + remote::RemoteActor,
+{
+    pub(crate) async fn new(
+        swarm: remote::SwarmHandle,
+        name: Arc<str>,
+    ) -> Result<Self, RegistryError> {
+        let actor_ref = RemoteActorRef::lookup_in(&swarm, name.clone()).await?;
+        let target = Target {
+            actor_id: actor_ref.as_ref().map(RemoteActorRef::id),
+            actor_ref,
+        };
+        Ok(ResilientRemoteActorRef {
+            swarm,
+            name,
+            max_retries: 3,
+            retry_interval: Duration::from_millis(500),
+            target: Arc::new(Mutex::new(target)),
+            on_target_changed: None,
+        })
+    }
+
+    /// Sets how many times a failed send is retried after re-resolving the name.
+    ///
+    /// Defaults to 3.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets how long to wait before re-resolving the name after a failed send.
+    ///
+    /// Defaults to 500 milliseconds.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Sets a hook called whenever the name resolves to a different actor.
+    pub fn on_target_changed(mut self, f: impl Fn(&TargetChanged) + Send + Sync + 'static) -> Self {
+        self.on_target_changed = Some(Arc::new(f));
+        self
+    }
+
+    /// Returns the name the actor is registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the actor currently targeted, or `None` if the name isn't currently resolved.
+    pub fn target(&self) -> Option<RemoteActorRef<A>> {
+        self.target.lock().unwrap().actor_ref.clone()
+    }
+
+    /// Sends a message to the actor and waits for a reply, re-resolving the name and retrying
+    /// if the actor can't be reached.
+    ///
+    /// Returns [`RemoteSendError::ActorNotRunning`] if no actor is registered under the name
+    /// after all retries.
+    pub async fn ask<M>(
+        &self,
+        msg: &M,
+    ) -> Result<<A::Reply as Reply>::Ok, RemoteSendError<<A::Reply as Reply>::Error>>
+    where
+        A: Message<M> + remote::RemoteMessage<M>,
+        M: serde::Serialize + Send + Sync + 'static,
+        <A::Reply as Reply>::Ok: serde::de::DeserializeOwned,
+        <A::Reply as Reply>::Error: serde::de::DeserializeOwned,
+    {
+        self.retry(|actor_ref| async move { actor_ref.ask(msg).await })
+            .await
+    }
+
+    /// Sends a message to the actor and waits for delivery acknowledgment, re-resolving
+    /// the name and retrying if the actor can't be reached.
+    ///
+    /// Returns [`RemoteSendError::ActorNotRunning`] if no actor is registered under the name
+    /// after all retries.
+    pub async fn tell<M>(&self, msg: &M) -> Result<(), RemoteSendError>
+    where
+        A: Message<M> + remote::RemoteMessage<M>,
+        M: serde::Serialize + Send + 'static,
+    {
+        self.retry(|actor_ref| async move { actor_ref.tell(msg).send_ack().await })
+            .await
+    }
+
+    async fn retry<F, Fut, T, E>(&self, f: F) -> Result<T, RemoteSendError<E>>
+    where
+        F: FnMut(RemoteActorRef<A>) -> Fut,
+        Fut: Future<Output = Result<T, RemoteSendError<E>>>,
+    {
+        let policy = RetryPolicy {
+            max_retries: self.max_retries,
+            interval: self.retry_interval,
+        };
+        let resolve = || async {
+            match self.resolve().await {
+                Ok(Some(actor_ref)) => Ok(actor_ref),
+                Ok(None) => Err(RemoteSendError::ActorNotRunning),
+                Err(RegistryError::SwarmNotBootstrapped) => {
+                    Err(RemoteSendError::SwarmNotBootstrapped)
+                }
+                // Lookups which fail are retried the same as failed sends
+                Err(_) => Err(RemoteSendError::ActorNotRunning),
+            }
+        };
+        send_with_retry(policy, resolve, |actor_id| self.clear_target(actor_id), f).await
+    }
+
+    /// Returns the current target, looking the name up if there isn't one.
+    async fn resolve(&self) -> Result<Option<RemoteActorRef<A>>, RegistryError> {
+        if let Some(actor_ref) = self.target() {
+            return Ok(Some(actor_ref));
+        }
+
+        let Some(actor_ref) =
+            RemoteActorRef::<A>::lookup_in(&self.swarm, self.name.clone()).await?
+        else {
+            return Ok(None);
+        };
+        let previous = {
+            let mut target = self.target.lock().unwrap();
+            target.actor_ref = Some(actor_ref.clone());
+            target.actor_id.replace(actor_ref.id())
+        };
+        if previous != Some(actor_ref.id())
+            && let Some(on_target_changed) = &self.on_target_changed
+        {
+            on_target_changed(&TargetChanged {
+                name: self.name.clone(),
+                previous,
+                current: actor_ref.id(),
+            });
+        }
+
+        Ok(Some(actor_ref))
+    }
+
+    /// Clears the target if it's still the given actor, so the next send looks the name up.
+    fn clear_target(&self, actor_id: ActorId) {
+        let mut target = self.target.lock().unwrap();
+        if target.actor_ref.as_ref().map(RemoteActorRef::id) == Some(actor_id) {
+            target.actor_ref = None;
+        }
+    }
+}
+
+impl<A: Actor> Clone for ResilientRemoteActorRef<A> {
+    fn clone(&self) -> Self {
+        ResilientRemoteActorRef {
+            swarm: self.swarm.clone(),
+            name: self.name.clone(),
+            max_retries: self.max_retries,
+            retry_interval: self.retry_interval,
+            target: self.target.clone(),
+            on_target_changed: self.on_target_changed.clone(),
+        }
+    }
+}
+
+impl<A: Actor> fmt::Debug for ResilientRemoteActorRef<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResilientRemoteActorRef")
+            .field("name", &self.name)
+            .field("max_retries", &self.max_retries)
+            .field("retry_interval", &self.retry_interval)
+            .field("target", &self.target.lock().unwrap().actor_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{
+        actor::Spawn,
+        remote::testing::{
+            Cluster,
+            fixtures::{Counter, Inc},
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn resends_to_actor_reregistered_on_another_node() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(3).await?;
+
+        let first = Counter::spawn(Counter {
+            count: Arc::new(AtomicUsize::new(0)),
+        });
+        cluster.node(0).register(&first, "counter").await?;
+        cluster.wait_for_lookup::<Counter>(2, "counter", 1).await?;
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let actor_ref =
+            RemoteActorRef::<Counter>::lookup_resilient_in(cluster.node(2).swarm(), "counter")
+                .await?
+                .with_retry_interval(Duration::from_millis(50))
+                .on_target_changed({
+                    let changes = changes.clone();
+                    move |change| changes.lock().unwrap().push(change.clone())
+                });
+        actor_ref.tell(&Inc).await?;
+
+        first.kill();
+        first.wait_for_shutdown().await;
+        let count = Arc::new(AtomicUsize::new(0));
+        let second = Counter::spawn(Counter {
+            count: count.clone(),
+        });
+        cluster.node(1).register(&second, "counter").await?;
+        let found = cluster.wait_for_lookup::<Counter>(2, "counter", 1).await?;
+        assert_eq!(found[0].id(), cluster.node(1).actor_id(&second));
+
+        actor_ref.tell(&Inc).await?;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, Some(cluster.node(0).actor_id(&first)));
+        assert_eq!(changes[0].current, cluster.node(1).actor_id(&second));
+
+        Ok(())
+    }
+}
@@ -300,9 +300,10 @@ where
                 actor_ref,
                 reply,
                 sent_within_actor,
+                metadata,
             }) => {
                 if let ControlFlow::Break(reason) = state
-                    .handle_message(message, actor_ref, reply, sent_within_actor)
+                    .handle_message(message, actor_ref, reply, sent_within_actor, metadata)
                     .await
                 {
                     return reason;
@@ -346,27 +347,25 @@ async fn notify_links(
                     );
                 }
                 #[cfg(feature = "remote")]
-                Link::Remote(notified_actor_remote_id) => {
-                    if let Some(swarm) = remote::ActorSwarm::get() {
-                        let reason = reason.clone();
-                        futs.push(
-                            async move {
-                                let res = swarm
-                                    .signal_link_died(
-                                        id,
-                                        link_actor_id,
-                                        notified_actor_remote_id,
-                                        reason,
-                                    )
-                                    .await;
-                                if let Err(err) = res {
-                                    #[cfg(feature = "tracing")]
-                                    error!("failed to notify actor a link died: {err}");
-                                }
+                Link::Remote(notified_actor_remote_id, swarm) => {
+                    let reason = reason.clone();
+                    futs.push(
+                        async move {
+                            let res = swarm
+                                .signal_link_died(
+                                    id,
+                                    link_actor_id,
+                                    notified_actor_remote_id,
+                                    reason,
+                                )
+                                .await;
+                            if let Err(err) = res {
+                                #[cfg(feature = "tracing")]
+                                error!("failed to notify actor a link died: {err}");
                             }
-                            .boxed(),
-                        );
-                    }
+                        }
+                        .boxed(),
+                    );
                 }
             }
         }
@@ -383,11 +382,11 @@ async fn unregister_actor(id: &ActorId) {
         .unwrap()
         .remove_by_id(id);
     #[cfg(feature = "remote")]
-    if let Some(entry) = remote::REMOTE_REGISTRY.lock().await.remove(id)
-        && let Some(registered_name) = entry.name
-        && let Some(swarm) = remote::ActorSwarm::get()
-    {
-        _ = swarm.unregister(registered_name);
+    if let Some(entry) = remote::REMOTE_REGISTRY.lock().unwrap().remove(id) {
+        // Remove every name the actor is still registered under, so lookups don't return it
+        for remote::RegisteredName { name, swarm } in entry.names {
+            _ = swarm.unregister_actor(name, *id);
+        }
     }
 }
 
@@ -404,7 +403,7 @@ fn log_actor_stop_reason(id: ActorId, name: &str, reason: &ActorStopReason) {
             error!(%id, %name, ?reason, "actor stopped")
         }
         #[cfg(feature = "remote")]
-        reason @ ActorStopReason::PeerDisconnected => {
+        reason @ ActorStopReason::PeerDisconnected | reason @ ActorStopReason::PeerUnreachable => {
             trace!(%id, %name, ?reason, "actor stopped");
         }
     }
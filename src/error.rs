@@ -360,6 +360,9 @@ pub enum ActorStopReason {
     /// The peer was disconnected.
     #[cfg(feature = "remote")]
     PeerDisconnected,
+    /// The peer stopped responding to heartbeats, and was deemed unreachable.
+    #[cfg(feature = "remote")]
+    PeerUnreachable,
 }
 
 impl fmt::Debug for ActorStopReason {
@@ -381,6 +384,8 @@ impl fmt::Debug for ActorStopReason {
                 .finish(),
             #[cfg(feature = "remote")]
             ActorStopReason::PeerDisconnected => write!(f, "PeerDisconnected"),
+            #[cfg(feature = "remote")]
+            ActorStopReason::PeerUnreachable => write!(f, "PeerUnreachable"),
         }
     }
 }
@@ -396,6 +401,8 @@ impl fmt::Display for ActorStopReason {
             }
             #[cfg(feature = "remote")]
             ActorStopReason::PeerDisconnected => write!(f, "peer disconnected"),
+            #[cfg(feature = "remote")]
+            ActorStopReason::PeerUnreachable => write!(f, "peer unreachable"),
         }
     }
 }
@@ -727,8 +734,8 @@ impl fmt::Display for PanicReason {
 #[derive(Copy, Serialize, Deserialize)]
 pub enum Infallible {}
 
+#[allow(clippy::non_canonical_clone_impl)]
 impl Clone for Infallible {
-    #[allow(clippy::non_canonical_clone_impl)]
     fn clone(&self) -> Infallible {
         match *self {}
     }
@@ -760,8 +767,8 @@ impl PartialEq for Infallible {
 
 impl Eq for Infallible {}
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Infallible {
-    #[allow(clippy::non_canonical_partial_ord_impl)]
     fn partial_cmp(&self, _other: &Self) -> Option<cmp::Ordering> {
         match *self {}
     }
@@ -782,7 +789,7 @@ impl Hash for Infallible {
 /// An error that can occur when registering & looking up actors by name.
 #[derive(Debug)]
 pub enum RegistryError {
-    /// The actor swarm has not been bootstrapped.
+    /// The actor swarm has not been bootstrapped, or has been shut down.
     #[cfg(feature = "remote")]
     SwarmNotBootstrapped,
     /// The remote actor was found given the ID, but was not the correct type.
@@ -807,6 +814,9 @@ pub enum RegistryError {
     /// Get providers error.
     #[cfg(feature = "remote")]
     GetProviders(libp2p::kad::GetProvidersError),
+    /// The gossip registry backend could not be set up.
+    #[cfg(feature = "remote")]
+    Gossip(String),
 }
 
 impl fmt::Display for RegistryError {
@@ -828,6 +838,8 @@ impl fmt::Display for RegistryError {
             RegistryError::InvalidActorRegistration(err) => err.fmt(f),
             #[cfg(feature = "remote")]
             RegistryError::GetProviders(err) => err.fmt(f),
+            #[cfg(feature = "remote")]
+            RegistryError::Gossip(err) => write!(f, "failed to set up the gossip registry: {err}"),
         }
     }
 }
@@ -898,6 +910,39 @@ pub enum RemoteSendError<E = Infallible> {
     },
     /// The remote actor was found given the ID, but was not the correct type.
     BadActorType,
+    /// The message was sent with a version the receiving node can't decode.
+    UnsupportedMessageVersion {
+        /// The remote ID of the message.
+        message_remote_id: std::borrow::Cow<'static, str>,
+        /// The version the message was sent with.
+        version: u32,
+        /// The current version of the message on the receiving node.
+        current_version: u32,
+    },
+    /// The actor can't be spawned remotely, as it isn't marked with `#[remote_actor(spawnable)]`.
+    SpawnUnsupported {
+        /// The remote ID of the actor.
+        actor_remote_id: std::borrow::Cow<'static, str>,
+    },
+    /// No shard region for the entity type could host the entity, either because no node is
+    /// hosting the region, or the node owning the entity's shard hasn't started it.
+    ShardRegionUnavailable {
+        /// The remote ID of the entity actor.
+        actor_remote_id: std::borrow::Cow<'static, str>,
+    },
+    /// No node is currently hosting the cluster singleton.
+    SingletonUnavailable {
+        /// The remote ID of the singleton actor.
+        actor_remote_id: std::borrow::Cow<'static, str>,
+    },
+    /// The peer isn't allowed to send the message to the actor by the receiving node's
+    /// [`AccessPolicy`](crate::remote::acl::AccessPolicy).
+    Unauthorized {
+        /// The remote ID of the actor.
+        actor_remote_id: std::borrow::Cow<'static, str>,
+        /// The remote ID of the message.
+        message_remote_id: std::borrow::Cow<'static, str>,
+    },
     /// The actors mailbox is full.
     MailboxFull,
     /// Timed out waiting for a reply.
@@ -915,7 +960,7 @@ pub enum RemoteSendError<E = Infallible> {
     /// Failed to deserialize the handler error.
     DeserializeHandlerError(String),
 
-    /// The actor swarm has not been bootstrapped.
+    /// The actor swarm has not been bootstrapped, or has been shut down.
     SwarmNotBootstrapped,
     /// The request could not be sent because a dialing attempt failed.
     DialFailure,
@@ -957,6 +1002,31 @@ impl<E> RemoteSendError<E> {
                 message_remote_id,
             },
             RemoteSendError::BadActorType => RemoteSendError::BadActorType,
+            RemoteSendError::UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            } => RemoteSendError::UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            },
+            RemoteSendError::SpawnUnsupported { actor_remote_id } => {
+                RemoteSendError::SpawnUnsupported { actor_remote_id }
+            }
+            RemoteSendError::ShardRegionUnavailable { actor_remote_id } => {
+                RemoteSendError::ShardRegionUnavailable { actor_remote_id }
+            }
+            RemoteSendError::SingletonUnavailable { actor_remote_id } => {
+                RemoteSendError::SingletonUnavailable { actor_remote_id }
+            }
+            RemoteSendError::Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            } => RemoteSendError::Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            },
             RemoteSendError::MailboxFull => RemoteSendError::MailboxFull,
             RemoteSendError::ReplyTimeout => RemoteSendError::ReplyTimeout,
             RemoteSendError::HandlerError(err) => RemoteSendError::HandlerError(op(err)),
@@ -979,6 +1049,84 @@ impl<E> RemoteSendError<E> {
     }
 }
 
+#[cfg(feature = "remote")]
+impl<E: Clone> Clone for RemoteSendError<E> {
+    fn clone(&self) -> Self {
+        match self {
+            RemoteSendError::ActorNotRunning => RemoteSendError::ActorNotRunning,
+            RemoteSendError::ActorStopped => RemoteSendError::ActorStopped,
+            RemoteSendError::UnknownActor { actor_remote_id } => RemoteSendError::UnknownActor {
+                actor_remote_id: actor_remote_id.clone(),
+            },
+            RemoteSendError::UnknownMessage {
+                actor_remote_id,
+                message_remote_id,
+            } => RemoteSendError::UnknownMessage {
+                actor_remote_id: actor_remote_id.clone(),
+                message_remote_id: message_remote_id.clone(),
+            },
+            RemoteSendError::BadActorType => RemoteSendError::BadActorType,
+            RemoteSendError::UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            } => RemoteSendError::UnsupportedMessageVersion {
+                message_remote_id: message_remote_id.clone(),
+                version: *version,
+                current_version: *current_version,
+            },
+            RemoteSendError::SpawnUnsupported { actor_remote_id } => {
+                RemoteSendError::SpawnUnsupported {
+                    actor_remote_id: actor_remote_id.clone(),
+                }
+            }
+            RemoteSendError::ShardRegionUnavailable { actor_remote_id } => {
+                RemoteSendError::ShardRegionUnavailable {
+                    actor_remote_id: actor_remote_id.clone(),
+                }
+            }
+            RemoteSendError::SingletonUnavailable { actor_remote_id } => {
+                RemoteSendError::SingletonUnavailable {
+                    actor_remote_id: actor_remote_id.clone(),
+                }
+            }
+            RemoteSendError::Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            } => RemoteSendError::Unauthorized {
+                actor_remote_id: actor_remote_id.clone(),
+                message_remote_id: message_remote_id.clone(),
+            },
+            RemoteSendError::MailboxFull => RemoteSendError::MailboxFull,
+            RemoteSendError::ReplyTimeout => RemoteSendError::ReplyTimeout,
+            RemoteSendError::HandlerError(err) => RemoteSendError::HandlerError(err.clone()),
+            RemoteSendError::SerializeMessage(err) => {
+                RemoteSendError::SerializeMessage(err.clone())
+            }
+            RemoteSendError::DeserializeMessage(err) => {
+                RemoteSendError::DeserializeMessage(err.clone())
+            }
+            RemoteSendError::SerializeReply(err) => RemoteSendError::SerializeReply(err.clone()),
+            RemoteSendError::SerializeHandlerError(err) => {
+                RemoteSendError::SerializeHandlerError(err.clone())
+            }
+            RemoteSendError::DeserializeHandlerError(err) => {
+                RemoteSendError::DeserializeHandlerError(err.clone())
+            }
+            RemoteSendError::SwarmNotBootstrapped => RemoteSendError::SwarmNotBootstrapped,
+            RemoteSendError::DialFailure => RemoteSendError::DialFailure,
+            RemoteSendError::NetworkTimeout => RemoteSendError::NetworkTimeout,
+            RemoteSendError::ConnectionClosed => RemoteSendError::ConnectionClosed,
+            RemoteSendError::UnsupportedProtocols => RemoteSendError::UnsupportedProtocols,
+            // io::Error isn't Clone, so only its kind and message are preserved
+            RemoteSendError::Io(err) => RemoteSendError::Io(
+                err.as_ref()
+                    .map(|err| std::io::Error::new(err.kind(), err.to_string())),
+            ),
+        }
+    }
+}
+
 #[cfg(feature = "remote")]
 impl<E> RemoteSendError<RemoteSendError<E>> {
     /// Flattens a nested SendError.
@@ -1002,6 +1150,43 @@ impl<E> RemoteSendError<RemoteSendError<E>> {
                 message_remote_id,
             },
             BadActorType | HandlerError(BadActorType) => BadActorType,
+            UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            }
+            | HandlerError(UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            }) => UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            },
+            SpawnUnsupported { actor_remote_id }
+            | HandlerError(SpawnUnsupported { actor_remote_id }) => {
+                SpawnUnsupported { actor_remote_id }
+            }
+            ShardRegionUnavailable { actor_remote_id }
+            | HandlerError(ShardRegionUnavailable { actor_remote_id }) => {
+                ShardRegionUnavailable { actor_remote_id }
+            }
+            SingletonUnavailable { actor_remote_id }
+            | HandlerError(SingletonUnavailable { actor_remote_id }) => {
+                SingletonUnavailable { actor_remote_id }
+            }
+            Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            }
+            | HandlerError(Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            }) => Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            },
             MailboxFull | HandlerError(MailboxFull) => MailboxFull,
             ReplyTimeout | HandlerError(ReplyTimeout) => ReplyTimeout,
             HandlerError(HandlerError(err)) => HandlerError(err),
@@ -1076,6 +1261,30 @@ where
                 "unknown message '{message_remote_id}' for actor '{actor_remote_id}'"
             ),
             RemoteSendError::BadActorType => write!(f, "bad actor type"),
+            RemoteSendError::UnsupportedMessageVersion {
+                message_remote_id,
+                version,
+                current_version,
+            } => write!(
+                f,
+                "unsupported version {version} of message '{message_remote_id}' (current version is {current_version})"
+            ),
+            RemoteSendError::SpawnUnsupported { actor_remote_id } => {
+                write!(f, "actor '{actor_remote_id}' can't be spawned remotely")
+            }
+            RemoteSendError::ShardRegionUnavailable { actor_remote_id } => {
+                write!(f, "no shard region available for actor '{actor_remote_id}'")
+            }
+            RemoteSendError::SingletonUnavailable { actor_remote_id } => {
+                write!(f, "no node is hosting singleton '{actor_remote_id}'")
+            }
+            RemoteSendError::Unauthorized {
+                actor_remote_id,
+                message_remote_id,
+            } => write!(
+                f,
+                "not authorized to send message '{message_remote_id}' to actor '{actor_remote_id}'"
+            ),
             RemoteSendError::MailboxFull => write!(f, "mailbox full"),
             RemoteSendError::ReplyTimeout => write!(f, "timeout"),
             RemoteSendError::HandlerError(err) => err.fmt(f),
@@ -1122,3 +1331,65 @@ impl fmt::Display for SwarmAlreadyBootstrappedError {
 
 #[cfg(feature = "remote")]
 impl error::Error for SwarmAlreadyBootstrappedError {}
+
+/// An error returned when a connection is denied because the peer isn't in the allowlist.
+#[cfg(feature = "remote")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerNotAllowedError {
+    /// The peer which was denied.
+    pub peer_id: libp2p::PeerId,
+}
+
+#[cfg(feature = "remote")]
+impl fmt::Display for PeerNotAllowedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peer {} is not allowed", self.peer_id)
+    }
+}
+
+#[cfg(feature = "remote")]
+impl error::Error for PeerNotAllowedError {}
+
+/// An error that can occur when loading or saving a node identity.
+#[cfg(feature = "remote")]
+#[derive(Debug)]
+pub enum IdentityError {
+    /// Reading or writing the key file failed.
+    Io(std::io::Error),
+    /// The keypair could not be decoded or encoded.
+    Decoding(libp2p::identity::DecodingError),
+}
+
+#[cfg(feature = "remote")]
+impl fmt::Display for IdentityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityError::Io(err) => write!(f, "failed to access key file: {err}"),
+            IdentityError::Decoding(err) => write!(f, "invalid keypair: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl error::Error for IdentityError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            IdentityError::Io(err) => Some(err),
+            IdentityError::Decoding(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl From<std::io::Error> for IdentityError {
+    fn from(err: std::io::Error) -> Self {
+        IdentityError::Io(err)
+    }
+}
+
+#[cfg(feature = "remote")]
+impl From<libp2p::identity::DecodingError> for IdentityError {
+    fn from(err: libp2p::identity::DecodingError) -> Self {
+        IdentityError::Decoding(err)
+    }
+}
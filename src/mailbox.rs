@@ -829,6 +829,8 @@ pub enum Signal<A: Actor> {
         reply: Option<BoxReplySender>,
         /// If the message sent from within the actor's tokio task/thread
         sent_within_actor: bool,
+        /// Where the message was sent from.
+        metadata: MessageMetadata,
     },
     /// A linked actor has died.
     LinkDied {
@@ -841,6 +843,25 @@ pub enum Signal<A: Actor> {
     Stop,
 }
 
+/// Where a message in a [`Signal::Message`] was sent from.
+///
+/// The metadata is opaque, so information can be carried with messages, such as the remote peer a
+/// message was received from, without changing the signal's fields.
+#[derive(Default)]
+pub struct MessageMetadata {
+    /// The remote sender, if the message was received from a remote peer.
+    ///
+    /// Boxed to keep the size of signals for local messages small.
+    #[cfg(feature = "remote")]
+    pub(crate) remote_sender: Option<Box<crate::remote::RemoteSender>>,
+}
+
+impl fmt::Debug for MessageMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageMetadata").finish_non_exhaustive()
+    }
+}
+
 impl<A: Actor> Signal<A> {
     pub(crate) fn downcast_message<M>(self) -> Option<M>
     where
@@ -17,6 +17,7 @@
 use std::{any, fmt};
 
 use futures::{Future, FutureExt, future::BoxFuture};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     Actor,
@@ -55,7 +56,7 @@ pub trait Message<T: Send + 'static>: Actor {
 /// allowing actors to react and process each message as it arrives from the stream.
 ///
 /// It's typically used with [ActorRef::attach_stream] to attach a stream to an actor.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StreamMessage<T, S, F> {
     /// The next item in a stream.
     Next(T),
@@ -75,6 +76,8 @@ where
     actor_ref: ActorRef<A>,
     reply: Option<ReplySender<R::Value>>,
     stop: bool,
+    #[cfg(feature = "remote")]
+    remote_sender: Option<crate::remote::RemoteSender>,
 }
 
 impl<A, R> Context<A, R>
@@ -91,6 +94,11 @@ where
             actor_ref,
             reply,
             stop,
+            #[cfg(feature = "remote")]
+            remote_sender: crate::remote::REMOTE_SENDER
+                .try_with(|sender| *sender)
+                .ok()
+                .flatten(),
         }
     }
 
@@ -99,6 +107,37 @@ where
         &self.actor_ref
     }
 
+    /// Returns the sender of the message if it was received from a remote peer.
+    ///
+    /// The sender contains the [`PeerId`](libp2p::PeerId) the message arrived from, along with the
+    /// sending actor's ID if the message was sent from within an actor. Returns `None` for messages
+    /// sent locally.
+    ///
+    /// Only the peer ID is authenticated; the actor ID is reported by the sending peer.
+    ///
+    /// ```
+    /// use kameo::message::{Context, Message};
+    ///
+    /// # #[derive(kameo::Actor)]
+    /// # struct MyActor;
+    /// #
+    /// struct Msg;
+    ///
+    /// impl Message<Msg> for MyActor {
+    ///     type Reply = ();
+    ///
+    ///     async fn handle(&mut self, msg: Msg, ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+    ///         if let Some(sender) = ctx.remote_sender() {
+    ///             println!("received message from peer {}", sender.peer_id());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "remote")]
+    pub fn remote_sender(&self) -> Option<&crate::remote::RemoteSender> {
+        self.remote_sender.as_ref()
+    }
+
     /// Stops the actor normally after processing the current message.
     pub fn stop(&mut self) {
         self.stop = true;
@@ -55,64 +55,95 @@
 
 use std::{
     any,
+    borrow::Cow,
     collections::HashMap,
     error, str,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, Mutex},
 };
 
-use futures::StreamExt;
-use libp2p::{
-    PeerId, SwarmBuilder, mdns, noise,
-    swarm::{NetworkBehaviour, SwarmEvent},
-    tcp, yamux,
-};
-use tokio::sync::Mutex;
+use libp2p::PeerId;
+use serde::Serialize;
+use tokio::task_local;
 
 use crate::{
     Actor,
-    actor::{ActorId, ActorRef, Links, WeakActorRef},
+    actor::{ActorId, ActorRef, Link, Links, RemoteActorRef, WeakActorRef},
     error::{RegistryError, RemoteSendError},
     mailbox::SignalMailbox,
+    message::Message,
 };
 
 #[doc(hidden)]
 pub mod _internal;
+pub mod acl;
 mod behaviour;
+mod bootstrap;
+pub mod codec;
+pub mod compression;
+pub mod heartbeat;
+pub mod identity;
+pub mod membership;
+mod message_target;
 pub mod messaging;
 pub mod registry;
+mod retry;
+pub mod sharding;
+pub mod singleton;
+pub mod streaming;
 mod swarm;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use behaviour::*;
+pub use bootstrap::*;
 pub use swarm::*;
 
+pub(crate) use message_target::MessageTarget;
+pub(crate) use retry::{RetryPolicy, send_with_retry};
+
 pub(crate) static REMOTE_REGISTRY: LazyLock<Mutex<HashMap<ActorId, RemoteRegistryActorRef>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+/// A name a local actor is registered under, along with the swarm it's registered in.
+pub(crate) struct RegisteredName {
+    pub(crate) name: Arc<str>,
+    pub(crate) swarm: ActorSwarm,
+}
+
+impl RegisteredName {
+    pub(crate) fn matches(&self, name: &str, swarm: &ActorSwarm) -> bool {
+        &*self.name == name && self.swarm.local_peer_id() == swarm.local_peer_id()
+    }
+}
+
 pub(crate) struct RemoteRegistryActorRef {
     actor_ref: BoxRegisteredActorRef,
-    pub(crate) name: Option<Arc<str>>,
+    pub(crate) names: Vec<RegisteredName>,
     pub(crate) signal_mailbox: Box<dyn SignalMailbox>,
     pub(crate) links: Links,
 }
 
 impl RemoteRegistryActorRef {
-    pub(crate) fn new<A: Actor>(actor_ref: ActorRef<A>, name: Option<Arc<str>>) -> Self {
+    pub(crate) fn new<A: Actor>(actor_ref: ActorRef<A>, name: Option<RegisteredName>) -> Self {
         let signal_mailbox = actor_ref.weak_signal_mailbox();
         let links = actor_ref.links.clone();
         RemoteRegistryActorRef {
             actor_ref: BoxRegisteredActorRef::Strong(Box::new(actor_ref)),
-            name,
+            names: name.into_iter().collect(),
             signal_mailbox,
             links,
         }
     }
 
-    pub(crate) fn new_weak<A: Actor>(actor_ref: WeakActorRef<A>, name: Option<Arc<str>>) -> Self {
+    pub(crate) fn new_weak<A: Actor>(
+        actor_ref: WeakActorRef<A>,
+        name: Option<RegisteredName>,
+    ) -> Self {
         let signal_mailbox = actor_ref.weak_signal_mailbox();
         let links = actor_ref.links.clone();
         RemoteRegistryActorRef {
             actor_ref: BoxRegisteredActorRef::Weak(Box::new(actor_ref)),
-            name,
+            names: name.into_iter().collect(),
             signal_mailbox,
             links,
         }
@@ -154,6 +185,45 @@ pub(crate) enum BoxRegisteredActorRef {
     Weak(Box<dyn any::Any + Send + Sync>),
 }
 
+task_local! {
+    pub(crate) static REMOTE_SENDER: Option<RemoteSender>;
+}
+
+/// The sender of a message received from a remote peer.
+///
+/// Available to message handlers through [`Context::remote_sender`](crate::message::Context::remote_sender)
+/// for messages delivered by remote `ask` and `tell` requests, allowing actors to implement per-peer
+/// authorization, quotas, or audit logging.
+///
+/// Only the [`peer_id`](RemoteSender::peer_id) is authenticated, by the noise handshake of the
+/// connection the message arrived on. The [`actor_id`](RemoteSender::actor_id) is self-reported by
+/// the sending peer and must not be trusted for authorization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RemoteSender {
+    peer_id: PeerId,
+    actor_id: Option<ActorId>,
+}
+
+impl RemoteSender {
+    pub(crate) fn new(peer_id: PeerId, actor_id: Option<ActorId>) -> Self {
+        RemoteSender { peer_id, actor_id }
+    }
+
+    /// Returns the peer ID the message was received from.
+    ///
+    /// The peer ID is authenticated by the connection's noise handshake.
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    /// Returns the ID of the actor which sent the message, if it was sent from within an actor.
+    ///
+    /// The actor ID is reported by the sending peer and is not verified.
+    pub fn actor_id(&self) -> Option<ActorId> {
+        self.actor_id
+    }
+}
+
 /// `RemoteActor` is a trait for identifying actors remotely.
 ///
 /// Each remote actor must implement this trait and provide a unique identifier string (`REMOTE_ID`).
@@ -193,6 +263,36 @@ pub trait RemoteActor {
 pub trait RemoteMessage<M> {
     /// The remote identifier string.
     const REMOTE_ID: &'static str;
+
+    /// The codec this message is always encoded with.
+    ///
+    /// When `None`, the codec configured for the swarm with
+    /// [`messaging::Config::with_codec`] is used.
+    const CODEC: Option<codec::CodecId> = None;
+
+    /// The version of the message's schema.
+    ///
+    /// The version is sent alongside each message. Receivers running a newer version of the
+    /// message decode older payloads with [`RemoteMessage::upcast`], and reject payloads from
+    /// newer versions with [`RemoteSendError::UnsupportedMessageVersion`].
+    const VERSION: u32 = 1;
+
+    /// The older versions of the message which can be upcast to the current version.
+    const UPCAST_VERSIONS: &'static [u32] = &[];
+
+    /// Decodes a payload sent by an older version of the message, converting it to the current version.
+    ///
+    /// Returns `None` if the version can't be upcast.
+    ///
+    /// This is typically implemented with `#[remote_message(version = 2, upcast(1 = MyMessageV1))]`,
+    /// which decodes version 1 payloads as `MyMessageV1` and converts them with `From<MyMessageV1>`.
+    fn upcast(
+        _version: u32,
+        _payload: &[u8],
+        _codec: codec::CodecId,
+    ) -> Option<Result<M, codec::CodecError>> {
+        None
+    }
 }
 
 /// Bootstrap a simple actor swarm with mDNS discovery for local development.
@@ -202,8 +302,8 @@ pub trait RemoteMessage<M> {
 /// - mDNS peer discovery (local network only)
 /// - Automatic listening on an OS-assigned port
 ///
-/// For production use or custom configuration, use `kameo::remote::Behaviour`
-/// with your own libp2p swarm setup.
+/// To configure the transports, discovery, and other settings, use [`Bootstrap`]. For full
+/// control, use `kameo::remote::Behaviour` with your own libp2p swarm setup.
 ///
 /// # Example
 /// ```ignore
@@ -224,75 +324,276 @@ pub fn bootstrap() -> Result<PeerId, Box<dyn error::Error>> {
 
 /// Bootstrap with a specific listen address.
 pub fn bootstrap_on(addr: &str) -> Result<PeerId, Box<dyn error::Error>> {
-    #[derive(NetworkBehaviour)]
-    struct BootstrapBehaviour {
-        kameo: Behaviour,
-        mdns: mdns::tokio::Behaviour,
-    }
+    Ok(Bootstrap::new().listen_on(addr.parse()?).start()?.peer_id())
+}
 
-    let mut swarm = SwarmBuilder::with_new_identity()
-        .with_tokio()
-        .with_tcp(
-            tcp::Config::default(),
-            noise::Config::new,
-            yamux::Config::default,
-        )?
-        .with_quic()
-        .with_behaviour(|key| {
-            let local_peer_id = key.public().to_peer_id();
-            let kameo = Behaviour::new(local_peer_id, messaging::Config::default());
-            let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), local_peer_id)?;
-
-            Ok(BootstrapBehaviour { kameo, mdns })
-        })?
-        .build();
-
-    swarm.behaviour().kameo.try_init_global()?;
-
-    swarm.listen_on(addr.parse()?)?;
-
-    let local_peer_id = *swarm.local_peer_id();
-
-    tokio::spawn(async move {
-        loop {
-            match swarm.select_next_some().await {
-                SwarmEvent::Behaviour(BootstrapBehaviourEvent::Mdns(mdns::Event::Discovered(
-                    list,
-                ))) => {
-                    for (peer_id, multiaddr) in list {
-                        #[cfg(feature = "tracing")]
-                        tracing::info!("mDNS discovered a new peer: {peer_id}");
-                        swarm.add_peer_address(peer_id, multiaddr);
-                    }
-                }
-                SwarmEvent::Behaviour(BootstrapBehaviourEvent::Mdns(mdns::Event::Expired(
-                    list,
-                ))) => {
-                    for (peer_id, _multiaddr) in list {
-                        #[cfg(feature = "tracing")]
-                        tracing::warn!("mDNS discover peer has expired: {peer_id}");
-                        let _ = swarm.disconnect_peer_id(peer_id);
-                    }
-                }
-                #[cfg(feature = "tracing")]
-                SwarmEvent::NewListenAddr { address, .. } => {
-                    tracing::info!("ActorSwarm listening on {address}");
+/// Returns the remote actors and messages supported by a connected peer.
+///
+/// Capabilities are exchanged in a handshake when a connection to a peer is established.
+/// Returns `None` if no handshake with the peer has completed, such as when the peer isn't
+/// connected or is running an older version of kameo.
+pub async fn peer_capabilities(
+    peer_id: PeerId,
+) -> Result<Option<messaging::PeerCapabilities>, RemoteSendError> {
+    Ok(ActorSwarm::get()
+        .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+        .peer_capabilities(peer_id)
+        .await)
+}
+
+/// Spawns an actor on a peer, returning a reference to the remote actor.
+///
+/// The actor's `Args` are encoded with the swarm's codec and sent to the peer, which spawns the
+/// actor with a default bounded mailbox. The actor must derive [`RemoteActor`] with
+/// `#[remote_actor(spawnable)]` on the peer, otherwise [`RemoteSendError::SpawnUnsupported`]
+/// is returned.
+///
+/// The spawned actor isn't registered under a name, and keeps running until it's stopped.
+/// Use [`spawn_link`] to link the spawned actor to a local actor.
+///
+/// # Example
+///
+/// ```no_run
+/// use kameo::remote;
+/// # use kameo::{Actor, RemoteActor};
+/// #
+/// # #[derive(Actor, RemoteActor, serde::Serialize, serde::Deserialize)]
+/// # #[remote_actor(spawnable)]
+/// # struct MyActor { count: i64 }
+///
+/// # tokio_test::block_on(async {
+/// # let peer_id = libp2p::PeerId::random();
+/// let actor_ref = remote::spawn::<MyActor>(peer_id, MyActor { count: 0 }).await?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub async fn spawn<A>(peer_id: PeerId, args: A::Args) -> Result<RemoteActorRef<A>, RemoteSendError>
+where
+    A: Actor + RemoteActor,
+    A::Args: Serialize,
+{
+    let swarm = SwarmHandle::global().ok_or(RemoteSendError::SwarmNotBootstrapped)?;
+    spawn_in(&swarm, peer_id, args).await
+}
+
+/// Spawns an actor on a peer within a specific actor swarm.
+///
+/// This behaves like [`spawn`], but sends the request through the swarm of the handle instead
+/// of the global actor swarm.
+pub async fn spawn_in<A>(
+    swarm: &SwarmHandle,
+    peer_id: PeerId,
+    args: A::Args,
+) -> Result<RemoteActorRef<A>, RemoteSendError>
+where
+    A: Actor + RemoteActor,
+    A::Args: Serialize,
+{
+    let swarm = swarm.actor_swarm();
+    let codec = swarm.sender().codec();
+    let args = swarm
+        .sender()
+        .enter(|| codec.encode(&args))
+        .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))?;
+
+    swarm.spawn(peer_id, args, codec, None).await
+}
+
+/// Spawns an actor on a peer, linking it to a local actor.
+///
+/// This behaves like [`spawn`], with the spawned actor and `link_ref` being linked together,
+/// notifying each other if either one dies.
+pub async fn spawn_link<A, B>(
+    peer_id: PeerId,
+    args: A::Args,
+    link_ref: &ActorRef<B>,
+) -> Result<RemoteActorRef<A>, RemoteSendError>
+where
+    A: Actor + RemoteActor,
+    A::Args: Serialize,
+    B: Actor + RemoteActor,
+{
+    let swarm = SwarmHandle::global().ok_or(RemoteSendError::SwarmNotBootstrapped)?;
+    spawn_link_in(&swarm, peer_id, args, link_ref).await
+}
+
+/// Spawns an actor on a peer within a specific actor swarm, linking it to a local actor.
+///
+/// This behaves like [`spawn_link`], but sends the request through the swarm of the handle
+/// instead of the global actor swarm.
+pub async fn spawn_link_in<A, B>(
+    swarm: &SwarmHandle,
+    peer_id: PeerId,
+    args: A::Args,
+    link_ref: &ActorRef<B>,
+) -> Result<RemoteActorRef<A>, RemoteSendError>
+where
+    A: Actor + RemoteActor,
+    A::Args: Serialize,
+    B: Actor + RemoteActor,
+{
+    let swarm = swarm.actor_swarm();
+    let codec = swarm.sender().codec();
+    let args = swarm
+        .sender()
+        .enter(|| codec.encode(&args))
+        .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))?;
+
+    let registered = {
+        let mut remote_actors = REMOTE_REGISTRY.lock().unwrap();
+        let registered = !remote_actors.contains_key(&link_ref.id());
+        if registered {
+            remote_actors.insert(
+                link_ref.id(),
+                RemoteRegistryActorRef::new(link_ref.clone(), None),
+            );
+        }
+        registered
+    };
+
+    // The spawned actor's ID is only known once the peer has spawned it, so the links are locked
+    // for the duration of the request. If `link_ref` stops in the meantime, notifying its links
+    // waits for the spawned actor to be added, and so notifies it too.
+    let mut links = link_ref.links.lock().await;
+    let res = swarm
+        .spawn(
+            peer_id,
+            args,
+            codec,
+            Some((link_ref.id(), Cow::Borrowed(B::REMOTE_ID))),
+        )
+        .await;
+    match res {
+        Ok(actor_ref) => {
+            links.insert(
+                actor_ref.id(),
+                Link::Remote(Cow::Borrowed(A::REMOTE_ID), swarm.clone()),
+            );
+            Ok(actor_ref)
+        }
+        Err(err) => {
+            drop(links);
+            if registered {
+                let mut remote_actors = REMOTE_REGISTRY.lock().unwrap();
+                if remote_actors
+                    .get(&link_ref.id())
+                    .is_some_and(|entry| entry.names.is_empty())
+                {
+                    remote_actors.remove(&link_ref.id());
                 }
-                _ => {}
             }
+            Err(err)
         }
-    });
+    }
+}
+
+/// Watches for changes to the actors registered under a name.
+///
+/// The returned stream first yields a [`WatchEvent::Registered`] for each actor currently
+/// known to be registered under the name, followed by [`WatchEvent::Registered`],
+/// [`WatchEvent::Unregistered`] and [`WatchEvent::Expired`] events as the registry observes
+/// them. How quickly remote changes are observed depends on the registry backend; the
+/// Kademlia backend periodically re-queries watched names, while the gossip backend
+/// receives changes as they're announced.
+///
+/// [`WatchEvent::Registered`]: registry::WatchEvent::Registered
+/// [`WatchEvent::Unregistered`]: registry::WatchEvent::Unregistered
+/// [`WatchEvent::Expired`]: registry::WatchEvent::Expired
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use futures::StreamExt;
+/// use kameo::remote::{self, registry::WatchEvent};
+///
+/// # tokio_test::block_on(async {
+/// let mut events = remote::watch("my-service")?;
+/// while let Some(event) = events.next().await {
+///     match event {
+///         WatchEvent::Registered { peer, actor_id } => println!("{actor_id} is up on {peer}"),
+///         WatchEvent::Unregistered { actor_id, .. } | WatchEvent::Expired { actor_id, .. } => {
+///             println!("{actor_id} is gone")
+///         }
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub fn watch(name: impl Into<Arc<str>>) -> Result<WatchStream, RegistryError> {
+    Ok(ActorSwarm::get()
+        .ok_or(RegistryError::SwarmNotBootstrapped)?
+        .watch(name.into()))
+}
 
-    Ok(local_peer_id)
+/// Returns the members of the cluster, including the local node, ordered from oldest to youngest.
+///
+/// See the [`membership`] module for how nodes become members.
+pub async fn members() -> Result<Vec<membership::Member>, RemoteSendError> {
+    Ok(ActorSwarm::get()
+        .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+        .members()
+        .await)
+}
+
+/// Subscribes an actor to changes to the members of the cluster.
+///
+/// The actor is first sent a [`MemberEvent`](membership::MemberEvent) for the current status of
+/// each member, followed by an event for each change. The subscription ends when the actor stops.
+///
+/// # Example
+///
+/// ```no_run
+/// use kameo::prelude::*;
+/// use kameo::remote::{self, membership::MemberEvent};
+///
+/// #[derive(Actor)]
+/// struct ClusterListener;
+///
+/// impl Message<MemberEvent> for ClusterListener {
+///     type Reply = ();
+///
+///     async fn handle(&mut self, event: MemberEvent, _ctx: &mut Context<Self, Self::Reply>) {
+///         println!("{} is now {:?}", event.member().peer_id, event.member().status);
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let actor_ref = ClusterListener::spawn(ClusterListener);
+/// remote::subscribe_members(&actor_ref)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub fn subscribe_members<A>(actor_ref: &ActorRef<A>) -> Result<(), RemoteSendError>
+where
+    A: Actor + Message<membership::MemberEvent>,
+{
+    SwarmHandle::global()
+        .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+        .subscribe_members(actor_ref);
+
+    Ok(())
+}
+
+/// Gracefully leaves the cluster.
+///
+/// Members are notified the local node is leaving, and once they've acknowledged it, or
+/// the membership request timeout has elapsed, connections to them are closed. The local node
+/// doesn't rejoin the cluster afterwards.
+pub async fn leave() -> Result<(), RemoteSendError> {
+    ActorSwarm::get()
+        .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+        .leave()
+        .await;
+    Ok(())
 }
 
 /// Unregisters an actor within the swarm.
 ///
 /// This will only unregister an actor previously registered by the current node.
 pub async fn unregister(name: impl Into<Arc<str>>) -> Result<(), RegistryError> {
-    ActorSwarm::get()
+    SwarmHandle::global()
         .ok_or(RegistryError::SwarmNotBootstrapped)?
-        .unregister(name.into())
+        .unregister(name)
         .await;
     Ok(())
 }
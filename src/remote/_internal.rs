@@ -8,12 +8,15 @@ pub use linkme;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
-use crate::actor::{ActorId, Link};
+use crate::actor::{ActorId, Link, Spawn};
 use crate::error::{ActorStopReason, Infallible, RemoteSendError};
 use crate::message::Message;
 use crate::{Actor, Reply};
 
-use super::REMOTE_REGISTRY;
+use super::codec::{CodecError, CodecId};
+use super::{
+    REMOTE_REGISTRY, RemoteActor, RemoteMessage, RemoteRegistryActorRef, RemoteSender, SwarmSender,
+};
 
 #[linkme::distributed_slice]
 pub static REMOTE_ACTORS: [(&'static str, RemoteActorFns)];
@@ -26,10 +29,13 @@ pub struct RemoteActorFns {
     pub link: RemoteLinkFn,
     pub unlink: RemoteUnlinkFn,
     pub signal_link_died: RemoteSignalLinkDiedFn,
+    pub spawn: RemoteSpawnFn,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct RemoteMessageFns {
+    pub version: u32,
+    pub upcast_versions: &'static [u32],
     pub ask: RemoteAskFn,
     pub try_ask: RemoteTryAskFn,
     pub tell: RemoteTellFn,
@@ -38,25 +44,39 @@ pub struct RemoteMessageFns {
 
 pub type RemoteAskFn = fn(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     mailbox_timeout: Option<Duration>,
     reply_timeout: Option<Duration>,
 ) -> BoxFuture<'static, Result<Vec<u8>, RemoteSendError<Vec<u8>>>>;
 
 pub type RemoteTryAskFn = fn(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     reply_timeout: Option<Duration>,
 ) -> BoxFuture<'static, Result<Vec<u8>, RemoteSendError<Vec<u8>>>>;
 
 pub type RemoteTellFn = fn(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     mailbox_timeout: Option<Duration>,
 ) -> BoxFuture<'static, Result<(), RemoteSendError>>;
 
-pub type RemoteTryTellFn =
-    fn(actor_id: ActorId, msg: Vec<u8>) -> BoxFuture<'static, Result<(), RemoteSendError>>;
+pub type RemoteTryTellFn = fn(
+    actor_id: ActorId,
+    sender: RemoteSender,
+    msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
+) -> BoxFuture<'static, Result<(), RemoteSendError>>;
 
 pub type RemoteLinkFn = fn(
     actor_id: ActorId,
@@ -76,6 +96,12 @@ pub type RemoteSignalLinkDiedFn = fn(
 )
     -> BoxFuture<'static, Result<(), RemoteSendError<Infallible>>>;
 
+pub type RemoteSpawnFn = fn(
+    args: Vec<u8>,
+    codec: CodecId,
+    link: Option<(ActorId, Cow<'static, str>)>,
+) -> BoxFuture<'static, Result<ActorId, RemoteSendError<Infallible>>>;
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct RemoteMessageRegistrationID<'a> {
     pub actor_remote_id: &'a str,
@@ -84,37 +110,41 @@ pub struct RemoteMessageRegistrationID<'a> {
 
 pub async fn ask<A, M>(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     mailbox_timeout: Option<Duration>,
     reply_timeout: Option<Duration>,
 ) -> Result<Vec<u8>, RemoteSendError<Vec<u8>>>
 where
-    A: Actor + Message<M>,
+    A: Actor + Message<M> + RemoteMessage<M>,
     M: DeserializeOwned + Send + 'static,
     <A::Reply as Reply>::Ok: Serialize,
     <A::Reply as Reply>::Error: Serialize,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
-    let msg: M = rmp_serde::decode::from_slice(&msg)
-        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()))?;
+    let msg: M = decode_message::<A, M, _>(&msg, codec, version)?;
 
     let res = actor_ref
         .ask(msg)
+        .remote_sender(sender)
         .mailbox_timeout_opt(mailbox_timeout)
         .reply_timeout_opt(reply_timeout)
         .send()
         .await;
     match res {
-        Ok(reply) => Ok(rmp_serde::to_vec_named(&reply)
+        Ok(reply) => Ok(codec
+            .encode(&reply)
             .map_err(|err| RemoteSendError::SerializeReply(err.to_string()))?),
         Err(err) => Err(RemoteSendError::from(err)
-            .map_err(|err| match rmp_serde::to_vec_named(&err) {
+            .map_err(|err| match codec.encode(&err) {
                 Ok(payload) => RemoteSendError::HandlerError(payload),
                 Err(err) => RemoteSendError::SerializeHandlerError(err.to_string()),
             })
@@ -124,35 +154,39 @@ where
 
 pub async fn try_ask<A, M>(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     reply_timeout: Option<Duration>,
 ) -> Result<Vec<u8>, RemoteSendError<Vec<u8>>>
 where
-    A: Actor + Message<M>,
+    A: Actor + Message<M> + RemoteMessage<M>,
     M: DeserializeOwned + Send + 'static,
     <A::Reply as Reply>::Ok: Serialize,
     <A::Reply as Reply>::Error: Serialize,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
-    let msg: M = rmp_serde::decode::from_slice(&msg)
-        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()))?;
+    let msg: M = decode_message::<A, M, _>(&msg, codec, version)?;
 
     let res = actor_ref
         .ask(msg)
+        .remote_sender(sender)
         .reply_timeout_opt(reply_timeout)
         .try_send()
         .await;
     match res {
-        Ok(reply) => Ok(rmp_serde::to_vec_named(&reply)
+        Ok(reply) => Ok(codec
+            .encode(&reply)
             .map_err(|err| RemoteSendError::SerializeReply(err.to_string()))?),
         Err(err) => Err(RemoteSendError::from(err)
-            .map_err(|err| match rmp_serde::to_vec_named(&err) {
+            .map_err(|err| match codec.encode(&err) {
                 Ok(payload) => RemoteSendError::HandlerError(payload),
                 Err(err) => RemoteSendError::SerializeHandlerError(err.to_string()),
             })
@@ -162,25 +196,28 @@ where
 
 pub async fn tell<A, M>(
     actor_id: ActorId,
+    sender: RemoteSender,
     msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
     mailbox_timeout: Option<Duration>,
 ) -> Result<(), RemoteSendError>
 where
-    A: Actor + Message<M>,
+    A: Actor + Message<M> + RemoteMessage<M>,
     M: DeserializeOwned + Send + 'static,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
-    let msg: M = rmp_serde::decode::from_slice(&msg)
-        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()))?;
+    let msg: M = decode_message::<A, M, _>(&msg, codec, version)?;
 
     let res = actor_ref
         .tell(msg)
+        .remote_sender(sender)
         .mailbox_timeout_opt(mailbox_timeout)
         .send()
         .await;
@@ -190,28 +227,64 @@ where
     }
 }
 
-pub async fn try_tell<A, M>(actor_id: ActorId, msg: Vec<u8>) -> Result<(), RemoteSendError>
+pub async fn try_tell<A, M>(
+    actor_id: ActorId,
+    sender: RemoteSender,
+    msg: Vec<u8>,
+    codec: CodecId,
+    version: u32,
+) -> Result<(), RemoteSendError>
 where
-    A: Actor + Message<M>,
+    A: Actor + Message<M> + RemoteMessage<M>,
     M: DeserializeOwned + Send + 'static,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
-    let msg: M = rmp_serde::decode::from_slice(&msg)
-        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()))?;
+    let msg: M = decode_message::<A, M, _>(&msg, codec, version)?;
 
-    let res = actor_ref.tell(msg).try_send();
+    let res = actor_ref.tell(msg).remote_sender(sender).try_send();
     match res {
         Ok(()) => Ok(()),
         Err(err) => Err(RemoteSendError::from(err)),
     }
 }
 
+fn decode_message<A, M, E>(
+    msg: &[u8],
+    codec: CodecId,
+    version: u32,
+) -> Result<M, RemoteSendError<E>>
+where
+    A: RemoteMessage<M>,
+    M: DeserializeOwned,
+{
+    let decode = || -> Result<Option<M>, CodecError> {
+        codec.check(<A as RemoteMessage<M>>::CODEC)?;
+        if version == <A as RemoteMessage<M>>::VERSION {
+            codec.decode(msg).map(Some)
+        } else if version < <A as RemoteMessage<M>>::VERSION {
+            <A as RemoteMessage<M>>::upcast(version, msg, codec).transpose()
+        } else {
+            Ok(None)
+        }
+    };
+
+    match decode() {
+        Ok(Some(msg)) => Ok(msg),
+        Ok(None) => Err(RemoteSendError::UnsupportedMessageVersion {
+            message_remote_id: Cow::Borrowed(<A as RemoteMessage<M>>::REMOTE_ID),
+            version,
+            current_version: <A as RemoteMessage<M>>::VERSION,
+        }),
+        Err(err) => Err(RemoteSendError::DeserializeMessage(err.to_string())),
+    }
+}
+
 pub async fn link<A>(
     actor_id: ActorId,
     sibbling_id: ActorId,
@@ -221,18 +294,22 @@ where
     A: Actor,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
 
+    // The link is notified through the swarm the request was received from
+    let swarm = SwarmSender::current()
+        .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+        .swarm();
     actor_ref
         .links
         .lock()
         .await
-        .insert(sibbling_id, Link::Remote(sibbling_remote_id));
+        .insert(sibbling_id, Link::Remote(sibbling_remote_id, swarm));
 
     Ok(())
 }
@@ -245,9 +322,9 @@ where
     A: Actor,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&actor_id)
+            .get(&actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
@@ -266,9 +343,9 @@ where
     A: Actor,
 {
     let actor_ref = {
-        let remote_actors = REMOTE_REGISTRY.lock().await;
+        let remote_actors = REMOTE_REGISTRY.lock().unwrap();
         remote_actors
-            .get(&notified_actor_id)
+            .get(&notified_actor_id.into_local())
             .ok_or(RemoteSendError::ActorNotRunning)?
             .downcast::<A>()?
     };
@@ -280,3 +357,251 @@ where
 
     Ok(())
 }
+
+pub async fn spawn<A>(
+    args: Vec<u8>,
+    codec: CodecId,
+    link: Option<(ActorId, Cow<'static, str>)>,
+) -> Result<ActorId, RemoteSendError<Infallible>>
+where
+    A: Actor + RemoteActor,
+    A::Args: DeserializeOwned,
+{
+    let args: A::Args = codec
+        .decode(&args)
+        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()))?;
+    let link = match link {
+        Some((sibbling_id, sibbling_remote_id)) => {
+            let swarm = SwarmSender::current()
+                .ok_or(RemoteSendError::SwarmNotBootstrapped)?
+                .swarm();
+            Some((sibbling_id, Link::Remote(sibbling_remote_id, swarm)))
+        }
+        None => None,
+    };
+
+    // Link and register the actor before it starts, so an actor which stops immediately still
+    // notifies its link and unregisters itself
+    let prepared_actor = A::prepare();
+    let actor_ref = prepared_actor.actor_ref().clone();
+    if let Some((sibbling_id, link)) = link {
+        actor_ref.links.lock().await.insert(sibbling_id, link);
+    }
+
+    let actor_id = actor_ref.id();
+    REMOTE_REGISTRY
+        .lock()
+        .unwrap()
+        .insert(actor_id, RemoteRegistryActorRef::new(actor_ref, None));
+    prepared_actor.spawn(args);
+
+    Ok(actor_id)
+}
+
+/// Rejects spawn requests for actors which don't opt in with `#[remote_actor(spawnable)]`.
+pub async fn spawn_unsupported<A>(
+    _args: Vec<u8>,
+    _codec: CodecId,
+    _link: Option<(ActorId, Cow<'static, str>)>,
+) -> Result<ActorId, RemoteSendError<Infallible>>
+where
+    A: RemoteActor,
+{
+    Err(RemoteSendError::SpawnUnsupported {
+        actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use std::{error, time::Duration};
+
+    use tokio::{sync::mpsc, time};
+
+    use crate::{
+        actor::{ActorRef, Spawn},
+        remote::{
+            self,
+            testing::{
+                Cluster, POLL_INTERVAL,
+                fixtures::{Get, NotSpawnable, Spawned, Watcher},
+                register_remote_actor,
+            },
+        },
+    };
+
+    use super::*;
+
+    struct Versioned;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct MsgV1 {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Msg {
+        name: String,
+        amount: u32,
+    }
+
+    impl From<MsgV1> for Msg {
+        fn from(msg: MsgV1) -> Self {
+            Msg {
+                name: msg.name,
+                amount: 0,
+            }
+        }
+    }
+
+    impl RemoteMessage<Msg> for Versioned {
+        const REMOTE_ID: &'static str = "msg";
+        const VERSION: u32 = 3;
+        const UPCAST_VERSIONS: &'static [u32] = &[1];
+
+        fn upcast(version: u32, payload: &[u8], codec: CodecId) -> Option<Result<Msg, CodecError>> {
+            match version {
+                1 => Some(codec.decode::<MsgV1>(payload).map(Msg::from)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn decode_current_version() {
+        let msg = Msg {
+            name: "kameo".to_string(),
+            amount: 42,
+        };
+        let payload = CodecId::MessagePack.encode(&msg).unwrap();
+        let decoded =
+            decode_message::<Versioned, Msg, Infallible>(&payload, CodecId::MessagePack, 3);
+        assert_eq!(decoded.unwrap(), msg);
+    }
+
+    #[test]
+    fn decode_upcasts_old_version() {
+        let payload = CodecId::MessagePack
+            .encode(&MsgV1 {
+                name: "kameo".to_string(),
+            })
+            .unwrap();
+        let decoded =
+            decode_message::<Versioned, Msg, Infallible>(&payload, CodecId::MessagePack, 1);
+        assert_eq!(
+            decoded.unwrap(),
+            Msg {
+                name: "kameo".to_string(),
+                amount: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn decode_rejects_unknown_versions() {
+        let payload = CodecId::MessagePack.encode(&()).unwrap();
+        for version in [2, 4] {
+            let decoded = decode_message::<Versioned, Msg, Infallible>(
+                &payload,
+                CodecId::MessagePack,
+                version,
+            );
+            assert!(matches!(
+                decoded,
+                Err(RemoteSendError::UnsupportedMessageVersion {
+                    message_remote_id,
+                    version: found,
+                    current_version: 3,
+                }) if message_remote_id == "msg" && found == version
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn spawn_requires_opt_in() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+        let (swarm, peer_id) = (cluster.node(1).swarm(), cluster.node(0).peer_id());
+
+        let spawned = remote::spawn_in::<Spawned>(swarm, peer_id, Spawned { value: 7 }).await?;
+        assert_eq!(spawned.id().peer_id(), Some(&peer_id));
+        assert_eq!(spawned.ask(&Get).await?, 7);
+
+        let err = remote::spawn_in::<NotSpawnable>(swarm, peer_id, NotSpawnable)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteSendError::SpawnUnsupported { .. }));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spawn_link_stops_spawned_actor_with_link() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let (swarm, peer_id) = (cluster.node(1).swarm(), cluster.node(0).peer_id());
+
+        let watcher = Watcher::spawn(Watcher { link_died: None });
+        let err = remote::spawn_link_in::<NotSpawnable, _>(swarm, peer_id, NotSpawnable, &watcher)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteSendError::SpawnUnsupported { .. }));
+        assert!(watcher.links.lock().await.is_empty());
+        assert!(!REMOTE_REGISTRY.lock().unwrap().contains_key(&watcher.id()));
+
+        let spawned =
+            remote::spawn_link_in::<Spawned, _>(swarm, peer_id, Spawned { value: 1 }, &watcher)
+                .await?;
+        assert_eq!(watcher.links.lock().await.len(), 1);
+        assert_eq!(spawned.ask(&Get).await?, 1);
+
+        watcher.kill();
+        watcher.wait_for_shutdown().await;
+        time::timeout(Duration::from_secs(5), async {
+            while !matches!(
+                spawned.ask(&Get).await,
+                Err(RemoteSendError::ActorNotRunning)
+            ) {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct FailsToStart;
+
+    impl Actor for FailsToStart {
+        type Args = Self;
+        type Error = String;
+
+        async fn on_start(
+            _state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Err("failed to start".to_string())
+        }
+    }
+
+    register_remote_actor!(FailsToStart, spawnable);
+
+    #[tokio::test]
+    async fn spawn_link_notifies_link_when_on_start_fails() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+        let (swarm, peer_id) = (cluster.node(1).swarm(), cluster.node(0).peer_id());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = Watcher::spawn(Watcher {
+            link_died: Some(tx),
+        });
+        remote::spawn_link_in::<FailsToStart, _>(swarm, peer_id, FailsToStart, &watcher).await?;
+
+        let reason = time::timeout(Duration::from_secs(5), rx.recv()).await?;
+        assert!(matches!(reason, Some(ActorStopReason::Panicked(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+//! Access control for remote actors and messages.
+//!
+//! By default, any peer which can connect to a node can send any registered remote message to any
+//! actor registered on the node. An [`AccessPolicy`] restricts this by deciding whether a peer is
+//! allowed to send a message to an actor, based on the peer ID, the actor's remote ID, and the
+//! message's remote ID.
+//!
+//! The policy is checked for every incoming ask, tell, and stream request before the message is
+//! decoded, and for every request to spawn, link, unlink, resolve an actor, or check its
+//! registration. Denied requests fail with [`RemoteSendError::Unauthorized`], and are reported
+//! with a [`messaging::Event::AccessDenied`] event for audit logging. The capabilities advertised
+//! to a peer when it connects only list the actors and messages the policy allows it to use.
+//!
+//! Requests which don't carry a message are checked with a reserved message remote ID naming the
+//! kind of request, such as [`SPAWN`] or [`LINK`], allowing rules to match them with
+//! [`Rule::message`]. Note that a policy denying requests by default must allow
+//! [`SIGNAL_LINK_DIED`] for links with actors on other peers to be notified when they stop, and
+//! [`CHECK_REGISTRATION`] for the peer's lookups to return its actors.
+//!
+//! # Configuration
+//!
+//! A policy is configured with [`messaging::Config::with_access_policy`], either as an [`Acl`] of
+//! allow and deny rules, or as any type implementing [`AccessPolicy`], including closures:
+//!
+//! ```ignore
+//! let acl = Acl::deny_by_default()
+//!     .allow(Rule::any().actor("my_actor"))
+//!     .deny(Rule::peer(untrusted_peer_id));
+//!
+//! let config = messaging::Config::default().with_access_policy(acl);
+//! ```
+//!
+//! [`RemoteSendError::Unauthorized`]: crate::error::RemoteSendError::Unauthorized
+//! [`messaging::Event::AccessDenied`]: super::messaging::Event::AccessDenied
+//! [`messaging::Config::with_access_policy`]: super::messaging::Config::with_access_policy
+
+use std::borrow::Cow;
+
+use libp2p::PeerId;
+
+/// A request from a peer to send a message to a local actor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessRequest<'a> {
+    /// The peer sending the message.
+    pub peer_id: PeerId,
+    /// The remote ID of the actor receiving the message.
+    pub actor_remote_id: &'a str,
+    /// The remote ID of the message, or one of the reserved IDs such as [`SPAWN`] for requests
+    /// which don't carry a message.
+    pub message_remote_id: &'a str,
+}
+
+/// The message remote ID checked for requests to spawn an actor.
+pub const SPAWN: &str = "$spawn";
+
+/// The message remote ID checked for requests to link an actor with a remote actor.
+pub const LINK: &str = "$link";
+
+/// The message remote ID checked for requests to unlink an actor from a remote actor.
+pub const UNLINK: &str = "$unlink";
+
+/// The message remote ID checked for notifications that an actor linked to a local actor died.
+pub const SIGNAL_LINK_DIED: &str = "$signal_link_died";
+
+/// The message remote ID checked for requests to resolve a sharded entity.
+pub const RESOLVE_ENTITY: &str = "$resolve_entity";
+
+/// The message remote ID checked for requests to resolve a cluster singleton.
+pub const RESOLVE_SINGLETON: &str = "$resolve_singleton";
+
+/// The message remote ID checked for requests asking whether an actor is still registered under a
+/// name, made by lookups before returning the actor.
+pub const CHECK_REGISTRATION: &str = "$check_registration";
+
+/// A policy deciding which peers are allowed to send messages to local actors.
+///
+/// This trait is implemented for [`Acl`], and for closures taking an [`AccessRequest`] and
+/// returning whether the request is allowed.
+pub trait AccessPolicy: Send + Sync + 'static {
+    /// Returns whether the request is allowed.
+    fn is_allowed(&self, request: &AccessRequest<'_>) -> bool;
+}
+
+impl<F> AccessPolicy for F
+where
+    F: Fn(&AccessRequest<'_>) -> bool + Send + Sync + 'static,
+{
+    fn is_allowed(&self, request: &AccessRequest<'_>) -> bool {
+        self(request)
+    }
+}
+
+/// A rule matching requests by peer ID, actor remote ID, and message remote ID.
+///
+/// Fields which aren't set match any value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rule {
+    peer_id: Option<PeerId>,
+    actor_remote_id: Option<Cow<'static, str>>,
+    message_remote_id: Option<Cow<'static, str>>,
+}
+
+impl Rule {
+    /// Creates a rule matching any request.
+    pub fn any() -> Self {
+        Rule::default()
+    }
+
+    /// Creates a rule matching requests from a peer.
+    pub fn peer(peer_id: PeerId) -> Self {
+        Rule::any().with_peer(peer_id)
+    }
+
+    /// Restricts the rule to requests from a peer.
+    pub fn with_peer(mut self, peer_id: PeerId) -> Self {
+        self.peer_id = Some(peer_id);
+        self
+    }
+
+    /// Restricts the rule to requests sent to an actor with the remote ID.
+    pub fn actor(mut self, actor_remote_id: impl Into<Cow<'static, str>>) -> Self {
+        self.actor_remote_id = Some(actor_remote_id.into());
+        self
+    }
+
+    /// Restricts the rule to messages with the remote ID.
+    pub fn message(mut self, message_remote_id: impl Into<Cow<'static, str>>) -> Self {
+        self.message_remote_id = Some(message_remote_id.into());
+        self
+    }
+
+    /// Returns whether the rule matches the request.
+    pub fn matches(&self, request: &AccessRequest<'_>) -> bool {
+        self.peer_id
+            .is_none_or(|peer_id| peer_id == request.peer_id)
+            && self
+                .actor_remote_id
+                .as_deref()
+                .is_none_or(|id| id == request.actor_remote_id)
+            && self
+                .message_remote_id
+                .as_deref()
+                .is_none_or(|id| id == request.message_remote_id)
+    }
+}
+
+/// Whether a [`Rule`] allows or denies the requests it matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// The request is allowed.
+    Allow,
+    /// The request is denied.
+    Deny,
+}
+
+/// An access control list of allow and deny rules.
+///
+/// Rules are checked in the order they were added, and the first matching rule decides whether a
+/// request is allowed. Requests matching no rule fall back to the list's default decision.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Acl {
+    default: Decision,
+    rules: Vec<(Decision, Rule)>,
+}
+
+impl Acl {
+    /// Creates an access control list allowing requests which match no rule.
+    pub fn allow_by_default() -> Self {
+        Acl {
+            default: Decision::Allow,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Creates an access control list denying requests which match no rule.
+    pub fn deny_by_default() -> Self {
+        Acl {
+            default: Decision::Deny,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Adds a rule allowing the requests it matches.
+    pub fn allow(mut self, rule: Rule) -> Self {
+        self.rules.push((Decision::Allow, rule));
+        self
+    }
+
+    /// Adds a rule denying the requests it matches.
+    pub fn deny(mut self, rule: Rule) -> Self {
+        self.rules.push((Decision::Deny, rule));
+        self
+    }
+
+    /// Returns the decision for a request.
+    pub fn decide(&self, request: &AccessRequest<'_>) -> Decision {
+        self.rules
+            .iter()
+            .find(|(_, rule)| rule.matches(request))
+            .map(|(decision, _)| *decision)
+            .unwrap_or(self.default)
+    }
+}
+
+impl AccessPolicy for Acl {
+    fn is_allowed(&self, request: &AccessRequest<'_>) -> bool {
+        self.decide(request) == Decision::Allow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, time::Duration};
+
+    use libp2p::identity::Keypair;
+    use tokio::time;
+
+    use crate::{
+        actor::{RemoteActorRef, Spawn},
+        error::RemoteSendError,
+        remote::{
+            self, messaging,
+            testing::{
+                Cluster, POLL_INTERVAL,
+                fixtures::{Get, Spawned, Target, Watcher},
+            },
+        },
+    };
+
+    use super::*;
+
+    fn request(
+        peer_id: PeerId,
+        actor: &'static str,
+        message: &'static str,
+    ) -> AccessRequest<'static> {
+        AccessRequest {
+            peer_id,
+            actor_remote_id: actor,
+            message_remote_id: message,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let trusted = PeerId::random();
+        let untrusted = PeerId::random();
+        let acl = Acl::deny_by_default()
+            .deny(Rule::peer(untrusted))
+            .allow(Rule::any().actor("counter").message("inc"))
+            .allow(Rule::peer(trusted));
+
+        assert!(acl.is_allowed(&request(trusted, "counter", "reset")));
+        assert!(acl.is_allowed(&request(PeerId::random(), "counter", "inc")));
+        assert!(!acl.is_allowed(&request(PeerId::random(), "counter", "reset")));
+        assert!(!acl.is_allowed(&request(untrusted, "counter", "inc")));
+    }
+
+    #[test]
+    fn default_decision_applies_without_match() {
+        let peer_id = PeerId::random();
+        assert!(Acl::allow_by_default().is_allowed(&request(peer_id, "a", "b")));
+        assert!(!Acl::deny_by_default().is_allowed(&request(peer_id, "a", "b")));
+    }
+
+    #[tokio::test]
+    async fn denied_peer_cannot_spawn_or_link() -> Result<(), Box<dyn error::Error>> {
+        let keypairs: Vec<_> = (0..3).map(|_| Keypair::generate_ed25519()).collect();
+        let denied = keypairs[1].public().to_peer_id();
+        let cluster = Cluster::builder()
+            .nodes(3)
+            .keypairs(keypairs)
+            .messaging_config(
+                messaging::Config::default()
+                    .with_access_policy(Acl::allow_by_default().deny(Rule::peer(denied))),
+            )
+            .start()
+            .await?;
+        let peer_id = cluster.node(0).peer_id();
+
+        let err =
+            remote::spawn_in::<Spawned>(cluster.node(1).swarm(), peer_id, Spawned { value: 1 })
+                .await
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            RemoteSendError::Unauthorized { message_remote_id, .. } if message_remote_id == SPAWN
+        ));
+
+        let target = Target::spawn(Target);
+        cluster.node(0).register(&target, "target").await?;
+        let remote_target = RemoteActorRef::<Target>::new(
+            cluster.node(0).actor_id(&target),
+            cluster.node(1).swarm().actor_swarm().sender().clone(),
+        );
+        let watcher = Watcher::spawn(Watcher { link_died: None });
+        watcher
+            .register_in(cluster.node(1).swarm(), "watcher")
+            .await?;
+        let remote_watcher = RemoteActorRef::<Watcher>::new(
+            cluster.node(1).actor_id(&watcher),
+            cluster.node(1).swarm().actor_swarm().sender().clone(),
+        );
+        let err = remote_watcher
+            .link_remote(&remote_target)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RemoteSendError::Unauthorized { message_remote_id, .. } if message_remote_id == LINK
+        ));
+
+        // Peers which aren't denied can still spawn actors
+        let spawned =
+            remote::spawn_in::<Spawned>(cluster.node(2).swarm(), peer_id, Spawned { value: 2 })
+                .await?;
+        assert_eq!(spawned.ask(&Get).await?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn denied_peer_cannot_check_registrations_or_see_capabilities()
+    -> Result<(), Box<dyn error::Error>> {
+        let keypairs: Vec<_> = (0..3).map(|_| Keypair::generate_ed25519()).collect();
+        let denied = keypairs[1].public().to_peer_id();
+        let cluster = Cluster::builder()
+            .nodes(3)
+            .keypairs(keypairs)
+            .messaging_config(
+                messaging::Config::default()
+                    .with_access_policy(Acl::allow_by_default().deny(Rule::peer(denied))),
+            )
+            .start()
+            .await?;
+        let peer_id = cluster.node(0).peer_id();
+
+        let target = Target::spawn(Target);
+        cluster.node(0).register(&target, "target").await?;
+        cluster.wait_for_lookup::<Target>(2, "target", 1).await?;
+        assert!(cluster.node(1).lookup::<Target>("target").await?.is_none());
+
+        let capabilities = time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(capabilities) = cluster.node(1).swarm().peer_capabilities(peer_id).await
+                {
+                    return capabilities;
+                }
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+        assert!(capabilities.actors.is_empty());
+        assert!(capabilities.messages.is_empty());
+        assert_eq!(
+            cluster
+                .node(2)
+                .swarm()
+                .peer_capabilities(peer_id)
+                .await
+                .as_ref(),
+            Some(messaging::PeerCapabilities::local())
+        );
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::task;
+use std::{collections::HashSet, task};
 
 use either::Either;
 use futures::{StreamExt, stream::FuturesUnordered};
@@ -13,18 +13,24 @@ use libp2p::{
 };
 use tokio::sync::mpsc;
 
-use crate::error::{ActorStopReason, SwarmAlreadyBootstrappedError};
+use crate::{
+    actor::Link,
+    error::{ActorStopReason, PeerNotAllowedError, RegistryError, SwarmAlreadyBootstrappedError},
+};
 
 use super::{
-    ActorSwarm, REMOTE_REGISTRY, RemoteRegistryActorRef, SwarmCommand, messaging, registry,
+    ActorSwarm, REMOTE_REGISTRY, SwarmCommand, SwarmHandle, heartbeat, membership, messaging,
+    registry, streaming,
 };
 
 /// A network behaviour that combines messaging and registry capabilities for remote actor communication.
 ///
 /// This behaviour integrates Kameo's remote actor functionality with libp2p, providing:
-/// - Actor registration and discovery through a Kademlia-based registry
+/// - Actor registration and discovery through a pluggable registry (Kademlia by default)
 /// - Remote message passing between actors across the network
 /// - Automatic lifecycle management for remote connections
+/// - Heartbeat based failure detection of peers with linked actors
+/// - Cluster membership of the kameo nodes connected to the swarm
 ///
 /// # Example
 ///
@@ -47,7 +53,18 @@ pub struct Behaviour {
     pub messaging: messaging::Behaviour,
     /// Registry behaviour for actor registration and discovery.
     pub registry: registry::Behaviour,
+    /// Streaming behaviour for delivering streams attached to remote actors.
+    pub streaming: streaming::Behaviour,
+    /// Heartbeat behaviour for detecting unreachable peers.
+    pub heartbeat: heartbeat::Behaviour,
+    /// Membership behaviour for tracking the nodes in the cluster.
+    pub membership: membership::Behaviour,
     local_peer_id: PeerId,
+    lookup_cache: Option<registry::LookupCache>,
+    allowed_peers: Option<HashSet<PeerId>>,
+    /// Peers whose links were notified as unreachable, which aren't notified again once their
+    /// connections are closed.
+    unreachable_peers: HashSet<PeerId>,
     cmd_tx: mpsc::UnboundedSender<SwarmCommand>,
     cmd_rx: mpsc::UnboundedReceiver<SwarmCommand>,
 }
@@ -75,13 +92,175 @@ impl Behaviour {
 
         let messaging = messaging::Behaviour::new(local_peer_id, messaging_config);
         let registry = registry::Behaviour::new(local_peer_id);
+        let mut streaming = streaming::Behaviour::new(local_peer_id, streaming::Config::default());
+        streaming.set_access_policy(messaging.access_policy());
+        let heartbeat = heartbeat::Behaviour::new(heartbeat::Config::default());
+        let membership = membership::Behaviour::new(local_peer_id, membership::Config::default());
 
-        Behaviour {
+        let mut behaviour = Behaviour {
             messaging,
             registry,
+            streaming,
+            heartbeat,
+            membership,
             local_peer_id,
+            lookup_cache: None,
+            allowed_peers: None,
+            unreachable_peers: HashSet::new(),
             cmd_tx,
             cmd_rx,
+        };
+        behaviour.bind_swarm();
+        behaviour
+    }
+
+    /// Sets the configuration for streams attached to remote actors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_streaming_config(remote::streaming::Config::default().with_window(8));
+    /// ```
+    pub fn with_streaming_config(mut self, streaming_config: streaming::Config) -> Self {
+        self.streaming = streaming::Behaviour::new(self.local_peer_id, streaming_config);
+        self.streaming
+            .set_access_policy(self.messaging.access_policy());
+        self.bind_swarm();
+        self
+    }
+
+    /// Sets the configuration for heartbeats sent to cluster members and peers with linked actors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_heartbeat_config(
+    ///         remote::heartbeat::Config::default()
+    ///             .with_interval(Duration::from_millis(500))
+    ///             .with_detector(remote::heartbeat::Detector::Timeout(Duration::from_secs(5))),
+    ///     );
+    /// ```
+    pub fn with_heartbeat_config(mut self, heartbeat_config: heartbeat::Config) -> Self {
+        self.heartbeat = heartbeat::Behaviour::new(heartbeat_config);
+        self
+    }
+
+    /// Sets the configuration for cluster membership, such as the roles of the local node.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_membership_config(remote::membership::Config::default().with_roles(["worker"]));
+    /// ```
+    pub fn with_membership_config(mut self, membership_config: membership::Config) -> Self {
+        self.membership = membership::Behaviour::new(self.local_peer_id, membership_config);
+        self
+    }
+
+    /// Sets the backend used by the registry for actor registration and discovery.
+    ///
+    /// Defaults to [`registry::Backend::Kademlia`]. Returns an error if the backend could not be
+    /// set up.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_registry_backend(remote::registry::Backend::Gossip(
+    ///         remote::registry::GossipConfig::default(),
+    ///     ))?;
+    /// # Ok::<(), kameo::error::RegistryError>(())
+    /// ```
+    pub fn with_registry_backend(
+        mut self,
+        backend: registry::Backend,
+    ) -> Result<Self, RegistryError> {
+        self.registry = registry::Behaviour::with_backend(self.local_peer_id, backend)?;
+        Ok(self)
+    }
+
+    /// Enables caching of lookup results on this node.
+    ///
+    /// Without a cache, every lookup queries the registry. With a cache, actors found by a
+    /// completed lookup are returned by later lookups of the same name until they expire, or
+    /// are invalidated by a registry change or a failed send to a cached actor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_lookup_cache(remote::registry::LookupCacheConfig::default());
+    /// ```
+    pub fn with_lookup_cache(mut self, config: registry::LookupCacheConfig) -> Self {
+        self.lookup_cache = Some(registry::LookupCache::new(config));
+        self.bind_swarm();
+        self
+    }
+
+    /// Restricts connections to an allowlist of peers.
+    ///
+    /// Connections with peers outside of the allowlist are denied with a [`PeerNotAllowedError`]
+    /// once the peer's identity is known, which is after the security handshake for inbound
+    /// connections, and before dialing for outbound connections to a known peer.
+    /// By default, connections with any peer are allowed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let trusted_peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default())
+    ///     .with_allowed_peers([trusted_peer_id]);
+    /// ```
+    pub fn with_allowed_peers(mut self, peers: impl IntoIterator<Item = PeerId>) -> Self {
+        self.allowed_peers = Some(peers.into_iter().collect());
+        self
+    }
+
+    /// Returns whether connections with a peer are allowed.
+    pub fn is_peer_allowed(&self, peer_id: &PeerId) -> bool {
+        *peer_id == self.local_peer_id
+            || self
+                .allowed_peers
+                .as_ref()
+                .is_none_or(|allowed_peers| allowed_peers.contains(peer_id))
+    }
+
+    fn check_peer_allowed(&self, peer_id: PeerId) -> Result<(), ConnectionDenied> {
+        if self.is_peer_allowed(&peer_id) {
+            Ok(())
+        } else {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%peer_id, "denied connection with peer outside of allowlist");
+            Err(ConnectionDenied::new(PeerNotAllowedError { peer_id }))
         }
     }
 
@@ -123,11 +302,47 @@ impl Behaviour {
     /// This method will return an error if `init_global()` has already been called
     /// on another `Behaviour` instance in the same process.
     pub fn try_init_global(&self) -> Result<(), SwarmAlreadyBootstrappedError> {
-        ActorSwarm::set(self.cmd_tx.clone(), self.local_peer_id)
-            .map_err(|_| SwarmAlreadyBootstrappedError)?;
+        ActorSwarm::set(self.actor_swarm()).map_err(|_| SwarmAlreadyBootstrappedError)?;
         Ok(())
     }
 
+    /// Returns a handle to the actor swarm for this behaviour.
+    ///
+    /// The handle can be used to register and look up actors in this swarm without initializing
+    /// it as the global actor swarm, allowing multiple independent swarms in one process.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = remote::Behaviour::new(peer_id, remote::messaging::Config::default());
+    /// let swarm = behaviour.swarm_handle();
+    /// assert_eq!(swarm.peer_id(), peer_id);
+    /// ```
+    pub fn swarm_handle(&self) -> SwarmHandle {
+        SwarmHandle::new(self.actor_swarm())
+    }
+
+    fn actor_swarm(&self) -> ActorSwarm {
+        ActorSwarm::new(
+            self.cmd_tx.clone(),
+            self.local_peer_id,
+            self.messaging.codec(),
+            self.streaming.config(),
+            self.lookup_cache.clone(),
+        )
+    }
+
+    /// Binds actor refs within received messages to this swarm, rather than the global swarm.
+    fn bind_swarm(&mut self) {
+        let swarm = self.actor_swarm();
+        self.messaging.set_swarm(swarm.sender().clone());
+        self.streaming.set_swarm(swarm.sender().clone());
+    }
+
     fn handle_command(&mut self, cmd: SwarmCommand) -> bool {
         match cmd {
             SwarmCommand::Lookup { name, reply } => {
@@ -148,7 +363,7 @@ impl Behaviour {
             {
                 Ok(_) => true, // We started a new lookup
                 Err((Some(reply), err)) => {
-                    let _ = reply.send(Err(err.into()));
+                    let _ = reply.send(Err(err));
                     false
                 }
                 Err((None, _)) => unreachable!("we should have the reply type here"),
@@ -156,26 +371,45 @@ impl Behaviour {
             SwarmCommand::Unregister { name, reply } => {
                 self.registry.unregister(&name);
                 let _ = reply.send(());
-                false // We only delete the actor from our local registry
+                true // The registry backend may need to notify other peers
+            }
+            SwarmCommand::UnregisterActor {
+                name,
+                actor_id,
+                reply,
+            } => {
+                let unregistered = self.registry.unregister_actor(&name, actor_id);
+                let _ = reply.send(());
+                unregistered
+            }
+            SwarmCommand::Watch { name, reply } => {
+                self.registry.watch_with_reply(name, reply);
+                true // The registry backend may have started a lookup
             }
             SwarmCommand::Ask {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
                 reply,
             } => {
                 self.messaging.ask_with_reply(
                     actor_id,
                     actor_remote_id,
                     message_remote_id,
+                    version,
                     payload,
+                    codec,
                     mailbox_timeout,
                     reply_timeout,
                     immediate,
+                    sender_id,
                     Some(reply),
                 );
                 true
@@ -184,18 +418,48 @@ impl Behaviour {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 immediate,
+                sender_id,
                 reply,
             } => {
                 self.messaging.tell_with_reply(
                     actor_id,
                     actor_remote_id,
                     message_remote_id,
+                    version,
                     payload,
+                    codec,
                     mailbox_timeout,
                     immediate,
+                    sender_id,
+                    reply,
+                );
+                true
+            }
+            SwarmCommand::ReliableTell {
+                actor_id,
+                actor_remote_id,
+                message_remote_id,
+                version,
+                payload,
+                codec,
+                mailbox_timeout,
+                sender_id,
+                reply,
+            } => {
+                self.messaging.reliable_tell_with_reply(
+                    actor_id,
+                    actor_remote_id,
+                    message_remote_id,
+                    version,
+                    payload,
+                    codec,
+                    mailbox_timeout,
+                    sender_id,
                     reply,
                 );
                 true
@@ -246,6 +510,81 @@ impl Behaviour {
                 );
                 true
             }
+            SwarmCommand::Spawn {
+                peer_id,
+                actor_remote_id,
+                args,
+                codec,
+                link,
+                reply,
+            } => {
+                self.messaging.spawn_with_reply(
+                    peer_id,
+                    actor_remote_id,
+                    args,
+                    codec,
+                    link,
+                    Some(reply),
+                );
+                true
+            }
+            SwarmCommand::ResolveEntity {
+                peer_id,
+                actor_remote_id,
+                entity_id,
+                reply,
+            } => {
+                self.messaging.resolve_entity_with_reply(
+                    peer_id,
+                    actor_remote_id,
+                    entity_id,
+                    Some(reply),
+                );
+                true
+            }
+            SwarmCommand::ResolveSingleton {
+                peer_id,
+                actor_remote_id,
+                reply,
+            } => {
+                self.messaging
+                    .resolve_singleton_with_reply(peer_id, actor_remote_id, Some(reply));
+                true
+            }
+            SwarmCommand::CheckRegistration {
+                actor_id,
+                actor_remote_id,
+                name,
+                reply,
+            } => {
+                self.messaging.check_registration_with_reply(
+                    actor_id,
+                    actor_remote_id,
+                    name,
+                    Some(reply),
+                );
+                true
+            }
+            SwarmCommand::PeerCapabilities { peer_id, reply } => {
+                let _ = reply.send(self.messaging.peer_capabilities(&peer_id).cloned());
+                false
+            }
+            SwarmCommand::StreamBatch { request, reply } => {
+                self.streaming.send_batch(request, reply);
+                true
+            }
+            SwarmCommand::Members { reply } => {
+                let _ = reply.send(self.membership.members());
+                false
+            }
+            SwarmCommand::SubscribeMembers { tx } => {
+                self.membership.subscribe(tx);
+                false
+            }
+            SwarmCommand::Leave { reply } => {
+                self.membership.leave_with_reply(reply);
+                true
+            }
         }
     }
 }
@@ -288,11 +627,37 @@ pub enum Event {
     /// These events relate to actor registration and discovery operations,
     /// including successful registrations, lookup results, and network topology changes.
     Registry(registry::Event),
+
+    /// An event from the streaming subsystem.
+    ///
+    /// These events relate to streams attached to local actors by remote peers.
+    Streaming(streaming::Event),
+
+    /// An event from the heartbeat subsystem.
+    ///
+    /// These events relate to the reachability of peers with linked actors.
+    Heartbeat(heartbeat::Event),
+
+    /// An event from the membership subsystem.
+    ///
+    /// These events relate to nodes joining and leaving the cluster.
+    Membership(membership::MemberEvent),
 }
 
 impl NetworkBehaviour for Behaviour {
-    type ConnectionHandler =
-        ConnectionHandlerSelect<THandler<messaging::Behaviour>, THandler<registry::Behaviour>>;
+    type ConnectionHandler = ConnectionHandlerSelect<
+        ConnectionHandlerSelect<
+            ConnectionHandlerSelect<
+                ConnectionHandlerSelect<
+                    THandler<messaging::Behaviour>,
+                    THandler<registry::Behaviour>,
+                >,
+                THandler<streaming::Behaviour>,
+            >,
+            THandler<heartbeat::Behaviour>,
+        >,
+        THandler<membership::Behaviour>,
+    >;
     type ToSwarm = Event;
 
     fn handle_pending_inbound_connection(
@@ -307,6 +672,18 @@ impl NetworkBehaviour for Behaviour {
         self.registry
             .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
 
+        self.streaming
+            .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
+
+        self.heartbeat
+            .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
+
+        self.membership.handle_pending_inbound_connection(
+            connection_id,
+            local_addr,
+            remote_addr,
+        )?;
+
         Ok(())
     }
 
@@ -317,14 +694,40 @@ impl NetworkBehaviour for Behaviour {
         local_addr: &Multiaddr,
         remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.check_peer_allowed(peer)?;
+
         Ok(ConnectionHandler::select(
-            self.messaging.handle_established_inbound_connection(
-                connection_id,
-                peer,
-                local_addr,
-                remote_addr,
-            )?,
-            self.registry.handle_established_inbound_connection(
+            ConnectionHandler::select(
+                ConnectionHandler::select(
+                    ConnectionHandler::select(
+                        self.messaging.handle_established_inbound_connection(
+                            connection_id,
+                            peer,
+                            local_addr,
+                            remote_addr,
+                        )?,
+                        self.registry.handle_established_inbound_connection(
+                            connection_id,
+                            peer,
+                            local_addr,
+                            remote_addr,
+                        )?,
+                    ),
+                    self.streaming.handle_established_inbound_connection(
+                        connection_id,
+                        peer,
+                        local_addr,
+                        remote_addr,
+                    )?,
+                ),
+                self.heartbeat.handle_established_inbound_connection(
+                    connection_id,
+                    peer,
+                    local_addr,
+                    remote_addr,
+                )?,
+            ),
+            self.membership.handle_established_inbound_connection(
                 connection_id,
                 peer,
                 local_addr,
@@ -340,6 +743,10 @@ impl NetworkBehaviour for Behaviour {
         addresses: &[Multiaddr],
         effective_role: Endpoint,
     ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        if let Some(peer) = maybe_peer {
+            self.check_peer_allowed(peer)?;
+        }
+
         let mut combined_addresses = Vec::new();
 
         combined_addresses.extend(self.messaging.handle_pending_outbound_connection(
@@ -356,6 +763,27 @@ impl NetworkBehaviour for Behaviour {
             effective_role,
         )?);
 
+        combined_addresses.extend(self.streaming.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )?);
+
+        combined_addresses.extend(self.heartbeat.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )?);
+
+        combined_addresses.extend(self.membership.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )?);
+
         Ok(combined_addresses)
     }
 
@@ -367,15 +795,44 @@ impl NetworkBehaviour for Behaviour {
         role_override: Endpoint,
         port_use: PortUse,
     ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.check_peer_allowed(peer)?;
+
         Ok(ConnectionHandler::select(
-            self.messaging.handle_established_outbound_connection(
-                connection_id,
-                peer,
-                addr,
-                role_override,
-                port_use,
-            )?,
-            self.registry.handle_established_outbound_connection(
+            ConnectionHandler::select(
+                ConnectionHandler::select(
+                    ConnectionHandler::select(
+                        self.messaging.handle_established_outbound_connection(
+                            connection_id,
+                            peer,
+                            addr,
+                            role_override,
+                            port_use,
+                        )?,
+                        self.registry.handle_established_outbound_connection(
+                            connection_id,
+                            peer,
+                            addr,
+                            role_override,
+                            port_use,
+                        )?,
+                    ),
+                    self.streaming.handle_established_outbound_connection(
+                        connection_id,
+                        peer,
+                        addr,
+                        role_override,
+                        port_use,
+                    )?,
+                ),
+                self.heartbeat.handle_established_outbound_connection(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    port_use,
+                )?,
+            ),
+            self.membership.handle_established_outbound_connection(
                 connection_id,
                 peer,
                 addr,
@@ -392,12 +849,21 @@ impl NetworkBehaviour for Behaviour {
         event: THandlerOutEvent<Self>,
     ) {
         match event {
-            Either::Left(ev) => {
-                self.messaging
+            Either::Left(Either::Left(Either::Left(Either::Left(ev)))) => self
+                .messaging
+                .on_connection_handler_event(peer_id, connection_id, ev),
+            Either::Left(Either::Left(Either::Left(Either::Right(ev)))) => self
+                .registry
+                .on_connection_handler_event(peer_id, connection_id, ev),
+            Either::Left(Either::Left(Either::Right(ev))) => self
+                .streaming
+                .on_connection_handler_event(peer_id, connection_id, ev),
+            Either::Left(Either::Right(ev)) => {
+                self.heartbeat
                     .on_connection_handler_event(peer_id, connection_id, ev)
             }
             Either::Right(ev) => {
-                self.registry
+                self.membership
                     .on_connection_handler_event(peer_id, connection_id, ev)
             }
         }
@@ -419,53 +885,169 @@ impl NetworkBehaviour for Behaviour {
 
         match self.messaging.poll(cx) {
             task::Poll::Ready(ev) => {
-                return task::Poll::Ready(ev.map_in(Either::Left).map_out(Event::Messaging));
+                return task::Poll::Ready(
+                    ev.map_in(|ev| Either::Left(Either::Left(Either::Left(Either::Left(ev)))))
+                        .map_out(Event::Messaging),
+                );
             }
             task::Poll::Pending => {}
         }
 
         match self.registry.poll(cx) {
             task::Poll::Ready(ev) => {
-                return task::Poll::Ready(ev.map_in(Either::Right).map_out(Event::Registry));
+                if let ToSwarm::GenerateEvent(registry::Event::RegistrationChanged { name, .. }) =
+                    &ev
+                    && let Some(lookup_cache) = &self.lookup_cache
+                {
+                    lookup_cache.invalidate_name(name);
+                }
+                return task::Poll::Ready(
+                    ev.map_in(|ev| Either::Left(Either::Left(Either::Left(Either::Right(ev)))))
+                        .map_out(Event::Registry),
+                );
             }
             task::Poll::Pending => {}
         }
 
-        task::Poll::Pending
-    }
+        match self.streaming.poll(cx) {
+            task::Poll::Ready(ev) => {
+                return task::Poll::Ready(
+                    ev.map_in(|ev| Either::Left(Either::Left(Either::Right(ev))))
+                        .map_out(Event::Streaming),
+                );
+            }
+            task::Poll::Pending => {}
+        }
 
-    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
-        if let FromSwarm::ConnectionClosed(ConnectionClosed { peer_id, .. }) = &event {
-            let peer_id = *peer_id;
-            tokio::spawn(async move {
-                let mut futures = FuturesUnordered::new();
-                for RemoteRegistryActorRef {
-                    signal_mailbox,
-                    links,
-                    ..
-                } in REMOTE_REGISTRY.lock().await.values()
+        match self.heartbeat.poll(cx) {
+            task::Poll::Ready(ev) => {
+                if let ToSwarm::GenerateEvent(heartbeat::Event::PeerUnreachable { peer, .. }) = &ev
+                    && self.unreachable_peers.insert(*peer)
                 {
-                    for linked_actor_id in (*links.lock().await).keys() {
-                        if linked_actor_id.peer_id() == Some(&peer_id) {
-                            let signal_mailbox = signal_mailbox.clone();
-                            let linked_actor_id = *linked_actor_id;
-                            futures.push(async move {
-                                signal_mailbox
-                                    .signal_link_died(
-                                        linked_actor_id,
-                                        ActorStopReason::PeerDisconnected,
-                                    )
-                                    .await
-                            });
-                        }
+                    notify_peer_links_died(
+                        self.local_peer_id,
+                        *peer,
+                        ActorStopReason::PeerUnreachable,
+                    );
+                }
+                return task::Poll::Ready(
+                    ev.map_in(|ev| Either::Left(Either::Right(ev)))
+                        .map_out(Event::Heartbeat),
+                );
+            }
+            task::Poll::Pending => {}
+        }
+
+        match self.membership.poll(cx) {
+            task::Poll::Ready(ev) => {
+                match &ev {
+                    ToSwarm::GenerateEvent(membership::MemberEvent::Up(member)) => {
+                        self.heartbeat.monitor(member.peer_id);
+                    }
+                    ToSwarm::GenerateEvent(membership::MemberEvent::Removed(member)) => {
+                        self.heartbeat.unmonitor(&member.peer_id);
+                        self.messaging.peer_removed(member.peer_id);
                     }
+                    _ => {}
                 }
+                return task::Poll::Ready(ev.map_in(Either::Right).map_out(Event::Membership));
+            }
+            task::Poll::Pending => {}
+        }
+
+        task::Poll::Pending
+    }
 
-                while (futures.next().await).is_some() {}
-            });
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = &event
+            && !self.unreachable_peers.remove(peer_id)
+        {
+            notify_peer_links_died(
+                self.local_peer_id,
+                *peer_id,
+                ActorStopReason::PeerDisconnected,
+            );
         }
 
         self.messaging.on_swarm_event(event);
         self.registry.on_swarm_event(event);
+        self.streaming.on_swarm_event(event);
+        self.heartbeat.on_swarm_event(event);
+        self.membership.on_swarm_event(event);
+    }
+}
+
+/// Signals every local actor linked to an actor on the peer that its link died.
+///
+/// Links made through another swarm in the process are skipped, as they don't depend on this
+/// swarm's connection to the peer.
+fn notify_peer_links_died(local_peer_id: PeerId, peer_id: PeerId, reason: ActorStopReason) {
+    tokio::spawn(async move {
+        // Links are locked after releasing the registry, as they can be held across remote requests
+        let entries: Vec<_> = REMOTE_REGISTRY
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| (entry.signal_mailbox.clone(), entry.links.clone()))
+            .collect();
+        let mut futures = FuturesUnordered::new();
+        for (signal_mailbox, links) in entries {
+            for (linked_actor_id, link) in links.lock().await.iter() {
+                let other_swarm = matches!(
+                    link,
+                    Link::Remote(_, swarm) if *swarm.local_peer_id() != local_peer_id
+                );
+                if linked_actor_id.peer_id() == Some(&peer_id) && !other_swarm {
+                    let signal_mailbox = signal_mailbox.clone();
+                    let linked_actor_id = *linked_actor_id;
+                    let reason = reason.clone();
+                    futures.push(async move {
+                        signal_mailbox
+                            .signal_link_died(linked_actor_id, reason)
+                            .await
+                    });
+                }
+            }
+        }
+
+        while (futures.next().await).is_some() {}
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, time::Duration};
+
+    use libp2p::identity::Keypair;
+
+    use crate::remote::testing::Cluster;
+
+    #[tokio::test]
+    async fn peers_outside_allowlist_are_denied() -> Result<(), Box<dyn error::Error>> {
+        let keypairs: Vec<_> = (0..3).map(|_| Keypair::generate_ed25519()).collect();
+        let allowed: Vec<_> = keypairs[..2]
+            .iter()
+            .map(|key| key.public().to_peer_id())
+            .collect();
+        let cluster = Cluster::builder()
+            .nodes(3)
+            .keypairs(keypairs)
+            .configure(move |behaviour| behaviour.with_allowed_peers(allowed.clone()))
+            .connected(false)
+            .timeout(Duration::from_secs(1))
+            .start()
+            .await?;
+
+        cluster.connect(0, 1).await?;
+        assert!(cluster.connect(0, 2).await.is_err());
+        assert!(cluster.connect(1, 2).await.is_err());
+        assert!(!cluster.node(0).is_connected(&cluster.node(2).peer_id()));
+        assert!(!cluster.node(2).is_connected(&cluster.node(0).peer_id()));
+
+        Ok(())
     }
 }
@@ -0,0 +1,704 @@
+//! Configurable startup of actor swarms.
+//!
+//! [`Bootstrap`] builds and starts a libp2p swarm running the remote actor [`Behaviour`], with
+//! TCP and QUIC transports and mDNS discovery by default. Nodes on networks without multicast
+//! can instead dial known seed peers, which are redialed with a backoff whenever the connection
+//! with them is lost. Which nodes can join the swarm is restricted with a private network
+//! pre-shared key and an allowlist of peers.
+//!
+//! The started swarm is driven by a spawned task, and controlled through the returned
+//! [`BootstrapHandle`]. Unless disabled with [`Bootstrap::global`], it's also initialized as the
+//! global actor swarm used by [`ActorRef::register`](crate::actor::ActorRef::register) and
+//! [`RemoteActorRef::lookup`](crate::actor::RemoteActorRef::lookup).
+
+use std::{
+    collections::HashSet,
+    error,
+    future::Future,
+    panic,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+    time::Duration,
+};
+
+use futures::{StreamExt, future::BoxFuture, stream::FuturesUnordered};
+use libp2p::{
+    Multiaddr, PeerId, Swarm, SwarmBuilder, Transport,
+    core::{muxing::StreamMuxerBox, transport::Boxed, upgrade},
+    dns,
+    identity::Keypair,
+    mdns, noise,
+    pnet::{PnetConfig, PreSharedKey},
+    quic,
+    swarm::{
+        ConnectionId, DialError, NetworkBehaviour, SwarmEvent,
+        behaviour::toggle::Toggle,
+        dial_opts::{DialOpts, PeerCondition},
+    },
+    tcp, yamux,
+};
+use tokio::{sync::Notify, task::JoinHandle};
+
+use super::{ActorSwarm, Behaviour, SwarmHandle, identity, messaging, registry};
+
+/// A builder for bootstrapping an actor swarm.
+///
+/// [`bootstrap`](super::bootstrap()) and [`bootstrap_on`](super::bootstrap_on) use this builder with
+/// its default settings: TCP and QUIC transports, mDNS discovery, and the default messaging and
+/// registry configuration. The builder allows configuring each of these, dialing known seed peers
+/// for networks where mDNS isn't available, and restricting which nodes can join the swarm with a
+/// private network and an allowlist of peers.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use kameo::remote::{self, messaging, registry};
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let handle = remote::Bootstrap::new()
+///     .listen_on("/ip4/0.0.0.0/tcp/8020".parse()?)
+///     .quic(false)
+///     .mdns(false)
+///     .seed_peers(["/dns4/seed.example.com/tcp/8020".parse()?])
+///     .messaging_config(
+///         messaging::Config::default().with_request_timeout(Duration::from_secs(30)),
+///     )
+///     .kademlia_config(registry::KademliaConfig::default().with_replication_factor(3))
+///     .start()?;
+///
+/// println!("node started: {}", handle.peer_id());
+///
+/// // Wait for the swarm to be shut down
+/// handle.await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Bootstrap {
+    identity: Identity,
+    global: bool,
+    listen_addrs: Vec<Multiaddr>,
+    tcp: bool,
+    quic: bool,
+    mdns: bool,
+    seed_peers: Vec<Multiaddr>,
+    reconnect_backoff: (Duration, Duration),
+    idle_connection_timeout: Duration,
+    messaging_config: messaging::Config,
+    registry_backend: registry::Backend,
+    psk: Option<PreSharedKey>,
+    allowed_peers: Option<HashSet<PeerId>>,
+}
+
+impl Bootstrap {
+    /// Creates a new bootstrap builder with the default settings.
+    pub fn new() -> Self {
+        Bootstrap {
+            identity: Identity::Generate,
+            global: true,
+            listen_addrs: Vec::new(),
+            tcp: true,
+            quic: true,
+            mdns: true,
+            seed_peers: Vec::new(),
+            reconnect_backoff: (Duration::from_secs(1), Duration::from_secs(60)),
+            idle_connection_timeout: Duration::from_secs(60),
+            messaging_config: messaging::Config::default(),
+            registry_backend: registry::Backend::default(),
+            psk: None,
+            allowed_peers: None,
+        }
+    }
+
+    /// Sets the keypair identifying the node.
+    ///
+    /// By default, a new keypair is generated each time the swarm starts, so the node's peer ID
+    /// changes on every restart. See the [`identity`] module for loading keypairs.
+    pub fn identity(mut self, keypair: Keypair) -> Self {
+        self.identity = Identity::Keypair(keypair);
+        self
+    }
+
+    /// Loads the keypair identifying the node from a key file when the swarm starts, generating
+    /// and saving a new keypair if the file doesn't exist.
+    ///
+    /// See [`identity::load_or_generate`].
+    pub fn identity_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity = Identity::File(path.into());
+        self
+    }
+
+    /// Sets whether the swarm is initialized as the global actor swarm.
+    ///
+    /// Enabled by default. When disabled, the swarm is only accessible through the
+    /// [`SwarmHandle`] returned by [`BootstrapHandle::swarm`], allowing multiple independent
+    /// swarms in one process.
+    pub fn global(mut self, global: bool) -> Self {
+        self.global = global;
+        self
+    }
+
+    /// Adds an address to listen on.
+    ///
+    /// This can be called multiple times to listen on several addresses, such as a TCP and a QUIC
+    /// address. If no address is added, the swarm listens on an OS-assigned TCP port on all
+    /// interfaces.
+    pub fn listen_on(mut self, addr: Multiaddr) -> Self {
+        self.listen_addrs.push(addr);
+        self
+    }
+
+    /// Enables or disables the TCP transport.
+    ///
+    /// Enabled by default.
+    pub fn tcp(mut self, enabled: bool) -> Self {
+        self.tcp = enabled;
+        self
+    }
+
+    /// Enables or disables the QUIC transport.
+    ///
+    /// Enabled by default, unless a [private network](Self::private_network) is used.
+    pub fn quic(mut self, enabled: bool) -> Self {
+        self.quic = enabled;
+        self
+    }
+
+    /// Enables or disables mDNS discovery of peers on the local network.
+    ///
+    /// Enabled by default. When disabled, the swarm only connects to [seed peers](Self::seed_peers)
+    /// and peers which connect to it.
+    pub fn mdns(mut self, enabled: bool) -> Self {
+        self.mdns = enabled;
+        self
+    }
+
+    /// Adds peers to dial when the swarm starts.
+    ///
+    /// Seed peers are redialed whenever the connection with them fails or is closed, waiting
+    /// between attempts according to the [reconnect backoff](Self::reconnect_backoff). Addresses
+    /// can use `/dns`, `/dns4` and `/dns6` hostnames, which are resolved on each attempt.
+    pub fn seed_peers(mut self, addrs: impl IntoIterator<Item = Multiaddr>) -> Self {
+        self.seed_peers.extend(addrs);
+        self
+    }
+
+    /// Sets the delays between attempts to reconnect to a seed peer.
+    ///
+    /// The first attempt waits for `initial`, doubling after each failed attempt up to `max`. The
+    /// delay is reset once a connection is established. Defaults to 1 second, up to 60 seconds.
+    pub fn reconnect_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.reconnect_backoff = (initial, max.max(initial));
+        self
+    }
+
+    /// Sets how long connections without any activity are kept open.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn idle_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_connection_timeout = timeout;
+        self
+    }
+
+    /// Sets the configuration for remote messaging, such as request timeouts and size limits.
+    pub fn messaging_config(mut self, config: messaging::Config) -> Self {
+        self.messaging_config = config;
+        self
+    }
+
+    /// Sets the backend used by the registry for actor registration and discovery.
+    ///
+    /// Defaults to [`registry::Backend::Kademlia`].
+    pub fn registry_backend(mut self, backend: registry::Backend) -> Self {
+        self.registry_backend = backend;
+        self
+    }
+
+    /// Uses the Kademlia registry backend with the given configuration.
+    pub fn kademlia_config(self, config: registry::KademliaConfig) -> Self {
+        self.registry_backend(registry::Backend::Kademlia(config))
+    }
+
+    /// Enables a private network, only allowing connections with nodes using the same pre-shared key.
+    ///
+    /// Connections are encrypted with the key before any other handshake, so nodes without the
+    /// key can't connect or discover the swarm's actors. Private networks are only supported over
+    /// TCP, so the QUIC transport is disabled when a key is set.
+    pub fn private_network(mut self, psk: PreSharedKey) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    /// Restricts connections to an allowlist of peers.
+    ///
+    /// Peers discovered through mDNS which aren't in the allowlist are ignored, and connections
+    /// with them are denied. See [`Behaviour::with_allowed_peers`].
+    pub fn allowed_peers(mut self, peers: impl IntoIterator<Item = PeerId>) -> Self {
+        self.allowed_peers = Some(peers.into_iter().collect());
+        self
+    }
+
+    /// Starts the swarm, initializing it as the global actor swarm unless disabled with
+    /// [`Bootstrap::global`].
+    ///
+    /// The swarm is driven by a spawned tokio task, so this must be called within a tokio runtime.
+    /// The task keeps running if the returned handle is dropped.
+    pub fn start(self) -> Result<BootstrapHandle, Box<dyn error::Error>> {
+        let keypair = match &self.identity {
+            Identity::Generate => Keypair::generate_ed25519(),
+            Identity::Keypair(keypair) => keypair.clone(),
+            Identity::File(path) => identity::load_or_generate(path)?,
+        };
+        let mut swarm = self.build_swarm(keypair)?;
+
+        if self.global {
+            swarm.behaviour().kameo.try_init_global()?;
+        }
+
+        if self.listen_addrs.is_empty() {
+            swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
+        }
+        for addr in self.listen_addrs {
+            swarm.listen_on(addr)?;
+        }
+
+        let swarm_handle = swarm.behaviour().kameo.swarm_handle();
+        let shutdown = Arc::new(Notify::new());
+        let event_loop = EventLoop {
+            swarm,
+            seed_peers: self
+                .seed_peers
+                .into_iter()
+                .map(|addr| SeedPeer {
+                    addr,
+                    peer_id: None,
+                    connection_id: None,
+                    backoff: self.reconnect_backoff.0,
+                })
+                .collect(),
+            reconnect_backoff: self.reconnect_backoff,
+            redials: FuturesUnordered::new(),
+        };
+        let task = tokio::spawn(event_loop.run(shutdown.clone()));
+
+        Ok(BootstrapHandle {
+            swarm: swarm_handle,
+            shutdown,
+            task,
+        })
+    }
+
+    fn build_swarm(
+        &self,
+        keypair: Keypair,
+    ) -> Result<Swarm<BootstrapBehaviour>, Box<dyn error::Error>> {
+        let swarm = SwarmBuilder::with_existing_identity(keypair)
+            .with_tokio()
+            .with_other_transport(|key| self.transport(key))?
+            .with_behaviour(|key| self.behaviour(key))?
+            .with_swarm_config(|config| {
+                config.with_idle_connection_timeout(self.idle_connection_timeout)
+            })
+            .build();
+
+        Ok(swarm)
+    }
+
+    fn transport(
+        &self,
+        key: &Keypair,
+    ) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn error::Error + Send + Sync>> {
+        let tcp = if self.tcp {
+            let noise_config = noise::Config::new(key)?;
+            let tcp = tcp::tokio::Transport::new(tcp::Config::default());
+            let transport = match self.psk {
+                Some(psk) => tcp
+                    .and_then(move |socket, _| PnetConfig::new(psk).handshake(socket))
+                    .upgrade(upgrade::Version::V1Lazy)
+                    .authenticate(noise_config)
+                    .multiplex(yamux::Config::default())
+                    .boxed(),
+                None => tcp
+                    .upgrade(upgrade::Version::V1Lazy)
+                    .authenticate(noise_config)
+                    .multiplex(yamux::Config::default())
+                    .boxed(),
+            };
+            Some(transport)
+        } else {
+            None
+        };
+
+        let quic = (self.quic && self.psk.is_none()).then(|| {
+            quic::tokio::Transport::new(quic::Config::new(key))
+                .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
+                .boxed()
+        });
+
+        let transport = match (tcp, quic) {
+            (Some(tcp), Some(quic)) => tcp
+                .or_transport(quic)
+                .map(|output, _| output.into_inner())
+                .boxed(),
+            (Some(transport), None) | (None, Some(transport)) => transport,
+            (None, None) => return Err("no transports enabled".into()),
+        };
+
+        // Resolves `/dns` addresses, such as those of seed peers, before dialing
+        Ok(dns::tokio::Transport::system(transport)?.boxed())
+    }
+
+    fn behaviour(
+        &self,
+        key: &Keypair,
+    ) -> Result<BootstrapBehaviour, Box<dyn error::Error + Send + Sync>> {
+        let local_peer_id = key.public().to_peer_id();
+        let mut kameo = Behaviour::new(local_peer_id, self.messaging_config.clone())
+            .with_registry_backend(self.registry_backend.clone())?;
+        if let Some(allowed_peers) = &self.allowed_peers {
+            kameo = kameo.with_allowed_peers(allowed_peers.iter().copied());
+        }
+        let mdns = if self.mdns {
+            Some(mdns::tokio::Behaviour::new(
+                mdns::Config::default(),
+                local_peer_id,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(BootstrapBehaviour {
+            kameo,
+            mdns: mdns.into(),
+        })
+    }
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Bootstrap::new()
+    }
+}
+
+/// A handle to a swarm started with [`Bootstrap::start`].
+///
+/// The handle can be awaited, completing once the swarm has been shut down.
+#[derive(Debug)]
+pub struct BootstrapHandle {
+    swarm: SwarmHandle,
+    shutdown: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl BootstrapHandle {
+    /// Returns the local peer ID of the swarm.
+    pub fn peer_id(&self) -> PeerId {
+        self.swarm.peer_id()
+    }
+
+    /// Returns a handle for registering and looking up actors in the swarm.
+    pub fn swarm(&self) -> SwarmHandle {
+        self.swarm.clone()
+    }
+
+    /// Signals the swarm to shut down, closing all of its connections.
+    ///
+    /// Await the handle to wait for the shutdown to complete. Remote operations fail with
+    /// `SwarmNotBootstrapped` errors once the swarm has been shut down. If the swarm is the global
+    /// actor swarm, it's cleared so another swarm can be started in its place.
+    pub fn shutdown(&self) {
+        ActorSwarm::unset(&self.peer_id());
+        self.shutdown.notify_one();
+    }
+
+    /// Returns whether the swarm has been shut down.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Future for BootstrapHandle {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.task).poll(cx) {
+            Poll::Ready(Err(err)) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+            Poll::Ready(_) => Poll::Ready(()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Identity {
+    Generate,
+    Keypair(Keypair),
+    File(PathBuf),
+}
+
+#[derive(NetworkBehaviour)]
+struct BootstrapBehaviour {
+    kameo: Behaviour,
+    mdns: Toggle<mdns::tokio::Behaviour>,
+}
+
+struct SeedPeer {
+    addr: Multiaddr,
+    /// The peer ID of the seed, known once a connection has been established.
+    peer_id: Option<PeerId>,
+    /// The connection ID of the pending dial.
+    connection_id: Option<ConnectionId>,
+    /// The delay before the next reconnect attempt.
+    backoff: Duration,
+}
+
+struct EventLoop {
+    swarm: Swarm<BootstrapBehaviour>,
+    seed_peers: Vec<SeedPeer>,
+    reconnect_backoff: (Duration, Duration),
+    redials: FuturesUnordered<BoxFuture<'static, usize>>,
+}
+
+impl EventLoop {
+    async fn run(mut self, shutdown: Arc<Notify>) {
+        for index in 0..self.seed_peers.len() {
+            self.dial_seed(index);
+        }
+
+        loop {
+            tokio::select! {
+                _ = shutdown.notified() => break,
+                Some(index) = self.redials.next() => self.dial_seed(index),
+                event = self.swarm.select_next_some() => self.handle_event(event),
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: SwarmEvent<BootstrapBehaviourEvent>) {
+        match event {
+            SwarmEvent::Behaviour(BootstrapBehaviourEvent::Mdns(mdns::Event::Discovered(list))) => {
+                for (peer_id, multiaddr) in list {
+                    if !self.swarm.behaviour().kameo.is_peer_allowed(&peer_id) {
+                        continue;
+                    }
+                    #[cfg(feature = "tracing")]
+                    tracing::info!("mDNS discovered a new peer: {peer_id}");
+                    self.swarm.add_peer_address(peer_id, multiaddr);
+                }
+            }
+            SwarmEvent::Behaviour(BootstrapBehaviourEvent::Mdns(mdns::Event::Expired(list))) => {
+                for (peer_id, _multiaddr) in list {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("mDNS discover peer has expired: {peer_id}");
+                    let _ = self.swarm.disconnect_peer_id(peer_id);
+                }
+            }
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                ..
+            } => {
+                let initial_backoff = self.reconnect_backoff.0;
+                let mut seed_addrs = Vec::new();
+                for seed in &mut self.seed_peers {
+                    if seed.connection_id == Some(connection_id) || seed.peer_id == Some(peer_id) {
+                        if seed.peer_id.is_none() {
+                            seed_addrs.push(seed.addr.clone());
+                        }
+                        seed.peer_id = Some(peer_id);
+                        seed.connection_id = None;
+                        seed.backoff = initial_backoff;
+                    }
+                }
+                // Make the seed's address known to the registry, as it would be when discovered
+                // through mDNS
+                for addr in seed_addrs {
+                    self.swarm.add_peer_address(peer_id, addr);
+                }
+            }
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                num_established: 0,
+                ..
+            } => {
+                let redials: Vec<_> = self
+                    .seed_peers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, seed)| {
+                        seed.peer_id == Some(peer_id) && seed.connection_id.is_none()
+                    })
+                    .map(|(index, _)| index)
+                    .collect();
+                for index in redials {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!("connection with seed peer {peer_id} closed, reconnecting");
+                    self.schedule_redial(index);
+                }
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                #[cfg(feature = "tracing")]
+                error,
+                ..
+            } => {
+                if let Some(index) = self
+                    .seed_peers
+                    .iter()
+                    .position(|seed| seed.connection_id == Some(connection_id))
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        "failed to dial seed peer {}: {error}",
+                        self.seed_peers[index].addr
+                    );
+                    self.seed_peers[index].connection_id = None;
+                    self.schedule_redial(index);
+                }
+            }
+            #[cfg(feature = "tracing")]
+            SwarmEvent::NewListenAddr { address, .. } => {
+                tracing::info!("ActorSwarm listening on {address}");
+            }
+            _ => {}
+        }
+    }
+
+    fn dial_seed(&mut self, index: usize) {
+        let seed = &mut self.seed_peers[index];
+        let opts = match seed.peer_id {
+            Some(peer_id) => DialOpts::peer_id(peer_id)
+                .addresses(vec![seed.addr.clone()])
+                .condition(PeerCondition::DisconnectedAndNotDialing)
+                .build(),
+            None => DialOpts::unknown_peer_id()
+                .address(seed.addr.clone())
+                .build(),
+        };
+        let connection_id = opts.connection_id();
+        match self.swarm.dial(opts) {
+            Ok(()) => {
+                self.seed_peers[index].connection_id = Some(connection_id);
+            }
+            Err(DialError::DialPeerConditionFalse(_)) => {}
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("failed to dial seed peer {}: {_err}", seed.addr);
+                self.schedule_redial(index);
+            }
+        }
+    }
+
+    fn schedule_redial(&mut self, index: usize) {
+        let seed = &mut self.seed_peers[index];
+        let delay = seed.backoff;
+        seed.backoff = (seed.backoff * 2).min(self.reconnect_backoff.1);
+        self.redials.push(Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            index
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, net::TcpListener};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        Actor,
+        actor::{ActorRef, RemoteActorRef, Spawn},
+        error::{RegistryError, RemoteSendError},
+        remote::{self, RemoteActor},
+    };
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Target;
+
+    impl Actor for Target {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    impl RemoteActor for Target {
+        const REMOTE_ID: &'static str = "kameo::remote::bootstrap::tests::Target";
+    }
+
+    fn local_node(port: u16) -> Bootstrap {
+        Bootstrap::new()
+            .global(false)
+            .mdns(false)
+            .quic(false)
+            .listen_on(format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn remote_operations_fail_after_shutdown() -> Result<(), Box<dyn error::Error>> {
+        let handle = local_node(0).start()?;
+        let (swarm, peer_id) = (handle.swarm(), handle.peer_id());
+        let actor_ref = Target::spawn(Target);
+        actor_ref.register_in(&swarm, "target").await?;
+
+        handle.shutdown();
+        handle.await;
+
+        assert!(matches!(
+            actor_ref.register_in(&swarm, "other").await,
+            Err(RegistryError::SwarmNotBootstrapped)
+        ));
+        assert!(matches!(
+            RemoteActorRef::<Target>::lookup_in(&swarm, "target").await,
+            Err(RegistryError::SwarmNotBootstrapped)
+        ));
+        assert!(matches!(
+            remote::spawn_in::<Target>(&swarm, peer_id, Target).await,
+            Err(RemoteSendError::SwarmNotBootstrapped)
+        ));
+        swarm.unregister("target").await;
+        assert!(swarm.members().await.is_empty());
+
+        // Stopping an actor still registered in the swarm doesn't panic while unregistering it
+        actor_ref.kill();
+        tokio::time::timeout(Duration::from_secs(5), actor_ref.wait_for_shutdown()).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn seed_peers_are_dialed_by_hostname() -> Result<(), Box<dyn error::Error>> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let seed = local_node(port).start()?;
+        let node = local_node(0)
+            .seed_peers([format!("/dns4/localhost/tcp/{port}").parse()?])
+            .start()?;
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !node
+                .swarm()
+                .members()
+                .await
+                .iter()
+                .any(|member| member.peer_id == seed.peer_id())
+            {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+}
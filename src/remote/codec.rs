@@ -0,0 +1,340 @@
+//! Serialization codecs for remote message payloads.
+//!
+//! Messages, replies and handler errors sent between nodes are encoded into bytes before being
+//! handed to the network. This module defines the [`Codec`] trait used for that encoding, along
+//! with the built-in codecs:
+//!
+//! - [`MessagePack`] (default), backed by `rmp-serde`
+//! - [`Bincode`], enabled with the `bincode` feature
+//! - [`Postcard`], enabled with the `postcard` feature
+//! - [`Json`], enabled with the `json` feature
+//!
+//! The [`Codec`] trait is sealed, so other serialization formats can't be used.
+//!
+//! The codec used for a payload is identified on the wire by its [`CodecId`], allowing the
+//! receiving node to decode it with the same codec, or to reject it with a clear
+//! [`RemoteSendError::DeserializeMessage`](crate::error::RemoteSendError::DeserializeMessage)
+//! error if the codec is not supported.
+//!
+//! # Selecting a codec
+//!
+//! The default codec for a swarm is configured with [`messaging::Config::with_codec`], and can be
+//! overridden for individual messages with the `codec` argument of `#[remote_message]`:
+//!
+//! ```ignore
+//! #[remote_message(codec = kameo::remote::codec::Json)]
+//! impl Message<Inc> for MyActor {
+//!     // ...
+//! }
+//! ```
+//!
+//! [`messaging::Config::with_codec`]: super::messaging::Config::with_codec
+
+use std::{error, fmt};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// A serialization format used to encode remote message payloads.
+///
+/// # Note
+///
+/// This trait is sealed and cannot be implemented outside of kameo. Payloads are decoded by their
+/// [`CodecId`] on the receiving node, which can only refer to the built-in codecs listed in the
+/// [module documentation](self).
+pub trait Codec: private::Sealed {
+    /// The identifier sent alongside payloads encoded with this codec.
+    const ID: CodecId;
+
+    /// Encodes a value into bytes.
+    fn encode<T>(value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized;
+
+    /// Decodes a value from bytes.
+    fn decode<T>(bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned;
+}
+
+/// Identifies the codec a payload was encoded with.
+///
+/// All variants exist regardless of enabled features, so that a payload encoded with a codec
+/// which is not compiled into the receiving node is reported as [`CodecError::Unsupported`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum CodecId {
+    /// MessagePack, using named struct fields.
+    #[default]
+    MessagePack,
+    /// Bincode, using the standard configuration.
+    Bincode,
+    /// Postcard.
+    Postcard,
+    /// JSON.
+    Json,
+}
+
+impl CodecId {
+    /// Returns whether this codec is compiled into the current build.
+    pub const fn is_supported(self) -> bool {
+        match self {
+            CodecId::MessagePack => true,
+            CodecId::Bincode => cfg!(feature = "bincode"),
+            CodecId::Postcard => cfg!(feature = "postcard"),
+            CodecId::Json => cfg!(feature = "json"),
+        }
+    }
+
+    /// Encodes a value with this codec.
+    pub fn encode<T>(self, value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self {
+            CodecId::MessagePack => MessagePack::encode(value),
+            #[cfg(feature = "bincode")]
+            CodecId::Bincode => Bincode::encode(value),
+            #[cfg(feature = "postcard")]
+            CodecId::Postcard => Postcard::encode(value),
+            #[cfg(feature = "json")]
+            CodecId::Json => Json::encode(value),
+            #[allow(unreachable_patterns)]
+            _ => Err(CodecError::Unsupported(self)),
+        }
+    }
+
+    /// Decodes a value with this codec.
+    pub fn decode<T>(self, bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            CodecId::MessagePack => MessagePack::decode(bytes),
+            #[cfg(feature = "bincode")]
+            CodecId::Bincode => Bincode::decode(bytes),
+            #[cfg(feature = "postcard")]
+            CodecId::Postcard => Postcard::decode(bytes),
+            #[cfg(feature = "json")]
+            CodecId::Json => Json::decode(bytes),
+            #[allow(unreachable_patterns)]
+            _ => Err(CodecError::Unsupported(self)),
+        }
+    }
+
+    /// Returns an error if a payload encoded with this codec can't be decoded by a message
+    /// pinned to the `expected` codec.
+    pub(crate) fn check(self, expected: Option<CodecId>) -> Result<(), CodecError> {
+        match expected {
+            Some(expected) if expected != self => Err(CodecError::Mismatch {
+                expected,
+                found: self,
+            }),
+            _ if !self.is_supported() => Err(CodecError::Unsupported(self)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for CodecId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecId::MessagePack => write!(f, "messagepack"),
+            CodecId::Bincode => write!(f, "bincode"),
+            CodecId::Postcard => write!(f, "postcard"),
+            CodecId::Json => write!(f, "json"),
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::MessagePack {}
+    #[cfg(feature = "bincode")]
+    impl Sealed for super::Bincode {}
+    #[cfg(feature = "postcard")]
+    impl Sealed for super::Postcard {}
+    #[cfg(feature = "json")]
+    impl Sealed for super::Json {}
+}
+
+/// The MessagePack codec, used by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessagePack;
+
+impl Codec for MessagePack {
+    const ID: CodecId = CodecId::MessagePack;
+
+    fn encode<T>(value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized,
+    {
+        rmp_serde::to_vec_named(value).map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode<T>(bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        rmp_serde::decode::from_slice(bytes).map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// The bincode codec.
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl Codec for Bincode {
+    const ID: CodecId = CodecId::Bincode;
+
+    fn encode<T>(value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized,
+    {
+        bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode<T>(bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+            .map(|(value, _)| value)
+            .map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// The postcard codec.
+#[cfg(feature = "postcard")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl Codec for Postcard {
+    const ID: CodecId = CodecId::Postcard;
+
+    fn encode<T>(value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized,
+    {
+        postcard::to_allocvec(value).map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode<T>(bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        postcard::from_bytes(bytes).map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// The JSON codec.
+#[cfg(feature = "json")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl Codec for Json {
+    const ID: CodecId = CodecId::Json;
+
+    fn encode<T>(value: &T) -> Result<Vec<u8>, CodecError>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde_json::to_vec(value).map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode<T>(bytes: &[u8]) -> Result<T, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(bytes).map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// An error returned when encoding or decoding a payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// The codec is not compiled into this build.
+    Unsupported(CodecId),
+    /// The payload was encoded with a different codec than the message requires.
+    Mismatch {
+        /// The codec required by the message.
+        expected: CodecId,
+        /// The codec the payload was encoded with.
+        found: CodecId,
+    },
+    /// Failed to encode the value.
+    Encode(String),
+    /// Failed to decode the value.
+    Decode(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Unsupported(codec) => write!(f, "unsupported codec '{codec}'"),
+            CodecError::Mismatch { expected, found } => {
+                write!(f, "codec mismatch: expected '{expected}', found '{found}'")
+            }
+            CodecError::Encode(err) => err.fmt(f),
+            CodecError::Decode(err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for CodecError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Msg {
+        name: String,
+        amount: u32,
+    }
+
+    #[test]
+    fn message_pack_round_trip() {
+        let msg = Msg {
+            name: "kameo".to_string(),
+            amount: 42,
+        };
+        let bytes = CodecId::MessagePack.encode(&msg).unwrap();
+        assert_eq!(MessagePack::encode(&msg).unwrap(), bytes);
+        assert_eq!(MessagePack::decode::<Msg>(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn check_codec() {
+        assert_eq!(CodecId::MessagePack.check(None), Ok(()));
+        assert_eq!(
+            CodecId::MessagePack.check(Some(CodecId::MessagePack)),
+            Ok(())
+        );
+        assert_eq!(
+            CodecId::MessagePack.check(Some(CodecId::Json)),
+            Err(CodecError::Mismatch {
+                expected: CodecId::Json,
+                found: CodecId::MessagePack,
+            })
+        );
+    }
+
+    #[cfg(not(feature = "json"))]
+    #[test]
+    fn unsupported_codec() {
+        assert_eq!(
+            CodecId::Json.encode(&()),
+            Err(CodecError::Unsupported(CodecId::Json))
+        );
+        assert_eq!(
+            CodecId::Json.check(None),
+            Err(CodecError::Unsupported(CodecId::Json))
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Compression of large remote message payloads.
+//!
+//! Ask and tell payloads, along with ask replies, can be compressed before being sent over the
+//! network when they exceed a configurable size threshold. The following algorithms are
+//! available:
+//!
+//! - [`Compression::Zstd`], enabled with the `zstd` feature
+//! - [`Compression::Lz4`], enabled with the `lz4` feature
+//!
+//! Compression is negotiated per peer. Every ask and tell request advertises the algorithms the
+//! sending node is able to decompress, and a node only compresses payloads sent to a peer with an
+//! algorithm that peer has advertised. Peers which don't support compression, or which are running
+//! an older version of kameo, always receive uncompressed payloads.
+//!
+//! Payloads are compressed and decompressed on tokio's blocking thread pool, so large payloads
+//! don't stall the swarm's event loop.
+//!
+//! # Configuration
+//!
+//! Compression is disabled by default, and is enabled with
+//! [`messaging::Config::with_compression`]:
+//!
+//! ```ignore
+//! let config = messaging::Config::default()
+//!     .with_compression(Compression::Zstd)
+//!     .with_compression_threshold(64 * 1024);
+//! ```
+//!
+//! # Metrics
+//!
+//! With the `metrics` feature enabled, the following metrics are recorded for each compressed
+//! payload, labelled by `algorithm`:
+//!
+//! - `kameo_remote_compression_ratio` (histogram): compressed size divided by uncompressed size
+//! - `kameo_remote_compression_uncompressed_bytes` (counter): bytes before compression
+//! - `kameo_remote_compression_compressed_bytes` (counter): bytes after compression
+//!
+//! [`messaging::Config::with_compression`]: super::messaging::Config::with_compression
+
+use std::{error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+/// A compression algorithm applied to remote message payloads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Compression {
+    /// No compression.
+    #[default]
+    None,
+    /// Zstandard, using the default compression level.
+    Zstd,
+    /// LZ4 block compression.
+    Lz4,
+}
+
+impl Compression {
+    /// Returns whether this algorithm is compiled into the current build.
+    pub const fn is_supported(self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Zstd => cfg!(feature = "zstd"),
+            Compression::Lz4 => cfg!(feature = "lz4"),
+        }
+    }
+
+    /// Returns the algorithms this node is able to decompress, excluding [`Compression::None`].
+    pub fn supported() -> Vec<Compression> {
+        [Compression::Zstd, Compression::Lz4]
+            .into_iter()
+            .filter(|compression| compression.is_supported())
+            .collect()
+    }
+
+    /// Returns the name of the algorithm.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Zstd => "zstd",
+            Compression::Lz4 => "lz4",
+        }
+    }
+
+    /// Compresses bytes with this algorithm.
+    pub fn compress(self, bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        let compressed = match self {
+            Compression::None => Ok(bytes.to_vec()),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::bulk::compress(bytes, zstd::DEFAULT_COMPRESSION_LEVEL)
+                .map_err(|err| CompressionError::Compress(err.to_string())),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Ok(lz4_flex::block::compress_prepend_size(bytes)),
+            #[allow(unreachable_patterns)]
+            _ => Err(CompressionError::Unsupported(self)),
+        }?;
+
+        #[cfg(feature = "metrics")]
+        if self != Compression::None {
+            let algorithm = self.as_str();
+            if !bytes.is_empty() {
+                metrics::histogram!("kameo_remote_compression_ratio", "algorithm" => algorithm)
+                    .record(compressed.len() as f64 / bytes.len() as f64);
+            }
+            metrics::counter!("kameo_remote_compression_uncompressed_bytes", "algorithm" => algorithm)
+                .increment(bytes.len() as u64);
+            metrics::counter!("kameo_remote_compression_compressed_bytes", "algorithm" => algorithm)
+                .increment(compressed.len() as u64);
+        }
+
+        Ok(compressed)
+    }
+
+    /// Decompresses bytes with this algorithm.
+    ///
+    /// Returns [`CompressionError::TooLarge`] if the decompressed payload would exceed
+    /// `size_maximum` bytes.
+    #[cfg_attr(not(any(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+    pub fn decompress(
+        self,
+        bytes: Vec<u8>,
+        size_maximum: u64,
+    ) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Compression::None => Ok(bytes),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                use std::io::Read;
+
+                let decoder = zstd::stream::read::Decoder::new(bytes.as_slice())
+                    .map_err(|err| CompressionError::Decompress(err.to_string()))?;
+                let mut decompressed = Vec::new();
+                decoder
+                    .take(size_maximum.saturating_add(1))
+                    .read_to_end(&mut decompressed)
+                    .map_err(|err| CompressionError::Decompress(err.to_string()))?;
+                if decompressed.len() as u64 > size_maximum {
+                    return Err(CompressionError::TooLarge { size_maximum });
+                }
+
+                Ok(decompressed)
+            }
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                let (size, _) = lz4_flex::block::uncompressed_size(&bytes)
+                    .map_err(|err| CompressionError::Decompress(err.to_string()))?;
+                if size as u64 > size_maximum {
+                    return Err(CompressionError::TooLarge { size_maximum });
+                }
+
+                lz4_flex::block::decompress_size_prepended(&bytes)
+                    .map_err(|err| CompressionError::Decompress(err.to_string()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(CompressionError::Unsupported(self)),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An error returned when compressing or decompressing a payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The algorithm is not compiled into this build.
+    Unsupported(Compression),
+    /// The decompressed payload exceeds the configured maximum size.
+    TooLarge {
+        /// The maximum decompressed size in bytes.
+        size_maximum: u64,
+    },
+    /// Failed to compress the payload.
+    Compress(String),
+    /// Failed to decompress the payload.
+    Decompress(String),
+}
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::Unsupported(compression) => {
+                write!(f, "unsupported compression '{compression}'")
+            }
+            CompressionError::TooLarge { size_maximum } => {
+                write!(f, "decompressed payload exceeds {size_maximum} bytes")
+            }
+            CompressionError::Compress(err) => write!(f, "failed to compress payload: {err}"),
+            CompressionError::Decompress(err) => write!(f, "failed to decompress payload: {err}"),
+        }
+    }
+}
+
+impl error::Error for CompressionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn none_is_passthrough() {
+        let bytes = b"hello".to_vec();
+        assert_eq!(Compression::None.compress(&bytes).unwrap(), bytes);
+        assert_eq!(
+            Compression::None.decompress(bytes.clone(), 5).unwrap(),
+            bytes
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let bytes = vec![7u8; 4096];
+        let compressed = Compression::Zstd.compress(&bytes).unwrap();
+        assert!(compressed.len() < bytes.len());
+        assert_eq!(
+            Compression::Zstd
+                .decompress(compressed.clone(), 4096)
+                .unwrap(),
+            bytes
+        );
+        assert_eq!(
+            Compression::Zstd.decompress(compressed, 4095),
+            Err(CompressionError::TooLarge { size_maximum: 4095 })
+        );
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_round_trip() {
+        let bytes = vec![7u8; 4096];
+        let compressed = Compression::Lz4.compress(&bytes).unwrap();
+        assert!(compressed.len() < bytes.len());
+        assert_eq!(
+            Compression::Lz4
+                .decompress(compressed.clone(), 4096)
+                .unwrap(),
+            bytes
+        );
+        assert_eq!(
+            Compression::Lz4.decompress(compressed, 4095),
+            Err(CompressionError::TooLarge { size_maximum: 4095 })
+        );
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn unsupported_compression() {
+        assert!(!Compression::supported().contains(&Compression::Zstd));
+        assert_eq!(
+            Compression::Zstd.compress(b"hello"),
+            Err(CompressionError::Unsupported(Compression::Zstd))
+        );
+    }
+}
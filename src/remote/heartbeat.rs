@@ -0,0 +1,749 @@
+//! Heartbeat based failure detection of remote peers.
+//!
+//! A closed connection is reported by libp2p as soon as it's noticed, but a peer which hangs with
+//! its connection still open would otherwise never be detected. To catch these, peers hosting
+//! actors linked to local actors, along with peers monitored with [`Behaviour::monitor`] such as
+//! the members of the cluster, are sent a heartbeat over a dedicated protocol
+//! (`/kameo/heartbeat/1.0.0`) every [`Config::with_interval`], and a [`Detector`] decides from
+//! the replies whether the peer is still reachable.
+//!
+//! When a peer is deemed unreachable, its connections are closed and local actors linked to actors
+//! on the peer are notified with [`ActorStopReason::PeerUnreachable`], instead of the
+//! [`ActorStopReason::PeerDisconnected`] they would otherwise be notified with once the
+//! connections are closed.
+//!
+//! Peers which don't support the heartbeat protocol, such as those running an older version of
+//! kameo, are never considered unreachable by the detector.
+//!
+//! [`ActorStopReason::PeerUnreachable`]: crate::error::ActorStopReason::PeerUnreachable
+//! [`ActorStopReason::PeerDisconnected`]: crate::error::ActorStopReason::PeerDisconnected
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    task,
+    time::{Duration, Instant},
+};
+
+use futures::{FutureExt, future::BoxFuture};
+use libp2p::{
+    PeerId, StreamProtocol, request_response,
+    swarm::{
+        CloseConnection, ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm,
+        NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+        behaviour::ConnectionEstablished,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::{Interval, MissedTickBehavior};
+
+use super::REMOTE_REGISTRY;
+
+const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/heartbeat/1.0.0");
+
+/// The maximum number of heartbeat intervals kept per peer by the phi accrual detector.
+const MAX_SAMPLE_SIZE: usize = 200;
+
+/// A heartbeat sent to a monitored peer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ping(u64);
+
+/// The reply to a [`Ping`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Pong(u64);
+
+/// Events emitted by the heartbeat behaviour.
+#[derive(Debug)]
+pub enum Event {
+    /// A monitored peer was deemed unreachable by the failure detector.
+    PeerUnreachable {
+        /// The unreachable peer.
+        peer: PeerId,
+        /// The time since the last heartbeat was received from the peer.
+        elapsed: Duration,
+    },
+}
+
+/// The failure detector used to decide whether a peer is reachable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Detector {
+    /// Deems a peer unreachable once no heartbeat has been received for the duration.
+    Timeout(Duration),
+    /// A phi accrual failure detector, adapting to the observed heartbeat intervals of each peer.
+    ///
+    /// Phi expresses the suspicion that a peer has failed, on a logarithmic scale. A threshold of
+    /// 8 means a false positive is expected roughly once in 10^8 heartbeats.
+    PhiAccrual {
+        /// The phi value at which a peer is deemed unreachable.
+        threshold: f64,
+        /// The duration of lost heartbeats tolerated before suspicion starts growing.
+        acceptable_pause: Duration,
+        /// The minimum standard deviation of heartbeat intervals, preventing a peer with very
+        /// regular heartbeats from being deemed unreachable after a small delay.
+        min_std_deviation: Duration,
+    },
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Detector::PhiAccrual {
+            threshold: 8.0,
+            acceptable_pause: Duration::from_secs(3),
+            min_std_deviation: Duration::from_millis(100),
+        }
+    }
+}
+
+/// The configuration for a `heartbeat::Behaviour` protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    interval: Duration,
+    request_timeout: Duration,
+    detector: Detector,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            request_timeout: Duration::from_secs(10),
+            detector: Detector::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Sets the interval between heartbeats sent to each monitored peer.
+    ///
+    /// Defaults to 1 second.
+    pub fn with_interval(mut self, v: Duration) -> Self {
+        self.interval = v.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Sets the timeout for a heartbeat to be replied to.
+    ///
+    /// No new heartbeat is sent to a peer while one is awaiting a reply.
+    pub fn with_request_timeout(mut self, v: Duration) -> Self {
+        self.request_timeout = v;
+        self
+    }
+
+    /// Sets the failure detector deciding whether a peer is reachable.
+    ///
+    /// Defaults to [`Detector::PhiAccrual`] with a threshold of 8.
+    pub fn with_detector(mut self, detector: Detector) -> Self {
+        self.detector = detector;
+        self
+    }
+}
+
+impl From<Config> for request_response::Config {
+    fn from(config: Config) -> Self {
+        request_response::Config::default().with_request_timeout(config.request_timeout)
+    }
+}
+
+/// Tracks the heartbeats received from a single peer.
+#[derive(Debug)]
+struct FailureDetector {
+    detector: Detector,
+    last_heartbeat: Instant,
+    intervals: VecDeque<f64>,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl FailureDetector {
+    /// Creates a detector, seeding its history with the expected heartbeat interval.
+    fn new(detector: Detector, interval: Duration, now: Instant) -> Self {
+        let mut failure_detector = FailureDetector {
+            detector,
+            last_heartbeat: now,
+            intervals: VecDeque::new(),
+            sum: 0.0,
+            sum_squares: 0.0,
+        };
+        let mean = interval.as_secs_f64() * 1000.0;
+        let std_deviation = mean / 4.0;
+        failure_detector.record(mean - std_deviation);
+        failure_detector.record(mean + std_deviation);
+
+        failure_detector
+    }
+
+    fn heartbeat(&mut self, now: Instant) {
+        let interval = now.saturating_duration_since(self.last_heartbeat);
+        self.last_heartbeat = now;
+        self.record(interval.as_secs_f64() * 1000.0);
+    }
+
+    fn record(&mut self, interval_ms: f64) {
+        if self.intervals.len() >= MAX_SAMPLE_SIZE
+            && let Some(oldest) = self.intervals.pop_front()
+        {
+            self.sum -= oldest;
+            self.sum_squares -= oldest * oldest;
+        }
+        self.intervals.push_back(interval_ms);
+        self.sum += interval_ms;
+        self.sum_squares += interval_ms * interval_ms;
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_heartbeat)
+    }
+
+    /// Returns the suspicion level of the peer having failed.
+    fn phi(&self, now: Instant, acceptable_pause: Duration, min_std_deviation: Duration) -> f64 {
+        let elapsed = self.elapsed(now).as_secs_f64() * 1000.0;
+        let samples = self.intervals.len() as f64;
+        let mean = self.sum / samples;
+        let variance = (self.sum_squares / samples - mean * mean).max(0.0);
+        let std_deviation = variance
+            .sqrt()
+            .max(min_std_deviation.as_secs_f64() * 1000.0)
+            .max(f64::EPSILON);
+        let mean = mean + acceptable_pause.as_secs_f64() * 1000.0;
+
+        // Logistic approximation of the normal distribution's cumulative distribution function
+        let y = (elapsed - mean) / std_deviation;
+        let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+        if elapsed > mean {
+            -(e / (1.0 + e)).log10()
+        } else {
+            -(1.0 - 1.0 / (1.0 + e)).log10()
+        }
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        match self.detector {
+            Detector::Timeout(timeout) => self.elapsed(now) <= timeout,
+            Detector::PhiAccrual {
+                threshold,
+                acceptable_pause,
+                min_std_deviation,
+            } => self.phi(now, acceptable_pause, min_std_deviation) < threshold,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MonitoredPeer {
+    failure_detector: FailureDetector,
+    ping: Option<request_response::OutboundRequestId>,
+}
+
+/// `Behaviour` is a `NetworkBehaviour` that sends heartbeats to peers with linked actors and
+/// monitored peers, detecting peers which have become unreachable.
+#[allow(missing_debug_implementations)]
+pub struct Behaviour {
+    request_response: request_response::cbor::Behaviour<Ping, Pong>,
+    config: Config,
+    next_ping: u64,
+    connected: HashSet<PeerId>,
+    unsupported: HashSet<PeerId>,
+    monitored: HashMap<PeerId, MonitoredPeer>,
+    /// Peers sent heartbeats whether or not they host linked actors.
+    watched: HashSet<PeerId>,
+    linked: HashSet<PeerId>,
+    linked_peers: Option<BoxFuture<'static, HashSet<PeerId>>>,
+    tick: Option<Interval>,
+    events: VecDeque<ToSwarm<Event, THandlerInEvent<Self>>>,
+}
+
+impl Behaviour {
+    /// Creates a new heartbeat behaviour.
+    pub fn new(config: Config) -> Self {
+        let request_response = request_response::cbor::Behaviour::new(
+            [(PROTO_NAME, request_response::ProtocolSupport::Full)],
+            config.into(),
+        );
+
+        Behaviour {
+            request_response,
+            config,
+            next_ping: 0,
+            connected: HashSet::new(),
+            unsupported: HashSet::new(),
+            monitored: HashMap::new(),
+            watched: HashSet::new(),
+            linked: HashSet::new(),
+            linked_peers: None,
+            tick: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns the heartbeat configuration.
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Returns whether a peer is currently being sent heartbeats.
+    pub fn is_monitored(&self, peer_id: &PeerId) -> bool {
+        self.monitored.contains_key(peer_id)
+    }
+
+    /// Sends heartbeats to a peer whenever it's connected, even if it hosts no linked actors.
+    ///
+    /// [`remote::Behaviour`](super::Behaviour) monitors the members of the cluster.
+    pub fn monitor(&mut self, peer_id: PeerId) {
+        if self.watched.insert(peer_id) {
+            self.start_monitoring(peer_id, Instant::now());
+        }
+    }
+
+    /// Stops sending heartbeats to a peer passed to [`Behaviour::monitor`], unless it hosts
+    /// linked actors.
+    pub fn unmonitor(&mut self, peer_id: &PeerId) {
+        if self.watched.remove(peer_id) && !self.linked.contains(peer_id) {
+            self.monitored.remove(peer_id);
+        }
+    }
+
+    fn on_tick(&mut self) {
+        if self.linked_peers.is_none() {
+            self.linked_peers = Some(linked_peers().boxed());
+        }
+
+        let now = Instant::now();
+        let unreachable: Vec<_> = self
+            .monitored
+            .iter()
+            .filter(|(_, peer)| !peer.failure_detector.is_available(now))
+            .map(|(peer_id, peer)| (*peer_id, peer.failure_detector.elapsed(now)))
+            .collect();
+        for (peer_id, elapsed) in unreachable {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%peer_id, ?elapsed, "peer is unreachable");
+            self.monitored.remove(&peer_id);
+            self.events
+                .push_back(ToSwarm::GenerateEvent(Event::PeerUnreachable {
+                    peer: peer_id,
+                    elapsed,
+                }));
+            self.events.push_back(ToSwarm::CloseConnection {
+                peer_id,
+                connection: CloseConnection::All,
+            });
+        }
+
+        let peer_ids: Vec<_> = self.monitored.keys().copied().collect();
+        for peer_id in peer_ids {
+            self.send_ping(peer_id);
+        }
+    }
+
+    fn send_ping(&mut self, peer_id: PeerId) {
+        let Some(peer) = self.monitored.get_mut(&peer_id) else {
+            return;
+        };
+        if peer.ping.is_some() {
+            return;
+        }
+
+        let ping = Ping(self.next_ping);
+        self.next_ping = self.next_ping.wrapping_add(1);
+        peer.ping = Some(self.request_response.send_request(&peer_id, ping));
+    }
+
+    fn set_linked_peers(&mut self, linked_peers: HashSet<PeerId>) {
+        self.linked = linked_peers;
+        self.monitored
+            .retain(|peer_id, _| self.linked.contains(peer_id) || self.watched.contains(peer_id));
+
+        let now = Instant::now();
+        let peer_ids: Vec<_> = self.linked.union(&self.watched).copied().collect();
+        for peer_id in peer_ids {
+            self.start_monitoring(peer_id, now);
+        }
+    }
+
+    fn start_monitoring(&mut self, peer_id: PeerId, now: Instant) {
+        if !self.connected.contains(&peer_id)
+            || self.unsupported.contains(&peer_id)
+            || self.monitored.contains_key(&peer_id)
+        {
+            return;
+        }
+
+        self.monitored.insert(
+            peer_id,
+            MonitoredPeer {
+                failure_detector: FailureDetector::new(
+                    self.config.detector,
+                    self.config.interval,
+                    now,
+                ),
+                ping: None,
+            },
+        );
+        self.send_ping(peer_id);
+    }
+
+    fn handle_request_response_event(&mut self, ev: request_response::Event<Ping, Pong>) {
+        match ev {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request: Ping(n),
+                    channel,
+                    ..
+                } => {
+                    let _ = self.request_response.send_response(channel, Pong(n));
+                }
+                request_response::Message::Response { request_id, .. } => {
+                    if let Some(monitored) = self.monitored.get_mut(&peer)
+                        && monitored.ping == Some(request_id)
+                    {
+                        monitored.ping = None;
+                        monitored.failure_detector.heartbeat(Instant::now());
+                    }
+                }
+            },
+            request_response::Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            } => {
+                if let request_response::OutboundFailure::UnsupportedProtocols = error {
+                    self.unsupported.insert(peer);
+                    self.monitored.remove(&peer);
+                } else if let Some(monitored) = self.monitored.get_mut(&peer)
+                    && monitored.ping == Some(request_id)
+                {
+                    // The missing heartbeat is picked up by the detector, try again next tick
+                    monitored.ping = None;
+                }
+            }
+            request_response::Event::InboundFailure { .. }
+            | request_response::Event::ResponseSent { .. } => {}
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = THandler<request_response::cbor::Behaviour<Ping, Pong>>;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &libp2p::Multiaddr,
+        remote_addr: &libp2p::Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &libp2p::Multiaddr,
+        role_override: libp2p::core::Endpoint,
+        port_use: libp2p::core::transport::PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response
+            .handle_established_outbound_connection(
+                connection_id,
+                peer,
+                addr,
+                role_override,
+                port_use,
+            )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionEstablished(ConnectionEstablished {
+            peer_id,
+            other_established: 0,
+            ..
+        }) = event
+        {
+            self.connected.insert(peer_id);
+        }
+
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = event
+        {
+            self.connected.remove(&peer_id);
+            self.unsupported.remove(&peer_id);
+            self.monitored.remove(&peer_id);
+        }
+
+        self.request_response.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.request_response
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        let interval = self.config.interval;
+        let tick = self.tick.get_or_insert_with(|| {
+            // The first tick is after a full interval, so peers monitored before then aren't
+            // checked before they've had a chance to reply to a heartbeat
+            let mut tick =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            tick
+        });
+        if tick.poll_tick(cx).is_ready() {
+            self.on_tick();
+        }
+
+        if let Some(linked_peers) = &mut self.linked_peers
+            && let task::Poll::Ready(linked_peers) = linked_peers.poll_unpin(cx)
+        {
+            self.linked_peers = None;
+            self.set_linked_peers(linked_peers);
+        }
+
+        loop {
+            if let Some(ev) = self.events.pop_front() {
+                return task::Poll::Ready(ev);
+            }
+
+            match self.request_response.poll(cx) {
+                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
+                    self.handle_request_response_event(ev);
+                    continue;
+                }
+                task::Poll::Ready(other_ev) => {
+                    return task::Poll::Ready(
+                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
+                    );
+                }
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Returns the peers hosting actors linked to local actors.
+async fn linked_peers() -> HashSet<PeerId> {
+    // Links are locked after releasing the registry, as they can be held across remote requests
+    let links: Vec<_> = REMOTE_REGISTRY
+        .lock()
+        .unwrap()
+        .values()
+        .map(|entry| entry.links.clone())
+        .collect();
+    let mut peers = HashSet::new();
+    for links in links {
+        for linked_actor_id in links.lock().await.keys() {
+            if let Some(peer_id) = linked_actor_id.peer_id() {
+                peers.insert(*peer_id);
+            }
+        }
+    }
+
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use tokio::{sync::mpsc, time};
+
+    use crate::{
+        actor::{RemoteActorRef, Spawn},
+        error::ActorStopReason,
+        remote::{
+            self,
+            testing::{
+                Cluster,
+                fixtures::{Target, Watcher},
+            },
+        },
+    };
+
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    fn phi_accrual() -> Detector {
+        Detector::PhiAccrual {
+            threshold: 8.0,
+            acceptable_pause: Duration::ZERO,
+            min_std_deviation: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn timeout_detector() {
+        let start = Instant::now();
+        let detector = FailureDetector::new(Detector::Timeout(INTERVAL * 3), INTERVAL, start);
+        assert!(detector.is_available(start + INTERVAL * 3));
+        assert!(!detector.is_available(start + INTERVAL * 4));
+    }
+
+    #[test]
+    fn timeout_detector_resets_on_heartbeat() {
+        let start = Instant::now();
+        let mut detector = FailureDetector::new(Detector::Timeout(INTERVAL * 3), INTERVAL, start);
+        detector.heartbeat(start + INTERVAL * 2);
+        assert!(detector.is_available(start + INTERVAL * 5));
+        assert!(!detector.is_available(start + INTERVAL * 6));
+    }
+
+    #[test]
+    fn phi_increases_with_elapsed_time() {
+        let start = Instant::now();
+        let mut detector = FailureDetector::new(phi_accrual(), INTERVAL, start);
+        let mut now = start;
+        for _ in 0..10 {
+            now += INTERVAL;
+            detector.heartbeat(now);
+        }
+
+        let phi = |elapsed| detector.phi(now + elapsed, Duration::ZERO, Duration::from_millis(100));
+        assert!(phi(Duration::ZERO) < 0.1);
+        assert!(phi(INTERVAL) < phi(INTERVAL * 2));
+        assert!(phi(INTERVAL * 2) < phi(INTERVAL * 3));
+        assert!(detector.is_available(now + INTERVAL));
+        assert!(!detector.is_available(now + INTERVAL * 3));
+    }
+
+    #[test]
+    fn phi_accrual_tolerates_acceptable_pause() {
+        let start = Instant::now();
+        let detector = FailureDetector::new(
+            Detector::PhiAccrual {
+                threshold: 8.0,
+                acceptable_pause: INTERVAL * 5,
+                min_std_deviation: Duration::from_millis(100),
+            },
+            INTERVAL,
+            start,
+        );
+        assert!(detector.is_available(start + INTERVAL * 5));
+        assert!(!detector.is_available(start + INTERVAL * 10));
+    }
+
+    #[test]
+    fn phi_accrual_adapts_to_irregular_heartbeats() {
+        let start = Instant::now();
+        let mut regular = FailureDetector::new(phi_accrual(), INTERVAL, start);
+        let mut irregular = FailureDetector::new(phi_accrual(), INTERVAL, start);
+        let mut now = start;
+        for i in 0..20 {
+            now += INTERVAL;
+            regular.heartbeat(now);
+            irregular.heartbeat(if i % 2 == 0 { now - INTERVAL / 2 } else { now });
+        }
+
+        let phi = |detector: &FailureDetector| {
+            detector.phi(
+                now + INTERVAL * 2,
+                Duration::ZERO,
+                Duration::from_millis(100),
+            )
+        };
+        assert!(phi(&irregular) < phi(&regular));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let start = Instant::now();
+        let mut detector = FailureDetector::new(phi_accrual(), INTERVAL, start);
+        let mut now = start;
+        for _ in 0..MAX_SAMPLE_SIZE * 2 {
+            now += INTERVAL;
+            detector.heartbeat(now);
+        }
+        assert_eq!(detector.intervals.len(), MAX_SAMPLE_SIZE);
+    }
+
+    /// Configures node 1 to deem every peer it monitors unreachable on the first heartbeat tick
+    /// after it started monitoring it.
+    fn unreachable_from_node_1(
+        interval: Duration,
+    ) -> impl Fn(remote::Behaviour) -> remote::Behaviour + Send + Sync + 'static {
+        let started = AtomicUsize::new(0);
+        move |behaviour| {
+            if started.fetch_add(1, Ordering::SeqCst) != 1 {
+                return behaviour;
+            }
+            behaviour.with_heartbeat_config(
+                Config::default()
+                    .with_interval(interval)
+                    .with_detector(Detector::Timeout(Duration::ZERO)),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn unreachable_peer_notifies_links_once() -> Result<(), Box<dyn error::Error>> {
+        // Node 0 is a member, so the interval leaves time to link to it before it's unreachable
+        let cluster = Cluster::builder()
+            .configure(unreachable_from_node_1(Duration::from_secs(1)))
+            .start()
+            .await?;
+
+        // The registry can't be relied on to resolve the target while node 1 keeps closing its
+        // connections to node 0
+        let target = Target::spawn(Target);
+        cluster.node(0).register(&target, "target").await?;
+        let remote_target = RemoteActorRef::<Target>::new(
+            cluster.node(0).actor_id(&target),
+            cluster.node(1).swarm().actor_swarm().sender().clone(),
+        );
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = Watcher::spawn(Watcher {
+            link_died: Some(tx),
+        });
+        watcher
+            .register_in(cluster.node(1).swarm(), "watcher")
+            .await?;
+        watcher.link_remote(&remote_target).await?;
+
+        let reason = time::timeout(Duration::from_secs(5), rx.recv()).await?;
+        assert!(matches!(reason, Some(ActorStopReason::PeerUnreachable)));
+        cluster.wait_for_disconnected(0, 1).await?;
+
+        // Closing the connections to the unreachable peer doesn't notify the link again
+        assert!(
+            time::timeout(Duration::from_millis(300), rx.recv())
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn heartbeats_are_sent_to_members_without_links() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::builder()
+            .configure(unreachable_from_node_1(Duration::from_millis(50)))
+            .start()
+            .await?;
+
+        cluster.wait_for_disconnected(0, 1).await?;
+
+        Ok(())
+    }
+}
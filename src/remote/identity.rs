@@ -0,0 +1,186 @@
+//! Persistent node identities.
+//!
+//! A node's [`PeerId`](libp2p::PeerId) is derived from its keypair. Swarms started with a freshly
+//! generated keypair get a new peer ID on every restart, which invalidates actor IDs, remote actor
+//! refs, and static registry configurations referring to the node. Loading the keypair from a key
+//! file keeps the node's identity stable across restarts:
+//!
+//! ```no_run
+//! use kameo::remote::{self, identity};
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let keypair = identity::load_or_generate("node.key")?;
+//!
+//! remote::Bootstrap::new().identity(keypair).start()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Keypairs are stored in libp2p's protobuf encoding, which is also used by other libp2p
+//! implementations. Raw 32 byte ed25519 secret keys are accepted when loading.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::Path,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use libp2p::identity::Keypair;
+
+use crate::error::IdentityError;
+
+/// Decodes a keypair from its protobuf encoding, or from a raw 32 byte ed25519 secret key.
+pub fn from_bytes(bytes: &[u8]) -> Result<Keypair, IdentityError> {
+    if bytes.len() == 32 {
+        let mut secret = bytes.to_vec();
+        return Ok(Keypair::ed25519_from_bytes(&mut secret)?);
+    }
+
+    Ok(Keypair::from_protobuf_encoding(bytes)?)
+}
+
+/// Encodes a keypair with its protobuf encoding.
+pub fn to_bytes(keypair: &Keypair) -> Result<Vec<u8>, IdentityError> {
+    Ok(keypair.to_protobuf_encoding()?)
+}
+
+/// Loads a keypair from a key file.
+pub fn load(path: impl AsRef<Path>) -> Result<Keypair, IdentityError> {
+    from_bytes(&fs::read(path)?)
+}
+
+/// Saves a keypair to a key file, failing if the file already exists.
+///
+/// Missing parent directories are created. The keypair is written to a temporary file in the same
+/// directory before being moved into place, so the key file is never left partially written. On
+/// Unix, the file is only readable and writable by its owner.
+///
+/// On file systems which don't support hard links, the temporary file is renamed into place
+/// instead, which may replace a key file created concurrently by another process.
+pub fn save(keypair: &Keypair, path: impl AsRef<Path>) -> Result<(), IdentityError> {
+    static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let path = path.as_ref();
+    let bytes = to_bytes(keypair)?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(parent)?;
+            parent
+        }
+        _ => Path::new("."),
+    };
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = dir.join(tmp_name);
+
+    let res = write_new(&tmp_path, &bytes).and_then(|()| {
+        // Linking rather than renaming moves the file into place atomically without replacing a
+        // key file created concurrently
+        match fs::hard_link(&tmp_path, path) {
+            // Some file systems don't support hard links, in which case the file is renamed into
+            // place if there's no key file yet
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::Unsupported | io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                match fs::symlink_metadata(path) {
+                    Ok(_) => Err(io::Error::from(io::ErrorKind::AlreadyExists)),
+                    Err(meta_err) if meta_err.kind() == io::ErrorKind::NotFound => {
+                        fs::rename(&tmp_path, path)
+                    }
+                    Err(_) => Err(err),
+                }
+            }
+            res => res,
+        }
+    });
+    let _ = fs::remove_file(&tmp_path);
+
+    Ok(res?)
+}
+
+/// Writes a new file, syncing its contents to disk.
+fn write_new(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    io::Write::write_all(&mut file, bytes)?;
+    file.sync_all()
+}
+
+/// Loads a keypair from a key file, or generates an ed25519 keypair and saves it if the file
+/// doesn't exist.
+pub fn load_or_generate(path: impl AsRef<Path>) -> Result<Keypair, IdentityError> {
+    let path = path.as_ref();
+    match load(path) {
+        Err(IdentityError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            let keypair = Keypair::generate_ed25519();
+            match save(&keypair, path) {
+                Ok(()) => Ok(keypair),
+                // Another process created the file first
+                Err(IdentityError::Io(err)) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    load(path)
+                }
+                Err(err) => Err(err),
+            }
+        }
+        res => res,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+
+    use super::*;
+
+    #[test]
+    fn load_or_generate_is_stable() {
+        let dir = std::env::temp_dir().join(format!("kameo-identity-{}", PeerId::random()));
+        let path = dir.join("node.key");
+
+        let generated = load_or_generate(&path).unwrap();
+        let loaded = load_or_generate(&path).unwrap();
+        assert_eq!(generated.public(), loaded.public());
+        assert!(matches!(
+            save(&generated, &path),
+            Err(IdentityError::Io(err)) if err.kind() == io::ErrorKind::AlreadyExists
+        ));
+
+        // Only the key file is left in the directory
+        let entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["node.key"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn from_bytes_accepts_raw_ed25519_secret() {
+        let keypair = Keypair::ed25519_from_bytes([7; 32]).unwrap();
+        assert_eq!(from_bytes(&[7; 32]).unwrap().public(), keypair.public());
+        assert_eq!(
+            from_bytes(&to_bytes(&keypair).unwrap()).unwrap().public(),
+            keypair.public()
+        );
+        assert!(from_bytes(b"not a key").is_err());
+    }
+}
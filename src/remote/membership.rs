@@ -0,0 +1,717 @@
+//! Cluster membership of kameo nodes.
+//!
+//! Every node running [`remote::Behaviour`](super::Behaviour) is a member of the cluster, whether
+//! or not it has any registered actors. When a connection to a peer is established, the nodes
+//! exchange their roles over a dedicated protocol (`/kameo/membership/1.0.0`), and the peer
+//! becomes a member. Peers which don't support the protocol, such as other libp2p nodes or those
+//! running an older version of kameo, are never members.
+//!
+//! # Member Lifecycle
+//!
+//! Members move through the [`MemberStatus`] states, with a [`MemberEvent`] emitted for each
+//! change:
+//!
+//! - [`Joining`](MemberStatus::Joining) while the membership handshake is in progress.
+//! - [`Up`](MemberStatus::Up) once the handshake has completed.
+//! - [`Leaving`](MemberStatus::Leaving) after the node has announced its departure with
+//!   [`remote::leave`](super::leave).
+//! - [`Down`](MemberStatus::Down) when the connection to the node was lost without it leaving,
+//!   including when the heartbeat failure detector deems it unreachable. A node which reconnects
+//!   within [`Config::with_removal_margin`] becomes `Up` again.
+//! - [`Removed`](MemberStatus::Removed) once the node has left, or has been down for longer than
+//!   the removal margin.
+//!
+//! Members are the peers directly connected to the local node, along with the local node itself.
+//! Membership isn't gossiped between nodes, so every node only knows of the nodes it's connected
+//! to. Features built on membership, such as [sharding](super::sharding) and
+//! [singletons](super::singleton), expect the cluster to be a full mesh, with every node
+//! connected to every other node; otherwise nodes disagree on the members, and so on which node
+//! owns a shard or hosts a singleton. Nodes bootstrapped with mDNS, or with every node listed as
+//! a seed peer, connect to each other as they're discovered.
+//!
+//! Actors can be notified of changes with [`remote::subscribe_members`](super::subscribe_members).
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    task,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use libp2p::{
+    PeerId, StreamProtocol, request_response,
+    swarm::{
+        CloseConnection, ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm,
+        NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+        behaviour::ConnectionEstablished,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{Interval, MissedTickBehavior},
+};
+
+const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/membership/1.0.0");
+
+/// How often down members are checked for removal.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A node in the cluster.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Member {
+    /// The peer ID of the node.
+    pub peer_id: PeerId,
+    /// The roles of the node.
+    pub roles: BTreeSet<String>,
+    /// The status of the node.
+    pub status: MemberStatus,
+    /// The time the node started, in milliseconds since the Unix epoch.
+    ///
+    /// This is used to order members by age, with ties broken by the peer ID.
+    pub up_since: u64,
+}
+
+impl Member {
+    /// Returns whether the node has a role.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.contains(role)
+    }
+
+    /// Returns whether the node started before another node.
+    pub fn is_older_than(&self, other: &Member) -> bool {
+        (self.up_since, self.peer_id) < (other.up_since, other.peer_id)
+    }
+}
+
+/// The status of a member in the cluster.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemberStatus {
+    /// The membership handshake with the node is in progress.
+    Joining,
+    /// The node is an active member of the cluster.
+    Up,
+    /// The node is gracefully leaving the cluster.
+    Leaving,
+    /// The connection to the node was lost without it leaving.
+    Down,
+    /// The node is no longer a member of the cluster.
+    Removed,
+}
+
+/// A change to the membership of the cluster.
+///
+/// Each event carries the member with its new status.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemberEvent {
+    /// A node started joining the cluster.
+    Joining(Member),
+    /// A node became an active member of the cluster.
+    Up(Member),
+    /// A node started gracefully leaving the cluster.
+    Leaving(Member),
+    /// The connection to a node was lost without it leaving.
+    Down(Member),
+    /// A node is no longer a member of the cluster.
+    Removed(Member),
+}
+
+impl MemberEvent {
+    fn new(member: Member) -> Self {
+        match member.status {
+            MemberStatus::Joining => MemberEvent::Joining(member),
+            MemberStatus::Up => MemberEvent::Up(member),
+            MemberStatus::Leaving => MemberEvent::Leaving(member),
+            MemberStatus::Down => MemberEvent::Down(member),
+            MemberStatus::Removed => MemberEvent::Removed(member),
+        }
+    }
+
+    /// Returns the member the event relates to.
+    pub fn member(&self) -> &Member {
+        match self {
+            MemberEvent::Joining(member)
+            | MemberEvent::Up(member)
+            | MemberEvent::Leaving(member)
+            | MemberEvent::Down(member)
+            | MemberEvent::Removed(member) => member,
+        }
+    }
+}
+
+/// A membership request sent to a peer.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum MembershipRequest {
+    /// Announces the local node to a newly connected peer.
+    Join {
+        /// The roles of the node.
+        roles: BTreeSet<String>,
+        /// The time the node started.
+        up_since: u64,
+    },
+    /// Announces the local node is leaving the cluster.
+    Leave,
+}
+
+/// The response to a [`MembershipRequest`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum MembershipResponse {
+    /// Announces the node in response to a join.
+    Welcome {
+        /// The roles of the node.
+        roles: BTreeSet<String>,
+        /// The time the node started.
+        up_since: u64,
+    },
+    /// Acknowledges a leave.
+    Ack,
+}
+
+/// The configuration for a `membership::Behaviour` protocol.
+#[derive(Debug, Clone)]
+pub struct Config {
+    roles: BTreeSet<String>,
+    removal_margin: Duration,
+    request_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            roles: BTreeSet::new(),
+            removal_margin: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Config {
+    /// Sets the roles of the local node, advertised to other members.
+    pub fn with_roles<I, S>(mut self, roles: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.roles = roles.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets how long a member stays down before being removed.
+    ///
+    /// A down member which reconnects within the margin becomes up again. Defaults to 10 seconds.
+    pub fn with_removal_margin(mut self, v: Duration) -> Self {
+        self.removal_margin = v;
+        self
+    }
+
+    /// Sets the timeout for membership requests to be acknowledged.
+    ///
+    /// This bounds how long [`remote::leave`](super::leave) waits for members to acknowledge
+    /// the departure.
+    pub fn with_request_timeout(mut self, v: Duration) -> Self {
+        self.request_timeout = v;
+        self
+    }
+}
+
+impl From<&Config> for request_response::Config {
+    fn from(config: &Config) -> Self {
+        request_response::Config::default().with_request_timeout(config.request_timeout)
+    }
+}
+
+struct Leave {
+    pending: HashSet<request_response::OutboundRequestId>,
+    replies: Vec<oneshot::Sender<()>>,
+}
+
+/// `Behaviour` is a `NetworkBehaviour` that tracks the kameo nodes in the cluster.
+#[allow(missing_debug_implementations)]
+pub struct Behaviour {
+    request_response: request_response::cbor::Behaviour<MembershipRequest, MembershipResponse>,
+    config: Config,
+    local: Member,
+    members: HashMap<PeerId, Member>,
+    down_since: HashMap<PeerId, Instant>,
+    joins: HashMap<request_response::OutboundRequestId, PeerId>,
+    leave: Option<Leave>,
+    subscribers: Vec<mpsc::UnboundedSender<MemberEvent>>,
+    tick: Option<Interval>,
+    events: VecDeque<ToSwarm<MemberEvent, THandlerInEvent<Self>>>,
+}
+
+impl Behaviour {
+    /// Creates a new membership behaviour.
+    pub fn new(local_peer_id: PeerId, config: Config) -> Self {
+        let request_response = request_response::cbor::Behaviour::new(
+            [(PROTO_NAME, request_response::ProtocolSupport::Full)],
+            (&config).into(),
+        );
+        let up_since = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let local = Member {
+            peer_id: local_peer_id,
+            roles: config.roles.clone(),
+            status: MemberStatus::Up,
+            up_since,
+        };
+
+        Behaviour {
+            request_response,
+            config,
+            local,
+            members: HashMap::new(),
+            down_since: HashMap::new(),
+            joins: HashMap::new(),
+            leave: None,
+            subscribers: Vec::new(),
+            tick: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns the local node's membership.
+    pub fn local_member(&self) -> &Member {
+        &self.local
+    }
+
+    /// Returns the members of the cluster, including the local node.
+    pub fn members(&self) -> Vec<Member> {
+        let mut members: Vec<_> = self.members.values().cloned().collect();
+        members.push(self.local.clone());
+        members.sort_by_key(|member| (member.up_since, member.peer_id));
+        members
+    }
+
+    /// Subscribes to membership changes.
+    ///
+    /// The subscriber is first sent an event for the current status of each member.
+    pub(super) fn subscribe(&mut self, tx: mpsc::UnboundedSender<MemberEvent>) {
+        for member in self.members() {
+            if tx.send(MemberEvent::new(member)).is_err() {
+                return;
+            }
+        }
+        self.subscribers.push(tx);
+    }
+
+    /// Gracefully leaves the cluster, replying once all members have acknowledged the departure.
+    pub(super) fn leave_with_reply(&mut self, reply: oneshot::Sender<()>) {
+        match (&mut self.leave, self.local.status) {
+            (Some(leave), _) => {
+                leave.replies.push(reply);
+                return;
+            }
+            (None, MemberStatus::Removed) => {
+                let _ = reply.send(());
+                return;
+            }
+            (None, _) => {}
+        }
+
+        self.set_local_status(MemberStatus::Leaving);
+        let peers: Vec<_> = self
+            .members
+            .values()
+            .filter(|member| member.status != MemberStatus::Down)
+            .map(|member| member.peer_id)
+            .collect();
+        let pending = peers
+            .into_iter()
+            .map(|peer_id| {
+                self.request_response
+                    .send_request(&peer_id, MembershipRequest::Leave)
+            })
+            .collect();
+        self.leave = Some(Leave {
+            pending,
+            replies: vec![reply],
+        });
+        self.try_finish_leave();
+    }
+
+    fn try_finish_leave(&mut self) {
+        if !self
+            .leave
+            .as_ref()
+            .is_some_and(|leave| leave.pending.is_empty())
+        {
+            return;
+        }
+
+        let leave = self.leave.take().unwrap();
+        self.set_local_status(MemberStatus::Removed);
+        for peer_id in self.members.keys() {
+            self.events.push_back(ToSwarm::CloseConnection {
+                peer_id: *peer_id,
+                connection: CloseConnection::All,
+            });
+        }
+        for reply in leave.replies {
+            let _ = reply.send(());
+        }
+    }
+
+    fn set_local_status(&mut self, status: MemberStatus) {
+        self.local.status = status;
+        self.notify(self.local.clone());
+    }
+
+    fn set_status(&mut self, peer_id: PeerId, status: MemberStatus) {
+        let Some(member) = self.members.get_mut(&peer_id) else {
+            return;
+        };
+        if member.status == status {
+            return;
+        }
+
+        member.status = status;
+        let member = member.clone();
+        match status {
+            MemberStatus::Down => {
+                self.down_since.insert(peer_id, Instant::now());
+            }
+            MemberStatus::Removed => {
+                self.members.remove(&peer_id);
+                self.down_since.remove(&peer_id);
+            }
+            MemberStatus::Joining | MemberStatus::Up | MemberStatus::Leaving => {
+                self.down_since.remove(&peer_id);
+            }
+        }
+        self.notify(member);
+    }
+
+    fn notify(&mut self, member: Member) {
+        let ev = MemberEvent::new(member);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(ev.clone()).is_ok());
+        self.events.push_back(ToSwarm::GenerateEvent(ev));
+    }
+
+    /// Records a peer's membership info, with new and returning members starting as joining.
+    fn handle_joined(&mut self, peer_id: PeerId, roles: BTreeSet<String>, up_since: u64) {
+        match self.members.get_mut(&peer_id) {
+            Some(member) if member.status != MemberStatus::Down => {
+                member.roles = roles;
+                member.up_since = up_since;
+            }
+            _ => {
+                let member = Member {
+                    peer_id,
+                    roles,
+                    status: MemberStatus::Joining,
+                    up_since,
+                };
+                self.members.insert(peer_id, member.clone());
+                self.down_since.remove(&peer_id);
+                self.notify(member);
+            }
+        }
+    }
+
+    fn send_join(&mut self, peer_id: PeerId) {
+        let request_id = self.request_response.send_request(
+            &peer_id,
+            MembershipRequest::Join {
+                roles: self.local.roles.clone(),
+                up_since: self.local.up_since,
+            },
+        );
+        self.joins.insert(request_id, peer_id);
+    }
+
+    fn is_leaving(&self) -> bool {
+        matches!(
+            self.local.status,
+            MemberStatus::Leaving | MemberStatus::Removed
+        )
+    }
+
+    fn handle_request_response_event(
+        &mut self,
+        ev: request_response::Event<MembershipRequest, MembershipResponse>,
+    ) {
+        match ev {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request: MembershipRequest::Join { roles, up_since },
+                    channel,
+                    ..
+                } => {
+                    if self.is_leaving() {
+                        // Dropping the channel fails the join, the peer isn't welcome anymore
+                        return;
+                    }
+                    self.handle_joined(peer, roles, up_since);
+                    let _ = self.request_response.send_response(
+                        channel,
+                        MembershipResponse::Welcome {
+                            roles: self.local.roles.clone(),
+                            up_since: self.local.up_since,
+                        },
+                    );
+                }
+                request_response::Message::Request {
+                    request: MembershipRequest::Leave,
+                    channel,
+                    ..
+                } => {
+                    self.set_status(peer, MemberStatus::Leaving);
+                    let _ = self
+                        .request_response
+                        .send_response(channel, MembershipResponse::Ack);
+                }
+                request_response::Message::Response {
+                    request_id,
+                    response: MembershipResponse::Welcome { roles, up_since },
+                } => {
+                    if self.joins.remove(&request_id).is_some() && !self.is_leaving() {
+                        self.handle_joined(peer, roles, up_since);
+                        self.set_status(peer, MemberStatus::Up);
+                    }
+                }
+                request_response::Message::Response {
+                    request_id,
+                    response: MembershipResponse::Ack,
+                } => {
+                    if let Some(leave) = &mut self.leave {
+                        leave.pending.remove(&request_id);
+                    }
+                    self.try_finish_leave();
+                }
+            },
+            request_response::Event::OutboundFailure { request_id, .. } => {
+                // Peers which don't support the protocol never become members
+                self.joins.remove(&request_id);
+                if let Some(leave) = &mut self.leave {
+                    leave.pending.remove(&request_id);
+                }
+                self.try_finish_leave();
+            }
+            request_response::Event::ResponseSent { peer, .. } => {
+                if !self.is_leaving()
+                    && self
+                        .members
+                        .get(&peer)
+                        .is_some_and(|member| member.status == MemberStatus::Joining)
+                {
+                    self.set_status(peer, MemberStatus::Up);
+                }
+            }
+            request_response::Event::InboundFailure { .. } => {}
+        }
+    }
+
+    fn remove_down_members(&mut self) {
+        let now = Instant::now();
+        let removed: Vec<_> = self
+            .down_since
+            .iter()
+            .filter(|(_, down_since)| {
+                now.saturating_duration_since(**down_since) >= self.config.removal_margin
+            })
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+        for peer_id in removed {
+            self.set_status(peer_id, MemberStatus::Removed);
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler =
+        THandler<request_response::cbor::Behaviour<MembershipRequest, MembershipResponse>>;
+    type ToSwarm = MemberEvent;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &libp2p::Multiaddr,
+        remote_addr: &libp2p::Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &libp2p::Multiaddr,
+        role_override: libp2p::core::Endpoint,
+        port_use: libp2p::core::transport::PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response
+            .handle_established_outbound_connection(
+                connection_id,
+                peer,
+                addr,
+                role_override,
+                port_use,
+            )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionEstablished(ConnectionEstablished {
+            peer_id,
+            endpoint,
+            other_established: 0,
+            ..
+        }) = event
+            && endpoint.is_dialer()
+            && !self.is_leaving()
+        {
+            self.send_join(peer_id);
+        }
+
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = event
+        {
+            match self.members.get(&peer_id).map(|member| member.status) {
+                Some(MemberStatus::Leaving) => self.set_status(peer_id, MemberStatus::Removed),
+                Some(MemberStatus::Joining | MemberStatus::Up) if self.is_leaving() => {
+                    self.set_status(peer_id, MemberStatus::Removed)
+                }
+                Some(MemberStatus::Joining | MemberStatus::Up) => {
+                    self.set_status(peer_id, MemberStatus::Down)
+                }
+                Some(MemberStatus::Down | MemberStatus::Removed) | None => {}
+            }
+        }
+
+        self.request_response.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.request_response
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        let tick = self.tick.get_or_insert_with(|| {
+            let mut tick = tokio::time::interval(TICK_INTERVAL);
+            tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            tick
+        });
+        if tick.poll_tick(cx).is_ready() {
+            self.remove_down_members();
+        }
+
+        loop {
+            if let Some(ev) = self.events.pop_front() {
+                return task::Poll::Ready(ev);
+            }
+
+            match self.request_response.poll(cx) {
+                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
+                    self.handle_request_response_event(ev);
+                    continue;
+                }
+                task::Poll::Ready(other_ev) => {
+                    return task::Poll::Ready(
+                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
+                    );
+                }
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn behaviour() -> Behaviour {
+        Behaviour::new(PeerId::random(), Config::default().with_roles(["worker"]))
+    }
+
+    fn statuses(rx: &mut mpsc::UnboundedReceiver<MemberEvent>) -> Vec<MemberStatus> {
+        let mut statuses = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            statuses.push(ev.member().status);
+        }
+        statuses
+    }
+
+    #[test]
+    fn members_are_ordered_by_age() {
+        let mut behaviour = behaviour();
+        let older = PeerId::random();
+        behaviour.handle_joined(older, BTreeSet::new(), 0);
+
+        let members = behaviour.members();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].peer_id, older);
+        assert!(members[0].is_older_than(&members[1]));
+        assert!(members[1].has_role("worker"));
+    }
+
+    #[test]
+    fn subscribe_sends_current_members() {
+        let mut behaviour = behaviour();
+        let peer_id = PeerId::random();
+        behaviour.handle_joined(peer_id, BTreeSet::new(), 0);
+        behaviour.set_status(peer_id, MemberStatus::Up);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        behaviour.subscribe(tx);
+        assert_eq!(statuses(&mut rx), [MemberStatus::Up, MemberStatus::Up]);
+
+        behaviour.set_status(peer_id, MemberStatus::Down);
+        behaviour.handle_joined(peer_id, BTreeSet::new(), 0);
+        behaviour.set_status(peer_id, MemberStatus::Up);
+        assert_eq!(
+            statuses(&mut rx),
+            [MemberStatus::Down, MemberStatus::Joining, MemberStatus::Up]
+        );
+    }
+
+    #[test]
+    fn removed_members_are_forgotten() {
+        let mut behaviour = behaviour();
+        let peer_id = PeerId::random();
+        behaviour.handle_joined(peer_id, BTreeSet::new(), 0);
+        behaviour.set_status(peer_id, MemberStatus::Down);
+        assert_eq!(behaviour.down_since.len(), 1);
+
+        behaviour.set_status(peer_id, MemberStatus::Removed);
+        assert!(behaviour.members.is_empty());
+        assert!(behaviour.down_since.is_empty());
+    }
+
+    #[test]
+    fn leave_without_members_completes() {
+        let mut behaviour = behaviour();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        behaviour.subscribe(tx);
+        statuses(&mut rx);
+
+        let (reply_tx, mut reply_rx) = oneshot::channel();
+        behaviour.leave_with_reply(reply_tx);
+        assert_eq!(reply_rx.try_recv(), Ok(()));
+        assert_eq!(behaviour.local_member().status, MemberStatus::Removed);
+        assert_eq!(
+            statuses(&mut rx),
+            [MemberStatus::Leaving, MemberStatus::Removed]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::{borrow::Cow, future::Future, time::Duration};
+
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::sync::oneshot;
+
+use crate::{
+    Actor,
+    actor::{self, ActorId, RemoteActorRef},
+    error::RemoteSendError,
+};
+
+use super::{
+    RemoteActor, RemoteMessage, SwarmCommand, SwarmSender, SwarmStopped,
+    codec::{CodecError, CodecId},
+    messaging::SwarmResponse,
+};
+
+/// A remote actor and message registration which messages are sent through.
+///
+/// Both [`RemoteActorRef`] and the type-erased remote recipients send messages through this, the
+/// former with the IDs of its actor and message types, and the latter with the IDs they were
+/// created or deserialized with.
+pub(crate) struct MessageTarget<'a> {
+    pub(crate) swarm_tx: &'a SwarmSender,
+    pub(crate) actor_id: ActorId,
+    pub(crate) actor_remote_id: Cow<'static, str>,
+    pub(crate) message_remote_id: Cow<'static, str>,
+    pub(crate) version: u32,
+    pub(crate) codec: CodecId,
+}
+
+impl<'a> MessageTarget<'a> {
+    /// Returns the target for sending message `M` to a remote actor.
+    pub(crate) fn new<A, M>(actor_ref: &'a RemoteActorRef<A>) -> Self
+    where
+        A: Actor + RemoteActor + RemoteMessage<M>,
+    {
+        MessageTarget {
+            swarm_tx: actor_ref.swarm_tx(),
+            actor_id: actor_ref.id(),
+            actor_remote_id: Cow::Borrowed(<A as RemoteActor>::REMOTE_ID),
+            message_remote_id: Cow::Borrowed(<A as RemoteMessage<M>>::REMOTE_ID),
+            version: <A as RemoteMessage<M>>::VERSION,
+            codec: actor_ref.message_codec::<M>(),
+        }
+    }
+
+    /// Sends a message without waiting for delivery acknowledgment.
+    pub(crate) fn tell<M: Serialize>(
+        &self,
+        msg: &M,
+        mailbox_timeout: Option<Duration>,
+        immediate: bool,
+    ) -> Result<(), RemoteSendError> {
+        self.swarm_tx.send(SwarmCommand::Tell {
+            actor_id: self.actor_id,
+            actor_remote_id: self.actor_remote_id.clone(),
+            message_remote_id: self.message_remote_id.clone(),
+            version: self.version,
+            payload: self.encode(msg)?,
+            codec: self.codec,
+            mailbox_timeout,
+            immediate,
+            sender_id: actor::current_actor_id(),
+            reply: None,
+        })?;
+
+        Ok(())
+    }
+
+    /// Sends a message and waits for delivery acknowledgment.
+    pub(crate) async fn tell_ack<M: Serialize>(
+        &self,
+        msg: &M,
+        mailbox_timeout: Option<Duration>,
+        immediate: bool,
+    ) -> Result<(), RemoteSendError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.swarm_tx.send(SwarmCommand::Tell {
+            actor_id: self.actor_id,
+            actor_remote_id: self.actor_remote_id.clone(),
+            message_remote_id: self.message_remote_id.clone(),
+            version: self.version,
+            payload: self.encode(msg)?,
+            codec: self.codec,
+            mailbox_timeout,
+            immediate,
+            sender_id: actor::current_actor_id(),
+            reply: Some(reply_tx),
+        })?;
+
+        self.tell_response(reply_rx).await
+    }
+
+    /// Sends a message reliably, waiting until its delivery is acknowledged.
+    pub(crate) async fn tell_reliable<M: Serialize>(
+        &self,
+        msg: &M,
+        mailbox_timeout: Option<Duration>,
+    ) -> Result<(), RemoteSendError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.swarm_tx.send(SwarmCommand::ReliableTell {
+            actor_id: self.actor_id,
+            actor_remote_id: self.actor_remote_id.clone(),
+            message_remote_id: self.message_remote_id.clone(),
+            version: self.version,
+            payload: self.encode(msg)?,
+            codec: self.codec,
+            mailbox_timeout,
+            sender_id: actor::current_actor_id(),
+            reply: Some(reply_tx),
+        })?;
+
+        self.tell_response(reply_rx).await
+    }
+
+    /// Sends a message and waits for a reply.
+    pub(crate) async fn ask<M, Ok, Err>(
+        &self,
+        msg: &M,
+        mailbox_timeout: Option<Duration>,
+        reply_timeout: Option<Duration>,
+        immediate: bool,
+    ) -> Result<Ok, RemoteSendError<Err>>
+    where
+        M: Serialize,
+        Ok: DeserializeOwned,
+        Err: DeserializeOwned,
+    {
+        self.enqueue_ask(msg, mailbox_timeout, reply_timeout, immediate)
+            .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))?
+            .await
+    }
+
+    /// Sends a message, returning a future which waits for its reply.
+    ///
+    /// The future fails with [`RemoteSendError::SwarmNotBootstrapped`] if the swarm has stopped.
+    pub(crate) fn enqueue_ask<M, Ok, Err>(
+        &self,
+        msg: &M,
+        mailbox_timeout: Option<Duration>,
+        reply_timeout: Option<Duration>,
+        immediate: bool,
+    ) -> Result<impl Future<Output = Result<Ok, RemoteSendError<Err>>> + use<M, Ok, Err>, CodecError>
+    where
+        M: Serialize,
+        Ok: DeserializeOwned,
+        Err: DeserializeOwned,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        // The reply sender is dropped with the command if the swarm has stopped, failing the reply
+        let _ = self.swarm_tx.send(SwarmCommand::Ask {
+            actor_id: self.actor_id,
+            actor_remote_id: self.actor_remote_id.clone(),
+            message_remote_id: self.message_remote_id.clone(),
+            version: self.version,
+            payload: self.swarm_tx.enter(|| self.codec.encode(msg))?,
+            codec: self.codec,
+            mailbox_timeout,
+            reply_timeout,
+            immediate,
+            sender_id: actor::current_actor_id(),
+            reply: reply_tx,
+        });
+
+        let swarm_tx = self.swarm_tx.clone();
+        let (actor_id, codec) = (self.actor_id, self.codec);
+        Ok(async move {
+            let res = match reply_rx.await.map_err(|_| SwarmStopped)? {
+                SwarmResponse::Ask(res) => match res {
+                    Ok(payload) => swarm_tx
+                        .enter(|| codec.decode(&payload))
+                        .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string())),
+                    Err(err) => Err(err
+                        .map_err(|err| match codec.decode(&err) {
+                            Ok(err) => RemoteSendError::HandlerError(err),
+                            Err(err) => RemoteSendError::DeserializeHandlerError(err.to_string()),
+                        })
+                        .flatten()),
+                },
+                SwarmResponse::OutboundFailure(err) => Err(err
+                    .map_err(|_| unreachable!("outbound failure doesn't contain handler errors"))),
+                _ => panic!("unexpected response"),
+            };
+            if let Err(err) = &res {
+                swarm_tx.invalidate_lookups(actor_id, err);
+            }
+            res
+        })
+    }
+
+    fn encode<M: Serialize>(&self, msg: &M) -> Result<Vec<u8>, RemoteSendError> {
+        self.swarm_tx
+            .enter(|| self.codec.encode(msg))
+            .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))
+    }
+
+    async fn tell_response(
+        &self,
+        reply_rx: oneshot::Receiver<SwarmResponse>,
+    ) -> Result<(), RemoteSendError> {
+        let res = match reply_rx.await.map_err(|_| SwarmStopped)? {
+            SwarmResponse::Tell(res) => res,
+            SwarmResponse::OutboundFailure(err) => Err(err),
+            _ => panic!("unexpected response"),
+        };
+        if let Err(err) = &res {
+            self.swarm_tx.invalidate_lookups(self.actor_id, err);
+        }
+        res
+    }
+}
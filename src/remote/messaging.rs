@@ -27,23 +27,32 @@
 //!
 //! The module integrates closely with Kameo's local actor system, allowing remote actors
 //! to be used interchangeably with local actors through the same `ActorRef` interface.
+//!
+//! # Protocol versions
+//!
+//! Requests are exchanged over `/kameo/messaging/1.1.0`, falling back to `/kameo/messaging/1.0.0`
+//! for peers running older versions of kameo. Asks, tells, links, and unlinks remain compatible
+//! with these peers, while requests they're unable to decode, such as spawning actors, capability
+//! handshakes, and reliable or compressed tells, fail locally with an outbound failure instead of
+//! being sent to them.
 
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     fmt,
-    future::Future,
-    sync::LazyLock,
+    future::{self as std_future, Future},
+    io,
+    sync::{Arc, LazyLock},
     task,
     time::Duration,
 };
 
-use futures::FutureExt;
+use futures::{AsyncRead, AsyncWrite, FutureExt, future::BoxFuture};
 use libp2p::{
     PeerId, StreamProtocol, request_response,
     swarm::{
-        ConnectionDenied, ConnectionId, DialFailure, FromSwarm, NetworkBehaviour, THandler,
-        THandlerInEvent, THandlerOutEvent, ToSwarm,
+        ConnectionClosed, ConnectionDenied, ConnectionId, DialFailure, FromSwarm, NetworkBehaviour,
+        THandler, THandlerInEvent, THandlerOutEvent, ToSwarm, behaviour::ConnectionEstablished,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -54,11 +63,33 @@ use crate::{
     error::{ActorStopReason, Infallible, RemoteSendError},
 };
 
-use super::_internal::{
-    REMOTE_ACTORS, REMOTE_MESSAGES, RemoteActorFns, RemoteMessageFns, RemoteMessageRegistrationID,
+use super::{
+    _internal::{
+        REMOTE_ACTORS, REMOTE_MESSAGES, RemoteActorFns, RemoteMessageFns,
+        RemoteMessageRegistrationID,
+    },
+    REMOTE_REGISTRY, RemoteSender,
+    acl::{self, AccessPolicy, AccessRequest},
+    codec::CodecId,
+    compression::{Compression, CompressionError},
+    sharding, singleton,
+    swarm::{SwarmSender, in_swarm},
 };
 
-const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/messaging/1.0.0");
+pub use delivery::Delivery;
+
+use delivery::{Admission, Inbox, Outbox, OutboxMessage};
+
+mod delivery;
+
+const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/messaging/1.1.0");
+
+/// The protocol spoken by peers running older versions of kameo, which only understand asks,
+/// tells, links, and unlinks.
+const LEGACY_PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/messaging/1.0.0");
+
+/// The version of messages sent by peers which don't specify one.
+const DEFAULT_MESSAGE_VERSION: u32 = 1;
 
 static REMOTE_ACTORS_MAP: LazyLock<HashMap<&'static str, RemoteActorFns>> = LazyLock::new(|| {
     let mut existing_ids = HashSet::new();
@@ -85,11 +116,44 @@ static REMOTE_MESSAGES_MAP: LazyLock<
     REMOTE_MESSAGES.iter().copied().collect()
 });
 
+static LOCAL_CAPABILITIES: LazyLock<PeerCapabilities> = LazyLock::new(|| {
+    let mut actors: Vec<_> = REMOTE_ACTORS_MAP
+        .keys()
+        .map(|id| Cow::Borrowed(*id))
+        .collect();
+    actors.sort();
+    let mut messages: Vec<_> = REMOTE_MESSAGES_MAP
+        .iter()
+        .map(|(id, fns)| MessageCapability {
+            actor_remote_id: Cow::Borrowed(id.actor_remote_id),
+            message_remote_id: Cow::Borrowed(id.message_remote_id),
+            version: fns.version,
+            upcast_versions: fns.upcast_versions.to_vec(),
+        })
+        .collect();
+    messages.sort_by(|a, b| {
+        (&a.actor_remote_id, &a.message_remote_id).cmp(&(&b.actor_remote_id, &b.message_remote_id))
+    });
+
+    PeerCapabilities {
+        actors,
+        messages,
+        compression: Compression::supported(),
+    }
+});
+
+fn default_message_version() -> u32 {
+    DEFAULT_MESSAGE_VERSION
+}
+
 type AskResult = Result<Vec<u8>, RemoteSendError<Vec<u8>>>;
 type TellResult = Result<(), RemoteSendError>;
 type LinkResult = Result<(), RemoteSendError>;
 type UnlinkResult = Result<(), RemoteSendError>;
 type SignalLinkDiedResult = Result<(), RemoteSendError>;
+type SpawnResult = Result<ActorId, RemoteSendError>;
+type ResolveEntityResult = Result<ActorId, RemoteSendError>;
+type ResolveSingletonResult = Result<ActorId, RemoteSendError>;
 
 /// Identifier for a request within the swarm behavior.
 ///
@@ -140,9 +204,21 @@ impl fmt::Display for RequestId {
 }
 
 enum ReplyChannel {
-    Event(RequestId),
+    Event(PeerId, Option<ConnectionId>, RequestId),
     Local(oneshot::Sender<SwarmResponse>),
     Remote(request_response::ResponseChannel<SwarmResponse>),
+    Delivery(
+        PeerId,
+        Delivery,
+        request_response::ResponseChannel<SwarmResponse>,
+    ),
+}
+
+/// Where the response to an outbound request is delivered once its payload has been compressed
+/// and the request sent.
+enum PendingRequest {
+    Reply(Option<oneshot::Sender<SwarmResponse>>),
+    Delivery((PeerId, u64)),
 }
 
 /// Represents different types of requests that can be made within the swarm.
@@ -158,14 +234,29 @@ pub enum SwarmRequest {
         actor_remote_id: Cow<'static, str>,
         /// Remote identifier of the message as a static string.
         message_remote_id: Cow<'static, str>,
+        /// The version of the message's schema.
+        #[serde(default = "default_message_version")]
+        version: u32,
         /// The payload data to be sent with the request.
         payload: Vec<u8>,
+        /// The codec the payload was encoded with.
+        #[serde(default)]
+        codec: CodecId,
+        /// The compression applied to the payload.
+        #[serde(default)]
+        compression: Compression,
+        /// The compression algorithms the sender is able to decompress.
+        #[serde(default)]
+        accept_compression: Vec<Compression>,
         /// Optional timeout duration for the mailbox to receive the request.
         mailbox_timeout: Option<Duration>,
         /// Optional timeout duration to wait for a reply to the request.
         reply_timeout: Option<Duration>,
         /// Indicates whether the request should be sent immediately.
         immediate: bool,
+        /// Identifier of the actor which sent the request, if sent from within an actor.
+        #[serde(default)]
+        sender_id: Option<ActorId>,
     },
     /// Represents a request to tell a peer some information without expecting a response.
     ///
@@ -177,12 +268,30 @@ pub enum SwarmRequest {
         actor_remote_id: Cow<'static, str>,
         /// Remote identifier of the message as a static string.
         message_remote_id: Cow<'static, str>,
+        /// The version of the message's schema.
+        #[serde(default = "default_message_version")]
+        version: u32,
         /// The payload data to be sent with the message.
         payload: Vec<u8>,
+        /// The codec the payload was encoded with.
+        #[serde(default)]
+        codec: CodecId,
+        /// The compression applied to the payload.
+        #[serde(default)]
+        compression: Compression,
+        /// The compression algorithms the sender is able to decompress.
+        #[serde(default)]
+        accept_compression: Vec<Compression>,
         /// Optional timeout duration for the mailbox to receive the message.
         mailbox_timeout: Option<Duration>,
         /// Indicates whether the message should be sent immediately.
         immediate: bool,
+        /// Identifier of the actor which sent the message, if sent from within an actor.
+        #[serde(default)]
+        sender_id: Option<ActorId>,
+        /// Identifies the message for deduplication, if it's being delivered reliably.
+        #[serde(default)]
+        delivery: Option<Delivery>,
     },
     /// A request to link two actors together.
     Link {
@@ -215,6 +324,63 @@ pub enum SwarmRequest {
         /// The reason the actor died.
         stop_reason: ActorStopReason,
     },
+    /// A request to spawn an actor on the receiving node.
+    Spawn {
+        /// Remote identifier of the actor to spawn.
+        actor_remote_id: Cow<'static, str>,
+        /// The actor's encoded `Args`.
+        args: Vec<u8>,
+        /// The codec the args were encoded with.
+        codec: CodecId,
+        /// An actor to link the spawned actor to, along with its remote identifier.
+        link: Option<(ActorId, Cow<'static, str>)>,
+    },
+    /// A request asking whether an actor is still registered under a name on the receiving node.
+    CheckRegistration {
+        /// The registered actor.
+        actor_id: ActorId,
+        /// Remote identifier of the registered actor.
+        actor_remote_id: Cow<'static, str>,
+        /// The name the actor was registered under.
+        name: String,
+    },
+    /// A request to resolve a sharded entity on the receiving node, spawning it if needed.
+    ResolveEntity {
+        /// Remote identifier of the entity actor.
+        actor_remote_id: Cow<'static, str>,
+        /// The entity's ID.
+        entity_id: String,
+    },
+    /// A request to resolve a cluster singleton hosted by the receiving node.
+    ResolveSingleton {
+        /// Remote identifier of the singleton actor.
+        actor_remote_id: Cow<'static, str>,
+    },
+    /// A handshake sent when a connection to a peer is established, advertising the remote actors
+    /// and messages supported by the local node.
+    Handshake(PeerCapabilities),
+}
+
+impl SwarmRequest {
+    /// Returns whether the request can be decoded by peers on the legacy protocol.
+    fn is_legacy(&self) -> bool {
+        match self {
+            SwarmRequest::Ask { compression, .. } => *compression == Compression::None,
+            SwarmRequest::Tell {
+                compression,
+                delivery,
+                ..
+            } => *compression == Compression::None && delivery.is_none(),
+            SwarmRequest::Link { .. }
+            | SwarmRequest::Unlink { .. }
+            | SwarmRequest::SignalLinkDied { .. } => true,
+            SwarmRequest::Spawn { .. }
+            | SwarmRequest::CheckRegistration { .. }
+            | SwarmRequest::ResolveEntity { .. }
+            | SwarmRequest::ResolveSingleton { .. }
+            | SwarmRequest::Handshake(_) => false,
+        }
+    }
 }
 
 /// Represents different types of responses that can be sent within the swarm.
@@ -225,6 +391,17 @@ pub enum SwarmResponse {
     /// Contains either the successful payload data or an error indicating why the send failed.
     Ask(Result<Vec<u8>, RemoteSendError<Vec<u8>>>),
 
+    /// Represents a successful response to an `Ask` request with a compressed payload.
+    ///
+    /// This is only sent to peers which advertised support for the compression algorithm, and is
+    /// decompressed into [`SwarmResponse::Ask`] before being handled.
+    AskCompressed {
+        /// The compression applied to the payload.
+        compression: Compression,
+        /// The compressed reply payload.
+        payload: Vec<u8>,
+    },
+
     /// Represents the response to a `Tell` request.
     ///
     /// Contains either a successful acknowledgment or an error indicating why the send failed.
@@ -239,12 +416,99 @@ pub enum SwarmResponse {
     /// Represents the response to a link died signal.
     SignalLinkDied(Result<(), RemoteSendError>),
 
+    /// Represents the response to a spawn request, containing the ID of the spawned actor.
+    Spawn(Result<ActorId, RemoteSendError>),
+
+    /// Represents the response to a registration check, indicating whether the actor is
+    /// still running and registered under the name.
+    CheckRegistration(bool),
+
+    /// Represents the response to an entity resolution, containing the ID of the entity actor.
+    ResolveEntity(Result<ActorId, RemoteSendError>),
+
+    /// Represents the response to a singleton resolution, containing the ID of the singleton actor.
+    ResolveSingleton(Result<ActorId, RemoteSendError>),
+
+    /// Represents the response to a handshake, advertising the remote actors and messages
+    /// supported by the responding node.
+    Handshake(PeerCapabilities),
+
     /// Represents a failure that occurred while attempting to send an outbound request.
     ///
     /// Contains the error that caused the outbound request to fail.
     OutboundFailure(RemoteSendError),
 }
 
+/// The remote actors and messages supported by a peer.
+///
+/// Capabilities are exchanged in a handshake when a connection to a peer is first established,
+/// and can be retrieved with [`Behaviour::peer_capabilities`] or
+/// [`remote::peer_capabilities`](super::peer_capabilities).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerCapabilities {
+    /// The remote IDs of actors registered on the peer.
+    pub actors: Vec<Cow<'static, str>>,
+    /// The remote messages registered on the peer.
+    pub messages: Vec<MessageCapability>,
+    /// The compression algorithms the peer is able to decompress.
+    pub compression: Vec<Compression>,
+}
+
+impl PeerCapabilities {
+    /// Returns the capabilities of the local node.
+    pub fn local() -> &'static PeerCapabilities {
+        &LOCAL_CAPABILITIES
+    }
+
+    /// Returns whether the peer supports an actor.
+    pub fn supports_actor(&self, actor_remote_id: &str) -> bool {
+        self.actors.iter().any(|id| id == actor_remote_id)
+    }
+
+    /// Returns the capability of a message, if the peer supports it.
+    pub fn message(
+        &self,
+        actor_remote_id: &str,
+        message_remote_id: &str,
+    ) -> Option<&MessageCapability> {
+        self.messages.iter().find(|message| {
+            message.actor_remote_id == actor_remote_id
+                && message.message_remote_id == message_remote_id
+        })
+    }
+
+    /// Returns whether the peer is able to decode a version of a message.
+    pub fn supports_message(
+        &self,
+        actor_remote_id: &str,
+        message_remote_id: &str,
+        version: u32,
+    ) -> bool {
+        self.message(actor_remote_id, message_remote_id)
+            .is_some_and(|message| message.supports_version(version))
+    }
+}
+
+/// A remote message supported by a peer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCapability {
+    /// The remote ID of the actor handling the message.
+    pub actor_remote_id: Cow<'static, str>,
+    /// The remote ID of the message.
+    pub message_remote_id: Cow<'static, str>,
+    /// The current version of the message.
+    pub version: u32,
+    /// The older versions of the message which can be upcast to the current version.
+    pub upcast_versions: Vec<u32>,
+}
+
+impl MessageCapability {
+    /// Returns whether a version of the message can be decoded.
+    pub fn supports_version(&self, version: u32) -> bool {
+        version == self.version || self.upcast_versions.contains(&version)
+    }
+}
+
 /// The events produced by the `Messaging` behaviour.
 ///
 /// See [`NetworkBehaviour::poll`].
@@ -310,6 +574,54 @@ pub enum Event {
         result: SignalLinkDiedResult,
     },
 
+    /// Result of spawning an actor on a peer.
+    SpawnResult {
+        /// The peer that spawned the actor.
+        peer: PeerId,
+        /// The connection used, if any.
+        connection_id: Option<ConnectionId>,
+        /// The request ID.
+        request_id: RequestId,
+        /// The result of the spawn operation.
+        result: SpawnResult,
+    },
+
+    /// Result of checking whether an actor is still registered under a name on a peer.
+    CheckRegistrationResult {
+        /// The peer hosting the actor.
+        peer: PeerId,
+        /// The connection used, if any.
+        connection_id: Option<ConnectionId>,
+        /// The request ID.
+        request_id: RequestId,
+        /// Whether the actor is still running and registered under the name.
+        registered: bool,
+    },
+
+    /// Result of resolving a sharded entity on a peer.
+    ResolveEntityResult {
+        /// The peer hosting the entity.
+        peer: PeerId,
+        /// The connection used, if any.
+        connection_id: Option<ConnectionId>,
+        /// The request ID.
+        request_id: RequestId,
+        /// The result of the resolution.
+        result: ResolveEntityResult,
+    },
+
+    /// Result of resolving a cluster singleton on a peer.
+    ResolveSingletonResult {
+        /// The peer hosting the singleton.
+        peer: PeerId,
+        /// The connection used, if any.
+        connection_id: Option<ConnectionId>,
+        /// The request ID.
+        request_id: RequestId,
+        /// The result of the resolution.
+        result: ResolveSingletonResult,
+    },
+
     /// An outbound request failed.
     OutboundFailure {
         /// The peer to whom the request was sent.
@@ -334,6 +646,25 @@ pub enum Event {
         error: request_response::InboundFailure,
     },
 
+    /// A handshake with a peer completed, and its capabilities are known.
+    Handshake {
+        /// The peer the handshake was performed with.
+        peer: PeerId,
+        /// The remote actors and messages supported by the peer.
+        capabilities: PeerCapabilities,
+    },
+
+    /// An inbound request was denied by the configured [`AccessPolicy`].
+    AccessDenied {
+        /// The peer which sent the request.
+        peer: PeerId,
+        /// The remote ID of the actor the message was sent to.
+        actor_remote_id: Cow<'static, str>,
+        /// The remote ID of the message, or a reserved ID such as [`acl::SPAWN`] for requests
+        /// which don't carry a message.
+        message_remote_id: Cow<'static, str>,
+    },
+
     /// A response to an inbound request has been sent.
     ///
     /// When this event is received, the response has been flushed on
@@ -349,12 +680,19 @@ pub enum Event {
 }
 
 /// The configuration for a `messaging::Behaviour` protocol.
-#[derive(Debug, Clone, Copy)]
+#[derive(Clone)]
 pub struct Config {
     request_timeout: Duration,
     max_concurrent_streams: usize,
     request_size_maximum: u64,
     response_size_maximum: u64,
+    codec: CodecId,
+    compression: Compression,
+    compression_threshold: u64,
+    decompressed_size_maximum: u64,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+    redelivery_backoff: (Duration, Duration),
+    max_delivery_attempts: Option<u32>,
 }
 
 impl Default for Config {
@@ -364,6 +702,13 @@ impl Default for Config {
             max_concurrent_streams: 100,
             request_size_maximum: 1024 * 1024,
             response_size_maximum: 10 * 1024 * 1024,
+            codec: CodecId::MessagePack,
+            compression: Compression::None,
+            compression_threshold: 64 * 1024,
+            decompressed_size_maximum: 256 * 1024 * 1024,
+            access_policy: None,
+            redelivery_backoff: (Duration::from_millis(500), Duration::from_secs(30)),
+            max_delivery_attempts: None,
         }
     }
 }
@@ -392,6 +737,88 @@ impl Config {
         self.response_size_maximum = bytes;
         self
     }
+
+    /// Sets the codec used to encode remote messages.
+    ///
+    /// Messages which specify their own codec with `#[remote_message(codec = ...)]` ignore this setting.
+    /// Defaults to [`CodecId::MessagePack`].
+    pub fn with_codec(mut self, codec: CodecId) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Sets the compression applied to ask and tell payloads, and to ask replies.
+    ///
+    /// Payloads are only compressed when sent to peers which support the algorithm, and when
+    /// they're at least as large as the [compression threshold](Config::with_compression_threshold).
+    /// Defaults to [`Compression::None`].
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the minimum payload size in bytes for compression to be applied.
+    ///
+    /// Defaults to 64 KiB.
+    pub fn with_compression_threshold(mut self, bytes: u64) -> Self {
+        self.compression_threshold = bytes;
+        self
+    }
+
+    /// Sets the limit for the size of decompressed payloads in bytes.
+    ///
+    /// Defaults to 256 MiB.
+    pub fn with_decompressed_size_maximum(mut self, bytes: u64) -> Self {
+        self.decompressed_size_maximum = bytes;
+        self
+    }
+
+    /// Sets the policy deciding which peers are allowed to send messages to local actors.
+    ///
+    /// Denied ask, tell, and stream requests fail with [`RemoteSendError::Unauthorized`], and emit an
+    /// [`Event::AccessDenied`]. Defaults to allowing all requests.
+    /// See the [`acl`] module for more information.
+    pub fn with_access_policy(mut self, policy: impl AccessPolicy) -> Self {
+        self.access_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Sets the backoff between attempts to deliver a reliable tell, doubling from `initial` up
+    /// to `max` after each failed attempt.
+    ///
+    /// Pending messages are also resent as soon as a connection to their peer is established.
+    /// Defaults to 500 milliseconds, up to 30 seconds.
+    pub fn with_redelivery_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.redelivery_backoff = (initial, max);
+        self
+    }
+
+    /// Sets the number of attempts made to deliver a reliable tell before giving up.
+    ///
+    /// Defaults to `None`, retrying until the message is acknowledged, fails with an error which
+    /// retrying can't fix, or its peer is removed from the cluster.
+    pub fn with_max_delivery_attempts(mut self, attempts: Option<u32>) -> Self {
+        self.max_delivery_attempts = attempts;
+        self
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("request_timeout", &self.request_timeout)
+            .field("max_concurrent_streams", &self.max_concurrent_streams)
+            .field("request_size_maximum", &self.request_size_maximum)
+            .field("response_size_maximum", &self.response_size_maximum)
+            .field("codec", &self.codec)
+            .field("compression", &self.compression)
+            .field("compression_threshold", &self.compression_threshold)
+            .field("decompressed_size_maximum", &self.decompressed_size_maximum)
+            .field("access_policy", &self.access_policy.is_some())
+            .field("redelivery_backoff", &self.redelivery_backoff)
+            .field("max_delivery_attempts", &self.max_delivery_attempts)
+            .finish()
+    }
 }
 
 impl From<Config> for request_response::Config {
@@ -402,11 +829,95 @@ impl From<Config> for request_response::Config {
     }
 }
 
-impl<Req, Resp> From<Config> for request_response::cbor::codec::Codec<Req, Resp> {
+impl From<Config> for SwarmCodec {
     fn from(config: Config) -> Self {
-        request_response::cbor::codec::Codec::default()
-            .set_request_size_maximum(config.request_size_maximum)
-            .set_response_size_maximum(config.response_size_maximum)
+        SwarmCodec {
+            inner: request_response::cbor::codec::Codec::default()
+                .set_request_size_maximum(config.request_size_maximum)
+                .set_response_size_maximum(config.response_size_maximum),
+        }
+    }
+}
+
+/// The CBOR codec for swarm requests and responses, refusing to write requests which peers on the
+/// legacy `/kameo/messaging/1.0.0` protocol are unable to decode.
+#[derive(Clone)]
+#[allow(missing_debug_implementations)]
+pub struct SwarmCodec {
+    inner: request_response::cbor::codec::Codec<SwarmRequest, SwarmResponse>,
+}
+
+impl request_response::Codec for SwarmCodec {
+    type Protocol = StreamProtocol;
+    type Request = SwarmRequest;
+    type Response = SwarmResponse;
+
+    fn read_request<'a, 'b, 'c, 'f, T>(
+        &'a mut self,
+        protocol: &'b StreamProtocol,
+        io: &'c mut T,
+    ) -> BoxFuture<'f, io::Result<SwarmRequest>>
+    where
+        T: AsyncRead + Unpin + Send + 'f,
+        'a: 'f,
+        'b: 'f,
+        'c: 'f,
+        Self: 'f,
+    {
+        self.inner.read_request(protocol, io)
+    }
+
+    fn read_response<'a, 'b, 'c, 'f, T>(
+        &'a mut self,
+        protocol: &'b StreamProtocol,
+        io: &'c mut T,
+    ) -> BoxFuture<'f, io::Result<SwarmResponse>>
+    where
+        T: AsyncRead + Unpin + Send + 'f,
+        'a: 'f,
+        'b: 'f,
+        'c: 'f,
+        Self: 'f,
+    {
+        self.inner.read_response(protocol, io)
+    }
+
+    fn write_request<'a, 'b, 'c, 'f, T>(
+        &'a mut self,
+        protocol: &'b StreamProtocol,
+        io: &'c mut T,
+        req: SwarmRequest,
+    ) -> BoxFuture<'f, io::Result<()>>
+    where
+        T: AsyncWrite + Unpin + Send + 'f,
+        'a: 'f,
+        'b: 'f,
+        'c: 'f,
+        Self: 'f,
+    {
+        if *protocol == LEGACY_PROTO_NAME && !req.is_legacy() {
+            return Box::pin(std_future::ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "request is not supported by peers on /kameo/messaging/1.0.0",
+            ))));
+        }
+        self.inner.write_request(protocol, io, req)
+    }
+
+    fn write_response<'a, 'b, 'c, 'f, T>(
+        &'a mut self,
+        protocol: &'b StreamProtocol,
+        io: &'c mut T,
+        res: SwarmResponse,
+    ) -> BoxFuture<'f, io::Result<()>>
+    where
+        T: AsyncWrite + Unpin + Send + 'f,
+        'a: 'f,
+        'b: 'f,
+        'c: 'f,
+        Self: 'f,
+    {
+        self.inner.write_response(protocol, io, res)
     }
 }
 
@@ -414,31 +925,80 @@ impl<Req, Resp> From<Config> for request_response::cbor::codec::Codec<Req, Resp>
 /// on top of the request response protocol.
 #[allow(missing_debug_implementations)]
 pub struct Behaviour {
-    request_response: request_response::cbor::Behaviour<SwarmRequest, SwarmResponse>,
+    request_response: request_response::Behaviour<SwarmCodec>,
     local_peer_id: PeerId,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+    codec: CodecId,
+    compression: Compression,
+    compression_threshold: u64,
+    decompressed_size_maximum: u64,
+    peer_compression: HashMap<PeerId, Vec<Compression>>,
+    peer_capabilities: HashMap<PeerId, PeerCapabilities>,
+    handshakes: HashSet<request_response::OutboundRequestId>,
     next_id: u64,
     requests: HashMap<RequestId, (PeerId, Option<oneshot::Sender<SwarmResponse>>)>,
+    compressing: JoinSet<(PeerId, SwarmRequest, PendingRequest)>,
+    outbox: Outbox,
+    inbox: Inbox<request_response::ResponseChannel<SwarmResponse>>,
     join_set: JoinSet<(ReplyChannel, SwarmResponse)>,
+    swarm: Option<SwarmSender>,
 }
 
 impl Behaviour {
     /// Creates a new messaging behaviour.
     pub fn new(local_peer_id: PeerId, config: Config) -> Self {
-        let request_response = request_response::cbor::Behaviour::with_codec(
-            config.into(),
-            [(PROTO_NAME, request_response::ProtocolSupport::Full)],
-            config.into(),
+        let request_response = request_response::Behaviour::with_codec(
+            config.clone().into(),
+            [
+                (PROTO_NAME, request_response::ProtocolSupport::Full),
+                (LEGACY_PROTO_NAME, request_response::ProtocolSupport::Full),
+            ],
+            config.clone().into(),
         );
 
         Behaviour {
             request_response,
             local_peer_id,
+            access_policy: config.access_policy,
+            codec: config.codec,
+            compression: config.compression,
+            compression_threshold: config.compression_threshold,
+            decompressed_size_maximum: config.decompressed_size_maximum,
+            peer_compression: HashMap::new(),
+            peer_capabilities: HashMap::new(),
+            handshakes: HashSet::new(),
             next_id: 0,
             requests: HashMap::new(),
+            compressing: JoinSet::new(),
+            outbox: Outbox::new(config.redelivery_backoff, config.max_delivery_attempts),
+            inbox: Inbox::new(),
             join_set: JoinSet::new(),
+            swarm: None,
         }
     }
 
+    /// Sets the swarm actor refs within received messages are bound to, instead of the global
+    /// actor swarm.
+    pub(super) fn set_swarm(&mut self, swarm: SwarmSender) {
+        self.swarm = Some(swarm);
+    }
+
+    /// Returns the codec messages are encoded with by default.
+    pub fn codec(&self) -> CodecId {
+        self.codec
+    }
+
+    pub(super) fn access_policy(&self) -> Option<Arc<dyn AccessPolicy>> {
+        self.access_policy.clone()
+    }
+
+    /// Returns the capabilities of a connected peer, if a handshake with it has completed.
+    ///
+    /// Peers running older versions of kameo don't support handshakes, and always return `None`.
+    pub fn peer_capabilities(&self, peer_id: &PeerId) -> Option<&PeerCapabilities> {
+        self.peer_capabilities.get(peer_id)
+    }
+
     /// Sends an ask request to a remote actor.
     ///
     /// This is a low-level method that sends a request expecting a reply and
@@ -450,14 +1010,18 @@ impl Behaviour {
     /// * `actor_id` - The target actor's ID
     /// * `actor_remote_id` - The target actor's remote type ID
     /// * `message_remote_id` - The message's remote type ID
-    /// * `payload` - The serialized message payload
+    /// * `payload` - The message payload, encoded with the configured codec as version 1 of the message
     /// * `mailbox_timeout` - Optional timeout for mailbox delivery
     /// * `reply_timeout` - Optional timeout for receiving a reply
     /// * `immediate` - Whether to fail if the mailbox is full
     ///
+    /// Payloads above the compression threshold are compressed on the blocking thread pool, and
+    /// sent once compressed.
+    ///
     /// # Returns
     ///
-    /// The request ID for tracking the ask progress.
+    /// The request ID for tracking the ask progress, or `None` if the payload is being
+    /// compressed first, as the request isn't sent until it's compressed.
     #[allow(clippy::too_many_arguments)]
     pub fn ask(
         &mut self,
@@ -475,18 +1039,20 @@ impl Behaviour {
         reply_timeout: Option<Duration>,
         // Fail if mailbox is full.
         immediate: bool,
-    ) -> RequestId {
+    ) -> Option<RequestId> {
         self.ask_with_reply(
             actor_id,
             actor_remote_id,
             message_remote_id,
+            DEFAULT_MESSAGE_VERSION,
             payload,
+            self.codec,
             mailbox_timeout,
             reply_timeout,
             immediate,
             None,
+            None,
         )
-        .unwrap()
     }
 
     /// Sends a tell message to a remote actor.
@@ -500,13 +1066,17 @@ impl Behaviour {
     /// * `actor_id` - The target actor's ID
     /// * `actor_remote_id` - The target actor's remote type ID
     /// * `message_remote_id` - The message's remote type ID
-    /// * `payload` - The serialized message payload
+    /// * `payload` - The message payload, encoded with the configured codec as version 1 of the message
     /// * `mailbox_timeout` - Optional timeout for mailbox delivery
     /// * `immediate` - Whether to fail if the mailbox is full
     ///
+    /// Payloads above the compression threshold are compressed on the blocking thread pool, and
+    /// sent once compressed.
+    ///
     /// # Returns
     ///
-    /// The request ID for tracking the tell progress.
+    /// The request ID for tracking the tell progress, or `None` if the payload is being
+    /// compressed first, as the request isn't sent until it's compressed.
     pub fn tell(
         &mut self,
         // Actor ID.
@@ -521,17 +1091,19 @@ impl Behaviour {
         mailbox_timeout: Option<Duration>,
         // Fail if mailbox is full.
         immediate: bool,
-    ) -> RequestId {
+    ) -> Option<RequestId> {
         self.tell_with_reply(
             actor_id,
             actor_remote_id,
             message_remote_id,
+            DEFAULT_MESSAGE_VERSION,
             payload,
+            self.codec,
             mailbox_timeout,
             immediate,
             None,
+            None,
         )
-        .unwrap()
     }
 
     /// Creates a link between two actors across the network.
@@ -636,19 +1208,125 @@ impl Behaviour {
         .unwrap()
     }
 
+    /// Spawns an actor on a peer.
+    ///
+    /// This is a low-level method that spawns an actor and generates events.
+    /// Use [`remote::spawn`](super::spawn) for higher-level spawning that doesn't emit events.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer to spawn the actor on
+    /// * `actor_remote_id` - The remote type ID of the actor to spawn
+    /// * `args` - The actor's `Args`, encoded with the configured codec
+    /// * `link` - An optional actor and its remote type ID to link the spawned actor to
+    ///
+    /// # Returns
+    ///
+    /// The request ID for tracking the spawn progress.
+    pub fn spawn(
+        &mut self,
+        // Peer ID.
+        peer_id: PeerId,
+        // Actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        // Encoded actor args.
+        args: Vec<u8>,
+        // Actor to link to.
+        link: Option<(ActorId, Cow<'static, str>)>,
+    ) -> RequestId {
+        let codec = self.codec;
+        self.spawn_with_reply(peer_id, actor_remote_id, args, codec, link, None)
+            .unwrap()
+    }
+
+    /// Checks whether an actor is still running and registered under a name on its peer.
+    ///
+    /// This is a low-level method that generates events. Lookups use this to filter out
+    /// registrations which have gone stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor_id` - The registered actor
+    /// * `actor_remote_id` - The registered actor's remote ID
+    /// * `name` - The name the actor was registered under
+    ///
+    /// # Returns
+    ///
+    /// The request ID for tracking the check.
+    pub fn check_registration(
+        &mut self,
+        actor_id: ActorId,
+        actor_remote_id: Cow<'static, str>,
+        name: impl Into<String>,
+    ) -> RequestId {
+        self.check_registration_with_reply(actor_id, actor_remote_id, name.into(), None)
+            .unwrap()
+    }
+
+    /// Resolves a sharded entity on a peer, spawning it if it isn't running.
+    ///
+    /// This is a low-level method that generates events. Use a
+    /// [`ShardRegion`](super::sharding::ShardRegion) to send messages to entities.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer owning the entity's shard
+    /// * `actor_remote_id` - The remote type ID of the entity actor
+    /// * `entity_id` - The entity's ID
+    ///
+    /// # Returns
+    ///
+    /// The request ID for tracking the resolution.
+    pub fn resolve_entity(
+        &mut self,
+        peer_id: PeerId,
+        actor_remote_id: Cow<'static, str>,
+        entity_id: impl Into<String>,
+    ) -> RequestId {
+        self.resolve_entity_with_reply(peer_id, actor_remote_id, entity_id.into(), None)
+            .unwrap()
+    }
+
+    /// Resolves a cluster singleton hosted by a peer.
+    ///
+    /// This is a low-level method that generates events. Use a
+    /// [`ClusterSingleton`](super::singleton::ClusterSingleton) proxy to send messages to
+    /// singletons.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The peer hosting the singleton
+    /// * `actor_remote_id` - The remote type ID of the singleton actor
+    ///
+    /// # Returns
+    ///
+    /// The request ID for tracking the resolution.
+    pub fn resolve_singleton(
+        &mut self,
+        peer_id: PeerId,
+        actor_remote_id: Cow<'static, str>,
+    ) -> RequestId {
+        self.resolve_singleton_with_reply(peer_id, actor_remote_id, None)
+            .unwrap()
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(super) fn ask_with_reply(
         &mut self,
         actor_id: ActorId,
         actor_remote_id: Cow<'static, str>,
         message_remote_id: Cow<'static, str>,
+        version: u32,
         payload: Vec<u8>,
+        codec: CodecId,
         mailbox_timeout: Option<Duration>,
         reply_timeout: Option<Duration>,
         immediate: bool,
+        sender_id: Option<ActorId>,
         reply: Option<oneshot::Sender<SwarmResponse>>,
     ) -> Option<RequestId> {
         let peer_id = actor_id.peer_id().expect("swarm should be bootstrapped");
+        let local_peer_id = self.local_peer_id;
         self.request_with_reply(
             peer_id,
             reply,
@@ -656,28 +1334,37 @@ impl Behaviour {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
             ),
             |(
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
             )| {
                 ask(
                     actor_id,
                     actor_remote_id,
                     message_remote_id,
+                    version,
                     payload,
+                    codec,
                     mailbox_timeout,
                     reply_timeout,
                     immediate,
+                    RemoteSender::new(local_peer_id, sender_id),
                 )
                 .map(SwarmResponse::Ask)
             },
@@ -685,18 +1372,26 @@ impl Behaviour {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
             )| SwarmRequest::Ask {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
+                compression: Compression::None,
+                accept_compression: Compression::supported(),
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
             },
         )
     }
@@ -707,12 +1402,16 @@ impl Behaviour {
         actor_id: ActorId,
         actor_remote_id: Cow<'static, str>,
         message_remote_id: Cow<'static, str>,
+        version: u32,
         payload: Vec<u8>,
+        codec: CodecId,
         mailbox_timeout: Option<Duration>,
         immediate: bool,
+        sender_id: Option<ActorId>,
         reply: Option<oneshot::Sender<SwarmResponse>>,
     ) -> Option<RequestId> {
         let peer_id = actor_id.peer_id().expect("swarm should be bootstrapped");
+        let local_peer_id = self.local_peer_id;
         self.request_with_reply(
             peer_id,
             reply,
@@ -720,25 +1419,34 @@ impl Behaviour {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 immediate,
+                sender_id,
             ),
             |(
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 immediate,
+                sender_id,
             )| {
                 tell(
                     actor_id,
                     actor_remote_id,
                     message_remote_id,
+                    version,
                     payload,
+                    codec,
                     mailbox_timeout,
                     immediate,
+                    RemoteSender::new(local_peer_id, sender_id),
                 )
                 .map(SwarmResponse::Tell)
             },
@@ -746,40 +1454,162 @@ impl Behaviour {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
                 mailbox_timeout,
                 immediate,
+                sender_id,
             )| SwarmRequest::Tell {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
+                compression: Compression::None,
+                accept_compression: Compression::supported(),
                 mailbox_timeout,
                 immediate,
+                sender_id,
+                delivery: None,
             },
         )
     }
 
-    pub(super) fn link_with_reply(
+    /// Sends a tell message which is resent until the receiving peer acknowledges it.
+    ///
+    /// Messages to local actors don't go through the outbox, since they can't be lost in transit.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn reliable_tell_with_reply(
         &mut self,
         actor_id: ActorId,
         actor_remote_id: Cow<'static, str>,
-        sibbling_id: ActorId,
-        sibbling_remote_id: Cow<'static, str>,
+        message_remote_id: Cow<'static, str>,
+        version: u32,
+        payload: Vec<u8>,
+        codec: CodecId,
+        mailbox_timeout: Option<Duration>,
+        sender_id: Option<ActorId>,
         reply: Option<oneshot::Sender<SwarmResponse>>,
-    ) -> Option<RequestId> {
-        let peer_id = actor_id.peer_id().expect("swarm should be bootstrapped");
-        self.request_with_reply(
+    ) {
+        let peer_id = *actor_id.peer_id().expect("swarm should be bootstrapped");
+        if peer_id == self.local_peer_id {
+            self.tell_with_reply(
+                actor_id,
+                actor_remote_id,
+                message_remote_id,
+                version,
+                payload,
+                codec,
+                mailbox_timeout,
+                false,
+                sender_id,
+                reply,
+            );
+            return;
+        }
+
+        let key = self.outbox.push(
             peer_id,
-            reply,
-            (actor_id, actor_remote_id, sibbling_id, sibbling_remote_id),
-            |(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)| {
-                link(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)
-                    .map(SwarmResponse::Link)
+            OutboxMessage {
+                actor_id,
+                actor_remote_id,
+                message_remote_id,
+                version,
+                payload,
+                codec,
+                mailbox_timeout,
+                sender_id,
             },
-            move |(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)| {
-                SwarmRequest::Link {
-                    actor_id,
+            reply,
+        );
+        self.send_reliable(key);
+    }
+
+    /// Gives up on reliable tells to a peer which has been removed from the cluster.
+    pub(super) fn peer_removed(&mut self, peer_id: PeerId) {
+        self.outbox.expire(peer_id);
+    }
+
+    /// Makes an attempt to deliver a message in the outbox, unless one is already in flight.
+    fn send_reliable(&mut self, key: (PeerId, u64)) {
+        let Some((message, delivery)) = self.outbox.start_attempt(key) else {
+            return;
+        };
+
+        let (peer_id, _) = key;
+        let request = SwarmRequest::Tell {
+            actor_id: message.actor_id,
+            actor_remote_id: message.actor_remote_id,
+            message_remote_id: message.message_remote_id,
+            version: message.version,
+            payload: message.payload,
+            codec: message.codec,
+            compression: Compression::None,
+            accept_compression: Compression::supported(),
+            mailbox_timeout: message.mailbox_timeout,
+            immediate: false,
+            sender_id: message.sender_id,
+            delivery: Some(delivery),
+        };
+        match self.outgoing_compression(&peer_id, &request) {
+            Some(compression) => {
+                self.compressing.spawn_blocking(move || {
+                    let request = compress_request(request, compression);
+                    (peer_id, request, PendingRequest::Delivery(key))
+                });
+            }
+            None => {
+                self.send_request(peer_id, request, PendingRequest::Delivery(key));
+            }
+        }
+    }
+
+    /// Records the outcome of delivering a reliable tell, and acknowledges it to the sender
+    /// along with any copies received while it was being delivered.
+    fn complete_delivery(
+        &mut self,
+        peer_id: PeerId,
+        delivery: Delivery,
+        channel: request_response::ResponseChannel<SwarmResponse>,
+        res: SwarmResponse,
+    ) {
+        let SwarmResponse::Tell(res) = res else {
+            unreachable!("reliable tells are always answered with a tell response");
+        };
+
+        let waiting = self.inbox.complete(peer_id, delivery, res.is_ok());
+        for channel in waiting {
+            let _ = self
+                .request_response
+                .send_response(channel, SwarmResponse::Tell(res.clone()));
+        }
+        let _ = self
+            .request_response
+            .send_response(channel, SwarmResponse::Tell(res));
+    }
+
+    pub(super) fn link_with_reply(
+        &mut self,
+        actor_id: ActorId,
+        actor_remote_id: Cow<'static, str>,
+        sibbling_id: ActorId,
+        sibbling_remote_id: Cow<'static, str>,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> Option<RequestId> {
+        let peer_id = actor_id.peer_id().expect("swarm should be bootstrapped");
+        self.request_with_reply(
+            peer_id,
+            reply,
+            (actor_id, actor_remote_id, sibbling_id, sibbling_remote_id),
+            |(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)| {
+                link(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)
+                    .map(SwarmResponse::Link)
+            },
+            move |(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)| {
+                SwarmRequest::Link {
+                    actor_id,
                     actor_remote_id,
                     sibbling_id,
                     sibbling_remote_id,
@@ -851,12 +1681,119 @@ impl Behaviour {
         )
     }
 
+    pub(super) fn spawn_with_reply(
+        &mut self,
+        peer_id: PeerId,
+        actor_remote_id: Cow<'static, str>,
+        args: Vec<u8>,
+        codec: CodecId,
+        link: Option<(ActorId, Cow<'static, str>)>,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> Option<RequestId> {
+        let local_peer_id = self.local_peer_id;
+        self.request_with_reply(
+            &peer_id,
+            reply,
+            (actor_remote_id, args, codec, link),
+            move |(actor_remote_id, args, codec, link)| {
+                spawn(actor_remote_id, args, codec, link)
+                    .map(move |res| SwarmResponse::Spawn(with_local_peer_id(res, local_peer_id)))
+            },
+            move |(actor_remote_id, args, codec, link)| SwarmRequest::Spawn {
+                actor_remote_id,
+                args,
+                codec,
+                link,
+            },
+        )
+    }
+
+    pub(super) fn check_registration_with_reply(
+        &mut self,
+        actor_id: ActorId,
+        actor_remote_id: Cow<'static, str>,
+        name: String,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> Option<RequestId> {
+        let peer_id = *actor_id.peer_id().expect("swarm should be bootstrapped");
+        self.request_with_reply(
+            &peer_id,
+            reply,
+            (actor_id, actor_remote_id, name),
+            |(actor_id, _, name)| {
+                check_registration(actor_id, name).map(SwarmResponse::CheckRegistration)
+            },
+            move |(actor_id, actor_remote_id, name)| SwarmRequest::CheckRegistration {
+                actor_id,
+                actor_remote_id,
+                name,
+            },
+        )
+    }
+
+    pub(super) fn resolve_entity_with_reply(
+        &mut self,
+        peer_id: PeerId,
+        actor_remote_id: Cow<'static, str>,
+        entity_id: String,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> Option<RequestId> {
+        let local_peer_id = self.local_peer_id;
+        self.request_with_reply(
+            &peer_id,
+            reply,
+            (actor_remote_id, entity_id),
+            move |(actor_remote_id, entity_id)| {
+                sharding::resolve_local_entity(local_peer_id, actor_remote_id, entity_id).map(
+                    move |res| SwarmResponse::ResolveEntity(with_local_peer_id(res, local_peer_id)),
+                )
+            },
+            move |(actor_remote_id, entity_id)| SwarmRequest::ResolveEntity {
+                actor_remote_id,
+                entity_id,
+            },
+        )
+    }
+
+    pub(super) fn resolve_singleton_with_reply(
+        &mut self,
+        peer_id: PeerId,
+        actor_remote_id: Cow<'static, str>,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> Option<RequestId> {
+        let local_peer_id = self.local_peer_id;
+        self.request_with_reply(
+            &peer_id,
+            reply,
+            actor_remote_id,
+            move |actor_remote_id| {
+                singleton::resolve_local_singleton(local_peer_id, actor_remote_id).map(move |res| {
+                    SwarmResponse::ResolveSingleton(with_local_peer_id(res, local_peer_id))
+                })
+            },
+            move |actor_remote_id| SwarmRequest::ResolveSingleton { actor_remote_id },
+        )
+    }
+
+    /// Spawns a request handler, with actor refs in its messages bound to this swarm.
+    fn spawn_handler<F>(&mut self, fut: F)
+    where
+        F: Future<Output = (ReplyChannel, SwarmResponse)> + Send + 'static,
+    {
+        self.join_set.spawn(in_swarm(self.swarm.clone(), fut));
+    }
+
     fn new_local_request_id(&mut self) -> RequestId {
         let id = RequestId::Local(self.next_id);
         self.next_id += 1;
         id
     }
 
+    /// Sends a request to a peer, or handles it locally if the peer is the local node.
+    ///
+    /// Large ask and tell payloads are compressed on the blocking thread pool before being sent,
+    /// in which case no request ID is returned.
+    #[allow(clippy::too_many_arguments)]
     fn request_with_reply<L, LF, R, T>(
         &mut self,
         peer_id: &PeerId,
@@ -875,22 +1812,166 @@ impl Behaviour {
                 Some(tx) => (None, ReplyChannel::Local(tx)),
                 None => {
                     let request_id = self.new_local_request_id();
-                    (Some(request_id), ReplyChannel::Event(request_id))
+                    (
+                        Some(request_id),
+                        ReplyChannel::Event(self.local_peer_id, None, request_id),
+                    )
                 }
             };
 
-            self.join_set
-                .spawn(local(shared_data).map(|resp| (channel, resp)));
+            self.spawn_handler(local(shared_data).map(|resp| (channel, resp)));
 
             request_id
         } else {
-            let request_id = RequestId::Outbound(
-                self.request_response
-                    .send_request(peer_id, remote(shared_data)),
-            );
-            self.requests.insert(request_id, (*peer_id, reply));
+            let request = remote(shared_data);
+            match self.outgoing_compression(peer_id, &request) {
+                Some(compression) => {
+                    let peer_id = *peer_id;
+                    let pending = PendingRequest::Reply(reply);
+                    self.compressing.spawn_blocking(move || {
+                        (peer_id, compress_request(request, compression), pending)
+                    });
+                    None
+                }
+                None => Some(self.send_request(*peer_id, request, PendingRequest::Reply(reply))),
+            }
+        }
+    }
+
+    /// Sends a request to a remote peer, tracking where its response should be delivered.
+    fn send_request(
+        &mut self,
+        peer_id: PeerId,
+        request: SwarmRequest,
+        pending: PendingRequest,
+    ) -> RequestId {
+        let request_id = self.request_response.send_request(&peer_id, request);
+        match pending {
+            PendingRequest::Reply(reply) => {
+                self.requests
+                    .insert(RequestId::Outbound(request_id), (peer_id, reply));
+            }
+            PendingRequest::Delivery(key) => self.outbox.sent(request_id, key),
+        }
+
+        RequestId::Outbound(request_id)
+    }
+
+    fn send_handshake(&mut self, peer_id: &PeerId) {
+        let capabilities = self.capabilities_for(*peer_id);
+        let request_id = self
+            .request_response
+            .send_request(peer_id, SwarmRequest::Handshake(capabilities));
+        self.handshakes.insert(request_id);
+    }
+
+    /// Returns the capabilities advertised to a peer, listing only the actors and messages the
+    /// access policy allows it to use.
+    fn capabilities_for(&self, peer: PeerId) -> PeerCapabilities {
+        let local = PeerCapabilities::local();
+        let Some(policy) = self.access_policy.as_deref() else {
+            return local.clone();
+        };
+
+        let messages: Vec<_> = local
+            .messages
+            .iter()
+            .filter(|message| {
+                is_allowed(
+                    Some(policy),
+                    peer,
+                    &message.actor_remote_id,
+                    &message.message_remote_id,
+                )
+            })
+            .cloned()
+            .collect();
+        let actors = local
+            .actors
+            .iter()
+            .filter(|actor_remote_id| {
+                messages
+                    .iter()
+                    .any(|message| message.actor_remote_id == **actor_remote_id)
+                    || [acl::SPAWN, acl::LINK]
+                        .into_iter()
+                        .any(|id| is_allowed(Some(policy), peer, actor_remote_id, id))
+            })
+            .cloned()
+            .collect();
+
+        PeerCapabilities {
+            actors,
+            messages,
+            compression: local.compression.clone(),
+        }
+    }
 
-            Some(request_id)
+    fn handle_handshake(&mut self, peer: PeerId, capabilities: PeerCapabilities) -> Event {
+        self.peer_compression
+            .insert(peer, capabilities.compression.clone());
+        self.peer_capabilities.insert(peer, capabilities.clone());
+        Event::Handshake { peer, capabilities }
+    }
+
+    /// Returns the compression to apply to payloads sent to a peer accepting the given algorithms.
+    fn negotiate_compression(&self, accepted: &[Compression]) -> Compression {
+        if accepted.contains(&self.compression) {
+            self.compression
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns the compression to apply to an outgoing ask or tell request, if its payload is large
+    /// enough and the peer supports it.
+    fn outgoing_compression(&self, peer_id: &PeerId, req: &SwarmRequest) -> Option<Compression> {
+        let (SwarmRequest::Ask { payload, .. } | SwarmRequest::Tell { payload, .. }) = req else {
+            return None;
+        };
+        if (payload.len() as u64) < self.compression_threshold {
+            return None;
+        }
+
+        let accepted = self
+            .peer_compression
+            .get(peer_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        match self.negotiate_compression(accepted) {
+            Compression::None => None,
+            compression => Some(compression),
+        }
+    }
+
+    /// Records that a peer is able to decompress an algorithm it has compressed a reply with.
+    fn accept_peer_compression(&mut self, peer: PeerId, compression: Compression) {
+        let accepted = self.peer_compression.entry(peer).or_default();
+        if !accepted.contains(&compression) {
+            accepted.push(compression);
+        }
+    }
+
+    /// Handles the response to a request, delivering it to its reply channel.
+    ///
+    /// Returns an event if the response is to be emitted from the behaviour.
+    fn handle_reply(&mut self, ch: ReplyChannel, res: SwarmResponse) -> Option<Event> {
+        match ch {
+            ReplyChannel::Event(peer, connection_id, request_id) => {
+                Some(Event::from_swarm_resp(res, peer, connection_id, request_id))
+            }
+            ReplyChannel::Local(tx) => {
+                let _ = tx.send(res);
+                None
+            }
+            ReplyChannel::Remote(ch) => {
+                let _ = self.request_response.send_response(ch, res);
+                None
+            }
+            ReplyChannel::Delivery(peer, delivery, ch) => {
+                self.complete_delivery(peer, delivery, ch, res);
+                None
+            }
         }
     }
 
@@ -908,8 +1989,8 @@ impl Behaviour {
                 request_response::Message::Request {
                     request, channel, ..
                 } => {
-                    self.handle_incoming_request(request, channel);
-                    (true, None)
+                    let ev = self.handle_incoming_request(peer, request, channel);
+                    (true, ev)
                 }
                 request_response::Message::Response {
                     request_id,
@@ -925,21 +2006,42 @@ impl Behaviour {
                 connection_id,
                 request_id,
                 error,
-            } => match self.requests.remove(&RequestId::Outbound(request_id)) {
-                Some((_, Some(tx))) => {
-                    let _ = tx.send(SwarmResponse::OutboundFailure(error.into()));
-                    (false, None)
+            } if self.handshakes.remove(&request_id) => {
+                // Peers running older versions don't support handshakes
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%peer, %connection_id, "handshake failed: {error}");
+                #[cfg(not(feature = "tracing"))]
+                let _ = (peer, connection_id, error);
+                (false, None)
+            }
+            request_response::Event::OutboundFailure {
+                peer,
+                connection_id,
+                request_id,
+                error,
+            } => {
+                if let Some(key) = self.outbox.take_request(&request_id) {
+                    self.outbox
+                        .complete(key, SwarmResponse::OutboundFailure(error.into()));
+                    return (false, None);
                 }
-                Some((_, None)) | None => (
-                    false,
-                    Some(Event::OutboundFailure {
-                        peer,
-                        connection_id,
-                        request_id,
-                        error: error.into(),
-                    }),
-                ),
-            },
+
+                match self.requests.remove(&RequestId::Outbound(request_id)) {
+                    Some((_, Some(tx))) => {
+                        let _ = tx.send(SwarmResponse::OutboundFailure(error.into()));
+                        (false, None)
+                    }
+                    Some((_, None)) | None => (
+                        false,
+                        Some(Event::OutboundFailure {
+                            peer,
+                            connection_id,
+                            request_id,
+                            error: error.into(),
+                        }),
+                    ),
+                }
+            }
             request_response::Event::InboundFailure {
                 peer,
                 connection_id,
@@ -969,54 +2071,168 @@ impl Behaviour {
         }
     }
 
+    /// Checks whether a peer is allowed to send a message to an actor, returning the event to emit if denied.
+    fn check_access(
+        &self,
+        peer: PeerId,
+        actor_remote_id: &str,
+        message_remote_id: &str,
+    ) -> Option<Event> {
+        if is_allowed(
+            self.access_policy.as_deref(),
+            peer,
+            actor_remote_id,
+            message_remote_id,
+        ) {
+            return None;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(%peer, %actor_remote_id, %message_remote_id, "denied remote message");
+        Some(Event::AccessDenied {
+            peer,
+            actor_remote_id: Cow::Owned(actor_remote_id.to_string()),
+            message_remote_id: Cow::Owned(message_remote_id.to_string()),
+        })
+    }
+
     fn handle_incoming_request(
         &mut self,
+        peer: PeerId,
         req: SwarmRequest,
         channel: request_response::ResponseChannel<SwarmResponse>,
-    ) {
-        let channel = ReplyChannel::Remote(channel);
+    ) -> Option<Event> {
+        if let SwarmRequest::Ask {
+            accept_compression, ..
+        }
+        | SwarmRequest::Tell {
+            accept_compression, ..
+        } = &req
+        {
+            self.peer_compression
+                .insert(peer, accept_compression.clone());
+        }
+
+        if let Some((actor_remote_id, message_remote_id)) = access_target(&req)
+            && let Some(event) = self.check_access(peer, actor_remote_id, message_remote_id)
+        {
+            let res = unauthorized_response(&req, actor_remote_id, message_remote_id);
+            let _ = self.request_response.send_response(channel, res);
+            return Some(event);
+        }
+
         match req {
             SwarmRequest::Ask {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
+                compression,
+                accept_compression,
                 mailbox_timeout,
                 reply_timeout,
                 immediate,
+                sender_id,
             } => {
-                self.join_set.spawn(
-                    ask(
+                let decompressed_size_maximum = self.decompressed_size_maximum;
+                let reply_compression = match self.negotiate_compression(&accept_compression) {
+                    Compression::None => None,
+                    compression => Some((compression, self.compression_threshold)),
+                };
+                let channel = ReplyChannel::Remote(channel);
+                self.spawn_handler(async move {
+                    let payload =
+                        match decompress_payload(compression, payload, decompressed_size_maximum)
+                            .await
+                        {
+                            Ok(payload) => payload,
+                            Err(err) => {
+                                let res = Err(RemoteSendError::DeserializeMessage(err.to_string()));
+                                return (channel, SwarmResponse::Ask(res));
+                            }
+                        };
+                    let res = ask(
                         actor_id,
                         actor_remote_id,
                         message_remote_id,
+                        version,
                         payload,
+                        codec,
                         mailbox_timeout,
                         reply_timeout,
                         immediate,
+                        RemoteSender::new(peer, sender_id),
                     )
-                    .map(|res| (channel, SwarmResponse::Ask(res))),
-                );
+                    .await;
+                    let res = match (res, reply_compression) {
+                        (Ok(payload), Some((compression, threshold)))
+                            if payload.len() as u64 >= threshold =>
+                        {
+                            compress_response(payload, compression).await
+                        }
+                        (res, _) => SwarmResponse::Ask(res),
+                    };
+                    (channel, res)
+                });
             }
             SwarmRequest::Tell {
                 actor_id,
                 actor_remote_id,
                 message_remote_id,
+                version,
                 payload,
+                codec,
+                compression,
                 mailbox_timeout,
                 immediate,
+                sender_id,
+                delivery,
+                ..
             } => {
-                self.join_set.spawn(
-                    tell(
+                let channel = match delivery {
+                    Some(delivery) => match self.inbox.admit(peer, delivery) {
+                        Admission::Deliver => ReplyChannel::Delivery(peer, delivery, channel),
+                        Admission::Duplicate => {
+                            let _ = self
+                                .request_response
+                                .send_response(channel, SwarmResponse::Tell(Ok(())));
+                            return None;
+                        }
+                        Admission::InProgress => {
+                            self.inbox.wait(peer, delivery, channel);
+                            return None;
+                        }
+                    },
+                    None => ReplyChannel::Remote(channel),
+                };
+                let decompressed_size_maximum = self.decompressed_size_maximum;
+                self.spawn_handler(async move {
+                    let payload =
+                        match decompress_payload(compression, payload, decompressed_size_maximum)
+                            .await
+                        {
+                            Ok(payload) => payload,
+                            Err(err) => {
+                                let res = Err(RemoteSendError::DeserializeMessage(err.to_string()));
+                                return (channel, SwarmResponse::Tell(res));
+                            }
+                        };
+                    let res = tell(
                         actor_id,
                         actor_remote_id,
                         message_remote_id,
+                        version,
                         payload,
+                        codec,
                         mailbox_timeout,
                         immediate,
+                        RemoteSender::new(peer, sender_id),
                     )
-                    .map(|res| (channel, SwarmResponse::Tell(res))),
-                );
+                    .await;
+                    (channel, SwarmResponse::Tell(res))
+                });
             }
             SwarmRequest::Link {
                 actor_id,
@@ -1024,7 +2240,8 @@ impl Behaviour {
                 sibbling_id,
                 sibbling_remote_id,
             } => {
-                self.join_set.spawn(
+                let channel = ReplyChannel::Remote(channel);
+                self.spawn_handler(
                     link(actor_id, actor_remote_id, sibbling_id, sibbling_remote_id)
                         .map(|res| (channel, SwarmResponse::Link(res))),
                 );
@@ -1034,7 +2251,8 @@ impl Behaviour {
                 actor_remote_id,
                 sibbling_id,
             } => {
-                self.join_set.spawn(
+                let channel = ReplyChannel::Remote(channel);
+                self.spawn_handler(
                     unlink(actor_id, actor_remote_id, sibbling_id)
                         .map(|res| (channel, SwarmResponse::Unlink(res))),
                 );
@@ -1045,7 +2263,8 @@ impl Behaviour {
                 notified_actor_remote_id,
                 stop_reason,
             } => {
-                self.join_set.spawn(
+                let channel = ReplyChannel::Remote(channel);
+                self.spawn_handler(
                     signal_link_died(
                         dead_actor_id,
                         notified_actor_id,
@@ -1055,7 +2274,63 @@ impl Behaviour {
                     .map(|res| (channel, SwarmResponse::SignalLinkDied(res))),
                 );
             }
+            SwarmRequest::Spawn {
+                actor_remote_id,
+                args,
+                codec,
+                link,
+            } => {
+                let channel = ReplyChannel::Remote(channel);
+                let local_peer_id = self.local_peer_id;
+                self.spawn_handler(spawn(actor_remote_id, args, codec, link).map(move |res| {
+                    let res = with_local_peer_id(res, local_peer_id);
+                    (channel, SwarmResponse::Spawn(res))
+                }));
+            }
+            SwarmRequest::CheckRegistration { actor_id, name, .. } => {
+                let channel = ReplyChannel::Remote(channel);
+                self.spawn_handler(
+                    check_registration(actor_id, name)
+                        .map(|registered| (channel, SwarmResponse::CheckRegistration(registered))),
+                );
+            }
+            SwarmRequest::ResolveEntity {
+                actor_remote_id,
+                entity_id,
+            } => {
+                let channel = ReplyChannel::Remote(channel);
+                let local_peer_id = self.local_peer_id;
+                self.spawn_handler(
+                    sharding::resolve_local_entity(local_peer_id, actor_remote_id, entity_id).map(
+                        move |res| {
+                            let res = with_local_peer_id(res, local_peer_id);
+                            (channel, SwarmResponse::ResolveEntity(res))
+                        },
+                    ),
+                );
+            }
+            SwarmRequest::ResolveSingleton { actor_remote_id } => {
+                let channel = ReplyChannel::Remote(channel);
+                let local_peer_id = self.local_peer_id;
+                self.spawn_handler(
+                    singleton::resolve_local_singleton(local_peer_id, actor_remote_id).map(
+                        move |res| {
+                            let res = with_local_peer_id(res, local_peer_id);
+                            (channel, SwarmResponse::ResolveSingleton(res))
+                        },
+                    ),
+                );
+            }
+            SwarmRequest::Handshake(capabilities) => {
+                let _ = self.request_response.send_response(
+                    channel,
+                    SwarmResponse::Handshake(self.capabilities_for(peer)),
+                );
+                return Some(self.handle_handshake(peer, capabilities));
+            }
         }
+
+        None
     }
 
     fn handle_incoming_response(
@@ -1065,6 +2340,43 @@ impl Behaviour {
         req_id: request_response::OutboundRequestId,
         res: SwarmResponse,
     ) -> Option<Event> {
+        if self.handshakes.remove(&req_id) {
+            return match res {
+                SwarmResponse::Handshake(capabilities) => {
+                    Some(self.handle_handshake(peer, capabilities))
+                }
+                _ => None,
+            };
+        }
+
+        if let Some(key) = self.outbox.take_request(&req_id) {
+            self.outbox.complete(key, res);
+            return None;
+        }
+
+        if let SwarmResponse::AskCompressed {
+            compression,
+            payload,
+        } = res
+        {
+            self.accept_peer_compression(peer, compression);
+            let ch = match self.requests.remove(&RequestId::Outbound(req_id)) {
+                Some((_, Some(tx))) => ReplyChannel::Local(tx),
+                Some((_, None)) => {
+                    ReplyChannel::Event(peer, Some(connection_id), RequestId::Outbound(req_id))
+                }
+                None => return None,
+            };
+            let decompressed_size_maximum = self.decompressed_size_maximum;
+            self.join_set.spawn(async move {
+                let res = decompress_payload(compression, payload, decompressed_size_maximum)
+                    .await
+                    .map_err(|err| RemoteSendError::DeserializeMessage(err.to_string()));
+                (ch, SwarmResponse::Ask(res))
+            });
+            return None;
+        }
+
         match self.requests.remove(&RequestId::Outbound(req_id)) {
             Some((_, Some(tx))) => {
                 // Reply to channel
@@ -1091,8 +2403,7 @@ impl Behaviour {
 }
 
 impl NetworkBehaviour for Behaviour {
-    type ConnectionHandler =
-        THandler<request_response::cbor::Behaviour<SwarmRequest, SwarmResponse>>;
+    type ConnectionHandler = THandler<request_response::Behaviour<SwarmCodec>>;
     type ToSwarm = Event;
 
     fn handle_established_inbound_connection(
@@ -1129,6 +2440,27 @@ impl NetworkBehaviour for Behaviour {
     }
 
     fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = event
+        {
+            self.peer_compression.remove(&peer_id);
+            self.peer_capabilities.remove(&peer_id);
+        }
+
+        if let FromSwarm::ConnectionEstablished(ConnectionEstablished {
+            peer_id,
+            endpoint,
+            other_established: 0,
+            ..
+        }) = event
+            && endpoint.is_dialer()
+        {
+            self.send_handshake(&peer_id);
+        }
+
         if let FromSwarm::DialFailure(DialFailure {
             peer_id: Some(peer_id),
             ..
@@ -1145,7 +2477,19 @@ impl NetworkBehaviour for Behaviour {
             }
         }
 
-        self.request_response.on_swarm_event(event)
+        self.request_response.on_swarm_event(event);
+
+        // Resend reliable tells once a connection to their peer is re-established
+        if let FromSwarm::ConnectionEstablished(ConnectionEstablished {
+            peer_id,
+            other_established: 0,
+            ..
+        }) = event
+        {
+            for key in self.outbox.pending(peer_id) {
+                self.send_reliable(key);
+            }
+        }
     }
 
     fn on_connection_handler_event(
@@ -1162,28 +2506,27 @@ impl NetworkBehaviour for Behaviour {
         &mut self,
         cx: &mut task::Context<'_>,
     ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        // Resend reliable tells whose backoff has elapsed
+        while let task::Poll::Ready(key) = self.outbox.poll_retry(cx) {
+            self.send_reliable(key);
+        }
+
+        // Send requests whose payloads have finished compressing
+        while let task::Poll::Ready(Some(res)) = self.compressing.poll_join_next(cx) {
+            let (peer_id, request, pending) = res.expect("compressing a payload should never fail");
+            self.send_request(peer_id, request, pending);
+        }
+
         loop {
             // First, check for completed futures from join_set
             match self.join_set.poll_join_next(cx) {
                 task::Poll::Ready(Some(Ok((ch, res)))) => {
-                    match ch {
-                        ReplyChannel::Event(request_id) => {
-                            // We have an event to return immediately
-                            return task::Poll::Ready(ToSwarm::GenerateEvent(
-                                Event::from_swarm_resp(res, self.local_peer_id, None, request_id),
-                            ));
-                        }
-                        ReplyChannel::Local(tx) => {
-                            let _ = tx.send(res);
-                            // Continue loop - no event to return but might have more work
-                            continue;
-                        }
-                        ReplyChannel::Remote(ch) => {
-                            let _ = self.request_response.send_response(ch, res);
-                            // Continue loop - sending response might trigger more request_response events
-                            continue;
-                        }
+                    if let Some(ev) = self.handle_reply(ch, res) {
+                        // We have an event to return immediately
+                        return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
                     }
+                    // Continue loop - sending a response might trigger more request_response events
+                    continue;
                 }
                 task::Poll::Ready(Some(Err(err))) => {
                     panic!("ask request futures should never fail: {err}");
@@ -1230,26 +2573,12 @@ impl NetworkBehaviour for Behaviour {
                     // Check one more time if join_set has completions
                     // (in case something completed while we were processing request_response events)
                     match self.join_set.poll_join_next(cx) {
-                        task::Poll::Ready(Some(Ok((ch, res)))) => match ch {
-                            ReplyChannel::Event(request_id) => {
-                                return task::Poll::Ready(ToSwarm::GenerateEvent(
-                                    Event::from_swarm_resp(
-                                        res,
-                                        self.local_peer_id,
-                                        None,
-                                        request_id,
-                                    ),
-                                ));
-                            }
-                            ReplyChannel::Local(tx) => {
-                                let _ = tx.send(res);
-                                continue; // Might have triggered more work
-                            }
-                            ReplyChannel::Remote(ch) => {
-                                let _ = self.request_response.send_response(ch, res);
-                                continue; // Might have triggered more request_response events
+                        task::Poll::Ready(Some(Ok((ch, res)))) => {
+                            if let Some(ev) = self.handle_reply(ch, res) {
+                                return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
                             }
-                        },
+                            continue; // Might have triggered more work
+                        }
                         task::Poll::Ready(Some(Err(err))) => {
                             panic!("ask request futures should never fail: {err}");
                         }
@@ -1278,6 +2607,12 @@ impl Event {
                 request_id,
                 result,
             },
+            SwarmResponse::AskCompressed { .. } => {
+                unreachable!("compressed responses are decompressed before being handled")
+            }
+            SwarmResponse::Handshake(_) => {
+                unreachable!("handshake responses are handled by the behaviour")
+            }
             SwarmResponse::Tell(result) => Event::TellResult {
                 peer,
                 connection_id,
@@ -1302,6 +2637,30 @@ impl Event {
                 request_id,
                 result,
             },
+            SwarmResponse::Spawn(result) => Event::SpawnResult {
+                peer,
+                connection_id,
+                request_id,
+                result,
+            },
+            SwarmResponse::CheckRegistration(registered) => Event::CheckRegistrationResult {
+                peer,
+                connection_id,
+                request_id,
+                registered,
+            },
+            SwarmResponse::ResolveEntity(result) => Event::ResolveEntityResult {
+                peer,
+                connection_id,
+                request_id,
+                result,
+            },
+            SwarmResponse::ResolveSingleton(result) => Event::ResolveSingletonResult {
+                peer,
+                connection_id,
+                request_id,
+                result,
+            },
             SwarmResponse::OutboundFailure(error) => Event::OutboundFailure {
                 peer,
                 connection_id: connection_id.unwrap(),
@@ -1312,14 +2671,169 @@ impl Event {
     }
 }
 
+/// Returns whether the access policy allows a peer to send a message to an actor.
+pub(super) fn is_allowed(
+    policy: Option<&dyn AccessPolicy>,
+    peer_id: PeerId,
+    actor_remote_id: &str,
+    message_remote_id: &str,
+) -> bool {
+    policy.is_none_or(|policy| {
+        policy.is_allowed(&AccessRequest {
+            peer_id,
+            actor_remote_id,
+            message_remote_id,
+        })
+    })
+}
+
+/// Returns the actor and message remote IDs a request is checked against by the access policy.
+///
+/// Requests which don't carry a message are checked with a reserved message remote ID, and
+/// requests which don't target an actor aren't checked.
+fn access_target(req: &SwarmRequest) -> Option<(&str, &str)> {
+    match req {
+        SwarmRequest::Ask {
+            actor_remote_id,
+            message_remote_id,
+            ..
+        }
+        | SwarmRequest::Tell {
+            actor_remote_id,
+            message_remote_id,
+            ..
+        } => Some((actor_remote_id, message_remote_id)),
+        SwarmRequest::Link {
+            actor_remote_id, ..
+        } => Some((actor_remote_id, acl::LINK)),
+        SwarmRequest::Unlink {
+            actor_remote_id, ..
+        } => Some((actor_remote_id, acl::UNLINK)),
+        SwarmRequest::SignalLinkDied {
+            notified_actor_remote_id,
+            ..
+        } => Some((notified_actor_remote_id, acl::SIGNAL_LINK_DIED)),
+        SwarmRequest::Spawn {
+            actor_remote_id, ..
+        } => Some((actor_remote_id, acl::SPAWN)),
+        SwarmRequest::ResolveEntity {
+            actor_remote_id, ..
+        } => Some((actor_remote_id, acl::RESOLVE_ENTITY)),
+        SwarmRequest::ResolveSingleton { actor_remote_id } => {
+            Some((actor_remote_id, acl::RESOLVE_SINGLETON))
+        }
+        SwarmRequest::CheckRegistration {
+            actor_remote_id, ..
+        } => Some((actor_remote_id, acl::CHECK_REGISTRATION)),
+        SwarmRequest::Handshake(_) => None,
+    }
+}
+
+/// Returns the response to a request denied by the access policy.
+fn unauthorized_response(
+    req: &SwarmRequest,
+    actor_remote_id: &str,
+    message_remote_id: &str,
+) -> SwarmResponse {
+    fn unauthorized<E>(actor_remote_id: &str, message_remote_id: &str) -> RemoteSendError<E> {
+        RemoteSendError::Unauthorized {
+            actor_remote_id: Cow::Owned(actor_remote_id.to_string()),
+            message_remote_id: Cow::Owned(message_remote_id.to_string()),
+        }
+    }
+
+    let err = unauthorized(actor_remote_id, message_remote_id);
+    match req {
+        SwarmRequest::Ask { .. } => {
+            SwarmResponse::Ask(Err(unauthorized(actor_remote_id, message_remote_id)))
+        }
+        SwarmRequest::Tell { .. } => SwarmResponse::Tell(Err(err)),
+        SwarmRequest::Link { .. } => SwarmResponse::Link(Err(err)),
+        SwarmRequest::Unlink { .. } => SwarmResponse::Unlink(Err(err)),
+        SwarmRequest::SignalLinkDied { .. } => SwarmResponse::SignalLinkDied(Err(err)),
+        SwarmRequest::Spawn { .. } => SwarmResponse::Spawn(Err(err)),
+        SwarmRequest::ResolveEntity { .. } => SwarmResponse::ResolveEntity(Err(err)),
+        SwarmRequest::ResolveSingleton { .. } => SwarmResponse::ResolveSingleton(Err(err)),
+        SwarmRequest::CheckRegistration { .. } => SwarmResponse::CheckRegistration(false),
+        SwarmRequest::Handshake(_) => SwarmResponse::Handshake(PeerCapabilities::local().clone()),
+    }
+}
+
+/// Compresses the payload of an ask or tell request, leaving it uncompressed if compression fails
+/// or doesn't shrink it.
+fn compress_request(mut req: SwarmRequest, algorithm: Compression) -> SwarmRequest {
+    if let SwarmRequest::Ask {
+        payload,
+        compression,
+        ..
+    }
+    | SwarmRequest::Tell {
+        payload,
+        compression,
+        ..
+    } = &mut req
+        && let Some(compressed) = compress_payload(algorithm, payload)
+    {
+        *payload = compressed;
+        *compression = algorithm;
+    }
+
+    req
+}
+
+/// Compresses a successful ask reply on the blocking thread pool.
+async fn compress_response(payload: Vec<u8>, compression: Compression) -> SwarmResponse {
+    tokio::task::spawn_blocking(move || match compress_payload(compression, &payload) {
+        Some(compressed) => SwarmResponse::AskCompressed {
+            compression,
+            payload: compressed,
+        },
+        None => SwarmResponse::Ask(Ok(payload)),
+    })
+    .await
+    .expect("compressing a payload should never fail")
+}
+
+/// Compresses a payload, returning `None` if compression fails or doesn't shrink it.
+fn compress_payload(compression: Compression, payload: &[u8]) -> Option<Vec<u8>> {
+    match compression.compress(payload) {
+        Ok(compressed) if compressed.len() < payload.len() => Some(compressed),
+        Ok(_) => None,
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%compression, "failed to compress payload: {_err}");
+            None
+        }
+    }
+}
+
+/// Decompresses a payload received from a peer on the blocking thread pool.
+async fn decompress_payload(
+    compression: Compression,
+    payload: Vec<u8>,
+    size_maximum: u64,
+) -> Result<Vec<u8>, CompressionError> {
+    if compression == Compression::None {
+        return Ok(payload);
+    }
+
+    tokio::task::spawn_blocking(move || compression.decompress(payload, size_maximum))
+        .await
+        .expect("decompressing a payload should never fail")
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn ask(
     actor_id: ActorId,
     actor_remote_id: Cow<'static, str>,
     message_remote_id: Cow<'static, str>,
+    version: u32,
     payload: Vec<u8>,
+    codec: CodecId,
     mailbox_timeout: Option<Duration>,
     reply_timeout: Option<Duration>,
     immediate: bool,
+    sender: RemoteSender,
 ) -> Result<Vec<u8>, RemoteSendError<Vec<u8>>> {
     let Some(fns) = REMOTE_MESSAGES_MAP.get(&RemoteMessageRegistrationID {
         actor_remote_id: &actor_remote_id,
@@ -1331,19 +2845,32 @@ async fn ask(
         });
     };
     if immediate {
-        (fns.try_ask)(actor_id, payload, reply_timeout).await
+        (fns.try_ask)(actor_id, sender, payload, codec, version, reply_timeout).await
     } else {
-        (fns.ask)(actor_id, payload, mailbox_timeout, reply_timeout).await
+        (fns.ask)(
+            actor_id,
+            sender,
+            payload,
+            codec,
+            version,
+            mailbox_timeout,
+            reply_timeout,
+        )
+        .await
     }
 }
 
-async fn tell(
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn tell(
     actor_id: ActorId,
     actor_remote_id: Cow<'static, str>,
     message_remote_id: Cow<'static, str>,
+    version: u32,
     payload: Vec<u8>,
+    codec: CodecId,
     mailbox_timeout: Option<Duration>,
     immediate: bool,
+    sender: RemoteSender,
 ) -> Result<(), RemoteSendError> {
     let Some(fns) = REMOTE_MESSAGES_MAP.get(&RemoteMessageRegistrationID {
         actor_remote_id: &actor_remote_id,
@@ -1355,9 +2882,9 @@ async fn tell(
         });
     };
     if immediate {
-        (fns.try_tell)(actor_id, payload).await
+        (fns.try_tell)(actor_id, sender, payload, codec, version).await
     } else {
-        (fns.tell)(actor_id, payload, mailbox_timeout).await
+        (fns.tell)(actor_id, sender, payload, codec, version, mailbox_timeout).await
     }
 }
 
@@ -1400,3 +2927,251 @@ async fn signal_link_died(
 
     (fns.signal_link_died)(dead_actor_id, notified_actor_id, stop_reason).await
 }
+
+/// Resolves the ID of a local actor to this swarm's peer, so other peers can address it.
+fn with_local_peer_id<E>(res: Result<ActorId, E>, local_peer_id: PeerId) -> Result<ActorId, E> {
+    res.map(|actor_id| actor_id.with_local_peer_id(local_peer_id))
+}
+
+async fn check_registration(actor_id: ActorId, name: String) -> bool {
+    // Actors are removed from the registry when they stop
+    REMOTE_REGISTRY
+        .lock()
+        .unwrap()
+        .get(&actor_id.into_local())
+        .is_some_and(|entry| {
+            entry
+                .names
+                .iter()
+                .any(|registered| *registered.name == *name)
+        })
+}
+
+async fn spawn(
+    actor_remote_id: Cow<'static, str>,
+    args: Vec<u8>,
+    codec: CodecId,
+    link: Option<(ActorId, Cow<'static, str>)>,
+) -> Result<ActorId, RemoteSendError<Infallible>> {
+    let Some(fns) = REMOTE_ACTORS_MAP.get(&*actor_remote_id) else {
+        return Err(RemoteSendError::UnknownActor { actor_remote_id });
+    };
+
+    (fns.spawn)(args, codec, link).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        error,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use libp2p::request_response::Codec as _;
+    use serde::{Deserialize, Serialize};
+    use tokio::time;
+
+    use crate::{
+        Actor,
+        actor::{ActorRef, Spawn},
+        message::{Context, Message},
+        remote::{
+            RemoteActor, RemoteMessage,
+            testing::{
+                Cluster, POLL_INTERVAL,
+                fixtures::{Counter, Inc},
+                register_remote_actor, register_remote_message,
+            },
+        },
+    };
+
+    use super::*;
+
+    struct Echo;
+
+    impl Actor for Echo {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Echo);
+
+    #[derive(Serialize, Deserialize)]
+    struct Bytes(Vec<u8>);
+
+    impl Message<Bytes> for Echo {
+        type Reply = Vec<u8>;
+
+        async fn handle(&mut self, msg: Bytes, _ctx: &mut Context<Self, Self::Reply>) -> Vec<u8> {
+            msg.0
+        }
+    }
+
+    register_remote_message!(Echo, Bytes);
+
+    #[derive(Serialize, Deserialize)]
+    struct WhoSent;
+
+    impl Message<WhoSent> for Echo {
+        type Reply = Option<String>;
+
+        async fn handle(
+            &mut self,
+            _msg: WhoSent,
+            ctx: &mut Context<Self, Self::Reply>,
+        ) -> Option<String> {
+            ctx.remote_sender()
+                .map(|sender| sender.peer_id().to_string())
+        }
+    }
+
+    register_remote_message!(Echo, WhoSent);
+
+    #[tokio::test]
+    async fn peer_capabilities_are_exchanged_on_connect() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let peer_id = cluster.node(0).peer_id();
+        let capabilities = time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(capabilities) = cluster.node(1).swarm().peer_capabilities(peer_id).await
+                {
+                    return capabilities;
+                }
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+
+        assert_eq!(&capabilities, PeerCapabilities::local());
+        let counter_id = <Counter as RemoteActor>::REMOTE_ID;
+        let inc_id = <Counter as RemoteMessage<Inc>>::REMOTE_ID;
+        assert!(capabilities.supports_actor(counter_id));
+        assert!(capabilities.supports_message(counter_id, inc_id, 1));
+        assert!(!capabilities.supports_message(counter_id, inc_id, 2));
+        assert!(!capabilities.supports_actor("unknown"));
+
+        Ok(())
+    }
+
+    #[cfg(any(feature = "zstd", feature = "lz4"))]
+    #[tokio::test]
+    async fn large_payloads_are_compressed_in_both_directions() -> Result<(), Box<dyn error::Error>>
+    {
+        let compression = crate::remote::compression::Compression::supported()[0];
+        let cluster = Cluster::builder()
+            .messaging_config(
+                Config::default()
+                    .with_compression(compression)
+                    .with_compression_threshold(1024),
+            )
+            .start()
+            .await?;
+
+        let echo = Echo::spawn(Echo);
+        cluster.node(0).register(&echo, "echo").await?;
+        let remote_echo = cluster
+            .wait_for_lookup::<Echo>(1, "echo", 1)
+            .await?
+            .remove(0);
+
+        let payload: Vec<u8> = (0..256 * 1024).map(|i| (i % 7) as u8).collect();
+        for _ in 0..2 {
+            let reply = remote_echo.ask(&Bytes(payload.clone())).await?;
+            assert_eq!(reply, payload);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handlers_see_the_remote_sender() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let echo = Echo::spawn(Echo);
+        cluster.node(0).register(&echo, "echo").await?;
+        let remote_echo = cluster
+            .wait_for_lookup::<Echo>(1, "echo", 1)
+            .await?
+            .remove(0);
+
+        let sender = remote_echo.ask(&WhoSent).await?;
+        assert_eq!(sender, Some(cluster.node(1).peer_id().to_string()));
+        assert_eq!(echo.ask(WhoSent).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reliable_tell_is_delivered_after_partition_heals() -> Result<(), Box<dyn error::Error>>
+    {
+        let cluster = Cluster::builder()
+            .messaging_config(
+                Config::default()
+                    .with_redelivery_backoff(Duration::from_millis(20), Duration::from_millis(100)),
+            )
+            .start()
+            .await?;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Counter::spawn(Counter {
+            count: count.clone(),
+        });
+        cluster.node(0).register(&counter, "counter").await?;
+        let remote_counter = cluster
+            .wait_for_lookup::<Counter>(1, "counter", 1)
+            .await?
+            .remove(0);
+
+        cluster.partition(&[0], &[1]).await?;
+        let send = tokio::spawn(async move { remote_counter.tell(&Inc).send_reliable().await });
+        time::sleep(Duration::from_millis(300)).await;
+        assert!(!send.is_finished());
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        cluster.heal().await?;
+        time::timeout(Duration::from_secs(5), send).await???;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn legacy_protocol_only_carries_legacy_requests() -> Result<(), Box<dyn error::Error>> {
+        let mut codec = SwarmCodec::from(Config::default());
+        let mut io = futures::io::Cursor::new(Vec::new());
+
+        let handshake = || SwarmRequest::Handshake(PeerCapabilities::default());
+        let err = codec
+            .write_request(&LEGACY_PROTO_NAME, &mut io, handshake())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        codec
+            .write_request(&PROTO_NAME, &mut io, handshake())
+            .await?;
+
+        let unlink = SwarmRequest::Unlink {
+            actor_id: ActorId::new(0),
+            actor_remote_id: Cow::Borrowed("actor"),
+            sibbling_id: ActorId::new(1),
+        };
+        codec
+            .write_request(&LEGACY_PROTO_NAME, &mut io, unlink)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,555 @@
+//! Reliable at-least-once delivery of tell messages.
+//!
+//! A tell which fails with [`RemoteSendError::NetworkTimeout`] or
+//! [`RemoteSendError::ConnectionClosed`] may or may not have reached the actor. Reliable tells
+//! are instead kept in an [`Outbox`] and resent until the receiving peer acknowledges them,
+//! backing off between attempts and resending immediately when a connection to the peer is
+//! re-established. Messages to a peer which is removed from the cluster are given up on.
+//!
+//! Every reliable tell carries a [`Delivery`], identifying the sender's session and the
+//! message's sequence number. Receivers track the sequence numbers delivered from each sender in
+//! an [`Inbox`], and acknowledge redelivered messages without handing them to the actor again,
+//! so each message is handled once per sender.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, hash_map::Entry},
+    hash::{BuildHasher, RandomState},
+    task,
+    time::Duration,
+};
+
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::FuturesUnordered};
+use libp2p::{PeerId, request_response::OutboundRequestId};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{actor::ActorId, error::RemoteSendError};
+
+use super::{super::codec::CodecId, SwarmResponse};
+
+/// Identifies a reliably delivered tell message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delivery {
+    /// A random identifier of the sending node, which changes when it restarts.
+    pub session: u64,
+    /// The sequence number of the message, counted per receiving peer.
+    pub seq: u64,
+    /// The lowest sequence number which the sender may still send to the receiving peer.
+    ///
+    /// Messages with lower sequence numbers have been acknowledged or given up on, and can be
+    /// forgotten by the receiver.
+    pub low: u64,
+}
+
+/// A tell message to be delivered reliably.
+#[derive(Clone, Debug)]
+pub(super) struct OutboxMessage {
+    pub(super) actor_id: ActorId,
+    pub(super) actor_remote_id: std::borrow::Cow<'static, str>,
+    pub(super) message_remote_id: std::borrow::Cow<'static, str>,
+    pub(super) version: u32,
+    pub(super) payload: Vec<u8>,
+    pub(super) codec: CodecId,
+    pub(super) mailbox_timeout: Option<Duration>,
+    pub(super) sender_id: Option<ActorId>,
+}
+
+type OutboxKey = (PeerId, u64);
+
+struct OutboxEntry {
+    message: OutboxMessage,
+    attempts: u32,
+    in_flight: bool,
+    /// The retry scheduled after the last failed attempt, cancelled if the message is resent
+    /// before it's due.
+    retry: Option<u64>,
+    reply: Option<oneshot::Sender<SwarmResponse>>,
+}
+
+/// Reliable tells waiting to be acknowledged by their receiving peers.
+pub(super) struct Outbox {
+    session: u64,
+    backoff: (Duration, Duration),
+    max_attempts: Option<u32>,
+    next_seq: HashMap<PeerId, u64>,
+    entries: BTreeMap<OutboxKey, OutboxEntry>,
+    requests: HashMap<OutboundRequestId, OutboxKey>,
+    next_retry: u64,
+    retries: FuturesUnordered<BoxFuture<'static, (OutboxKey, u64)>>,
+}
+
+impl Outbox {
+    pub(super) fn new(backoff: (Duration, Duration), max_attempts: Option<u32>) -> Self {
+        Outbox {
+            session: RandomState::new().hash_one(PeerId::random()),
+            backoff,
+            max_attempts,
+            next_seq: HashMap::new(),
+            entries: BTreeMap::new(),
+            requests: HashMap::new(),
+            next_retry: 0,
+            retries: FuturesUnordered::new(),
+        }
+    }
+
+    /// Adds a message to the outbox, returning its key.
+    pub(super) fn push(
+        &mut self,
+        peer_id: PeerId,
+        message: OutboxMessage,
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    ) -> OutboxKey {
+        let seq = self.next_seq.entry(peer_id).or_insert(1);
+        let key = (peer_id, *seq);
+        *seq += 1;
+        self.entries.insert(
+            key,
+            OutboxEntry {
+                message,
+                attempts: 0,
+                in_flight: false,
+                retry: None,
+                reply,
+            },
+        );
+
+        key
+    }
+
+    /// Marks a message as being sent, returning it along with its delivery.
+    ///
+    /// Returns `None` if the message has already been acknowledged, or is currently being sent.
+    pub(super) fn start_attempt(&mut self, key: OutboxKey) -> Option<(OutboxMessage, Delivery)> {
+        let low = self.low(key.0);
+        let entry = self.entries.get_mut(&key)?;
+        if entry.in_flight {
+            return None;
+        }
+        entry.in_flight = true;
+        entry.attempts += 1;
+
+        Some((
+            entry.message.clone(),
+            Delivery {
+                session: self.session,
+                seq: key.1,
+                low,
+            },
+        ))
+    }
+
+    /// Associates an outbound request with the message it's delivering.
+    pub(super) fn sent(&mut self, request_id: OutboundRequestId, key: OutboxKey) {
+        self.requests.insert(request_id, key);
+    }
+
+    /// Returns the key of the message being delivered by an outbound request.
+    pub(super) fn take_request(&mut self, request_id: &OutboundRequestId) -> Option<OutboxKey> {
+        self.requests.remove(request_id)
+    }
+
+    /// Handles the response to a delivery attempt.
+    ///
+    /// The message is removed and its sender notified if it was acknowledged, failed with an
+    /// error which retrying won't fix, or ran out of attempts. Otherwise it's retried after a
+    /// backoff.
+    pub(super) fn complete(&mut self, key: OutboxKey, res: SwarmResponse) {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return;
+        };
+        entry.in_flight = false;
+
+        let retry = match &res {
+            SwarmResponse::Tell(Err(err)) | SwarmResponse::OutboundFailure(err) => {
+                is_retryable(err)
+                    && self
+                        .max_attempts
+                        .is_none_or(|max_attempts| entry.attempts < max_attempts)
+            }
+            _ => false,
+        };
+        if retry {
+            let retry = self.next_retry;
+            self.next_retry += 1;
+            entry.retry = Some(retry);
+            let delay = backoff_delay(self.backoff, entry.attempts);
+            self.retries
+                .push(tokio::time::sleep(delay).map(move |_| (key, retry)).boxed());
+            return;
+        }
+
+        let entry = self.entries.remove(&key).unwrap();
+        if let Some(tx) = entry.reply {
+            let _ = tx.send(res);
+        }
+    }
+
+    /// Returns the messages to a peer which aren't currently being sent, to be resent after a
+    /// connection to the peer is established.
+    ///
+    /// Retries scheduled for the messages are cancelled, as they're being resent now.
+    pub(super) fn pending(&mut self, peer_id: PeerId) -> Vec<OutboxKey> {
+        self.entries
+            .range_mut((peer_id, 0)..=(peer_id, u64::MAX))
+            .filter(|(_, entry)| !entry.in_flight)
+            .map(|(key, entry)| {
+                entry.retry = None;
+                *key
+            })
+            .collect()
+    }
+
+    /// Gives up on the messages to a peer which has been removed from the cluster, notifying
+    /// their senders that the connection closed.
+    pub(super) fn expire(&mut self, peer_id: PeerId) {
+        let keys: Vec<_> = self
+            .entries
+            .range((peer_id, 0)..=(peer_id, u64::MAX))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in keys {
+            let entry = self.entries.remove(&key).unwrap();
+            if let Some(tx) = entry.reply {
+                let _ = tx.send(SwarmResponse::OutboundFailure(
+                    RemoteSendError::ConnectionClosed,
+                ));
+            }
+        }
+    }
+
+    /// Polls for the next message whose backoff has elapsed.
+    ///
+    /// Retries which were cancelled, or whose message is no longer in the outbox, are skipped.
+    pub(super) fn poll_retry(&mut self, cx: &mut task::Context<'_>) -> task::Poll<OutboxKey> {
+        while let task::Poll::Ready(Some((key, retry))) = self.retries.poll_next_unpin(cx) {
+            if let Some(entry) = self.entries.get_mut(&key)
+                && entry.retry == Some(retry)
+            {
+                entry.retry = None;
+                return task::Poll::Ready(key);
+            }
+        }
+
+        task::Poll::Pending
+    }
+
+    fn low(&self, peer_id: PeerId) -> u64 {
+        self.entries
+            .range((peer_id, 0)..=(peer_id, u64::MAX))
+            .next()
+            .map(|((_, seq), _)| *seq)
+            .unwrap_or_else(|| self.next_seq.get(&peer_id).copied().unwrap_or(1))
+    }
+}
+
+/// Returns the delay before the next delivery attempt, doubling with each failed attempt.
+fn backoff_delay((initial, max): (Duration, Duration), attempts: u32) -> Duration {
+    initial
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(max)
+}
+
+/// Returns whether a failed delivery attempt may succeed if retried.
+fn is_retryable(err: &RemoteSendError) -> bool {
+    matches!(
+        err,
+        RemoteSendError::MailboxFull
+            | RemoteSendError::DialFailure
+            | RemoteSendError::NetworkTimeout
+            | RemoteSendError::ConnectionClosed
+            | RemoteSendError::Io(_)
+    )
+}
+
+/// The outcome of receiving a reliable tell.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Admission {
+    /// The message should be delivered to the actor.
+    Deliver,
+    /// The message has already been delivered, and should be acknowledged again.
+    Duplicate,
+    /// An earlier copy of the message is still being delivered.
+    InProgress,
+}
+
+/// The sequence numbers of reliable tells received from each peer.
+pub(super) struct Inbox<C> {
+    peers: HashMap<PeerId, Window<C>>,
+}
+
+struct Window<C> {
+    session: u64,
+    low: u64,
+    delivered: BTreeSet<u64>,
+    in_progress: HashMap<u64, Vec<C>>,
+}
+
+impl<C> Inbox<C> {
+    pub(super) fn new() -> Self {
+        Inbox {
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Decides whether a message received from a peer should be delivered.
+    ///
+    /// Messages admitted with [`Admission::Deliver`] must be passed to [`Inbox::complete`] once
+    /// delivered. A message from a new session of the peer replaces the previous session, whose
+    /// outbox was lost when the peer restarted.
+    pub(super) fn admit(&mut self, peer_id: PeerId, delivery: Delivery) -> Admission {
+        let window = self.peers.entry(peer_id).or_insert_with(|| Window {
+            session: delivery.session,
+            low: 0,
+            delivered: BTreeSet::new(),
+            in_progress: HashMap::new(),
+        });
+        if window.session != delivery.session {
+            *window = Window {
+                session: delivery.session,
+                low: 0,
+                delivered: BTreeSet::new(),
+                in_progress: HashMap::new(),
+            };
+        }
+
+        if delivery.low > window.low {
+            window.low = delivery.low;
+            window.delivered = window.delivered.split_off(&delivery.low);
+        }
+
+        if delivery.seq < window.low || window.delivered.contains(&delivery.seq) {
+            return Admission::Duplicate;
+        }
+
+        match window.in_progress.entry(delivery.seq) {
+            Entry::Occupied(_) => Admission::InProgress,
+            Entry::Vacant(entry) => {
+                entry.insert(Vec::new());
+                Admission::Deliver
+            }
+        }
+    }
+
+    /// Holds the reply channel of a message admitted with [`Admission::InProgress`], to be
+    /// returned by [`Inbox::complete`].
+    ///
+    /// The channel is dropped if the message is no longer in progress.
+    pub(super) fn wait(&mut self, peer_id: PeerId, delivery: Delivery, channel: C) {
+        if let Some(waiting) = self
+            .peers
+            .get_mut(&peer_id)
+            .filter(|window| window.session == delivery.session)
+            .and_then(|window| window.in_progress.get_mut(&delivery.seq))
+        {
+            waiting.push(channel);
+        }
+    }
+
+    /// Records the outcome of delivering a message, returning the reply channels of any copies
+    /// received while it was in progress.
+    ///
+    /// Messages which failed to be delivered are delivered again if they're resent.
+    pub(super) fn complete(
+        &mut self,
+        peer_id: PeerId,
+        delivery: Delivery,
+        delivered: bool,
+    ) -> Vec<C> {
+        let Some(window) = self
+            .peers
+            .get_mut(&peer_id)
+            .filter(|window| window.session == delivery.session)
+        else {
+            return Vec::new();
+        };
+
+        let waiting = window.in_progress.remove(&delivery.seq).unwrap_or_default();
+        if delivered && delivery.seq >= window.low {
+            window.delivered.insert(delivery.seq);
+        }
+
+        waiting
+    }
+}
+
+impl<C> Default for Inbox<C> {
+    fn default() -> Self {
+        Inbox::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery(session: u64, seq: u64, low: u64) -> Delivery {
+        Delivery { session, seq, low }
+    }
+
+    fn message() -> OutboxMessage {
+        OutboxMessage {
+            actor_id: ActorId::new(0),
+            actor_remote_id: "actor".into(),
+            message_remote_id: "message".into(),
+            version: 1,
+            payload: Vec::new(),
+            codec: CodecId::default(),
+            mailbox_timeout: None,
+            sender_id: None,
+        }
+    }
+
+    #[test]
+    fn inbox_deduplicates_per_session() {
+        let peer = PeerId::random();
+        let mut inbox = Inbox::<u32>::new();
+
+        assert_eq!(inbox.admit(peer, delivery(1, 1, 1)), Admission::Deliver);
+        assert_eq!(inbox.admit(peer, delivery(1, 1, 1)), Admission::InProgress);
+        inbox.wait(peer, delivery(1, 1, 1), 7);
+        assert_eq!(inbox.complete(peer, delivery(1, 1, 1), true), vec![7]);
+        assert_eq!(inbox.admit(peer, delivery(1, 1, 1)), Admission::Duplicate);
+
+        // Failed deliveries are retried
+        assert_eq!(inbox.admit(peer, delivery(1, 2, 1)), Admission::Deliver);
+        assert!(inbox.complete(peer, delivery(1, 2, 1), false).is_empty());
+        assert_eq!(inbox.admit(peer, delivery(1, 2, 1)), Admission::Deliver);
+
+        // A restarted sender starts a new session
+        assert_eq!(inbox.admit(peer, delivery(2, 1, 1)), Admission::Deliver);
+        assert_eq!(inbox.admit(peer, delivery(2, 2, 1)), Admission::Deliver);
+        assert!(inbox.complete(peer, delivery(1, 2, 1), true).is_empty());
+        assert_eq!(inbox.admit(peer, delivery(2, 2, 1)), Admission::InProgress);
+    }
+
+    #[test]
+    fn inbox_forgets_messages_below_low() {
+        let peer = PeerId::random();
+        let mut inbox = Inbox::<()>::new();
+
+        for seq in 1..=3 {
+            assert_eq!(inbox.admit(peer, delivery(1, seq, 1)), Admission::Deliver);
+            inbox.complete(peer, delivery(1, seq, 1), true);
+        }
+        assert_eq!(inbox.admit(peer, delivery(1, 4, 3)), Admission::Deliver);
+
+        assert_eq!(inbox.peers[&peer].delivered, BTreeSet::from([3]));
+        assert_eq!(inbox.admit(peer, delivery(1, 2, 3)), Admission::Duplicate);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn outbox_retries_until_acknowledged() {
+        let peer = PeerId::random();
+        let mut outbox = Outbox::new((Duration::from_secs(1), Duration::from_secs(5)), None);
+        let (tx, mut rx) = oneshot::channel();
+
+        let key = outbox.push(peer, message(), Some(tx));
+        let (_, first) = outbox.start_attempt(key).unwrap();
+        assert_eq!((first.seq, first.low), (1, 1));
+        assert!(outbox.start_attempt(key).is_none());
+
+        outbox.complete(
+            key,
+            SwarmResponse::OutboundFailure(RemoteSendError::ConnectionClosed),
+        );
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            futures::future::poll_fn(|cx| outbox.poll_retry(cx)).await,
+            key
+        );
+        assert_eq!(outbox.pending(peer), vec![key]);
+
+        let (_, second) = outbox.start_attempt(key).unwrap();
+        assert_eq!(second, first);
+        outbox.complete(key, SwarmResponse::Tell(Ok(())));
+        assert!(matches!(rx.try_recv(), Ok(SwarmResponse::Tell(Ok(())))));
+        assert!(outbox.pending(peer).is_empty());
+
+        let next = outbox.push(peer, message(), None);
+        assert_eq!(outbox.start_attempt(next).unwrap().1.low, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn outbox_cancels_retry_when_resent() {
+        let peer = PeerId::random();
+        let mut outbox = Outbox::new((Duration::from_secs(1), Duration::from_secs(5)), None);
+
+        let key = outbox.push(peer, message(), None);
+        outbox.start_attempt(key).unwrap();
+        outbox.complete(
+            key,
+            SwarmResponse::OutboundFailure(RemoteSendError::ConnectionClosed),
+        );
+
+        // Reconnecting resends the message, cancelling the scheduled retry
+        assert_eq!(outbox.pending(peer), vec![key]);
+        outbox.start_attempt(key).unwrap();
+        outbox.complete(
+            key,
+            SwarmResponse::OutboundFailure(RemoteSendError::ConnectionClosed),
+        );
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(
+            futures::future::poll_fn(|cx| outbox.poll_retry(cx)).await,
+            key
+        );
+        assert!(futures::poll!(futures::future::poll_fn(|cx| outbox.poll_retry(cx))).is_pending());
+    }
+
+    #[test]
+    fn outbox_expires_messages_to_removed_peer() {
+        let peer = PeerId::random();
+        let other = PeerId::random();
+        let mut outbox = Outbox::new((Duration::from_secs(1), Duration::from_secs(5)), None);
+
+        let (tx, mut rx) = oneshot::channel();
+        let key = outbox.push(peer, message(), Some(tx));
+        outbox.start_attempt(key).unwrap();
+        let other_key = outbox.push(other, message(), None);
+
+        outbox.expire(peer);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SwarmResponse::OutboundFailure(
+                RemoteSendError::ConnectionClosed
+            ))
+        ));
+        assert!(outbox.pending(peer).is_empty());
+        assert_eq!(outbox.pending(other), vec![other_key]);
+
+        // Late responses to expired messages are ignored
+        outbox.complete(key, SwarmResponse::Tell(Ok(())));
+    }
+
+    #[test]
+    fn outbox_gives_up_on_permanent_errors_and_max_attempts() {
+        let peer = PeerId::random();
+        let mut outbox = Outbox::new((Duration::from_secs(1), Duration::from_secs(5)), Some(1));
+
+        let (tx, mut rx) = oneshot::channel();
+        let key = outbox.push(peer, message(), Some(tx));
+        outbox.start_attempt(key).unwrap();
+        outbox.complete(
+            key,
+            SwarmResponse::Tell(Err(RemoteSendError::ActorNotRunning)),
+        );
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SwarmResponse::Tell(Err(RemoteSendError::ActorNotRunning)))
+        ));
+
+        let (tx, mut rx) = oneshot::channel();
+        let key = outbox.push(peer, message(), Some(tx));
+        outbox.start_attempt(key).unwrap();
+        outbox.complete(
+            key,
+            SwarmResponse::OutboundFailure(RemoteSendError::DialFailure),
+        );
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SwarmResponse::OutboundFailure(RemoteSendError::DialFailure))
+        ));
+        assert!(outbox.pending(peer).is_empty());
+    }
+}
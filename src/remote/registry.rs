@@ -1,9 +1,9 @@
 //! Actor registration and discovery system for distributed actor networks.
 //!
 //! This module implements a distributed registry that allows actors to register themselves
-//! under human-readable names and be discovered by other actors across the network. It uses
-//! Kademlia DHT (Distributed Hash Table) for decentralized storage and lookup of actor
-//! registrations, ensuring high availability and fault tolerance.
+//! under human-readable names and be discovered by other actors across the network. The
+//! storage and propagation of registrations is delegated to a pluggable [`Backend`], chosen
+//! when constructing the [`Behaviour`].
 //!
 //! # Key Responsibilities
 //!
@@ -13,41 +13,43 @@
 //!   under a given name, regardless of which peer they're running on
 //! - **Metadata Storage**: Stores actor metadata including unique identifiers, peer locations,
 //!   and other registration details in a distributed manner
-//! - **Network Resilience**: Uses Kademlia's distributed nature to ensure actor registrations
-//!   remain available even if some network nodes go offline
-//! - **Local Caching**: Maintains local caches of discovered actors to reduce lookup latency
-//!   and network overhead for frequently accessed actors
+//! - **Network Resilience**: Keeps actor registrations available even if some network nodes
+//!   go offline
 //!
-//! # Architecture
+//! # Backends
 //!
-//! The registry leverages libp2p's Kademlia implementation to create a distributed hash table
-//! where actor names serve as keys and actor metadata serves as values. Each actor registration
-//! involves two operations: advertising the actor name as a "provider" and storing the actor's
-//! detailed metadata as a record in the DHT.
+//! - [`Backend::Kademlia`] (the default) uses libp2p's Kademlia DHT, where actor names serve
+//!   as keys and actor metadata serves as values. Each actor registration involves two
+//!   operations: advertising the actor name as a "provider" and storing the actor's detailed
+//!   metadata as a record in the DHT. Clients first find all peers providing a given actor
+//!   name, then retrieve the specific metadata for each instance.
+//! - [`Backend::Static`] resolves lookups from a fixed set of registrations supplied up front,
+//!   typically loaded from configuration, along with any actors registered locally. It
+//!   performs no network traffic.
+//! - [`Backend::Gossip`] fully replicates registrations to every peer via gossipsub, so
+//!   lookups are answered from local state without any network round trips. Peers
+//!   periodically re-announce their registrations, and entries which are not refreshed
+//!   expire.
 //!
-//! This dual approach enables efficient discovery where clients first find all peers providing
-//! a given actor name, then retrieve the specific metadata for each instance. This supports
-//! scenarios where multiple actors may be registered under the same logical name across
-//! different peers for load balancing or redundancy.
+//! All backends support multiple actors registered under the same logical name across
+//! different peers for load balancing or redundancy, and all produce the same [`Event`]s.
 
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet, VecDeque, hash_map::Entry},
-    fmt,
-    num::NonZero,
-    str::{self, FromStr},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt, str,
     sync::Arc,
     task,
-    time::{Duration, Instant},
 };
 
+use either::Either;
 use libp2p::{
-    Multiaddr, PeerId, StreamProtocol,
-    kad::{self, StoreInserts, store::RecordStore},
+    Multiaddr, PeerId,
+    core::{Endpoint, transport::PortUse},
+    gossipsub, kad,
     swarm::{
-        ConnectionDenied, ConnectionId, DialError, DialFailure, FromSwarm, NetworkBehaviour,
-        NewExternalAddrOfPeer, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
-        behaviour::ConnectionEstablished,
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler, THandlerInEvent,
+        THandlerOutEvent, ToSwarm, dummy,
     },
 };
 use tokio::sync::{mpsc, oneshot};
@@ -57,7 +59,16 @@ use crate::{
     error::RegistryError,
 };
 
-const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/registry/1.0.0");
+pub(crate) use cache::LookupCache;
+pub use cache::LookupCacheConfig;
+pub use gossip::GossipConfig;
+pub use kademlia::KademliaConfig;
+pub use static_config::StaticConfig;
+
+mod cache;
+mod gossip;
+mod kademlia;
+mod static_config;
 
 type RegisterResult = Result<(), RegistryError>;
 pub(super) type LookupResult = Result<ActorRegistration<'static>, RegistryError>;
@@ -67,6 +78,52 @@ pub(super) type RegisterReply = oneshot::Sender<RegisterResult>;
 pub(super) type LookupReply = mpsc::UnboundedSender<LookupResult>;
 pub(super) type LookupLocalReply = oneshot::Sender<LookupLocalResult>;
 pub(super) type UnregisterReply = oneshot::Sender<()>;
+pub(super) type WatchReply = mpsc::UnboundedSender<WatchEvent>;
+
+/// A change to the actors registered under a watched name.
+///
+/// See [`remote::watch`](crate::remote::watch).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// An actor was registered under the name, or moved to a different actor on the peer.
+    Registered {
+        /// The peer hosting the actor.
+        peer: PeerId,
+        /// The ID of the registered actor.
+        actor_id: ActorId,
+    },
+    /// An actor was explicitly unregistered from the name.
+    Unregistered {
+        /// The peer which hosted the actor.
+        peer: PeerId,
+        /// The ID of the unregistered actor.
+        actor_id: ActorId,
+    },
+    /// An actor registered under the name is no longer known to the registry without having
+    /// been explicitly unregistered, such as when its registration was not refreshed in time.
+    Expired {
+        /// The peer which hosted the actor.
+        peer: PeerId,
+        /// The ID of the expired actor.
+        actor_id: ActorId,
+    },
+}
+
+/// Identifies a registration or lookup started on the registry [`Behaviour`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QueryId(QueryIdInner);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum QueryIdInner {
+    Kademlia(kad::QueryId),
+    Local(u64),
+}
+
+impl From<kad::QueryId> for QueryId {
+    fn from(id: kad::QueryId) -> Self {
+        QueryId(QueryIdInner::Kademlia(id))
+    }
+}
 
 /// The events produced by the `Registry` behaviour.
 ///
@@ -76,9 +133,9 @@ pub enum Event {
     /// Progress in looking up actors by name.
     LookupProgressed {
         /// The original provider query ID.
-        provider_query_id: kad::QueryId,
+        provider_query_id: QueryId,
         /// The metadata query ID.
-        get_query_id: kad::QueryId,
+        get_query_id: QueryId,
         /// The actor registration found, or an error.
         result: Result<ActorRegistration<'static>, RegistryError>,
     },
@@ -87,36 +144,39 @@ pub enum Event {
     /// More metadata might still arrive from providers found before timeout.
     LookupTimeout {
         /// The provider query ID that timed out.
-        provider_query_id: kad::QueryId,
+        provider_query_id: QueryId,
     },
 
     /// A lookup operation completed. This is always the final event for a lookup.
     LookupCompleted {
         /// The completed provider query ID.
-        provider_query_id: kad::QueryId,
+        provider_query_id: QueryId,
     },
 
     /// An actor registration failed.
     RegistrationFailed {
         /// The provider query ID that failed.
-        provider_query_id: kad::QueryId,
+        provider_query_id: QueryId,
         /// The error that caused the failure.
         error: RegistryError,
     },
 
     /// An actor was successfully registered.
+    ///
+    /// Backends other than Kademlia complete registrations locally, reporting `Ok` results
+    /// keyed by the actor name, and the same query ID for both the provider and metadata.
     RegisteredActor {
         /// The result of the provider registration.
         provider_result: kad::AddProviderResult,
         /// The provider query ID.
-        provider_query_id: kad::QueryId,
+        provider_query_id: QueryId,
         /// The result of storing metadata.
         metadata_result: kad::PutRecordResult,
         /// The metadata query ID.
-        metadata_query_id: kad::QueryId,
+        metadata_query_id: QueryId,
     },
 
-    /// The routing table has been updated with a new peer and / or
+    /// The Kademlia routing table has been updated with a new peer and / or
     /// address, thereby possibly evicting another peer.
     RoutingUpdated {
         /// The ID of the peer that was added or updated.
@@ -159,60 +219,105 @@ pub enum Event {
         /// Address.
         address: Multiaddr,
     },
+
+    /// The actors registered under a name have changed, either on the local peer or as
+    /// observed from a remote peer.
+    ///
+    /// Lookup caches use this to discard results for the name.
+    RegistrationChanged {
+        /// The registered name.
+        name: Arc<str>,
+        /// The peer hosting the actor which was registered or unregistered.
+        peer: PeerId,
+    },
+}
+
+/// The backend used by the registry [`Behaviour`] to store and discover actor registrations.
+///
+/// # Example
+///
+/// ```rust
+/// use kameo::remote::registry;
+///
+/// let backend = registry::Backend::Gossip(registry::GossipConfig::default());
+/// ```
+#[derive(Clone, Debug)]
+pub enum Backend {
+    /// Registrations are stored in a Kademlia DHT.
+    Kademlia(KademliaConfig),
+    /// Registrations are resolved from a fixed configuration, plus actors registered locally.
+    Static(StaticConfig),
+    /// Registrations are fully replicated to every peer via gossipsub.
+    Gossip(GossipConfig),
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Kademlia(KademliaConfig::default())
+    }
 }
 
 /// `Behaviour` is a `NetworkBehaviour` that implements the kameo registry behaviour
-/// on top of the Kademlia protocol.
+/// on top of a configurable [`Backend`].
 #[allow(missing_debug_implementations)]
 pub struct Behaviour {
-    kademlia: kad::Behaviour<kad::store::MemoryStore>,
-    local_peer_id: PeerId,
-    pending_peers: HashMap<(PeerId, Multiaddr), Instant>,
-    pending_events: VecDeque<Event>,
-    registration_queries: HashMap<kad::QueryId, RegistrationQuery>,
-    lookup_queries: HashMap<kad::QueryId, LookupQuery>,
+    backend: BackendBehaviour,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum BackendBehaviour {
+    Kademlia(kademlia::Kademlia),
+    Static(static_config::Static),
+    Gossip(gossip::Gossip),
 }
 
 impl Behaviour {
-    /// Creates a new registry behaviour.
+    /// Creates a new registry behaviour backed by Kademlia.
     pub fn new(local_peer_id: PeerId) -> Self {
-        let mut config = kad::Config::new(PROTO_NAME);
-
-        // Faster lookups for responsive actor discovery
-        config.set_query_timeout(Duration::from_secs(10)); // Default: 60s
-
-        // Lower replication for efficiency while maintaining availability
-        config.set_replication_factor(NonZero::new(5).unwrap()); // Default: 20
-
-        // Shorter TTL since actors are more dynamic than files
-        config.set_record_ttl(Some(Duration::from_secs(3600))); // 1 hour, Default: 36 hours
-
-        // More frequent re-publication for dynamic actors
-        config.set_publication_interval(Some(Duration::from_secs(1800))); // 30 minutes, Default: 24 hours
-
-        // Filter records to prevent registry pollution
-        config.set_record_filtering(StoreInserts::FilterBoth); // Default: Unfiltered
-
-        let mut kademlia = kad::Behaviour::with_config(
-            local_peer_id,
-            kad::store::MemoryStore::new(local_peer_id),
-            config,
-        );
-        kademlia.set_mode(Some(kad::Mode::Server));
-
         Behaviour {
-            kademlia,
-            local_peer_id,
-            pending_peers: HashMap::new(),
-            pending_events: VecDeque::new(),
-            registration_queries: HashMap::new(),
-            lookup_queries: HashMap::new(),
+            backend: BackendBehaviour::Kademlia(kademlia::Kademlia::new(
+                local_peer_id,
+                KademliaConfig::default(),
+            )),
         }
     }
 
-    /// Registers an actor in the distributed registry.
+    /// Creates a new registry behaviour using the given backend.
     ///
-    /// This is a low-level method that directly interacts with the Kademlia DHT
+    /// Returns an error if the backend could not be set up.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kameo::remote::registry;
+    /// use libp2p::PeerId;
+    ///
+    /// let peer_id = PeerId::random();
+    /// let behaviour = registry::Behaviour::with_backend(
+    ///     peer_id,
+    ///     registry::Backend::Static(registry::StaticConfig::default()),
+    /// )?;
+    /// # Ok::<(), kameo::error::RegistryError>(())
+    /// ```
+    pub fn with_backend(local_peer_id: PeerId, backend: Backend) -> Result<Self, RegistryError> {
+        let backend = match backend {
+            Backend::Kademlia(config) => {
+                BackendBehaviour::Kademlia(kademlia::Kademlia::new(local_peer_id, config))
+            }
+            Backend::Static(config) => {
+                BackendBehaviour::Static(static_config::Static::new(local_peer_id, config))
+            }
+            Backend::Gossip(config) => {
+                BackendBehaviour::Gossip(gossip::Gossip::new(local_peer_id, config)?)
+            }
+        };
+
+        Ok(Behaviour { backend })
+    }
+
+    /// Registers an actor in the registry.
+    ///
+    /// This is a low-level method that directly interacts with the registry backend
     /// and generates events. Use `ActorRef::register` for higher-level registration
     /// that doesn't emit events.
     ///
@@ -229,7 +334,7 @@ impl Behaviour {
         &mut self,
         name: impl Into<Arc<str>>,
         registration: ActorRegistration<'static>,
-    ) -> Result<kad::QueryId, kad::store::Error> {
+    ) -> Result<QueryId, RegistryError> {
         self.register_with_reply(name.into(), registration, None)
             .map_err(|(_, err)| err)
     }
@@ -238,29 +343,57 @@ impl Behaviour {
     ///
     /// This is a low-level method. Returns `true` if the registration was found
     /// and cancelled, `false` if no registration with the given query ID exists.
-    pub fn cancel_registration(&mut self, query_id: &kad::QueryId) -> bool {
-        self.registration_queries.remove(query_id).is_some()
+    pub fn cancel_registration(&mut self, query_id: &QueryId) -> bool {
+        match (&mut self.backend, query_id.0) {
+            (BackendBehaviour::Kademlia(kademlia), QueryIdInner::Kademlia(query_id)) => {
+                kademlia.cancel_registration(&query_id)
+            }
+            // Other backends complete registrations immediately
+            _ => false,
+        }
     }
 
-    /// Unregisters an actor from the distributed registry.
+    /// Unregisters an actor from the registry.
     ///
-    /// This is a low-level method that removes the actor from both the provider
-    /// records and metadata storage in the Kademlia DHT.
+    /// This is a low-level method that removes the local peer's registration
+    /// for the given name from the registry backend.
     ///
     /// # Arguments
     ///
     /// * `name` - The name the actor was registered under
     pub fn unregister(&mut self, name: &str) {
-        self.kademlia
-            .stop_providing(&kad::RecordKey::new(&name.as_bytes()));
-        let key = format!("{}:meta:{}", name, self.local_peer_id);
-        self.kademlia
-            .remove_record(&kad::RecordKey::from(key.into_bytes()));
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.unregister(name),
+            BackendBehaviour::Static(table) => table.unregister(name),
+            BackendBehaviour::Gossip(gossip) => gossip.unregister(name),
+        }
     }
 
-    /// Looks up actors by name in the distributed registry.
+    /// Unregisters a name only if it's registered to the given actor on the local peer.
     ///
-    /// This is a low-level method that queries the Kademlia DHT and generates
+    /// This avoids removing a newer registration of the name by another actor, such as
+    /// when an actor is replaced before the old one has finished stopping.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the name was unregistered.
+    pub fn unregister_actor(&mut self, name: &str, actor_id: ActorId) -> bool {
+        let registered = match &self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.local_actor(name),
+            BackendBehaviour::Static(table) => table.local_actor(name),
+            BackendBehaviour::Gossip(gossip) => gossip.local_actor(name),
+        };
+        if registered != Some(actor_id) {
+            return false;
+        }
+
+        self.unregister(name);
+        true
+    }
+
+    /// Looks up actors by name in the registry.
+    ///
+    /// This is a low-level method that queries the registry backend and generates
     /// events as actors are discovered. Use `RemoteActorRef::lookup` for
     /// higher-level lookups that don't emit events.
     ///
@@ -271,13 +404,13 @@ impl Behaviour {
     /// # Returns
     ///
     /// The query ID for tracking the lookup progress.
-    pub fn lookup(&mut self, name: impl Into<Arc<str>>) -> kad::QueryId {
+    pub fn lookup(&mut self, name: impl Into<Arc<str>>) -> QueryId {
         self.lookup_with_reply(name.into(), None)
     }
 
     /// Looks up an actor in the local registry only.
     ///
-    /// This is a low-level method that checks if this peer is providing
+    /// This is a low-level method that checks if this peer has registered
     /// the requested actor name without querying remote peers.
     ///
     /// # Arguments
@@ -292,24 +425,10 @@ impl Behaviour {
         &mut self,
         name: &str,
     ) -> Result<Option<ActorRegistration<'static>>, RegistryError> {
-        // Check if we're providing this key locally
-        let key = kad::RecordKey::new(&name);
-        let store_mut = self.kademlia.store_mut();
-        let is_providing = store_mut.provided().any(|k| k.key == key);
-
-        if is_providing {
-            // Get metadata for local provider
-            let metadata_key = format!("{name}:meta:{}", self.local_peer_id);
-            store_mut
-                .get(&kad::RecordKey::new(&metadata_key))
-                .map(|record| {
-                    ActorRegistration::from_bytes(&record.value)
-                        .map(ActorRegistration::into_owned)
-                        .map_err(RegistryError::from)
-                })
-                .transpose()
-        } else {
-            Ok(None)
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.lookup_local(name),
+            BackendBehaviour::Static(table) => Ok(table.lookup_local(name)),
+            BackendBehaviour::Gossip(gossip) => Ok(gossip.lookup_local(name)),
         }
     }
 
@@ -317,8 +436,14 @@ impl Behaviour {
     ///
     /// This is a low-level method. Returns `true` if the lookup was found
     /// and cancelled, `false` if no lookup with the given query ID exists.
-    pub fn cancel_lookup(&mut self, query_id: &kad::QueryId) -> bool {
-        self.lookup_queries.remove(query_id).is_some()
+    pub fn cancel_lookup(&mut self, query_id: &QueryId) -> bool {
+        match (&mut self.backend, query_id.0) {
+            (BackendBehaviour::Kademlia(kademlia), QueryIdInner::Kademlia(query_id)) => {
+                kademlia.cancel_lookup(&query_id)
+            }
+            // Other backends complete lookups immediately
+            _ => false,
+        }
     }
 
     pub(super) fn register_with_reply(
@@ -326,652 +451,114 @@ impl Behaviour {
         name: Arc<str>,
         registration: ActorRegistration<'static>,
         reply: Option<RegisterReply>,
-    ) -> Result<kad::QueryId, (Option<RegisterReply>, kad::store::Error)> {
-        let key = kad::RecordKey::new(&name.as_bytes());
-        let provider_query_id = match self.kademlia.start_providing(key) {
-            Ok(id) => id,
-            Err(err) => {
-                return Err((reply, err));
+    ) -> Result<QueryId, (Option<RegisterReply>, RegistryError)> {
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia
+                .register_with_reply(name, registration, reply)
+                .map(QueryId::from)
+                .map_err(|(reply, err)| (reply, err.into())),
+            BackendBehaviour::Static(table) => {
+                Ok(table.register_with_reply(name, registration, reply))
             }
-        };
-
-        self.registration_queries.insert(
-            provider_query_id,
-            RegistrationQuery {
-                name,
-                registration: Some(registration),
-                put_query_id: None,
-                provider_result: None,
-                reply,
-            },
-        );
-
-        Ok(provider_query_id)
+            BackendBehaviour::Gossip(gossip) => {
+                Ok(gossip.register_with_reply(name, registration, reply))
+            }
+        }
     }
 
     pub(super) fn lookup_with_reply(
         &mut self,
         name: Arc<str>,
         reply: Option<LookupReply>,
-    ) -> kad::QueryId {
-        let query_id = self
-            .kademlia
-            .get_providers(kad::RecordKey::new(&name.as_bytes()));
-        self.lookup_queries.insert(
-            query_id,
-            LookupQuery {
-                name,
-                providers_finished: false,
-                metadata_queries: HashSet::new(),
-                queried_providers: HashSet::new(),
-                reported_providers: HashSet::new(),
-                reply,
-            },
-        );
-
-        query_id
-    }
-
-    fn handle_kademlia_event(&mut self, ev: kad::Event) -> (bool, Option<Event>) {
-        match ev {
-            kad::Event::InboundRequest { request } => {
-                match request {
-                    kad::InboundRequest::AddProvider { record } => {
-                        let record =
-                            record.expect("filtering is enabled, so the record should be present");
-
-                        if self.validate_provider_registration(&record) {
-                            // Accept the provider registration
-                            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
-                            if let Err(err) = self.kademlia.store_mut().add_provider(record) {
-                                #[cfg(feature = "tracing")]
-                                tracing::warn!("failed to store provider: {err}");
-                            }
-                        }
-
-                        (false, None)
-                    }
-                    kad::InboundRequest::PutRecord { source, record, .. } => {
-                        let record =
-                            record.expect("filtering is enabled, so the record should be present");
-
-                        if self.validate_metadata_record(&source, &record) {
-                            // Store the metadata record
-                            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
-                            if let Err(err) = self.kademlia.store_mut().put(record) {
-                                #[cfg(feature = "tracing")]
-                                tracing::warn!("failed to store metadata record: {err}");
-                            }
-                        }
-
-                        (false, None)
-                    }
-                    _ => (false, None),
-                }
-            }
-            kad::Event::OutboundQueryProgressed {
-                id,
-                result,
-                stats: _,
-                step,
-            } => match result {
-                #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
-                kad::QueryResult::Bootstrap(res) => {
-                    #[cfg(feature = "tracing")]
-                    if let Err(err) = res {
-                        tracing::warn!("bootstrap failed: {err}");
-                    }
-
-                    let failed_peers = self
-                        .pending_peers
-                        .extract_if(|_, failed_at| failed_at.elapsed() > Duration::from_secs(5));
-                    for ((peer_id, addr), _) in failed_peers {
-                        #[cfg(feature = "tracing")]
-                        tracing::debug!(%peer_id, %addr, "removing address for peer");
-                        self.kademlia.remove_address(&peer_id, &addr);
-                    }
-
-                    (false, None)
-                }
-                kad::QueryResult::GetClosestPeers(_) => (false, None),
-                // Getting the providers has progressed
-                kad::QueryResult::GetProviders(res) => {
-                    let Entry::Occupied(mut lookup_query_entry) = self.lookup_queries.entry(id)
-                    else {
-                        #[cfg(feature = "tracing")]
-                        tracing::warn!("ignoring GetProviders event for unknown lookup query");
-                        return (false, None);
-                    };
-                    let lookup_query = lookup_query_entry.get_mut();
-
-                    match res {
-                        Ok(kad::GetProvidersOk::FoundProviders { providers, .. }) => {
-                            let mut wake = false;
-                            lookup_query.providers_finished = step.last;
-
-                            for provider in providers {
-                                wake |=
-                                    lookup_query.get_metadata_record(&mut self.kademlia, &provider);
-                            }
-
-                            let last = step.last && lookup_query.is_finished();
-                            if last {
-                                if lookup_query.reply.is_none() {
-                                    self.pending_events.push_back(Event::LookupCompleted {
-                                        provider_query_id: id,
-                                    });
-                                }
-                                lookup_query_entry.remove();
-                            }
-
-                            (wake, None)
-                        }
-                        Ok(kad::GetProvidersOk::FinishedWithNoAdditionalRecord { .. }) => {
-                            lookup_query.providers_finished = step.last;
-                            let last = step.last && lookup_query.is_finished();
-                            if last {
-                                if lookup_query.reply.is_none() {
-                                    self.pending_events.push_back(Event::LookupCompleted {
-                                        provider_query_id: id,
-                                    });
-                                }
-                                lookup_query_entry.remove();
-                            }
-
-                            (false, None)
-                        }
-                        Err(kad::GetProvidersError::Timeout { .. }) => {
-                            lookup_query.providers_finished = step.last;
-                            let last = step.last && lookup_query.is_finished();
-                            match &lookup_query.reply {
-                                Some(tx) => {
-                                    let _ = tx.send(Err(RegistryError::Timeout));
-                                }
-                                None => {
-                                    self.pending_events.push_back(Event::LookupTimeout {
-                                        provider_query_id: id,
-                                    });
-                                }
-                            }
-                            if last {
-                                if lookup_query.reply.is_none() {
-                                    self.pending_events.push_back(Event::LookupCompleted {
-                                        provider_query_id: id,
-                                    });
-                                }
-                                lookup_query_entry.remove();
-                            }
-
-                            (false, None)
-                        }
-                    }
-                }
-                // Registering an actor has progressed
-                kad::QueryResult::StartProviding(res) => {
-                    let Entry::Occupied(mut registration_query_entry) =
-                        self.registration_queries.entry(id)
-                    else {
-                        #[cfg(feature = "tracing")]
-                        tracing::warn!(
-                            "ignoring StartProviding event for unknown registration query"
-                        );
-                        return (false, None);
-                    };
-                    let registration_query = registration_query_entry.get_mut();
-
-                    match res {
-                        Ok(kad::AddProviderOk { .. }) => {
-                            let Some(registration) = registration_query.registration.take() else {
-                                panic!("the registration should exist here");
-                            };
-                            registration_query.provider_result = Some(res.clone());
-
-                            // Store the metadata record
-                            let key =
-                                format!("{}:meta:{}", registration_query.name, self.local_peer_id);
-                            let registration_bytes = registration.into_bytes();
-                            let record = kad::Record::new(key.into_bytes(), registration_bytes);
-
-                            match self.kademlia.put_record(record, kad::Quorum::One) {
-                                Ok(put_query_id) => {
-                                    registration_query.put_query_id = Some(put_query_id);
-                                }
-                                Err(err) => {
-                                    // Put record failed immediately
-                                    match registration_query_entry.remove().reply {
-                                        Some(tx) => {
-                                            let _ = tx.send(Err(err.into()));
-                                        }
-                                        None => {
-                                            self.pending_events.push_back(
-                                                Event::RegistrationFailed {
-                                                    provider_query_id: id,
-                                                    error: err.into(),
-                                                },
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-
-                            (true, None)
-                        }
-                        Err(kad::AddProviderError::Timeout { .. }) => {
-                            match registration_query_entry.remove().reply {
-                                Some(tx) => {
-                                    let _ = tx.send(Err(RegistryError::Timeout));
-                                }
-                                None => {
-                                    self.pending_events.push_back(Event::RegistrationFailed {
-                                        provider_query_id: id,
-                                        error: RegistryError::Timeout,
-                                    });
-                                }
-                            }
-
-                            (false, None)
-                        }
-                    }
-                }
-                kad::QueryResult::RepublishProvider(_) => (false, None),
-                // Getting a metadata record has progressed
-                kad::QueryResult::GetRecord(res) => {
-                    let Some((provider_query_id, lookup_query)) = self
-                        .lookup_queries
-                        .iter_mut()
-                        .find(|(_, lookup_query)| lookup_query.has_metadata_query(&id))
-                    else {
-                        #[cfg(feature = "tracing")]
-                        tracing::warn!("ignoring GetRecord event for unknown lookup query");
-                        return (false, None);
-                    };
-                    let provider_query_id = *provider_query_id;
-
-                    match res {
-                        Ok(kad::GetRecordOk::FoundRecord(kad::PeerRecord {
-                            record: kad::Record { value, .. },
-                            ..
-                        })) => {
-                            let result = ActorRegistration::from_bytes(&value)
-                                .map(|registration| registration.into_owned())
-                                .map_err(RegistryError::from);
-
-                            // Check if we've already reported this provider to avoid duplicates
-                            let should_emit = if let Ok(ref registration) = result {
-                                if let Some(peer_id) = registration.actor_id.peer_id() {
-                                    if lookup_query.reported_providers.contains(peer_id) {
-                                        false // Already reported this provider
-                                    } else {
-                                        lookup_query.reported_providers.insert(*peer_id);
-                                        true
-                                    }
-                                } else {
-                                    true // No peer_id, emit anyway
-                                }
-                            } else {
-                                true // Error case, emit anyway
-                            };
-
-                            if should_emit {
-                                match &lookup_query.reply {
-                                    Some(tx) => {
-                                        let _ = tx.send(result);
-                                    }
-                                    None => {
-                                        self.pending_events.push_back(Event::LookupProgressed {
-                                            provider_query_id,
-                                            get_query_id: id,
-                                            result,
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                        // These cases don't provide useful information to the user
-                        Ok(kad::GetRecordOk::FinishedWithNoAdditionalRecord { .. })
-                        | Err(kad::GetRecordError::NotFound { .. }) => {
-                            // No progress event needed
-                        }
-                        // Error cases are still useful to report
-                        Err(kad::GetRecordError::QuorumFailed { quorum, .. }) => {
-                            match &lookup_query.reply {
-                                Some(tx) => {
-                                    let _ = tx.send(Err(RegistryError::QuorumFailed { quorum }));
-                                }
-                                None => {
-                                    self.pending_events.push_back(Event::LookupProgressed {
-                                        provider_query_id,
-                                        get_query_id: id,
-                                        result: Err(RegistryError::QuorumFailed { quorum }),
-                                    });
-                                }
-                            }
-                        }
-                        Err(kad::GetRecordError::Timeout { .. }) => match &lookup_query.reply {
-                            Some(tx) => {
-                                let _ = tx.send(Err(RegistryError::Timeout));
-                            }
-                            None => {
-                                self.pending_events.push_back(Event::LookupProgressed {
-                                    provider_query_id,
-                                    get_query_id: id,
-                                    result: Err(RegistryError::Timeout),
-                                });
-                            }
-                        },
-                    }
-
-                    if step.last {
-                        lookup_query.metadata_query_finished(&id);
-                        let last = lookup_query.is_finished();
-
-                        if last {
-                            if lookup_query.reply.is_none() {
-                                self.pending_events
-                                    .push_back(Event::LookupCompleted { provider_query_id });
-                            }
-                            self.lookup_queries.remove(&provider_query_id);
-                        }
-                    }
-
-                    (false, None)
-                }
-                // Putting a metadata record has progressed
-                kad::QueryResult::PutRecord(res) => {
-                    let Some(provider_query_id) =
-                        self.registration_queries
-                            .iter()
-                            .find_map(|(query_id, reg)| {
-                                if reg.put_query_id == Some(id) {
-                                    Some(*query_id)
-                                } else {
-                                    None
-                                }
-                            })
-                    else {
-                        #[cfg(feature = "tracing")]
-                        tracing::warn!("ignoring PutRecord event for unknown registration query");
-                        return (false, None);
-                    };
-
-                    match res {
-                        Ok(ok) => {
-                            let mut registration_query = self
-                                .registration_queries
-                                .remove(&provider_query_id)
-                                .unwrap();
-                            match registration_query.reply.take() {
-                                Some(tx) => {
-                                    let _ = tx.send(Ok(()));
-                                }
-                                None => {
-                                    self.pending_events.push_back(Event::RegisteredActor {
-                                        provider_result: registration_query
-                                            .provider_result
-                                            .unwrap(),
-                                        provider_query_id,
-                                        metadata_result: Ok(ok.clone()),
-                                        metadata_query_id: id,
-                                    });
-                                }
-                            }
-                        }
-                        Err(err) => {
-                            match self
-                                .registration_queries
-                                .remove(&provider_query_id)
-                                .and_then(|q| q.reply)
-                            {
-                                Some(tx) => {
-                                    let _ = tx.send(Err(err.clone().into()));
-                                }
-                                None => {
-                                    self.pending_events.push_back(Event::RegistrationFailed {
-                                        provider_query_id,
-                                        error: err.clone().into(),
-                                    });
-                                }
-                            }
-                        }
-                    }
-
-                    (false, None)
-                }
-                kad::QueryResult::RepublishRecord(_) => (false, None),
-            },
-            kad::Event::RoutingUpdated {
-                peer,
-                is_new_peer,
-                addresses,
-                bucket_range,
-                old_peer,
-            } => (
-                false,
-                Some(Event::RoutingUpdated {
-                    peer,
-                    is_new_peer,
-                    addresses,
-                    bucket_range,
-                    old_peer,
-                }),
-            ),
-            kad::Event::UnroutablePeer { peer } => (false, Some(Event::UnroutablePeer { peer })),
-            kad::Event::RoutablePeer { peer, address } => {
-                (false, Some(Event::RoutablePeer { peer, address }))
-            }
-            kad::Event::PendingRoutablePeer { peer, address } => {
-                (false, Some(Event::PendingRoutablePeer { peer, address }))
-            }
-            kad::Event::ModeChanged { .. } => (false, None),
-        }
-    }
-
-    fn validate_provider_registration(&mut self, provider: &kad::ProviderRecord) -> bool {
-        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
-        let source = &provider.provider;
-
-        // Should be valid UTF8
-        let key_str = match std::str::from_utf8(provider.key.as_ref()) {
-            Ok(s) => s,
-            Err(_) => {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("invalid UTF-8 in provider key from {source}");
-                return false;
-            }
-        };
-
-        if !self.is_valid_actor_name(key_str) {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("invalid actor name in provider registration from {source}: {key_str}");
-            return false;
-        }
-
-        if !self.is_valid_provider_record(provider) {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("invalid provider record from {source}");
-            return false;
-        }
-
-        #[cfg(feature = "tracing")]
-        tracing::debug!("validated provider registration for {key_str} from {source}");
-        true
-    }
-
-    fn is_valid_provider_record(&self, provider: &kad::ProviderRecord) -> bool {
-        if let Some(expires) = provider.expires {
-            // Must not be expired
-            if expires < Instant::now() {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("provider record is already expired");
-                return false;
-            }
+    ) -> QueryId {
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.lookup_with_reply(name, reply).into(),
+            BackendBehaviour::Static(table) => table.lookup_with_reply(&name, reply),
+            BackendBehaviour::Gossip(gossip) => gossip.lookup_with_reply(&name, reply),
         }
-
-        true
     }
 
-    fn validate_metadata_record(&mut self, source: &PeerId, record: &kad::Record) -> bool {
-        // Validate key format: "{name}:meta:{peer_id}"
-        let key_str = match str::from_utf8(record.key.as_ref()) {
-            Ok(s) => s,
-            Err(_) => {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("invalid UTF-8 in metadata record key from {source}");
-                return false;
-            }
-        };
-
-        let parts: Vec<_> = key_str.splitn(2, ":meta:").collect();
-        if parts.len() != 2 {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("invalid metadata key format from {source}: {key_str}");
-            return false;
-        }
-
-        let actor_name = parts[0];
-        let peer_id_str = parts[1];
-
-        if !self.is_valid_actor_name(actor_name) {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("invalid actor name from {source}: {actor_name}");
-            return false;
+    pub(super) fn watch_with_reply(&mut self, name: Arc<str>, reply: WatchReply) {
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.watch_with_reply(name, reply),
+            BackendBehaviour::Static(table) => table.watch_with_reply(name, reply),
+            BackendBehaviour::Gossip(gossip) => gossip.watch_with_reply(name, reply),
         }
-
-        // Parse and validate peer ID from key
-        let record_peer_id = match PeerId::from_str(peer_id_str) {
-            Ok(id) => id,
-            Err(_) => {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("invalid peer ID in metadata key from {source}: {peer_id_str}");
-                return false;
-            }
-        };
-
-        // Verify the peer_id in key matches the source (prevents impersonation)
-        if record_peer_id != *source {
-            #[cfg(feature = "tracing")]
-            tracing::warn!(
-                "peer ID mismatch: source {source} trying to register metadata for peer {record_peer_id}"
-            );
-            return false;
-        }
-
-        // Validate metadata format and size
-        if !self.is_valid_metadata(&record.value) {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("invalid metadata format from {source}");
-            return false;
-        }
-
-        // Disabled for now: what if the record gets received before the start providing message
-        // Verify the source peer is actually providing this actor
-        // let actor_key = kad::RecordKey::new(&actor_name);
-        // let providers = self.kademlia.store_mut().providers(&actor_key);
-
-        // let is_provider = providers
-        //     .iter()
-        //     .any(|provider_record| provider_record.provider == *source);
-
-        // if !is_provider {
-        //     #[cfg(feature = "tracing")]
-        //     tracing::warn!(
-        //         "peer {source} trying to register metadata for {actor_name} without being a provider"
-        //     );
-        //     return false;
-        // }
-
-        #[cfg(feature = "tracing")]
-        tracing::debug!("validated metadata record for {actor_name} from {source}");
-
-        true
-    }
-
-    fn is_valid_actor_name(&self, name: &str) -> bool {
-        !name.is_empty()
-    }
-
-    fn is_valid_metadata(&self, data: &[u8]) -> bool {
-        // Size check
-        if data.len() > 64 * 1024 {
-            // 64KB limit
-            return false;
-        }
-
-        ActorRegistration::from_bytes(data).is_ok()
     }
 }
 
 impl NetworkBehaviour for Behaviour {
-    type ConnectionHandler = THandler<kad::Behaviour<kad::store::MemoryStore>>;
+    type ConnectionHandler = Either<
+        THandler<kad::Behaviour<kad::store::MemoryStore>>,
+        Either<THandler<gossipsub::Behaviour>, dummy::ConnectionHandler>,
+    >;
     type ToSwarm = Event;
 
     fn handle_established_inbound_connection(
         &mut self,
-        connection_id: libp2p::swarm::ConnectionId,
+        connection_id: ConnectionId,
         peer: PeerId,
-        local_addr: &libp2p::Multiaddr,
-        remote_addr: &libp2p::Multiaddr,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        self.kademlia.handle_established_inbound_connection(
-            connection_id,
-            peer,
-            local_addr,
-            remote_addr,
-        )
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia
+                .handle_established_inbound_connection(connection_id, peer, local_addr, remote_addr)
+                .map(Either::Left),
+            BackendBehaviour::Static(table) => table
+                .handle_established_inbound_connection(connection_id, peer, local_addr, remote_addr)
+                .map(|handler| Either::Right(Either::Right(handler))),
+            BackendBehaviour::Gossip(gossip) => gossip
+                .handle_established_inbound_connection(connection_id, peer, local_addr, remote_addr)
+                .map(|handler| Either::Right(Either::Left(handler))),
+        }
     }
 
     fn handle_established_outbound_connection(
         &mut self,
         connection_id: ConnectionId,
         peer: PeerId,
-        addr: &libp2p::Multiaddr,
-        role_override: libp2p::core::Endpoint,
-        port_use: libp2p::core::transport::PortUse,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        port_use: PortUse,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        self.kademlia.handle_established_outbound_connection(
-            connection_id,
-            peer,
-            addr,
-            role_override,
-            port_use,
-        )
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia
+                .handle_established_outbound_connection(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    port_use,
+                )
+                .map(Either::Left),
+            BackendBehaviour::Static(table) => table
+                .handle_established_outbound_connection(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    port_use,
+                )
+                .map(|handler| Either::Right(Either::Right(handler))),
+            BackendBehaviour::Gossip(gossip) => gossip
+                .handle_established_outbound_connection(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    port_use,
+                )
+                .map(|handler| Either::Right(Either::Left(handler))),
+        }
     }
 
     fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
-        // We need to manually add the address, because kademlia doesn't do this by default (yet)
-        // https://github.com/libp2p/rust-libp2p/issues/5313
-        match event {
-            FromSwarm::ConnectionEstablished(ConnectionEstablished {
-                peer_id,
-                failed_addresses,
-                ..
-            }) => {
-                self.pending_peers.retain(|(pending_peer_id, addr), _| {
-                    // Keep all entries for different peers
-                    pending_peer_id != &peer_id
-                    // OR keep same-peer entries that failed
-                    || failed_addresses.iter().any(|failed_addr| failed_addr == addr)
-                });
-            }
-            FromSwarm::NewExternalAddrOfPeer(NewExternalAddrOfPeer { peer_id, addr }) => {
-                self.kademlia.add_address(&peer_id, addr.clone());
-            }
-            FromSwarm::DialFailure(DialFailure {
-                peer_id: Some(peer_id),
-                error: DialError::Transport(errors),
-                ..
-            }) => {
-                let now = Instant::now();
-                for (addr, _) in errors {
-                    self.pending_peers
-                        .entry((peer_id, addr.clone()))
-                        .or_insert(now);
-                }
-            }
-            _ => {}
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => kademlia.on_swarm_event(event),
+            BackendBehaviour::Static(table) => table.on_swarm_event(event),
+            BackendBehaviour::Gossip(gossip) => gossip.on_swarm_event(event),
         }
-
-        self.kademlia.on_swarm_event(event)
     }
 
     fn on_connection_handler_event(
@@ -980,118 +567,264 @@ impl NetworkBehaviour for Behaviour {
         connection_id: ConnectionId,
         event: THandlerOutEvent<Self>,
     ) {
-        self.kademlia
-            .on_connection_handler_event(peer_id, connection_id, event)
+        match (&mut self.backend, event) {
+            (BackendBehaviour::Kademlia(kademlia), Either::Left(ev)) => {
+                kademlia.on_connection_handler_event(peer_id, connection_id, ev)
+            }
+            (BackendBehaviour::Gossip(gossip), Either::Right(Either::Left(ev))) => {
+                gossip.on_connection_handler_event(peer_id, connection_id, ev)
+            }
+            (_, Either::Right(Either::Right(ev))) => match ev {},
+            _ => unreachable!("connection handler event does not match the registry backend"),
+        }
     }
 
     fn poll(
         &mut self,
         cx: &mut task::Context<'_>,
     ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
-        loop {
-            // First priority: return any pending events
-            if let Some(ev) = self.pending_events.pop_front() {
-                // Wake if we have more pending events to process
-                if !self.pending_events.is_empty() {
-                    cx.waker().wake_by_ref();
-                }
-                return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
-            }
-
-            // Second priority: poll Kademlia for new events
-            match self.kademlia.poll(cx) {
-                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
-                    let (wake, ev) = self.handle_kademlia_event(ev);
-
-                    // If we have an immediate event to return, return it
-                    if let Some(ev) = ev {
-                        // Wake if requested by handler OR if we have pending events
-                        if wake || !self.pending_events.is_empty() {
-                            cx.waker().wake_by_ref();
-                        }
-                        return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
-                    }
-
-                    // No immediate event, but if wake was requested or we have pending events,
-                    // continue the loop to process them
-                    if wake || !self.pending_events.is_empty() {
-                        continue;
-                    }
-
-                    // No immediate event and no wake needed, continue polling Kademlia
-                    // in case it has more events queued
-                    continue;
-                }
-                task::Poll::Ready(other_ev) => {
-                    // Non-GenerateEvent from Kademlia (dial events, etc.)
-                    if !self.pending_events.is_empty() {
-                        cx.waker().wake_by_ref();
-                    }
-
-                    return task::Poll::Ready(
-                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
-                    );
-                }
-                task::Poll::Pending => {
-                    // Kademlia has no more work ready
-                    // Final check: do we have any pending events that were added by the last handler call?
-                    if !self.pending_events.is_empty() {
-                        continue; // Go back to the top to process them
-                    }
-
-                    // Nothing left to do
-                    return task::Poll::Pending;
-                }
+        match &mut self.backend {
+            BackendBehaviour::Kademlia(kademlia) => {
+                kademlia.poll(cx).map(|ev| ev.map_in(Either::Left))
             }
+            BackendBehaviour::Static(table) => table
+                .poll(cx)
+                .map(|ev| ev.map_in(|ev| Either::Right(Either::Right(ev)))),
+            BackendBehaviour::Gossip(gossip) => gossip
+                .poll(cx)
+                .map(|ev| ev.map_in(|ev| Either::Right(Either::Left(ev)))),
         }
     }
 }
 
-struct RegistrationQuery {
-    name: Arc<str>,
-    registration: Option<ActorRegistration<'static>>,
-    put_query_id: Option<kad::QueryId>,
-    provider_result: Option<kad::AddProviderResult>,
-    reply: Option<RegisterReply>,
+/// Watchers subscribed to changes under registered names, along with the actors
+/// currently known to be registered under each watched name.
+///
+/// Changes to any name, watched or not, are also queued as [`Event::RegistrationChanged`]
+/// events for the backend to emit.
+struct Watchers {
+    local_peer_id: PeerId,
+    names: HashMap<Arc<str>, WatchedName>,
+    changes: VecDeque<Event>,
 }
 
-struct LookupQuery {
-    name: Arc<str>,
-    providers_finished: bool,
-    metadata_queries: HashSet<kad::QueryId>,
-    queried_providers: HashSet<PeerId>,
-    reported_providers: HashSet<PeerId>,
-    reply: Option<LookupReply>,
+#[derive(Default)]
+struct WatchedName {
+    senders: Vec<WatchReply>,
+    known: HashMap<PeerId, ActorId>,
 }
 
-impl LookupQuery {
-    fn get_metadata_record(
+impl Watchers {
+    fn new(local_peer_id: PeerId) -> Self {
+        Watchers {
+            local_peer_id,
+            names: HashMap::new(),
+            changes: VecDeque::new(),
+        }
+    }
+
+    /// Adds a watcher for the name, returning `true` if the name was not watched before.
+    ///
+    /// The currently registered actors are reported to the new watcher, with `current`
+    /// used as the initial state for names which were not already watched.
+    fn add<'a>(
         &mut self,
-        kademlia: &mut kad::Behaviour<kad::store::MemoryStore>,
-        provider: &PeerId,
+        name: Arc<str>,
+        reply: WatchReply,
+        current: impl IntoIterator<Item = &'a ActorRegistration<'static>>,
     ) -> bool {
-        // Skip if we've already queried this provider
-        if self.queried_providers.contains(provider) {
-            return false;
+        let local_peer_id = self.local_peer_id;
+        let mut is_new = false;
+        let watched = self.names.entry(name).or_insert_with(|| {
+            is_new = true;
+            WatchedName {
+                senders: Vec::new(),
+                known: current
+                    .into_iter()
+                    .map(|registration| {
+                        let peer = registration
+                            .actor_id
+                            .peer_id()
+                            .copied()
+                            .unwrap_or(local_peer_id);
+                        (peer, registration.actor_id)
+                    })
+                    .collect(),
+            }
+        });
+        for (peer, actor_id) in &watched.known {
+            let _ = reply.send(WatchEvent::Registered {
+                peer: *peer,
+                actor_id: *actor_id,
+            });
         }
+        watched.senders.push(reply);
 
-        self.queried_providers.insert(*provider);
-        let key = format!("{}:meta:{provider}", self.name);
-        let query_id = kademlia.get_record(key.into_bytes().into());
-        self.metadata_queries.insert(query_id);
-        true
+        is_new
+    }
+
+    fn names(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.names.keys()
+    }
+
+    /// Reports an actor registered under the name, if it's not already known.
+    ///
+    /// Names which aren't watched have no known state, so are always reported as changed.
+    fn registered(&mut self, name: &str, registration: &ActorRegistration<'_>) {
+        let peer = registration
+            .actor_id
+            .peer_id()
+            .copied()
+            .unwrap_or(self.local_peer_id);
+        let actor_id = registration.actor_id;
+        match self.names.get_mut(name) {
+            Some(watched) => {
+                if watched.known.insert(peer, actor_id) != Some(actor_id) {
+                    watched.notify(WatchEvent::Registered { peer, actor_id });
+                    self.changed(name, peer);
+                }
+            }
+            None => self.changed(name, peer),
+        }
     }
 
-    fn has_metadata_query(&self, query_id: &kad::QueryId) -> bool {
-        self.metadata_queries.contains(query_id)
+    /// Reports the peer's actor as explicitly unregistered from the name.
+    fn unregistered(&mut self, name: &str, peer: &PeerId) {
+        self.changed(name, *peer);
+        if let Some(watched) = self.names.get_mut(name)
+            && let Some(actor_id) = watched.known.remove(peer)
+        {
+            watched.notify(WatchEvent::Unregistered {
+                peer: *peer,
+                actor_id,
+            });
+        }
     }
 
-    fn metadata_query_finished(&mut self, query_id: &kad::QueryId) {
-        self.metadata_queries.remove(query_id);
+    /// Reports the peer's actor registered under the name as expired.
+    fn expired(&mut self, name: &str, peer: &PeerId) {
+        self.changed(name, *peer);
+        if let Some(watched) = self.names.get_mut(name)
+            && let Some(actor_id) = watched.known.remove(peer)
+        {
+            watched.notify(WatchEvent::Expired {
+                peer: *peer,
+                actor_id,
+            });
+        }
     }
 
-    fn is_finished(&self) -> bool {
-        self.providers_finished && self.metadata_queries.is_empty()
+    /// Expires every actor registered under the name which is not hosted by one of the given peers.
+    fn retain_peers(&mut self, name: &str, peers: &HashSet<PeerId>) {
+        if let Some(watched) = self.names.get_mut(name) {
+            let expired: Vec<_> = watched
+                .known
+                .extract_if(|peer, _| !peers.contains(peer))
+                .collect();
+            for (peer, actor_id) in expired {
+                watched.notify(WatchEvent::Expired { peer, actor_id });
+                self.changes.push_back(Event::RegistrationChanged {
+                    name: name.into(),
+                    peer,
+                });
+            }
+        }
+    }
+
+    fn changed(&mut self, name: &str, peer: PeerId) {
+        self.changes.push_back(Event::RegistrationChanged {
+            name: name.into(),
+            peer,
+        });
+    }
+
+    fn poll_change(&mut self) -> Option<Event> {
+        self.changes.pop_front()
+    }
+
+    /// Removes watchers whose streams have been dropped.
+    fn prune(&mut self) {
+        self.names.retain(|_, watched| {
+            watched.senders.retain(|tx| !tx.is_closed());
+            !watched.senders.is_empty()
+        });
+    }
+}
+
+impl WatchedName {
+    fn notify(&mut self, event: WatchEvent) {
+        self.senders.retain(|tx| tx.send(event).is_ok());
+    }
+}
+
+/// Query bookkeeping for backends which resolve registrations and lookups from local state.
+///
+/// Queries are completed as soon as they're started, either by replying directly or by
+/// queueing the same events the Kademlia backend would produce.
+#[derive(Default)]
+struct LocalQueries {
+    next_query_id: u64,
+    pending_events: VecDeque<Event>,
+}
+
+impl LocalQueries {
+    fn next_query_id(&mut self) -> QueryId {
+        let query_id = QueryId(QueryIdInner::Local(self.next_query_id));
+        self.next_query_id += 1;
+        query_id
+    }
+
+    fn complete_registration(&mut self, name: &str, reply: Option<RegisterReply>) -> QueryId {
+        let query_id = self.next_query_id();
+        match reply {
+            Some(tx) => {
+                let _ = tx.send(Ok(()));
+            }
+            None => {
+                let key = kad::RecordKey::new(&name);
+                self.pending_events.push_back(Event::RegisteredActor {
+                    provider_result: Ok(kad::AddProviderOk { key: key.clone() }),
+                    provider_query_id: query_id,
+                    metadata_result: Ok(kad::PutRecordOk { key }),
+                    metadata_query_id: query_id,
+                });
+            }
+        }
+
+        query_id
+    }
+
+    fn complete_lookup(
+        &mut self,
+        registrations: impl IntoIterator<Item = ActorRegistration<'static>>,
+        reply: Option<LookupReply>,
+    ) -> QueryId {
+        let query_id = self.next_query_id();
+        match reply {
+            Some(tx) => {
+                for registration in registrations {
+                    let _ = tx.send(Ok(registration));
+                }
+            }
+            None => {
+                for registration in registrations {
+                    self.pending_events.push_back(Event::LookupProgressed {
+                        provider_query_id: query_id,
+                        get_query_id: query_id,
+                        result: Ok(registration),
+                    });
+                }
+                self.pending_events.push_back(Event::LookupCompleted {
+                    provider_query_id: query_id,
+                });
+            }
+        }
+
+        query_id
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        self.pending_events.pop_front()
     }
 }
 
@@ -1100,12 +833,14 @@ impl LookupQuery {
 /// Contains the actor's unique ID and its remote type identifier,
 /// which together allow remote peers to locate and communicate
 /// with the actor.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ActorRegistration<'a> {
     /// The unique identifier of the actor.
     pub actor_id: ActorId,
     /// The remote type identifier for the actor.
     pub remote_id: Cow<'a, str>,
+    /// Arbitrary key/value tags attached to the registration, such as a region or version.
+    pub tags: BTreeMap<String, String>,
 }
 
 impl<'a> ActorRegistration<'a> {
@@ -1119,12 +854,28 @@ impl<'a> ActorRegistration<'a> {
         ActorRegistration {
             actor_id,
             remote_id,
+            tags: BTreeMap::new(),
         }
     }
 
+    /// Adds a tag to the registration, replacing any existing value for the key.
+    ///
+    /// Tags are returned alongside the actor by lookups, and can be used to choose
+    /// between multiple actors registered under the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The tag key, such as `"region"`
+    /// * `value` - The tag value, such as `"eu-west"`
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
     /// Serializes the actor registration into bytes for storage in the DHT.
     ///
-    /// The format includes the peer ID length, actor ID bytes, and remote ID string.
+    /// The format includes the peer ID length, actor ID bytes, and remote ID string,
+    /// followed by a nul byte and the MessagePack encoded tags if there are any.
     pub fn into_bytes(self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(1 + 8 + 42 + self.remote_id.len());
         let actor_id_bytes = self.actor_id.to_bytes();
@@ -1132,6 +883,11 @@ impl<'a> ActorRegistration<'a> {
         bytes.extend_from_slice(&peer_id_len.to_le_bytes());
         bytes.extend_from_slice(&actor_id_bytes);
         bytes.extend_from_slice(self.remote_id.as_bytes());
+        if !self.tags.is_empty() {
+            bytes.push(0);
+            rmp_serde::encode::write(&mut bytes, &self.tags)
+                .expect("encoding string tags should never fail");
+        }
         bytes
     }
 
@@ -1151,10 +907,23 @@ impl<'a> ActorRegistration<'a> {
 
         let peer_id_bytes_len = u8::from_le_bytes(bytes[..1].try_into().unwrap()) as usize;
         let actor_id = ActorId::from_bytes(&bytes[1..1 + 8 + peer_id_bytes_len])?;
-        let remote_id = std::str::from_utf8(&bytes[1 + 8 + peer_id_bytes_len..])
+        let rest = &bytes[1 + 8 + peer_id_bytes_len..];
+        let (remote_id, tags) = match rest.iter().position(|b| *b == 0) {
+            Some(i) => (
+                &rest[..i],
+                rmp_serde::from_slice(&rest[i + 1..])
+                    .map_err(InvalidActorRegistration::InvalidTags)?,
+            ),
+            None => (rest, BTreeMap::new()),
+        };
+        let remote_id = std::str::from_utf8(remote_id)
             .map_err(InvalidActorRegistration::InvalidRemoteIDUtf8)?;
 
-        Ok(ActorRegistration::new(actor_id, Cow::Borrowed(remote_id)))
+        Ok(ActorRegistration {
+            actor_id,
+            remote_id: Cow::Borrowed(remote_id),
+            tags,
+        })
     }
 
     /// Converts a borrowed actor registration into an owned one.
@@ -1162,7 +931,11 @@ impl<'a> ActorRegistration<'a> {
     /// This is useful when you need to store the registration beyond
     /// the lifetime of the original borrowed data.
     pub fn into_owned(self) -> ActorRegistration<'static> {
-        ActorRegistration::new(self.actor_id, Cow::Owned(self.remote_id.into_owned()))
+        ActorRegistration {
+            actor_id: self.actor_id,
+            remote_id: Cow::Owned(self.remote_id.into_owned()),
+            tags: self.tags,
+        }
     }
 }
 
@@ -1175,6 +948,8 @@ pub enum InvalidActorRegistration {
     ActorId(ActorIdFromBytesError),
     /// The remote ID contains invalid UTF-8.
     InvalidRemoteIDUtf8(str::Utf8Error),
+    /// The registration tags could not be decoded.
+    InvalidTags(rmp_serde::decode::Error),
 }
 
 impl From<ActorIdFromBytesError> for InvalidActorRegistration {
@@ -1191,6 +966,200 @@ impl fmt::Display for InvalidActorRegistration {
             }
             InvalidActorRegistration::ActorId(err) => err.fmt(f),
             InvalidActorRegistration::InvalidRemoteIDUtf8(err) => err.fmt(f),
+            InvalidActorRegistration::InvalidTags(err) => write!(f, "invalid tags: {err}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+
+    use std::{collections::HashSet, error, time::Duration};
+
+    use futures::StreamExt;
+    use libp2p::identity::Keypair;
+    use tokio::time;
+
+    use crate::{
+        actor::Spawn,
+        remote::{
+            RemoteActor, WatchStream,
+            testing::{Cluster, fixtures::Target},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn registration_round_trip() {
+        let actor_id = ActorId::new_with_peer_id(7, PeerId::random());
+        let bytes = ActorRegistration::new(actor_id, Cow::Borrowed("my_app::Counter")).into_bytes();
+        let registration = ActorRegistration::from_bytes(&bytes).unwrap();
+        assert_eq!(registration.actor_id, actor_id);
+        assert_eq!(registration.remote_id, "my_app::Counter");
+        assert!(registration.tags.is_empty());
+    }
+
+    #[test]
+    fn registration_with_tags_round_trip() {
+        let actor_id = ActorId::new_with_peer_id(7, PeerId::random());
+        let bytes = ActorRegistration::new(actor_id, Cow::Borrowed("my_app::Counter"))
+            .with_tag("region", "eu-west")
+            .with_tag("version", "2")
+            .into_bytes();
+        let registration = ActorRegistration::from_bytes(&bytes).unwrap();
+        assert_eq!(registration.actor_id, actor_id);
+        assert_eq!(registration.remote_id, "my_app::Counter");
+        assert_eq!(registration.tags.len(), 2);
+        assert_eq!(registration.tags["region"], "eu-west");
+        assert_eq!(registration.tags["version"], "2");
+    }
+
+    #[tokio::test]
+    async fn gossip_registry_converges_across_nodes() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::builder()
+            .nodes(3)
+            .registry_backend(Backend::Gossip(
+                GossipConfig::default().with_announce_interval(Duration::from_millis(200)),
+            ))
+            .start()
+            .await?;
+
+        let first = Target::spawn(Target);
+        let second = Target::spawn(Target);
+        cluster.node(0).register(&first, "target").await?;
+        cluster.node(1).register(&second, "target").await?;
+
+        for node in 0..3 {
+            let found = cluster.wait_for_lookup::<Target>(node, "target", 2).await?;
+            let ids: HashSet<_> = found.iter().map(|actor_ref| actor_ref.id()).collect();
+            assert_eq!(
+                ids,
+                HashSet::from([
+                    cluster.node(0).actor_id(&first),
+                    cluster.node(1).actor_id(&second)
+                ])
+            );
+        }
+
+        cluster.node(0).swarm().unregister("target").await;
+        for node in 0..3 {
+            let found = cluster.wait_for_lookup::<Target>(node, "target", 1).await?;
+            assert_eq!(found[0].id(), cluster.node(1).actor_id(&second));
+        }
+
+        // Registrations missed while partitioned are caught up by the periodic snapshot
+        cluster.partition(&[2], &[0, 1]).await?;
+        let third = Target::spawn(Target);
+        cluster.node(2).register(&third, "isolated").await?;
+        cluster.heal().await?;
+        for node in 0..2 {
+            let found = cluster
+                .wait_for_lookup::<Target>(node, "isolated", 1)
+                .await?;
+            assert_eq!(found[0].id(), cluster.node(2).actor_id(&third));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn static_registry_resolves_configured_actors() -> Result<(), Box<dyn error::Error>> {
+        let keypairs: Vec<_> = (0..3).map(|_| Keypair::generate_ed25519()).collect();
+        let peer_id = keypairs[0].public().to_peer_id();
+        let target = Target::spawn(Target);
+        let config = StaticConfig::default().with_registration(
+            "target",
+            ActorRegistration::new(
+                target.id().with_local_peer_id(peer_id),
+                Cow::Borrowed(Target::REMOTE_ID),
+            ),
+        );
+        let cluster = Cluster::builder()
+            .nodes(3)
+            .keypairs(keypairs)
+            .registry_backend(Backend::Static(config))
+            .start()
+            .await?;
+        // Lookups confirm with the hosting node that the actor is registered under the name
+        cluster.node(0).register(&target, "target").await?;
+
+        for node in 0..3 {
+            let found = cluster.wait_for_lookup::<Target>(node, "target", 1).await?;
+            assert_eq!(found[0].id(), cluster.node(0).actor_id(&target));
+        }
+
+        // Actors registered at runtime are only resolved by the node they are registered on
+        let local = Target::spawn(Target);
+        cluster.node(1).register(&local, "local").await?;
+        let found = cluster.node(1).lookup::<Target>("local").await?;
+        assert_eq!(
+            found.map(|actor_ref| actor_ref.id()),
+            Some(cluster.node(1).actor_id(&local))
+        );
+        for node in [0, 2] {
+            assert!(
+                cluster
+                    .node(node)
+                    .lookup::<Target>("local")
+                    .await?
+                    .is_none()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn next_event(
+        events: &mut WatchStream,
+    ) -> Result<Option<WatchEvent>, time::error::Elapsed> {
+        time::timeout(Duration::from_secs(5), events.next()).await
+    }
+
+    #[tokio::test]
+    async fn watch_reports_registered_unregistered_and_expired_actors()
+    -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::builder()
+            .registry_backend(Backend::Gossip(
+                GossipConfig::default()
+                    .with_announce_interval(Duration::from_millis(100))
+                    .with_entry_ttl(Duration::from_millis(500)),
+            ))
+            .start()
+            .await?;
+        let peer = cluster.node(0).peer_id();
+        let mut events = cluster.node(1).swarm().watch("target");
+
+        let first = Target::spawn(Target);
+        cluster.node(0).register(&first, "target").await?;
+        let actor_id = cluster.node(0).actor_id(&first);
+        assert_eq!(
+            next_event(&mut events).await?,
+            Some(WatchEvent::Registered { peer, actor_id })
+        );
+
+        cluster.node(0).swarm().unregister("target").await;
+        assert_eq!(
+            next_event(&mut events).await?,
+            Some(WatchEvent::Unregistered { peer, actor_id })
+        );
+
+        // Registrations which stop being announced expire
+        let second = Target::spawn(Target);
+        cluster.node(0).register(&second, "target").await?;
+        let actor_id = cluster.node(0).actor_id(&second);
+        assert_eq!(
+            next_event(&mut events).await?,
+            Some(WatchEvent::Registered { peer, actor_id })
+        );
+
+        cluster.partition(&[0], &[1]).await?;
+        assert_eq!(
+            next_event(&mut events).await?,
+            Some(WatchEvent::Expired { peer, actor_id })
+        );
+
+        Ok(())
+    }
+}
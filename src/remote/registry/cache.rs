@@ -0,0 +1,278 @@
+//! A client-side cache of lookup results.
+//!
+//! Lookups through the registry can take up to the query timeout to complete, which is too
+//! slow for looking up actors on every request. The cache stores the registrations found by
+//! completed lookups for a short time, along with lookups which found nothing.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use libp2p::PeerId;
+
+use crate::actor::ActorId;
+
+use super::ActorRegistration;
+
+/// Configuration for the lookup cache enabled with
+/// [`Behaviour::with_lookup_cache`](crate::remote::Behaviour::with_lookup_cache).
+///
+/// Cached results are discarded when they expire, when the registry observes a change to the
+/// registered name, or when sending to a cached actor fails because it's no longer running or
+/// its peer can't be reached.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kameo::remote::registry;
+///
+/// let config = registry::LookupCacheConfig::default()
+///     .with_ttl(Duration::from_secs(60))
+///     .with_negative_ttl(Duration::from_secs(2));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct LookupCacheConfig {
+    ttl: Duration,
+    negative_ttl: Duration,
+}
+
+impl LookupCacheConfig {
+    /// Sets how long actors found by a lookup are cached for.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long lookups which found no actors are cached for.
+    ///
+    /// Defaults to 5 seconds. A zero duration disables negative caching.
+    pub fn with_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+}
+
+impl Default for LookupCacheConfig {
+    fn default() -> Self {
+        LookupCacheConfig {
+            ttl: Duration::from_secs(30),
+            negative_ttl: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A lookup cache shared between the swarm behaviour and actor refs.
+#[derive(Clone, Debug)]
+pub(crate) struct LookupCache {
+    config: LookupCacheConfig,
+    state: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<Arc<str>, CacheEntry>,
+    /// Incremented on every invalidation, so lookups started before it aren't cached.
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    registrations: Vec<ActorRegistration<'static>>,
+    expires_at: Instant,
+}
+
+impl LookupCache {
+    pub(crate) fn new(config: LookupCacheConfig) -> Self {
+        LookupCache {
+            config,
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns the cached registrations for the name, if they haven't expired.
+    pub(crate) fn get(&self, name: &str) -> Option<Vec<ActorRegistration<'static>>> {
+        let mut state = self.state.lock().unwrap();
+        match state.entries.get(name) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.registrations.clone()),
+            Some(_) => {
+                state.entries.remove(name);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Returns the current generation, to be passed to [`LookupCache::insert`] once the
+    /// lookup completes.
+    pub(crate) fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Caches the results of a completed lookup, unless the cache was invalidated since the
+    /// lookup started.
+    pub(crate) fn insert(
+        &self,
+        name: Arc<str>,
+        registrations: Vec<ActorRegistration<'static>>,
+        generation: u64,
+    ) {
+        let ttl = if registrations.is_empty() {
+            self.config.negative_ttl
+        } else {
+            self.config.ttl
+        };
+        if ttl.is_zero() {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        let now = Instant::now();
+        state.entries.retain(|_, entry| entry.expires_at > now);
+        state.entries.insert(
+            name,
+            CacheEntry {
+                registrations,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Discards cached results for the name.
+    pub(crate) fn invalidate_name(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.remove(name);
+    }
+
+    /// Discards cached results containing the actor.
+    pub(crate) fn invalidate_actor(&self, actor_id: ActorId) {
+        self.invalidate_where(|registration| registration.actor_id == actor_id);
+    }
+
+    /// Discards cached results containing actors hosted by the peer.
+    pub(crate) fn invalidate_peer(&self, peer_id: &PeerId) {
+        self.invalidate_where(|registration| registration.actor_id.peer_id() == Some(peer_id));
+    }
+
+    fn invalidate_where(&self, mut f: impl FnMut(&ActorRegistration<'static>) -> bool) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state
+            .entries
+            .retain(|_, entry| !entry.registrations.iter().any(&mut f));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, thread};
+
+    use super::*;
+
+    fn registration(peer_id: PeerId, sequence_id: u64) -> ActorRegistration<'static> {
+        ActorRegistration::new(
+            ActorId::new_with_peer_id(sequence_id, peer_id),
+            Cow::Borrowed("my_app::Counter"),
+        )
+    }
+
+    fn new_cache(ttl: Duration, negative_ttl: Duration) -> LookupCache {
+        LookupCache::new(
+            LookupCacheConfig::default()
+                .with_ttl(ttl)
+                .with_negative_ttl(negative_ttl),
+        )
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = new_cache(Duration::from_millis(50), Duration::from_secs(60));
+        let found = registration(PeerId::random(), 1);
+        cache.insert("counter".into(), vec![found.clone()], cache.generation());
+
+        let cached = cache.get("counter").unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].actor_id, found.actor_id);
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(cache.get("counter").is_none());
+    }
+
+    #[test]
+    fn empty_lookups_are_cached_for_negative_ttl() {
+        let cache = new_cache(Duration::from_secs(60), Duration::from_millis(50));
+        cache.insert("counter".into(), vec![], cache.generation());
+        assert_eq!(cache.get("counter").map(|cached| cached.len()), Some(0));
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(cache.get("counter").is_none());
+
+        let disabled = new_cache(Duration::from_secs(60), Duration::ZERO);
+        disabled.insert("counter".into(), vec![], disabled.generation());
+        assert!(disabled.get("counter").is_none());
+    }
+
+    #[test]
+    fn lookups_started_before_invalidation_are_not_cached() {
+        let cache = new_cache(Duration::from_secs(60), Duration::from_secs(60));
+        let generation = cache.generation();
+        cache.invalidate_name("other");
+        cache.insert(
+            "counter".into(),
+            vec![registration(PeerId::random(), 1)],
+            generation,
+        );
+        assert!(cache.get("counter").is_none());
+
+        cache.insert(
+            "counter".into(),
+            vec![registration(PeerId::random(), 1)],
+            cache.generation(),
+        );
+        assert!(cache.get("counter").is_some());
+        cache.invalidate_name("counter");
+        assert!(cache.get("counter").is_none());
+    }
+
+    #[test]
+    fn invalidate_peer_discards_entries_hosted_by_peer() {
+        let cache = new_cache(Duration::from_secs(60), Duration::from_secs(60));
+        let (a, b) = (PeerId::random(), PeerId::random());
+        cache.insert(
+            "both".into(),
+            vec![registration(a, 1), registration(b, 2)],
+            cache.generation(),
+        );
+        cache.insert("b".into(), vec![registration(b, 3)], cache.generation());
+
+        cache.invalidate_peer(&a);
+        assert!(cache.get("both").is_none());
+        assert!(cache.get("b").is_some());
+    }
+
+    #[test]
+    fn invalidate_actor_discards_entries_containing_actor() {
+        let cache = new_cache(Duration::from_secs(60), Duration::from_secs(60));
+        let peer_id = PeerId::random();
+        let first = registration(peer_id, 1);
+        cache.insert("first".into(), vec![first.clone()], cache.generation());
+        cache.insert(
+            "second".into(),
+            vec![registration(peer_id, 2)],
+            cache.generation(),
+        );
+
+        cache.invalidate_actor(first.actor_id);
+        assert!(cache.get("first").is_none());
+        assert!(cache.get("second").is_some());
+    }
+}
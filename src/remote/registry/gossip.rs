@@ -0,0 +1,499 @@
+//! The gossip registry backend.
+//!
+//! Every peer keeps a full replica of the registry. Registrations and unregistrations are
+//! published on a gossipsub topic as they happen, and each peer periodically re-announces a
+//! snapshot of its own registrations so that newly joined peers catch up and entries from
+//! peers which have gone away expire.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    task,
+    time::Duration,
+};
+
+use libp2p::{
+    Multiaddr, PeerId,
+    core::{Endpoint, transport::PortUse},
+    gossipsub::{self, IdentTopic},
+    swarm::{
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler, THandlerInEvent,
+        THandlerOutEvent, ToSwarm,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+use crate::{actor::ActorId, error::RegistryError};
+
+use super::{
+    ActorRegistration, Event, LocalQueries, LookupReply, QueryId, RegisterReply, WatchReply,
+    Watchers,
+};
+
+const TOPIC: &str = "/kameo/registry/gossip/1.0.0";
+
+/// Configuration for the [`Backend::Gossip`](super::Backend::Gossip) registry backend.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kameo::remote::registry;
+///
+/// let config = registry::GossipConfig::default()
+///     .with_announce_interval(Duration::from_secs(10))
+///     .with_entry_ttl(Duration::from_secs(30));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GossipConfig {
+    announce_interval: Duration,
+    entry_ttl: Duration,
+    max_transmit_size: usize,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        GossipConfig {
+            announce_interval: Duration::from_secs(30),
+            entry_ttl: Duration::from_secs(90),
+            max_transmit_size: 1024 * 1024, // 1 MiB
+        }
+    }
+}
+
+impl GossipConfig {
+    /// Sets how often each peer re-announces a snapshot of its registrations.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn with_announce_interval(mut self, interval: Duration) -> Self {
+        self.announce_interval = interval;
+        self
+    }
+
+    /// Sets how long a registration received from another peer remains valid
+    /// without being re-announced.
+    ///
+    /// This should be a multiple of the announce interval, so that a single lost
+    /// announcement doesn't expire registrations. Defaults to 90 seconds.
+    pub fn with_entry_ttl(mut self, ttl: Duration) -> Self {
+        self.entry_ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum size of a single gossip message, which bounds the size of the
+    /// registration snapshot a peer can announce.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn with_max_transmit_size(mut self, size: usize) -> Self {
+        self.max_transmit_size = size;
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Announcement {
+    Snapshot {
+        registrations: Vec<(String, Vec<u8>)>,
+    },
+    Registered {
+        name: String,
+        registration: Vec<u8>,
+    },
+    Unregistered {
+        name: String,
+    },
+}
+
+struct RemoteEntry {
+    registration: ActorRegistration<'static>,
+    expires_at: Instant,
+}
+
+pub(super) struct Gossip {
+    gossipsub: gossipsub::Behaviour,
+    topic: IdentTopic,
+    config: GossipConfig,
+    local_peer_id: PeerId,
+    local: HashMap<Arc<str>, ActorRegistration<'static>>,
+    remote: HashMap<Arc<str>, HashMap<PeerId, RemoteEntry>>,
+    announce: Option<Interval>,
+    queries: LocalQueries,
+    watchers: Watchers,
+}
+
+impl Gossip {
+    pub(super) fn new(local_peer_id: PeerId, config: GossipConfig) -> Result<Self, RegistryError> {
+        let gossipsub_config = gossipsub::ConfigBuilder::default()
+            .max_transmit_size(config.max_transmit_size)
+            // Messages carry their author but are not signed, as the behaviour
+            // is constructed without access to the local keypair
+            .validation_mode(gossipsub::ValidationMode::Permissive)
+            .build()
+            .map_err(|err| RegistryError::Gossip(err.to_string()))?;
+        let mut gossipsub = gossipsub::Behaviour::new(
+            gossipsub::MessageAuthenticity::Author(local_peer_id),
+            gossipsub_config,
+        )
+        .map_err(|err| RegistryError::Gossip(err.to_string()))?;
+        let topic = IdentTopic::new(TOPIC);
+        gossipsub
+            .subscribe(&topic)
+            .map_err(|err| RegistryError::Gossip(err.to_string()))?;
+
+        Ok(Gossip {
+            gossipsub,
+            topic,
+            config,
+            local_peer_id,
+            local: HashMap::new(),
+            remote: HashMap::new(),
+            announce: None,
+            queries: LocalQueries::default(),
+            watchers: Watchers::new(local_peer_id),
+        })
+    }
+
+    pub(super) fn register_with_reply(
+        &mut self,
+        name: Arc<str>,
+        registration: ActorRegistration<'static>,
+        reply: Option<RegisterReply>,
+    ) -> QueryId {
+        self.publish(&Announcement::Registered {
+            name: name.to_string(),
+            registration: registration.clone().into_bytes(),
+        });
+        let query_id = self.queries.complete_registration(&name, reply);
+        self.watchers.registered(&name, &registration);
+        self.local.insert(name, registration);
+        query_id
+    }
+
+    pub(super) fn unregister(&mut self, name: &str) {
+        if self.local.remove(name).is_some() {
+            self.watchers.unregistered(name, &self.local_peer_id);
+            self.publish(&Announcement::Unregistered {
+                name: name.to_string(),
+            });
+        }
+    }
+
+    pub(super) fn lookup_with_reply(&mut self, name: &str, reply: Option<LookupReply>) -> QueryId {
+        let now = Instant::now();
+        let registrations = self
+            .local
+            .get(name)
+            .into_iter()
+            .chain(
+                self.remote
+                    .get(name)
+                    .into_iter()
+                    .flat_map(|entries| entries.values())
+                    .filter(|entry| entry.expires_at > now)
+                    .map(|entry| &entry.registration),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+        self.queries.complete_lookup(registrations, reply)
+    }
+
+    pub(super) fn watch_with_reply(&mut self, name: Arc<str>, reply: WatchReply) {
+        self.watchers.prune();
+        let now = Instant::now();
+        let current = self.local.get(&name).into_iter().chain(
+            self.remote
+                .get(&name)
+                .into_iter()
+                .flat_map(|entries| entries.values())
+                .filter(|entry| entry.expires_at > now)
+                .map(|entry| &entry.registration),
+        );
+        self.watchers.add(name.clone(), reply, current);
+    }
+
+    pub(super) fn local_actor(&self, name: &str) -> Option<ActorId> {
+        self.local
+            .get(name)
+            .map(|registration| registration.actor_id)
+    }
+
+    pub(super) fn lookup_local(&self, name: &str) -> Option<ActorRegistration<'static>> {
+        self.local.get(name).cloned()
+    }
+
+    fn publish(&mut self, announcement: &Announcement) {
+        let data = match rmp_serde::to_vec(announcement) {
+            Ok(data) => data,
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!("failed to encode registry announcement: {err}");
+                return;
+            }
+        };
+
+        match self.gossipsub.publish(self.topic.clone(), data) {
+            Ok(_) => {}
+            // Peers which subscribe later receive our next snapshot
+            Err(gossipsub::PublishError::NoPeersSubscribedToTopic) => {}
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("failed to publish registry announcement: {err}");
+            }
+        }
+    }
+
+    fn announce_snapshot(&mut self) {
+        if self.local.is_empty() {
+            return;
+        }
+
+        let registrations = self
+            .local
+            .iter()
+            .map(|(name, registration)| (name.to_string(), registration.clone().into_bytes()))
+            .collect();
+        self.publish(&Announcement::Snapshot { registrations });
+    }
+
+    fn expire_entries(&mut self) {
+        let now = Instant::now();
+        let watchers = &mut self.watchers;
+        self.remote.retain(|name, entries| {
+            entries.retain(|peer_id, entry| {
+                let expired = entry.expires_at <= now;
+                if expired {
+                    watchers.expired(name, peer_id);
+                }
+                !expired
+            });
+            !entries.is_empty()
+        });
+    }
+
+    fn remove_peer(&mut self, peer_id: &PeerId) {
+        let watchers = &mut self.watchers;
+        self.remote.retain(|name, entries| {
+            if entries.remove(peer_id).is_some() {
+                watchers.expired(name, peer_id);
+            }
+            !entries.is_empty()
+        });
+    }
+
+    fn remove_entry(&mut self, source: &PeerId, name: &str) {
+        if let Some(entries) = self.remote.get_mut(name) {
+            if entries.remove(source).is_some() {
+                self.watchers.unregistered(name, source);
+            }
+            if entries.is_empty() {
+                self.remote.remove(name);
+            }
+        }
+    }
+
+    fn insert_entry(&mut self, source: PeerId, name: String, bytes: &[u8]) {
+        let registration = match ActorRegistration::from_bytes(bytes) {
+            Ok(registration) => registration.into_owned(),
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("invalid registration for {name} from {source}: {err}");
+                return;
+            }
+        };
+
+        if registration.actor_id.peer_id() != Some(&source) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("peer {source} tried to register {name} for an actor on another peer");
+            return;
+        }
+
+        let name: Arc<str> = name.into();
+        let entries = self.remote.entry(name.clone()).or_default();
+        // Snapshots repeat known registrations, which only need their expiry extended
+        if entries
+            .get(&source)
+            .is_none_or(|entry| entry.registration != registration)
+        {
+            self.watchers.registered(&name, &registration);
+        }
+        entries.insert(
+            source,
+            RemoteEntry {
+                registration,
+                expires_at: Instant::now() + self.config.entry_ttl,
+            },
+        );
+    }
+
+    fn handle_gossipsub_event(&mut self, ev: gossipsub::Event) {
+        match ev {
+            gossipsub::Event::Message { message, .. } => {
+                if message.topic != self.topic.hash() {
+                    return;
+                }
+                let Some(source) = message.source else {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("ignoring registry announcement without a source");
+                    return;
+                };
+                if source == self.local_peer_id {
+                    return;
+                }
+
+                let announcement: Announcement = match rmp_serde::from_slice(&message.data) {
+                    Ok(announcement) => announcement,
+                    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                    Err(err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("invalid registry announcement from {source}: {err}");
+                        return;
+                    }
+                };
+
+                match announcement {
+                    Announcement::Snapshot { registrations } => {
+                        // A snapshot replaces everything previously known about the peer,
+                        // so any names missing from it were unregistered
+                        let names: HashSet<&str> = registrations
+                            .iter()
+                            .map(|(name, _)| name.as_str())
+                            .collect();
+                        let unregistered: Vec<_> = self
+                            .remote
+                            .iter()
+                            .filter(|(name, entries)| {
+                                entries.contains_key(&source) && !names.contains(name.as_ref())
+                            })
+                            .map(|(name, _)| name.clone())
+                            .collect();
+                        for name in unregistered {
+                            self.remove_entry(&source, &name);
+                        }
+                        for (name, registration) in registrations {
+                            self.insert_entry(source, name, &registration);
+                        }
+                    }
+                    Announcement::Registered { name, registration } => {
+                        self.insert_entry(source, name, &registration);
+                    }
+                    Announcement::Unregistered { name } => {
+                        self.remove_entry(&source, &name);
+                    }
+                }
+            }
+            gossipsub::Event::Subscribed { topic, .. } => {
+                if topic == self.topic.hash() {
+                    // Bring the new peer up to date with our registrations
+                    self.announce_snapshot();
+                }
+            }
+            gossipsub::Event::Unsubscribed { peer_id, topic } => {
+                if topic == self.topic.hash() {
+                    self.remove_peer(&peer_id);
+                }
+            }
+            gossipsub::Event::GossipsubNotSupported { .. } | gossipsub::Event::SlowPeer { .. } => {}
+        }
+    }
+}
+
+impl NetworkBehaviour for Gossip {
+    type ConnectionHandler = THandler<gossipsub::Behaviour>;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.gossipsub.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        port_use: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.gossipsub.handle_established_outbound_connection(
+            connection_id,
+            peer,
+            addr,
+            role_override,
+            port_use,
+        )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        self.gossipsub.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.gossipsub
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        // The interval is created lazily, as the behaviour may be constructed outside of a runtime
+        let announce_interval = self.config.announce_interval;
+        while self
+            .announce
+            .get_or_insert_with(|| {
+                let mut interval =
+                    tokio::time::interval_at(Instant::now() + announce_interval, announce_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            })
+            .poll_tick(cx)
+            .is_ready()
+        {
+            self.watchers.prune();
+            self.expire_entries();
+            self.announce_snapshot();
+        }
+
+        loop {
+            if let Some(ev) = self
+                .queries
+                .poll_event()
+                .or_else(|| self.watchers.poll_change())
+            {
+                return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+            }
+
+            match self.gossipsub.poll(cx) {
+                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
+                    self.handle_gossipsub_event(ev);
+                }
+                task::Poll::Ready(other_ev) => {
+                    return task::Poll::Ready(
+                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
+                    );
+                }
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,1097 @@
+//! The Kademlia registry backend.
+//!
+//! Each registration is advertised as a provider record for the actor name, with the
+//! registration metadata stored as a separate record keyed by the name and peer ID.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque, hash_map::Entry},
+    num::NonZero,
+    str::{self, FromStr},
+    sync::Arc,
+    task,
+    time::{Duration, Instant},
+};
+
+use libp2p::{
+    Multiaddr, PeerId, StreamProtocol,
+    kad::{self, StoreInserts, store::RecordStore},
+    swarm::{
+        ConnectionDenied, ConnectionId, DialError, DialFailure, FromSwarm, NetworkBehaviour,
+        NewExternalAddrOfPeer, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+        behaviour::ConnectionEstablished,
+    },
+};
+
+use tokio::{
+    sync::mpsc,
+    time::{Interval, MissedTickBehavior},
+};
+
+use crate::{actor::ActorId, error::RegistryError};
+
+use super::{
+    ActorRegistration, Event, LookupReply, LookupResult, RegisterReply, WatchReply, Watchers,
+};
+
+const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/registry/1.0.0");
+const WATCH_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The registry backend built on top of the Kademlia protocol.
+pub(super) struct Kademlia {
+    kademlia: kad::Behaviour<kad::store::MemoryStore>,
+    local_peer_id: PeerId,
+    pending_peers: HashMap<(PeerId, Multiaddr), Instant>,
+    pending_events: VecDeque<Event>,
+    registration_queries: HashMap<kad::QueryId, RegistrationQuery>,
+    lookup_queries: HashMap<kad::QueryId, LookupQuery>,
+    /// Actors registered on this peer, including registrations still in progress.
+    local: HashMap<Arc<str>, ActorId>,
+    watchers: Watchers,
+    watch_refreshes: HashMap<Arc<str>, WatchRefresh>,
+    watch_interval: Option<Interval>,
+}
+
+/// Configuration for the [`Backend::Kademlia`](super::Backend::Kademlia) registry backend.
+///
+/// The defaults are tuned for actor discovery rather than file sharing, with faster queries,
+/// lower replication, and shorter-lived records than libp2p's Kademlia defaults.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kameo::remote::registry;
+///
+/// let config = registry::KademliaConfig::default()
+///     .with_query_timeout(Duration::from_secs(5))
+///     .with_replication_factor(3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct KademliaConfig {
+    query_timeout: Duration,
+    replication_factor: NonZero<usize>,
+    record_ttl: Option<Duration>,
+    publication_interval: Option<Duration>,
+}
+
+impl Default for KademliaConfig {
+    fn default() -> Self {
+        KademliaConfig {
+            query_timeout: Duration::from_secs(10),
+            replication_factor: NonZero::new(5).unwrap(),
+            record_ttl: Some(Duration::from_secs(3600)), // 1 hour
+            publication_interval: Some(Duration::from_secs(1800)), // 30 minutes
+        }
+    }
+}
+
+impl KademliaConfig {
+    /// Sets the timeout for a single registration or lookup query.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn with_query_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
+
+    /// Sets the number of peers each registration is replicated to.
+    ///
+    /// Defaults to 5.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is zero.
+    pub fn with_replication_factor(mut self, factor: usize) -> Self {
+        self.replication_factor =
+            NonZero::new(factor).expect("replication factor must be non-zero");
+        self
+    }
+
+    /// Sets how long registration records remain valid without being re-published, or `None`
+    /// for records which never expire.
+    ///
+    /// Defaults to 1 hour.
+    pub fn with_record_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.record_ttl = ttl;
+        self
+    }
+
+    /// Sets how often registration records are re-published, or `None` to disable
+    /// re-publication.
+    ///
+    /// This should be shorter than the record TTL. Defaults to 30 minutes.
+    pub fn with_publication_interval(mut self, interval: Option<Duration>) -> Self {
+        self.publication_interval = interval;
+        self
+    }
+}
+
+impl Kademlia {
+    /// Creates a new Kademlia registry backend.
+    pub(super) fn new(local_peer_id: PeerId, config: KademliaConfig) -> Self {
+        let KademliaConfig {
+            query_timeout,
+            replication_factor,
+            record_ttl,
+            publication_interval,
+        } = config;
+        let mut config = kad::Config::new(PROTO_NAME);
+
+        config.set_query_timeout(query_timeout); // Default: 60s
+        config.set_replication_factor(replication_factor); // Default: 20
+        config.set_record_ttl(record_ttl); // Default: 36 hours
+        config.set_publication_interval(publication_interval); // Default: 24 hours
+
+        // Filter records to prevent registry pollution
+        config.set_record_filtering(StoreInserts::FilterBoth); // Default: Unfiltered
+
+        let mut kademlia = kad::Behaviour::with_config(
+            local_peer_id,
+            kad::store::MemoryStore::new(local_peer_id),
+            config,
+        );
+        kademlia.set_mode(Some(kad::Mode::Server));
+
+        Kademlia {
+            kademlia,
+            local_peer_id,
+            pending_peers: HashMap::new(),
+            pending_events: VecDeque::new(),
+            registration_queries: HashMap::new(),
+            lookup_queries: HashMap::new(),
+            local: HashMap::new(),
+            watchers: Watchers::new(local_peer_id),
+            watch_refreshes: HashMap::new(),
+            watch_interval: None,
+        }
+    }
+
+    /// Cancels an ongoing actor registration, returning `true` if it was found.
+    pub(super) fn cancel_registration(&mut self, query_id: &kad::QueryId) -> bool {
+        self.registration_queries.remove(query_id).is_some()
+    }
+
+    /// Removes the actor from both the provider records and metadata storage in the DHT.
+    pub(super) fn unregister(&mut self, name: &str) {
+        self.local.remove(name);
+        self.watchers.unregistered(name, &self.local_peer_id);
+        self.kademlia
+            .stop_providing(&kad::RecordKey::new(&name.as_bytes()));
+        let key = format!("{}:meta:{}", name, self.local_peer_id);
+        self.kademlia
+            .remove_record(&kad::RecordKey::from(key.into_bytes()));
+    }
+
+    pub(super) fn local_actor(&self, name: &str) -> Option<ActorId> {
+        self.local.get(name).copied()
+    }
+
+    /// Looks up an actor this peer is providing, without querying remote peers.
+    pub(super) fn lookup_local(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<ActorRegistration<'static>>, RegistryError> {
+        // Check if we're providing this key locally
+        let key = kad::RecordKey::new(&name);
+        let store_mut = self.kademlia.store_mut();
+        let is_providing = store_mut.provided().any(|k| k.key == key);
+
+        if is_providing {
+            // Get metadata for local provider
+            let metadata_key = format!("{name}:meta:{}", self.local_peer_id);
+            store_mut
+                .get(&kad::RecordKey::new(&metadata_key))
+                .map(|record| {
+                    ActorRegistration::from_bytes(&record.value)
+                        .map(ActorRegistration::into_owned)
+                        .map_err(RegistryError::from)
+                })
+                .transpose()
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Cancels an ongoing actor lookup, returning `true` if it was found.
+    pub(super) fn cancel_lookup(&mut self, query_id: &kad::QueryId) -> bool {
+        self.lookup_queries.remove(query_id).is_some()
+    }
+
+    pub(super) fn register_with_reply(
+        &mut self,
+        name: Arc<str>,
+        registration: ActorRegistration<'static>,
+        reply: Option<RegisterReply>,
+    ) -> Result<kad::QueryId, (Option<RegisterReply>, kad::store::Error)> {
+        let key = kad::RecordKey::new(&name.as_bytes());
+        let provider_query_id = match self.kademlia.start_providing(key) {
+            Ok(id) => id,
+            Err(err) => {
+                return Err((reply, err));
+            }
+        };
+        self.watchers.registered(&name, &registration);
+        self.local.insert(name.clone(), registration.actor_id);
+
+        self.registration_queries.insert(
+            provider_query_id,
+            RegistrationQuery {
+                name,
+                registration: Some(registration),
+                put_query_id: None,
+                provider_result: None,
+                reply,
+            },
+        );
+
+        Ok(provider_query_id)
+    }
+
+    pub(super) fn lookup_with_reply(
+        &mut self,
+        name: Arc<str>,
+        reply: Option<LookupReply>,
+    ) -> kad::QueryId {
+        let query_id = self
+            .kademlia
+            .get_providers(kad::RecordKey::new(&name.as_bytes()));
+        self.lookup_queries.insert(
+            query_id,
+            LookupQuery {
+                name,
+                providers_finished: false,
+                metadata_queries: HashSet::new(),
+                queried_providers: HashSet::new(),
+                reported_providers: HashSet::new(),
+                reply,
+            },
+        );
+
+        query_id
+    }
+
+    pub(super) fn watch_with_reply(&mut self, name: Arc<str>, reply: WatchReply) {
+        self.watchers.prune();
+        let local = self.lookup_local(&name).ok().flatten();
+        if self.watchers.add(name.clone(), reply, local.as_ref()) {
+            self.refresh_watch(name);
+        }
+    }
+
+    /// Starts a lookup for a watched name, unless one is already in progress.
+    fn refresh_watch(&mut self, name: Arc<str>) {
+        if self.watch_refreshes.contains_key(&name) {
+            return;
+        }
+
+        let mut peers = HashSet::new();
+        if let Ok(Some(_)) = self.lookup_local(&name) {
+            peers.insert(self.local_peer_id);
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lookup_with_reply(name.clone(), Some(tx));
+        self.watch_refreshes.insert(
+            name,
+            WatchRefresh {
+                results: rx,
+                peers,
+                failed: false,
+            },
+        );
+    }
+
+    /// Periodically refreshes watched names, reporting changes once each lookup completes.
+    fn poll_watches(&mut self, cx: &mut task::Context<'_>) {
+        // The interval is created lazily, as the behaviour may be constructed outside of a runtime
+        while self
+            .watch_interval
+            .get_or_insert_with(|| {
+                let mut interval = tokio::time::interval_at(
+                    tokio::time::Instant::now() + WATCH_REFRESH_INTERVAL,
+                    WATCH_REFRESH_INTERVAL,
+                );
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            })
+            .poll_tick(cx)
+            .is_ready()
+        {
+            self.watchers.prune();
+            let names: Vec<_> = self.watchers.names().cloned().collect();
+            for name in names {
+                self.refresh_watch(name);
+            }
+        }
+
+        let watchers = &mut self.watchers;
+        self.watch_refreshes.retain(|name, refresh| {
+            loop {
+                match refresh.results.poll_recv(cx) {
+                    task::Poll::Ready(Some(Ok(registration))) => {
+                        if let Some(peer_id) = registration.actor_id.peer_id() {
+                            refresh.peers.insert(*peer_id);
+                        }
+                        watchers.registered(name, &registration);
+                    }
+                    task::Poll::Ready(Some(Err(_))) => {
+                        refresh.failed = true;
+                    }
+                    task::Poll::Ready(None) => {
+                        // Only expire actors missing from a lookup which completed successfully
+                        if !refresh.failed {
+                            watchers.retain_peers(name, &refresh.peers);
+                        }
+                        return false;
+                    }
+                    task::Poll::Pending => return true,
+                }
+            }
+        });
+    }
+
+    fn handle_kademlia_event(&mut self, ev: kad::Event) -> (bool, Option<Event>) {
+        match ev {
+            kad::Event::InboundRequest { request } => {
+                match request {
+                    kad::InboundRequest::AddProvider { record } => {
+                        let record =
+                            record.expect("filtering is enabled, so the record should be present");
+
+                        if self.validate_provider_registration(&record) {
+                            // Accept the provider registration
+                            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                            if let Err(err) = self.kademlia.store_mut().add_provider(record) {
+                                #[cfg(feature = "tracing")]
+                                tracing::warn!("failed to store provider: {err}");
+                            }
+                        }
+
+                        (false, None)
+                    }
+                    kad::InboundRequest::PutRecord { source, record, .. } => {
+                        let record =
+                            record.expect("filtering is enabled, so the record should be present");
+
+                        if self.validate_metadata_record(&source, &record) {
+                            // Notify watchers of the name being registered
+                            if let Some((name, _)) = str::from_utf8(record.key.as_ref())
+                                .ok()
+                                .and_then(|key| key.split_once(":meta:"))
+                                && let Ok(registration) =
+                                    ActorRegistration::from_bytes(&record.value)
+                            {
+                                self.watchers.registered(name, &registration);
+                            }
+
+                            // Store the metadata record
+                            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                            if let Err(err) = self.kademlia.store_mut().put(record) {
+                                #[cfg(feature = "tracing")]
+                                tracing::warn!("failed to store metadata record: {err}");
+                            }
+                        }
+
+                        (false, None)
+                    }
+                    _ => (false, None),
+                }
+            }
+            kad::Event::OutboundQueryProgressed {
+                id,
+                result,
+                stats: _,
+                step,
+            } => match result {
+                #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                kad::QueryResult::Bootstrap(res) => {
+                    #[cfg(feature = "tracing")]
+                    if let Err(err) = res {
+                        tracing::warn!("bootstrap failed: {err}");
+                    }
+
+                    let failed_peers = self
+                        .pending_peers
+                        .extract_if(|_, failed_at| failed_at.elapsed() > Duration::from_secs(5));
+                    for ((peer_id, addr), _) in failed_peers {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(%peer_id, %addr, "removing address for peer");
+                        self.kademlia.remove_address(&peer_id, &addr);
+                    }
+
+                    (false, None)
+                }
+                kad::QueryResult::GetClosestPeers(_) => (false, None),
+                // Getting the providers has progressed
+                kad::QueryResult::GetProviders(res) => {
+                    let Entry::Occupied(mut lookup_query_entry) = self.lookup_queries.entry(id)
+                    else {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("ignoring GetProviders event for unknown lookup query");
+                        return (false, None);
+                    };
+                    let lookup_query = lookup_query_entry.get_mut();
+
+                    match res {
+                        Ok(kad::GetProvidersOk::FoundProviders { providers, .. }) => {
+                            let mut wake = false;
+                            lookup_query.providers_finished = step.last;
+
+                            for provider in providers {
+                                wake |=
+                                    lookup_query.get_metadata_record(&mut self.kademlia, &provider);
+                            }
+
+                            let last = step.last && lookup_query.is_finished();
+                            if last {
+                                if lookup_query.reply.is_none() {
+                                    self.pending_events.push_back(Event::LookupCompleted {
+                                        provider_query_id: id.into(),
+                                    });
+                                }
+                                lookup_query_entry.remove();
+                            }
+
+                            (wake, None)
+                        }
+                        Ok(kad::GetProvidersOk::FinishedWithNoAdditionalRecord { .. }) => {
+                            lookup_query.providers_finished = step.last;
+                            let last = step.last && lookup_query.is_finished();
+                            if last {
+                                if lookup_query.reply.is_none() {
+                                    self.pending_events.push_back(Event::LookupCompleted {
+                                        provider_query_id: id.into(),
+                                    });
+                                }
+                                lookup_query_entry.remove();
+                            }
+
+                            (false, None)
+                        }
+                        Err(kad::GetProvidersError::Timeout { .. }) => {
+                            lookup_query.providers_finished = step.last;
+                            let last = step.last && lookup_query.is_finished();
+                            match &lookup_query.reply {
+                                Some(tx) => {
+                                    let _ = tx.send(Err(RegistryError::Timeout));
+                                }
+                                None => {
+                                    self.pending_events.push_back(Event::LookupTimeout {
+                                        provider_query_id: id.into(),
+                                    });
+                                }
+                            }
+                            if last {
+                                if lookup_query.reply.is_none() {
+                                    self.pending_events.push_back(Event::LookupCompleted {
+                                        provider_query_id: id.into(),
+                                    });
+                                }
+                                lookup_query_entry.remove();
+                            }
+
+                            (false, None)
+                        }
+                    }
+                }
+                // Registering an actor has progressed
+                kad::QueryResult::StartProviding(res) => {
+                    let Entry::Occupied(mut registration_query_entry) =
+                        self.registration_queries.entry(id)
+                    else {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(
+                            "ignoring StartProviding event for unknown registration query"
+                        );
+                        return (false, None);
+                    };
+                    let registration_query = registration_query_entry.get_mut();
+
+                    match res {
+                        Ok(kad::AddProviderOk { .. }) => {
+                            let Some(registration) = registration_query.registration.take() else {
+                                panic!("the registration should exist here");
+                            };
+                            registration_query.provider_result = Some(res.clone());
+
+                            // Store the metadata record
+                            let key =
+                                format!("{}:meta:{}", registration_query.name, self.local_peer_id);
+                            let registration_bytes = registration.into_bytes();
+                            let record = kad::Record::new(key.into_bytes(), registration_bytes);
+
+                            match self.kademlia.put_record(record, kad::Quorum::One) {
+                                Ok(put_query_id) => {
+                                    registration_query.put_query_id = Some(put_query_id);
+                                }
+                                Err(err) => {
+                                    // Put record failed immediately
+                                    match registration_query_entry.remove().reply {
+                                        Some(tx) => {
+                                            let _ = tx.send(Err(err.into()));
+                                        }
+                                        None => {
+                                            self.pending_events.push_back(
+                                                Event::RegistrationFailed {
+                                                    provider_query_id: id.into(),
+                                                    error: err.into(),
+                                                },
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+
+                            (true, None)
+                        }
+                        Err(kad::AddProviderError::Timeout { .. }) => {
+                            match registration_query_entry.remove().reply {
+                                Some(tx) => {
+                                    let _ = tx.send(Err(RegistryError::Timeout));
+                                }
+                                None => {
+                                    self.pending_events.push_back(Event::RegistrationFailed {
+                                        provider_query_id: id.into(),
+                                        error: RegistryError::Timeout,
+                                    });
+                                }
+                            }
+
+                            (false, None)
+                        }
+                    }
+                }
+                kad::QueryResult::RepublishProvider(_) => (false, None),
+                // Getting a metadata record has progressed
+                kad::QueryResult::GetRecord(res) => {
+                    let Some((provider_query_id, lookup_query)) = self
+                        .lookup_queries
+                        .iter_mut()
+                        .find(|(_, lookup_query)| lookup_query.has_metadata_query(&id))
+                    else {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("ignoring GetRecord event for unknown lookup query");
+                        return (false, None);
+                    };
+                    let provider_query_id = *provider_query_id;
+
+                    match res {
+                        Ok(kad::GetRecordOk::FoundRecord(kad::PeerRecord {
+                            record: kad::Record { value, .. },
+                            ..
+                        })) => {
+                            let result = ActorRegistration::from_bytes(&value)
+                                .map(|registration| registration.into_owned())
+                                .map_err(RegistryError::from);
+
+                            // Check if we've already reported this provider to avoid duplicates
+                            let should_emit = if let Ok(ref registration) = result {
+                                if let Some(peer_id) = registration.actor_id.peer_id() {
+                                    if lookup_query.reported_providers.contains(peer_id) {
+                                        false // Already reported this provider
+                                    } else {
+                                        lookup_query.reported_providers.insert(*peer_id);
+                                        true
+                                    }
+                                } else {
+                                    true // No peer_id, emit anyway
+                                }
+                            } else {
+                                true // Error case, emit anyway
+                            };
+
+                            if should_emit {
+                                match &lookup_query.reply {
+                                    Some(tx) => {
+                                        let _ = tx.send(result);
+                                    }
+                                    None => {
+                                        self.pending_events.push_back(Event::LookupProgressed {
+                                            provider_query_id: provider_query_id.into(),
+                                            get_query_id: id.into(),
+                                            result,
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                        // These cases don't provide useful information to the user
+                        Ok(kad::GetRecordOk::FinishedWithNoAdditionalRecord { .. })
+                        | Err(kad::GetRecordError::NotFound { .. }) => {
+                            // No progress event needed
+                        }
+                        // Error cases are still useful to report
+                        Err(kad::GetRecordError::QuorumFailed { quorum, .. }) => {
+                            match &lookup_query.reply {
+                                Some(tx) => {
+                                    let _ = tx.send(Err(RegistryError::QuorumFailed { quorum }));
+                                }
+                                None => {
+                                    self.pending_events.push_back(Event::LookupProgressed {
+                                        provider_query_id: provider_query_id.into(),
+                                        get_query_id: id.into(),
+                                        result: Err(RegistryError::QuorumFailed { quorum }),
+                                    });
+                                }
+                            }
+                        }
+                        Err(kad::GetRecordError::Timeout { .. }) => match &lookup_query.reply {
+                            Some(tx) => {
+                                let _ = tx.send(Err(RegistryError::Timeout));
+                            }
+                            None => {
+                                self.pending_events.push_back(Event::LookupProgressed {
+                                    provider_query_id: provider_query_id.into(),
+                                    get_query_id: id.into(),
+                                    result: Err(RegistryError::Timeout),
+                                });
+                            }
+                        },
+                    }
+
+                    if step.last {
+                        lookup_query.metadata_query_finished(&id);
+                        let last = lookup_query.is_finished();
+
+                        if last {
+                            if lookup_query.reply.is_none() {
+                                self.pending_events.push_back(Event::LookupCompleted {
+                                    provider_query_id: provider_query_id.into(),
+                                });
+                            }
+                            self.lookup_queries.remove(&provider_query_id);
+                        }
+                    }
+
+                    (false, None)
+                }
+                // Putting a metadata record has progressed
+                kad::QueryResult::PutRecord(res) => {
+                    let Some(provider_query_id) =
+                        self.registration_queries
+                            .iter()
+                            .find_map(|(query_id, reg)| {
+                                if reg.put_query_id == Some(id) {
+                                    Some(*query_id)
+                                } else {
+                                    None
+                                }
+                            })
+                    else {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("ignoring PutRecord event for unknown registration query");
+                        return (false, None);
+                    };
+
+                    match res {
+                        Ok(ok) => {
+                            let mut registration_query = self
+                                .registration_queries
+                                .remove(&provider_query_id)
+                                .unwrap();
+                            match registration_query.reply.take() {
+                                Some(tx) => {
+                                    let _ = tx.send(Ok(()));
+                                }
+                                None => {
+                                    self.pending_events.push_back(Event::RegisteredActor {
+                                        provider_result: registration_query
+                                            .provider_result
+                                            .unwrap(),
+                                        provider_query_id: provider_query_id.into(),
+                                        metadata_result: Ok(ok.clone()),
+                                        metadata_query_id: id.into(),
+                                    });
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            match self
+                                .registration_queries
+                                .remove(&provider_query_id)
+                                .and_then(|q| q.reply)
+                            {
+                                Some(tx) => {
+                                    let _ = tx.send(Err(err.clone().into()));
+                                }
+                                None => {
+                                    self.pending_events.push_back(Event::RegistrationFailed {
+                                        provider_query_id: provider_query_id.into(),
+                                        error: err.clone().into(),
+                                    });
+                                }
+                            }
+                        }
+                    }
+
+                    (false, None)
+                }
+                kad::QueryResult::RepublishRecord(_) => (false, None),
+            },
+            kad::Event::RoutingUpdated {
+                peer,
+                is_new_peer,
+                addresses,
+                bucket_range,
+                old_peer,
+            } => (
+                false,
+                Some(Event::RoutingUpdated {
+                    peer,
+                    is_new_peer,
+                    addresses,
+                    bucket_range,
+                    old_peer,
+                }),
+            ),
+            kad::Event::UnroutablePeer { peer } => (false, Some(Event::UnroutablePeer { peer })),
+            kad::Event::RoutablePeer { peer, address } => {
+                (false, Some(Event::RoutablePeer { peer, address }))
+            }
+            kad::Event::PendingRoutablePeer { peer, address } => {
+                (false, Some(Event::PendingRoutablePeer { peer, address }))
+            }
+            kad::Event::ModeChanged { .. } => (false, None),
+        }
+    }
+
+    fn validate_provider_registration(&mut self, provider: &kad::ProviderRecord) -> bool {
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+        let source = &provider.provider;
+
+        // Should be valid UTF8
+        let key_str = match std::str::from_utf8(provider.key.as_ref()) {
+            Ok(s) => s,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("invalid UTF-8 in provider key from {source}");
+                return false;
+            }
+        };
+
+        if !self.is_valid_actor_name(key_str) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("invalid actor name in provider registration from {source}: {key_str}");
+            return false;
+        }
+
+        if !self.is_valid_provider_record(provider) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("invalid provider record from {source}");
+            return false;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("validated provider registration for {key_str} from {source}");
+        true
+    }
+
+    fn is_valid_provider_record(&self, provider: &kad::ProviderRecord) -> bool {
+        if let Some(expires) = provider.expires {
+            // Must not be expired
+            if expires < Instant::now() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("provider record is already expired");
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn validate_metadata_record(&mut self, source: &PeerId, record: &kad::Record) -> bool {
+        // Validate key format: "{name}:meta:{peer_id}"
+        let key_str = match str::from_utf8(record.key.as_ref()) {
+            Ok(s) => s,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("invalid UTF-8 in metadata record key from {source}");
+                return false;
+            }
+        };
+
+        let parts: Vec<_> = key_str.splitn(2, ":meta:").collect();
+        if parts.len() != 2 {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("invalid metadata key format from {source}: {key_str}");
+            return false;
+        }
+
+        let actor_name = parts[0];
+        let peer_id_str = parts[1];
+
+        if !self.is_valid_actor_name(actor_name) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("invalid actor name from {source}: {actor_name}");
+            return false;
+        }
+
+        // Parse and validate peer ID from key
+        let record_peer_id = match PeerId::from_str(peer_id_str) {
+            Ok(id) => id,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("invalid peer ID in metadata key from {source}: {peer_id_str}");
+                return false;
+            }
+        };
+
+        // Verify the peer_id in key matches the source (prevents impersonation)
+        if record_peer_id != *source {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "peer ID mismatch: source {source} trying to register metadata for peer {record_peer_id}"
+            );
+            return false;
+        }
+
+        // Validate metadata format and size
+        if !self.is_valid_metadata(&record.value) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("invalid metadata format from {source}");
+            return false;
+        }
+
+        // Disabled for now: what if the record gets received before the start providing message
+        // Verify the source peer is actually providing this actor
+        // let actor_key = kad::RecordKey::new(&actor_name);
+        // let providers = self.kademlia.store_mut().providers(&actor_key);
+
+        // let is_provider = providers
+        //     .iter()
+        //     .any(|provider_record| provider_record.provider == *source);
+
+        // if !is_provider {
+        //     #[cfg(feature = "tracing")]
+        //     tracing::warn!(
+        //         "peer {source} trying to register metadata for {actor_name} without being a provider"
+        //     );
+        //     return false;
+        // }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("validated metadata record for {actor_name} from {source}");
+
+        true
+    }
+
+    fn is_valid_actor_name(&self, name: &str) -> bool {
+        !name.is_empty()
+    }
+
+    fn is_valid_metadata(&self, data: &[u8]) -> bool {
+        // Size check
+        if data.len() > 64 * 1024 {
+            // 64KB limit
+            return false;
+        }
+
+        ActorRegistration::from_bytes(data).is_ok()
+    }
+}
+
+impl NetworkBehaviour for Kademlia {
+    type ConnectionHandler = THandler<kad::Behaviour<kad::store::MemoryStore>>;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: libp2p::swarm::ConnectionId,
+        peer: PeerId,
+        local_addr: &libp2p::Multiaddr,
+        remote_addr: &libp2p::Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.kademlia.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &libp2p::Multiaddr,
+        role_override: libp2p::core::Endpoint,
+        port_use: libp2p::core::transport::PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.kademlia.handle_established_outbound_connection(
+            connection_id,
+            peer,
+            addr,
+            role_override,
+            port_use,
+        )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        // We need to manually add the address, because kademlia doesn't do this by default (yet)
+        // https://github.com/libp2p/rust-libp2p/issues/5313
+        match event {
+            FromSwarm::ConnectionEstablished(ConnectionEstablished {
+                peer_id,
+                failed_addresses,
+                ..
+            }) => {
+                self.pending_peers.retain(|(pending_peer_id, addr), _| {
+                    // Keep all entries for different peers
+                    pending_peer_id != &peer_id
+                    // OR keep same-peer entries that failed
+                    || failed_addresses.iter().any(|failed_addr| failed_addr == addr)
+                });
+            }
+            FromSwarm::NewExternalAddrOfPeer(NewExternalAddrOfPeer { peer_id, addr }) => {
+                self.kademlia.add_address(&peer_id, addr.clone());
+            }
+            FromSwarm::DialFailure(DialFailure {
+                peer_id: Some(peer_id),
+                error: DialError::Transport(errors),
+                ..
+            }) => {
+                let now = Instant::now();
+                for (addr, _) in errors {
+                    self.pending_peers
+                        .entry((peer_id, addr.clone()))
+                        .or_insert(now);
+                }
+            }
+            _ => {}
+        }
+
+        self.kademlia.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.kademlia
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        self.poll_watches(cx);
+
+        loop {
+            // First priority: return any pending events
+            if let Some(ev) = self
+                .pending_events
+                .pop_front()
+                .or_else(|| self.watchers.poll_change())
+            {
+                // Wake if we have more pending events to process
+                if !self.pending_events.is_empty() {
+                    cx.waker().wake_by_ref();
+                }
+                return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+            }
+
+            // Second priority: poll Kademlia for new events
+            match self.kademlia.poll(cx) {
+                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
+                    let (wake, ev) = self.handle_kademlia_event(ev);
+
+                    // If we have an immediate event to return, return it
+                    if let Some(ev) = ev {
+                        // Wake if requested by handler OR if we have pending events
+                        if wake || !self.pending_events.is_empty() {
+                            cx.waker().wake_by_ref();
+                        }
+                        return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+                    }
+
+                    // No immediate event, but if wake was requested or we have pending events,
+                    // continue the loop to process them
+                    if wake || !self.pending_events.is_empty() {
+                        continue;
+                    }
+
+                    // No immediate event and no wake needed, continue polling Kademlia
+                    // in case it has more events queued
+                    continue;
+                }
+                task::Poll::Ready(other_ev) => {
+                    // Non-GenerateEvent from Kademlia (dial events, etc.)
+                    if !self.pending_events.is_empty() {
+                        cx.waker().wake_by_ref();
+                    }
+
+                    return task::Poll::Ready(
+                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
+                    );
+                }
+                task::Poll::Pending => {
+                    // Kademlia has no more work ready
+                    // Final check: do we have any pending events that were added by the last handler call?
+                    if !self.pending_events.is_empty() {
+                        continue; // Go back to the top to process them
+                    }
+
+                    // Nothing left to do
+                    return task::Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+struct WatchRefresh {
+    results: mpsc::UnboundedReceiver<LookupResult>,
+    peers: HashSet<PeerId>,
+    failed: bool,
+}
+
+struct RegistrationQuery {
+    name: Arc<str>,
+    registration: Option<ActorRegistration<'static>>,
+    put_query_id: Option<kad::QueryId>,
+    provider_result: Option<kad::AddProviderResult>,
+    reply: Option<RegisterReply>,
+}
+
+struct LookupQuery {
+    name: Arc<str>,
+    providers_finished: bool,
+    metadata_queries: HashSet<kad::QueryId>,
+    queried_providers: HashSet<PeerId>,
+    reported_providers: HashSet<PeerId>,
+    reply: Option<LookupReply>,
+}
+
+impl LookupQuery {
+    fn get_metadata_record(
+        &mut self,
+        kademlia: &mut kad::Behaviour<kad::store::MemoryStore>,
+        provider: &PeerId,
+    ) -> bool {
+        // Skip if we've already queried this provider
+        if self.queried_providers.contains(provider) {
+            return false;
+        }
+
+        self.queried_providers.insert(*provider);
+        let key = format!("{}:meta:{provider}", self.name);
+        let query_id = kademlia.get_record(key.into_bytes().into());
+        self.metadata_queries.insert(query_id);
+        true
+    }
+
+    fn has_metadata_query(&self, query_id: &kad::QueryId) -> bool {
+        self.metadata_queries.contains(query_id)
+    }
+
+    fn metadata_query_finished(&mut self, query_id: &kad::QueryId) {
+        self.metadata_queries.remove(query_id);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.providers_finished && self.metadata_queries.is_empty()
+    }
+}
@@ -0,0 +1,191 @@
+//! The static registry backend.
+//!
+//! Lookups are resolved from a fixed set of registrations supplied up front, such as from a
+//! configuration file, along with any actors registered on the local peer. No registry
+//! traffic is exchanged with other peers.
+
+use std::{collections::HashMap, sync::Arc, task};
+
+use libp2p::{
+    Multiaddr, PeerId,
+    core::{Endpoint, transport::PortUse},
+    swarm::{
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler, THandlerInEvent,
+        THandlerOutEvent, ToSwarm, dummy,
+    },
+};
+
+use crate::actor::ActorId;
+
+use super::{
+    ActorRegistration, Event, LocalQueries, LookupReply, QueryId, RegisterReply, WatchReply,
+    Watchers,
+};
+
+/// Configuration for the [`Backend::Static`](super::Backend::Static) registry backend.
+///
+/// # Example
+///
+/// ```rust
+/// use std::borrow::Cow;
+///
+/// use kameo::{actor::ActorId, remote::registry};
+/// use libp2p::PeerId;
+///
+/// let peer_id: PeerId = PeerId::random();
+/// let config = registry::StaticConfig::default().with_registration(
+///     "counter",
+///     registry::ActorRegistration::new(
+///         ActorId::new_with_peer_id(0, peer_id),
+///         Cow::Borrowed("my_app::Counter"),
+///     ),
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StaticConfig {
+    registrations: HashMap<Arc<str>, Vec<ActorRegistration<'static>>>,
+}
+
+impl StaticConfig {
+    /// Adds a registration for an actor under the given name.
+    ///
+    /// Multiple registrations may be added under the same name, and are returned
+    /// by lookups in the order they were added.
+    pub fn with_registration(
+        mut self,
+        name: impl Into<Arc<str>>,
+        registration: ActorRegistration<'static>,
+    ) -> Self {
+        self.registrations
+            .entry(name.into())
+            .or_default()
+            .push(registration);
+        self
+    }
+}
+
+pub(super) struct Static {
+    config: StaticConfig,
+    local_peer_id: PeerId,
+    local: HashMap<Arc<str>, ActorRegistration<'static>>,
+    queries: LocalQueries,
+    watchers: Watchers,
+}
+
+impl Static {
+    pub(super) fn new(local_peer_id: PeerId, config: StaticConfig) -> Self {
+        Static {
+            config,
+            local_peer_id,
+            local: HashMap::new(),
+            queries: LocalQueries::default(),
+            watchers: Watchers::new(local_peer_id),
+        }
+    }
+
+    pub(super) fn register_with_reply(
+        &mut self,
+        name: Arc<str>,
+        registration: ActorRegistration<'static>,
+        reply: Option<RegisterReply>,
+    ) -> QueryId {
+        let query_id = self.queries.complete_registration(&name, reply);
+        self.watchers.registered(&name, &registration);
+        self.local.insert(name, registration);
+        query_id
+    }
+
+    pub(super) fn unregister(&mut self, name: &str) {
+        if self.local.remove(name).is_some() {
+            self.watchers.unregistered(name, &self.local_peer_id);
+        }
+    }
+
+    pub(super) fn lookup_with_reply(&mut self, name: &str, reply: Option<LookupReply>) -> QueryId {
+        let registrations = self.registrations(name).cloned().collect::<Vec<_>>();
+        self.queries.complete_lookup(registrations, reply)
+    }
+
+    pub(super) fn watch_with_reply(&mut self, name: Arc<str>, reply: WatchReply) {
+        self.watchers.prune();
+        let current = self.registrations(&name).cloned().collect::<Vec<_>>();
+        self.watchers.add(name, reply, current.iter());
+    }
+
+    pub(super) fn local_actor(&self, name: &str) -> Option<ActorId> {
+        self.local
+            .get(name)
+            .map(|registration| registration.actor_id)
+    }
+
+    pub(super) fn lookup_local(&self, name: &str) -> Option<ActorRegistration<'static>> {
+        self.local.get(name).cloned()
+    }
+
+    fn registrations(&self, name: &str) -> impl Iterator<Item = &ActorRegistration<'static>> {
+        let local = self.local.get(name);
+        // The configuration may list actors hosted by the local peer, which are already
+        // returned once registered locally
+        local.into_iter().chain(
+            self.config
+                .registrations
+                .get(name)
+                .into_iter()
+                .flatten()
+                .filter(move |registration| {
+                    local.is_none_or(|local| local.actor_id != registration.actor_id)
+                }),
+        )
+    }
+}
+
+impl NetworkBehaviour for Static {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+        _port_use: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm<'_>) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        match event {}
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self
+            .queries
+            .poll_event()
+            .or_else(|| self.watchers.poll_change())
+        {
+            Some(ev) => task::Poll::Ready(ToSwarm::GenerateEvent(ev)),
+            None => task::Poll::Pending,
+        }
+    }
+}
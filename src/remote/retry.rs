@@ -0,0 +1,80 @@
+use std::{future::Future, time::Duration};
+
+use crate::{
+    Actor,
+    actor::{ActorId, RemoteActorRef},
+    error::RemoteSendError,
+};
+
+use super::RemoteActor;
+
+/// How many times, and how often, a send through a re-resolved actor is retried.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RetryPolicy {
+    pub(crate) max_retries: usize,
+    pub(crate) interval: Duration,
+}
+
+/// Sends through the actor returned by `resolve`, resolving it again and retrying when the actor
+/// can't be reached.
+///
+/// When a send fails because the actor isn't running or its peer can't be reached, `invalidate`
+/// is called with the actor's ID so the next attempt resolves it again. Resolving fails the same
+/// way while a new actor is starting or its peer is being dialed, so those errors are retried
+/// too, and any other error is returned.
+pub(crate) async fn send_with_retry<A, R, RFut, F, Fut, T, E>(
+    policy: RetryPolicy,
+    mut resolve: R,
+    mut invalidate: impl FnMut(ActorId),
+    mut f: F,
+) -> Result<T, RemoteSendError<E>>
+where
+    A: Actor + RemoteActor,
+    R: FnMut() -> RFut,
+    RFut: Future<Output = Result<RemoteActorRef<A>, RemoteSendError>>,
+    F: FnMut(RemoteActorRef<A>) -> Fut,
+    Fut: Future<Output = Result<T, RemoteSendError<E>>>,
+{
+    let mut retries = 0;
+    loop {
+        let err = match resolve().await {
+            Ok(actor_ref) => {
+                let actor_id = actor_ref.id();
+                match f(actor_ref).await {
+                    Err(err) if is_unreachable(&err) => {
+                        invalidate(actor_id);
+                        err
+                    }
+                    res => return res,
+                }
+            }
+            Err(err) if is_unreachable(&err) || is_unavailable(&err) => {
+                err.map_err(|err| match err {})
+            }
+            Err(err) => return Err(err.map_err(|err| match err {})),
+        };
+
+        if retries >= policy.max_retries {
+            return Err(err);
+        }
+        retries += 1;
+        if !policy.interval.is_zero() {
+            tokio::time::sleep(policy.interval).await;
+        }
+    }
+}
+
+/// Returns whether a send failed because the actor isn't running or its peer can't be reached.
+fn is_unreachable<E>(err: &RemoteSendError<E>) -> bool {
+    matches!(
+        err,
+        RemoteSendError::ActorNotRunning
+            | RemoteSendError::DialFailure
+            | RemoteSendError::ConnectionClosed
+    )
+}
+
+/// Returns whether resolving failed because no actor is currently available.
+fn is_unavailable(err: &RemoteSendError) -> bool {
+    matches!(err, RemoteSendError::SingletonUnavailable { .. })
+}
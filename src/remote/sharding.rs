@@ -0,0 +1,761 @@
+//! Cluster sharding of keyed entities across nodes.
+//!
+//! Sharding spreads a large number of actors, each identified by an entity ID such as a user or
+//! device ID, over the members of the cluster. Entity IDs are mapped to a fixed number of shards,
+//! and each shard is owned by one member, chosen by rendezvous hashing among the members which
+//! are [`Up`](super::membership::MemberStatus::Up) and have the region's role. When membership
+//! changes, shards are rebalanced: only shards owned by the changed members move, and local
+//! entities of shards now owned by another member are stopped.
+//!
+//! Entities are spawned on the owning node the first time they're resolved, with args built from
+//! their entity ID, and passivated (stopped) once they've been idle for the passivation timeout.
+//! The next message sent to a passivated entity spawns it again.
+//!
+//! Every node sending messages to an entity type must start a [`ShardRegion`] for it, and nodes
+//! owning shards must start one to host entities.
+//!
+//! Shard owners are chosen from the members each node is directly connected to, so every node
+//! must be connected to every other node. In a partially connected cluster, nodes disagree on the
+//! owner of a shard and the same entity runs on several nodes. See the
+//! [membership docs](super::membership) for details.
+//!
+//! Nodes see membership changes at slightly different times, so while the cluster is changing
+//! an entity may briefly run on two nodes. Entities should persist any state they need to keep
+//! across rebalancing and passivation.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kameo::prelude::*;
+//! use kameo::remote::sharding::{self, ShardRegion};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Actor, RemoteActor)]
+//! struct Device {
+//!     id: String,
+//!     readings: Vec<f64>,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Record(f64);
+//!
+//! #[kameo::remote_message("device-record")]
+//! impl Message<Record> for Device {
+//!     type Reply = usize;
+//!
+//!     async fn handle(&mut self, Record(value): Record, _ctx: &mut Context<Self, Self::Reply>) -> usize {
+//!         self.readings.push(value);
+//!         self.readings.len()
+//!     }
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let region = ShardRegion::<Device>::start(sharding::Config::default(), |id| Device {
+//!     id: id.to_string(),
+//!     readings: Vec::new(),
+//! })
+//! .await?;
+//!
+//! let count = region.ask("device-42", &Record(21.5)).await?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! # });
+//! ```
+
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{Future, future::BoxFuture};
+use libp2p::PeerId;
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    Actor, Reply,
+    actor::{ActorId, ActorRef, RemoteActorRef, Spawn},
+    error::RemoteSendError,
+    message::Message,
+};
+
+use super::{
+    ActorSwarm, REMOTE_REGISTRY, RemoteActor, RemoteMessage, RemoteRegistryActorRef, RetryPolicy,
+    SwarmHandle,
+    membership::{Member, MemberStatus},
+    send_with_retry,
+};
+
+/// How often idle entities are checked for passivation.
+const PASSIVATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The regions started on this node, by the local peer ID of the swarm they were started in and
+/// the remote ID of their entity actor.
+static REGIONS: LazyLock<Mutex<HashMap<(PeerId, &'static str), RegionEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The identifier of a shard.
+pub type ShardId = u32;
+
+/// The configuration for a [`ShardRegion`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    shards: u32,
+    role: Option<String>,
+    passivation_timeout: Option<Duration>,
+    shard_fn: fn(&str, u32) -> ShardId,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            shards: 100,
+            role: None,
+            passivation_timeout: Some(Duration::from_secs(120)),
+            shard_fn: default_shard_fn,
+        }
+    }
+}
+
+impl Config {
+    /// Sets the number of shards entity IDs are mapped to.
+    ///
+    /// All nodes must use the same number of shards for an entity type. It should be several
+    /// times larger than the expected number of nodes, so shards can be spread evenly.
+    ///
+    /// Defaults to 100.
+    pub fn with_shards(mut self, shards: u32) -> Self {
+        self.shards = shards.max(1);
+        self
+    }
+
+    /// Restricts hosting entities to members with the role.
+    ///
+    /// Nodes without the role can still send messages to entities through their region.
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Sets how long an entity may be idle before it's passivated, or `None` to keep entities
+    /// running until their shard moves to another node.
+    ///
+    /// Defaults to 2 minutes.
+    pub fn with_passivation_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.passivation_timeout = timeout;
+        self
+    }
+
+    /// Sets the function mapping an entity ID to a shard, given the number of shards.
+    ///
+    /// Shards returned outside of the number of shards wrap around. All nodes must use the same
+    /// function for an entity type. Defaults to a hash of the entity ID.
+    pub fn with_shard_fn(mut self, f: fn(&str, u32) -> ShardId) -> Self {
+        self.shard_fn = f;
+        self
+    }
+
+    /// Returns the shard an entity ID belongs to.
+    pub fn shard_of(&self, entity_id: &str) -> ShardId {
+        (self.shard_fn)(entity_id, self.shards) % self.shards
+    }
+}
+
+fn default_shard_fn(entity_id: &str, shards: u32) -> ShardId {
+    (const_fnv1a_hash::fnv1a_hash_str_64(entity_id) % shards as u64) as ShardId
+}
+
+/// A handle for sending messages to the entities of type `A`, spread across the cluster.
+///
+/// See the [module level docs](self) for how entities are placed and passivated.
+/// Clones share the same region.
+pub struct ShardRegion<A: Actor> {
+    inner: Arc<Region<A>>,
+}
+
+struct Region<A: Actor> {
+    config: Config,
+    swarm: ActorSwarm,
+    entity_args: EntityArgsFn<A>,
+    /// Members eligible to own shards, by peer ID.
+    owners: Mutex<Vec<PeerId>>,
+    /// Entities hosted by this node.
+    entities: tokio::sync::Mutex<HashMap<String, Entity<A>>>,
+    /// Entities hosted by other nodes which have been resolved.
+    resolved: Mutex<HashMap<String, Resolved<A>>>,
+}
+
+type EntityArgsFn<A> = Box<dyn Fn(&str) -> <A as Actor>::Args + Send + Sync>;
+
+struct Entity<A: Actor> {
+    actor_ref: ActorRef<A>,
+    last_active: Instant,
+}
+
+struct Resolved<A: Actor> {
+    actor_ref: RemoteActorRef<A>,
+    resolved_at: Instant,
+}
+
+struct RegionEntry {
+    host: Arc<dyn RegionHost>,
+    region: Arc<dyn Any + Send + Sync>,
+}
+
+/// Hosts the entities of a region for resolution requests from other nodes.
+trait RegionHost: Send + Sync {
+    fn resolve(self: Arc<Self>, entity_id: String) -> BoxFuture<'static, ActorId>;
+}
+
+impl<A> ShardRegion<A>
+where
+    A: Actor + RemoteActor,
+{
+    /// Starts the region for entities of type `A`, building each entity's args from its ID.
+    ///
+    /// If a region for `A` has already been started on this node, it's returned instead, and
+    /// `config` and `entity_args` are ignored.
+    pub async fn start<F>(config: Config, entity_args: F) -> Result<Self, RemoteSendError>
+    where
+        F: Fn(&str) -> A::Args + Send + Sync + 'static,
+    {
+        let swarm = SwarmHandle::global().ok_or(RemoteSendError::SwarmNotBootstrapped)?;
+        Self::start_in(&swarm, config, entity_args).await
+    }
+
+    /// Starts the region for entities of type `A` within a specific actor swarm.
+    ///
+    /// This behaves like [`ShardRegion::start`], but spreads entities over the members of the
+    /// swarm of the handle instead of the global actor swarm. Each swarm in the process has its
+    /// own region.
+    pub async fn start_in<F>(
+        swarm: &SwarmHandle,
+        config: Config,
+        entity_args: F,
+    ) -> Result<Self, RemoteSendError>
+    where
+        F: Fn(&str) -> A::Args + Send + Sync + 'static,
+    {
+        if let Some(region) = Self::get_in(swarm) {
+            return Ok(region);
+        }
+
+        let swarm = swarm.actor_swarm().clone();
+        let key = (*swarm.local_peer_id(), A::REMOTE_ID);
+        // Subscribing first ensures no change is missed between the two
+        let mut events = swarm.subscribe_members();
+        let members = swarm.members().await;
+
+        let region = Arc::new(Region {
+            owners: Mutex::new(owners(&config, members.iter())),
+            config,
+            swarm,
+            entity_args: Box::new(entity_args),
+            entities: tokio::sync::Mutex::new(HashMap::new()),
+            resolved: Mutex::new(HashMap::new()),
+        });
+
+        {
+            let mut regions = REGIONS.lock().unwrap();
+            // Another task may have started the region while members were being fetched
+            if let Some(entry) = regions.get(&key) {
+                let inner = entry.region.clone().downcast().unwrap();
+                return Ok(ShardRegion { inner });
+            }
+            regions.insert(
+                key,
+                RegionEntry {
+                    host: region.clone(),
+                    region: region.clone(),
+                },
+            );
+        }
+
+        let mut members: HashMap<_, _> = members
+            .into_iter()
+            .map(|member| (member.peer_id, member))
+            .collect();
+        let task_region = region.clone();
+        tokio::spawn(async move {
+            let region = task_region;
+            let mut interval = tokio::time::interval(PASSIVATION_CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    event = events.recv() => {
+                        let Some(event) = event else {
+                            break;
+                        };
+                        let member = event.member();
+                        match member.status {
+                            MemberStatus::Removed => members.remove(&member.peer_id),
+                            _ => members.insert(member.peer_id, member.clone()),
+                        };
+                        let owners = owners(&region.config, members.values());
+                        if *region.owners.lock().unwrap() != owners {
+                            *region.owners.lock().unwrap() = owners;
+                            region.rebalance().await;
+                        }
+                    }
+                    _ = interval.tick() => region.passivate_idle().await,
+                }
+            }
+        });
+
+        Ok(ShardRegion { inner: region })
+    }
+
+    /// Returns the region for entities of type `A`, if one has been started on this node.
+    pub fn get() -> Option<Self> {
+        Self::get_in(&SwarmHandle::global()?)
+    }
+
+    /// Returns the region for entities of type `A`, if one has been started within a specific
+    /// actor swarm.
+    pub fn get_in(swarm: &SwarmHandle) -> Option<Self> {
+        let regions = REGIONS.lock().unwrap();
+        let inner = regions
+            .get(&(swarm.peer_id(), A::REMOTE_ID))?
+            .region
+            .clone()
+            .downcast()
+            .ok()?;
+        Some(ShardRegion { inner })
+    }
+
+    /// Returns the region's configuration.
+    pub fn config(&self) -> &Config {
+        &self.inner.config
+    }
+
+    /// Returns the shard an entity ID belongs to.
+    pub fn shard_of(&self, entity_id: &str) -> ShardId {
+        self.inner.config.shard_of(entity_id)
+    }
+
+    /// Returns the member currently owning a shard, or `None` if no member can host entities.
+    pub fn owner_of(&self, shard: ShardId) -> Option<PeerId> {
+        owner_of(&self.inner.owners.lock().unwrap(), shard)
+    }
+
+    /// Returns the IDs of the entities currently running on this node.
+    pub async fn local_entities(&self) -> Vec<String> {
+        self.inner.entities.lock().await.keys().cloned().collect()
+    }
+
+    /// Returns a reference to an entity, spawning it on the node owning its shard if it isn't
+    /// running.
+    ///
+    /// The reference is only valid until the entity is passivated or its shard moves, so
+    /// messages should generally be sent with [`ShardRegion::ask`] and [`ShardRegion::tell`].
+    pub async fn entity(&self, entity_id: &str) -> Result<RemoteActorRef<A>, RemoteSendError> {
+        let region = &self.inner;
+        let owner = self.owner_of(self.shard_of(entity_id)).ok_or(
+            RemoteSendError::ShardRegionUnavailable {
+                actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+            },
+        )?;
+        if &owner == region.swarm.local_peer_id() {
+            let actor_ref = region.resolve_local(entity_id).await;
+            return Ok(RemoteActorRef::new(
+                actor_ref.id().with_local_peer_id(owner),
+                region.swarm.sender().clone(),
+            ));
+        }
+
+        if let Some(actor_ref) = region.cached(entity_id, owner) {
+            return Ok(actor_ref);
+        }
+        let actor_ref = region
+            .swarm
+            .resolve_entity::<A>(owner, entity_id.to_string())
+            .await?;
+        region.resolved.lock().unwrap().insert(
+            entity_id.to_string(),
+            Resolved {
+                actor_ref: actor_ref.clone(),
+                resolved_at: Instant::now(),
+            },
+        );
+
+        Ok(actor_ref)
+    }
+
+    /// Sends a message to an entity and waits for a reply.
+    ///
+    /// If the entity was passivated or its node can't be reached, it's resolved again and the
+    /// message is retried once, so the message may be processed more than once.
+    pub async fn ask<M>(
+        &self,
+        entity_id: &str,
+        msg: &M,
+    ) -> Result<<A::Reply as Reply>::Ok, RemoteSendError<<A::Reply as Reply>::Error>>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: serde::Serialize + Send + Sync + 'static,
+        <A::Reply as Reply>::Ok: serde::de::DeserializeOwned,
+        <A::Reply as Reply>::Error: serde::de::DeserializeOwned,
+    {
+        self.retry(
+            entity_id,
+            |actor_ref| async move { actor_ref.ask(msg).await },
+        )
+        .await
+    }
+
+    /// Sends a message to an entity and waits for delivery acknowledgment.
+    ///
+    /// If the entity was passivated or its node can't be reached, it's resolved again and the
+    /// message is retried once.
+    pub async fn tell<M>(&self, entity_id: &str, msg: &M) -> Result<(), RemoteSendError>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: serde::Serialize + Send + 'static,
+    {
+        self.retry(entity_id, |actor_ref| async move {
+            actor_ref.tell(msg).send_ack().await
+        })
+        .await
+    }
+
+    async fn retry<F, Fut, T, E>(&self, entity_id: &str, f: F) -> Result<T, RemoteSendError<E>>
+    where
+        F: FnMut(RemoteActorRef<A>) -> Fut,
+        Fut: Future<Output = Result<T, RemoteSendError<E>>>,
+    {
+        // Entities are resolved again immediately, since a passivated entity is respawned when
+        // it's next resolved
+        let policy = RetryPolicy {
+            max_retries: 1,
+            interval: Duration::ZERO,
+        };
+        send_with_retry(
+            policy,
+            || self.entity(entity_id),
+            |actor_id| self.inner.invalidate(entity_id, actor_id),
+            f,
+        )
+        .await
+    }
+}
+
+impl<A: Actor + RemoteActor> Region<A> {
+    /// Returns the local entity, spawning it if it isn't running.
+    async fn resolve_local(&self, entity_id: &str) -> ActorRef<A> {
+        let mut entities = self.entities.lock().await;
+        if let Some(entity) = entities.get_mut(entity_id)
+            && entity.actor_ref.is_alive()
+        {
+            entity.last_active = Instant::now();
+            return entity.actor_ref.clone();
+        }
+
+        // Register the entity before it starts, so an entity which stops immediately still
+        // unregisters itself
+        let prepared_actor = A::prepare();
+        let actor_ref = prepared_actor.actor_ref().clone();
+        REMOTE_REGISTRY.lock().unwrap().insert(
+            actor_ref.id(),
+            RemoteRegistryActorRef::new(actor_ref.clone(), None),
+        );
+        prepared_actor.spawn((self.entity_args)(entity_id));
+        entities.insert(
+            entity_id.to_string(),
+            Entity {
+                actor_ref: actor_ref.clone(),
+                last_active: Instant::now(),
+            },
+        );
+
+        actor_ref
+    }
+
+    /// Returns a previously resolved entity on `owner`, if it hasn't expired.
+    fn cached(&self, entity_id: &str, owner: PeerId) -> Option<RemoteActorRef<A>> {
+        let resolved = self.resolved.lock().unwrap();
+        let entry = resolved.get(entity_id)?;
+        let fresh = self
+            .resolved_ttl()
+            .is_none_or(|ttl| entry.resolved_at.elapsed() < ttl);
+        (fresh && entry.actor_ref.id().peer_id() == Some(&owner)).then(|| entry.actor_ref.clone())
+    }
+
+    /// Forgets a resolved entity, if it's still the given actor.
+    fn invalidate(&self, entity_id: &str, actor_id: ActorId) {
+        let mut resolved = self.resolved.lock().unwrap();
+        if resolved
+            .get(entity_id)
+            .is_some_and(|entry| entry.actor_ref.id() == actor_id)
+        {
+            resolved.remove(entity_id);
+        }
+    }
+
+    /// How long a resolved remote entity is used before being resolved again.
+    ///
+    /// Resolving an entity marks it as active on its node, so entities are resolved again well
+    /// within the passivation timeout while messages are being sent to them.
+    fn resolved_ttl(&self) -> Option<Duration> {
+        self.config.passivation_timeout.map(|timeout| timeout / 2)
+    }
+
+    fn is_local_owner(&self, entity_id: &str) -> bool {
+        owner_of(
+            &self.owners.lock().unwrap(),
+            self.config.shard_of(entity_id),
+        )
+        .as_ref()
+            == Some(self.swarm.local_peer_id())
+    }
+
+    /// Stops local entities whose shards are now owned by another member, and forgets resolved
+    /// entities whose shards have moved.
+    async fn rebalance(&self) {
+        let owners = self.owners.lock().unwrap().clone();
+        self.resolved.lock().unwrap().retain(|entity_id, entry| {
+            let owner = owner_of(&owners, self.config.shard_of(entity_id));
+            entry.actor_ref.id().peer_id() == owner.as_ref()
+        });
+        self.stop_entities(|entity_id, _| !self.is_local_owner(entity_id))
+            .await;
+    }
+
+    /// Stops local entities which have been idle for the passivation timeout, or whose shards
+    /// are owned by another member.
+    async fn passivate_idle(&self) {
+        let Some(timeout) = self.config.passivation_timeout else {
+            return;
+        };
+        if let Some(ttl) = self.resolved_ttl() {
+            self.resolved
+                .lock()
+                .unwrap()
+                .retain(|_, entry| entry.resolved_at.elapsed() < ttl);
+        }
+        self.stop_entities(|entity_id, entity| {
+            entity.last_active.elapsed() >= timeout || !self.is_local_owner(entity_id)
+        })
+        .await;
+    }
+
+    async fn stop_entities(&self, mut f: impl FnMut(&str, &Entity<A>) -> bool) {
+        let mut entities = self.entities.lock().await;
+        entities.retain(|entity_id, entity| {
+            if !entity.actor_ref.is_alive() {
+                return false;
+            }
+            if !f(entity_id, entity) {
+                return true;
+            }
+            let actor_ref = entity.actor_ref.clone();
+            tokio::spawn(async move {
+                let _ = actor_ref.stop_gracefully().await;
+            });
+            false
+        });
+    }
+}
+
+impl<A: Actor + RemoteActor> RegionHost for Region<A> {
+    fn resolve(self: Arc<Self>, entity_id: String) -> BoxFuture<'static, ActorId> {
+        Box::pin(async move { self.resolve_local(&entity_id).await.id() })
+    }
+}
+
+/// Resolves an entity hosted by the local node in the region started in the swarm with
+/// `local_peer_id`, spawning it if it isn't running.
+pub(super) async fn resolve_local_entity(
+    local_peer_id: PeerId,
+    actor_remote_id: Cow<'static, str>,
+    entity_id: String,
+) -> Result<ActorId, RemoteSendError> {
+    let host = REGIONS
+        .lock()
+        .unwrap()
+        .get(&(local_peer_id, &*actor_remote_id))
+        .map(|entry| entry.host.clone());
+    match host {
+        Some(host) => Ok(host.resolve(entity_id).await),
+        None => Err(RemoteSendError::ShardRegionUnavailable { actor_remote_id }),
+    }
+}
+
+/// Returns the members eligible to own shards.
+fn owners<'a>(config: &Config, members: impl Iterator<Item = &'a Member>) -> Vec<PeerId> {
+    let mut owners: Vec<_> = members
+        .filter(|member| member.status == MemberStatus::Up)
+        .filter(|member| {
+            config
+                .role
+                .as_ref()
+                .is_none_or(|role| member.has_role(role))
+        })
+        .map(|member| member.peer_id)
+        .collect();
+    owners.sort();
+    owners
+}
+
+/// Selects the owner of a shard with rendezvous hashing, so a change in members only moves the
+/// shards owned by the members which changed.
+fn owner_of(owners: &[PeerId], shard: ShardId) -> Option<PeerId> {
+    owners.iter().copied().max_by_key(|peer_id| {
+        let mut bytes = peer_id.to_bytes();
+        bytes.extend_from_slice(&shard.to_be_bytes());
+        mix(const_fnv1a_hash::fnv1a_hash_64(&bytes, None))
+    })
+}
+
+/// Finalizes a hash so that every bit depends on every input byte.
+///
+/// FNV-1a hashes of peer IDs followed by the same shard keep the order of the peer ID hashes, so
+/// without mixing one owner would win every shard.
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+    hash
+}
+
+impl<A: Actor> Clone for ShardRegion<A> {
+    fn clone(&self) -> Self {
+        ShardRegion {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A: Actor> fmt::Debug for ShardRegion<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardRegion")
+            .field("config", &self.inner.config)
+            .field("owners", &self.inner.owners.lock().unwrap())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+
+    use tokio::time;
+
+    use crate::remote::testing::{
+        Cluster, POLL_INTERVAL,
+        fixtures::{Get, Spawned},
+    };
+
+    use super::*;
+
+    #[test]
+    fn shard_of_is_stable_and_in_range() {
+        let config = Config::default().with_shards(16);
+        for i in 0..1000 {
+            let entity_id = format!("entity-{i}");
+            let shard = config.shard_of(&entity_id);
+            assert!(shard < 16);
+            assert_eq!(shard, config.shard_of(&entity_id));
+        }
+
+        let config = config.with_shard_fn(|entity_id, _| entity_id.len() as ShardId);
+        assert_eq!(config.shard_of("abc"), 3);
+        assert_eq!(config.shard_of("0123456789abcdefgh"), 2);
+    }
+
+    #[test]
+    fn removing_an_owner_only_moves_its_shards() {
+        let owners: Vec<_> = (0..5).map(|_| PeerId::random()).collect();
+        let removed = owners[2];
+        let remaining: Vec<_> = owners.iter().copied().filter(|p| *p != removed).collect();
+
+        for shard in 0..1000 {
+            let before = owner_of(&owners, shard).unwrap();
+            let after = owner_of(&remaining, shard).unwrap();
+            if before != removed {
+                assert_eq!(before, after);
+            }
+        }
+        assert_eq!(owner_of(&[], 0), None);
+    }
+
+    #[test]
+    fn shards_are_spread_across_owners() {
+        let owners: Vec<_> = (0..3).map(|_| PeerId::random()).collect();
+
+        for owner in &owners {
+            let owned = (0..300)
+                .filter(|shard| owner_of(&owners, *shard) == Some(*owner))
+                .count();
+            assert!(owned >= 50, "owner has {owned} of 300 shards");
+        }
+    }
+
+    #[test]
+    fn owners_are_up_members_with_the_role() {
+        let member = |status, roles: &[&str]| Member {
+            peer_id: PeerId::random(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            status,
+            up_since: 0,
+        };
+        let members = [
+            member(MemberStatus::Up, &["worker"]),
+            member(MemberStatus::Up, &[]),
+            member(MemberStatus::Joining, &["worker"]),
+            member(MemberStatus::Leaving, &["worker"]),
+        ];
+
+        let config = Config::default();
+        assert_eq!(owners(&config, members.iter()).len(), 2);
+        let config = config.with_role("worker");
+        assert_eq!(owners(&config, members.iter()), [members[0].peer_id]);
+    }
+
+    #[tokio::test]
+    async fn regions_are_started_per_swarm() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let mut regions = Vec::new();
+        for i in 0..2 {
+            let value = 10 + i as u32;
+            let region = ShardRegion::<Spawned>::start_in(
+                cluster.node(i).swarm(),
+                Config::default().with_shards(32),
+                move |_| Spawned { value },
+            )
+            .await?;
+            regions.push(region);
+        }
+        assert!(ShardRegion::<Spawned>::get().is_none());
+
+        let peer_ids: Vec<_> = (0..2).map(|i| cluster.node(i).peer_id()).collect();
+        time::timeout(Duration::from_secs(5), async {
+            while !(0..32).all(|shard| {
+                regions[0].owner_of(shard).is_some()
+                    && regions[1].owner_of(shard) == regions[0].owner_of(shard)
+            }) || !peer_ids
+                .iter()
+                .all(|peer_id| (0..32).any(|shard| regions[0].owner_of(shard) == Some(*peer_id)))
+            {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+
+        // Entities are spawned by the region of the swarm owning their shard
+        for entity in 0..16 {
+            let entity_id = format!("entity-{entity}");
+            let owner = regions[0].owner_of(regions[0].shard_of(&entity_id));
+            let expected = if owner == Some(peer_ids[0]) { 10 } else { 11 };
+            for region in &regions {
+                assert_eq!(region.ask(&entity_id, &Get).await?, expected);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,721 @@
+//! Cluster singleton actors, running on exactly one node in the cluster.
+//!
+//! A [`ClusterSingleton`] is started on every node for a singleton actor type. The singleton is
+//! hosted by the oldest member which is [`Up`](super::membership::MemberStatus::Up) and has the
+//! configured role. When that member leaves, or its connection closes and it's marked
+//! [`Down`](super::membership::MemberStatus::Down), the next oldest member spawns the singleton.
+//! If the singleton stops while its node is still the host, it's spawned again.
+//!
+//! Messages are sent to the singleton through a [`SingletonProxy`], which routes them to
+//! whichever node currently hosts it, retrying while the singleton is being handed over.
+//!
+//! Members which haven't yet seen a membership change may briefly disagree on the host, so
+//! during a handover the old and new instance can overlap for a short time.
+//!
+//! The host is chosen from the members each node is directly connected to, so every node must be
+//! connected to every other node. In a partially connected cluster, nodes disagree on the oldest
+//! member and the singleton runs on several nodes. See the [membership docs](super::membership)
+//! for details.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kameo::prelude::*;
+//! use kameo::remote::singleton::{self, ClusterSingleton};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Actor, RemoteActor, Default)]
+//! struct Scheduler {
+//!     jobs: Vec<String>,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Schedule(String);
+//!
+//! #[kameo::remote_message("schedule")]
+//! impl Message<Schedule> for Scheduler {
+//!     type Reply = ();
+//!
+//!     async fn handle(&mut self, Schedule(job): Schedule, _ctx: &mut Context<Self, Self::Reply>) {
+//!         self.jobs.push(job);
+//!     }
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let singleton =
+//!     ClusterSingleton::<Scheduler>::start(singleton::Config::default(), Scheduler::default)
+//!         .await?;
+//!
+//! singleton.proxy().tell(&Schedule("backup".to_string())).await?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! # });
+//! ```
+
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashMap,
+    fmt, mem,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{Future, future::BoxFuture};
+use libp2p::PeerId;
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    Actor, Reply,
+    actor::{ActorId, ActorRef, RemoteActorRef, Spawn},
+    error::RemoteSendError,
+    message::Message,
+};
+
+use super::{
+    ActorSwarm, REMOTE_REGISTRY, RemoteActor, RemoteMessage, RemoteRegistryActorRef, RetryPolicy,
+    SwarmHandle,
+    membership::{Member, MemberStatus},
+    send_with_retry,
+};
+
+/// How often the host checks the singleton is still running.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long the host waits before starting the singleton again after it failed to start twice in
+/// a row, doubling with each further failure.
+const MIN_START_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The longest the host waits before starting a singleton which keeps failing to start.
+const MAX_START_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The singletons started on this node, by the local peer ID of the swarm they were started in
+/// and the remote ID of their actor.
+static SINGLETONS: LazyLock<Mutex<HashMap<(PeerId, &'static str), SingletonEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The configuration for a [`ClusterSingleton`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    role: Option<String>,
+    max_retries: usize,
+    retry_interval: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            role: None,
+            max_retries: 10,
+            retry_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl Config {
+    /// Restricts hosting the singleton to members with the role.
+    ///
+    /// Nodes without the role can still send messages to the singleton through a proxy.
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Sets how many times a proxy retries a send which failed because the singleton couldn't
+    /// be reached, such as while it's being handed over.
+    ///
+    /// Defaults to 10.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets how long a proxy waits before retrying a failed send.
+    ///
+    /// Defaults to 500 milliseconds.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+}
+
+/// Manages a singleton actor of type `A`, spawning it when the local node becomes its host.
+///
+/// See the [module level docs](self) for how the host is chosen. Clones share the same manager.
+pub struct ClusterSingleton<A: Actor> {
+    inner: Arc<Manager<A>>,
+}
+
+/// A handle routing messages to a cluster singleton, wherever it currently runs.
+///
+/// Created with [`ClusterSingleton::proxy`].
+pub struct SingletonProxy<A: Actor> {
+    inner: Arc<Manager<A>>,
+}
+
+struct Manager<A: Actor> {
+    config: Config,
+    swarm: ActorSwarm,
+    args: ArgsFn<A>,
+    /// The member currently hosting the singleton.
+    host: Mutex<Option<PeerId>>,
+    /// The singleton, if it's hosted by this node.
+    instance: tokio::sync::Mutex<Option<ActorRef<A>>>,
+    /// Failed starts of the singleton, updated while `instance` is locked.
+    start_failures: Mutex<StartFailures>,
+    /// The singleton last resolved by proxies.
+    resolved: Mutex<Option<RemoteActorRef<A>>>,
+}
+
+type ArgsFn<A> = Box<dyn Fn() -> <A as Actor>::Args + Send + Sync>;
+
+/// Consecutive failed starts of a singleton, and when it may next be started.
+#[derive(Default)]
+struct StartFailures {
+    count: u32,
+    retry_at: Option<Instant>,
+}
+
+impl StartFailures {
+    /// Records whether the last instance failed to start, backing off after repeated failures.
+    fn record(&mut self, failed: bool) {
+        if !failed {
+            *self = StartFailures::default();
+            return;
+        }
+
+        self.count += 1;
+        // The first failure is retried immediately, as it may have been caused by the previous
+        // host still shutting down
+        self.retry_at = self.count.checked_sub(2).map(|retries| {
+            let backoff = MIN_START_BACKOFF
+                .saturating_mul(2u32.saturating_pow(retries))
+                .min(MAX_START_BACKOFF);
+            Instant::now() + backoff
+        });
+    }
+
+    fn is_backing_off(&self) -> bool {
+        self.retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+    }
+}
+
+struct SingletonEntry {
+    host: Arc<dyn SingletonHost>,
+    manager: Arc<dyn Any + Send + Sync>,
+}
+
+/// Hosts a singleton for resolution requests from other nodes.
+trait SingletonHost: Send + Sync {
+    fn resolve(self: Arc<Self>) -> BoxFuture<'static, Option<ActorId>>;
+}
+
+impl<A> ClusterSingleton<A>
+where
+    A: Actor + RemoteActor,
+{
+    /// Starts managing the singleton for actors of type `A`, building its args with `args`
+    /// whenever it's spawned on this node.
+    ///
+    /// Every member able to host the singleton must start it. If the singleton has already been
+    /// started on this node, it's returned instead, and `config` and `args` are ignored.
+    pub async fn start<F>(config: Config, args: F) -> Result<Self, RemoteSendError>
+    where
+        F: Fn() -> A::Args + Send + Sync + 'static,
+    {
+        let swarm = SwarmHandle::global().ok_or(RemoteSendError::SwarmNotBootstrapped)?;
+        Self::start_in(&swarm, config, args).await
+    }
+
+    /// Starts managing the singleton for actors of type `A` within a specific actor swarm.
+    ///
+    /// This behaves like [`ClusterSingleton::start`], but follows the members of the swarm of the
+    /// handle instead of the global actor swarm. Each swarm in the process has its own singleton.
+    pub async fn start_in<F>(
+        swarm: &SwarmHandle,
+        config: Config,
+        args: F,
+    ) -> Result<Self, RemoteSendError>
+    where
+        F: Fn() -> A::Args + Send + Sync + 'static,
+    {
+        if let Some(singleton) = Self::get_in(swarm) {
+            return Ok(singleton);
+        }
+
+        let swarm = swarm.actor_swarm().clone();
+        let key = (*swarm.local_peer_id(), A::REMOTE_ID);
+        // Subscribing first ensures no change is missed between the two
+        let mut events = swarm.subscribe_members();
+        let members = swarm.members().await;
+
+        let manager = Arc::new(Manager {
+            host: Mutex::new(host(&config, members.iter())),
+            config,
+            swarm,
+            args: Box::new(args),
+            instance: tokio::sync::Mutex::new(None),
+            start_failures: Mutex::new(StartFailures::default()),
+            resolved: Mutex::new(None),
+        });
+
+        {
+            let mut singletons = SINGLETONS.lock().unwrap();
+            // Another task may have started the singleton while members were being fetched
+            if let Some(entry) = singletons.get(&key) {
+                let inner = entry.manager.clone().downcast().unwrap();
+                return Ok(ClusterSingleton { inner });
+            }
+            singletons.insert(
+                key,
+                SingletonEntry {
+                    host: manager.clone(),
+                    manager: manager.clone(),
+                },
+            );
+        }
+
+        let mut members: HashMap<_, _> = members
+            .into_iter()
+            .map(|member| (member.peer_id, member))
+            .collect();
+        let task_manager = manager.clone();
+        tokio::spawn(async move {
+            let manager = task_manager;
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    event = events.recv() => {
+                        let Some(event) = event else {
+                            break;
+                        };
+                        let member = event.member();
+                        match member.status {
+                            MemberStatus::Removed => members.remove(&member.peer_id),
+                            _ => members.insert(member.peer_id, member.clone()),
+                        };
+                        let host = host(&manager.config, members.values());
+                        let previous = mem::replace(&mut *manager.host.lock().unwrap(), host);
+                        if previous != host {
+                            *manager.resolved.lock().unwrap() = None;
+                            manager.ensure_running().await;
+                        }
+                    }
+                    _ = interval.tick() => {
+                        manager.ensure_running().await;
+                    }
+                }
+            }
+        });
+
+        Ok(ClusterSingleton { inner: manager })
+    }
+
+    /// Returns the singleton for actors of type `A`, if it has been started on this node.
+    pub fn get() -> Option<Self> {
+        Self::get_in(&SwarmHandle::global()?)
+    }
+
+    /// Returns the singleton for actors of type `A`, if it has been started within a specific
+    /// actor swarm.
+    pub fn get_in(swarm: &SwarmHandle) -> Option<Self> {
+        let singletons = SINGLETONS.lock().unwrap();
+        let inner = singletons
+            .get(&(swarm.peer_id(), A::REMOTE_ID))?
+            .manager
+            .clone()
+            .downcast()
+            .ok()?;
+        Some(ClusterSingleton { inner })
+    }
+
+    /// Returns a proxy for sending messages to the singleton.
+    pub fn proxy(&self) -> SingletonProxy<A> {
+        SingletonProxy {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Returns the member currently hosting the singleton, or `None` if no member can host it.
+    pub fn host(&self) -> Option<PeerId> {
+        *self.inner.host.lock().unwrap()
+    }
+
+    /// Returns whether the singleton is currently hosted by this node.
+    pub fn is_local_host(&self) -> bool {
+        self.inner.is_local_host()
+    }
+
+    /// Returns the singleton, if it's running on this node.
+    pub async fn local_instance(&self) -> Option<ActorRef<A>> {
+        self.inner
+            .instance
+            .lock()
+            .await
+            .clone()
+            .filter(ActorRef::is_alive)
+    }
+}
+
+impl<A> SingletonProxy<A>
+where
+    A: Actor + RemoteActor,
+{
+    /// Returns a reference to the singleton on the node currently hosting it.
+    ///
+    /// The reference is only valid until the singleton is handed over to another node, so
+    /// messages should generally be sent with [`SingletonProxy::ask`] and
+    /// [`SingletonProxy::tell`].
+    pub async fn actor_ref(&self) -> Result<RemoteActorRef<A>, RemoteSendError> {
+        let manager = &self.inner;
+        let host = manager
+            .host
+            .lock()
+            .unwrap()
+            .ok_or(RemoteSendError::SingletonUnavailable {
+                actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+            })?;
+        if let Some(actor_ref) = manager.resolved.lock().unwrap().as_ref()
+            && actor_ref.id().peer_id() == Some(&host)
+        {
+            return Ok(actor_ref.clone());
+        }
+
+        let actor_ref =
+            if &host == manager.swarm.local_peer_id() {
+                let actor_ref = manager.ensure_running().await.ok_or(
+                    RemoteSendError::SingletonUnavailable {
+                        actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+                    },
+                )?;
+                RemoteActorRef::new(
+                    actor_ref
+                        .id()
+                        .with_local_peer_id(*manager.swarm.local_peer_id()),
+                    manager.swarm.sender().clone(),
+                )
+            } else {
+                manager.swarm.resolve_singleton::<A>(host).await?
+            };
+        *manager.resolved.lock().unwrap() = Some(actor_ref.clone());
+
+        Ok(actor_ref)
+    }
+
+    /// Sends a message to the singleton and waits for a reply.
+    ///
+    /// Sends which fail because the singleton can't be reached are retried, so the message
+    /// may be processed more than once.
+    pub async fn ask<M>(
+        &self,
+        msg: &M,
+    ) -> Result<<A::Reply as Reply>::Ok, RemoteSendError<<A::Reply as Reply>::Error>>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: serde::Serialize + Send + Sync + 'static,
+        <A::Reply as Reply>::Ok: serde::de::DeserializeOwned,
+        <A::Reply as Reply>::Error: serde::de::DeserializeOwned,
+    {
+        self.retry(|actor_ref| async move { actor_ref.ask(msg).await })
+            .await
+    }
+
+    /// Sends a message to the singleton and waits for delivery acknowledgment.
+    ///
+    /// Sends which fail because the singleton can't be reached are retried.
+    pub async fn tell<M>(&self, msg: &M) -> Result<(), RemoteSendError>
+    where
+        A: Message<M> + RemoteMessage<M>,
+        M: serde::Serialize + Send + 'static,
+    {
+        self.retry(|actor_ref| async move { actor_ref.tell(msg).send_ack().await })
+            .await
+    }
+
+    async fn retry<F, Fut, T, E>(&self, f: F) -> Result<T, RemoteSendError<E>>
+    where
+        F: FnMut(RemoteActorRef<A>) -> Fut,
+        Fut: Future<Output = Result<T, RemoteSendError<E>>>,
+    {
+        let policy = RetryPolicy {
+            max_retries: self.inner.config.max_retries,
+            interval: self.inner.config.retry_interval,
+        };
+        send_with_retry(
+            policy,
+            || self.actor_ref(),
+            |actor_id| self.inner.invalidate(actor_id),
+            f,
+        )
+        .await
+    }
+}
+
+impl<A: Actor + RemoteActor> Manager<A> {
+    fn is_local_host(&self) -> bool {
+        self.host.lock().unwrap().as_ref() == Some(self.swarm.local_peer_id())
+    }
+
+    /// Spawns the singleton if this node is the host and it isn't running, or stops it if
+    /// another node is now the host.
+    async fn ensure_running(&self) -> Option<ActorRef<A>> {
+        let mut instance = self.instance.lock().await;
+        if !self.is_local_host() {
+            if let Some(actor_ref) = instance.take() {
+                let _ = actor_ref.stop_gracefully().await;
+            }
+            *self.start_failures.lock().unwrap() = StartFailures::default();
+            return None;
+        }
+
+        if let Some(actor_ref) = instance.take() {
+            if actor_ref.is_alive() {
+                *instance = Some(actor_ref.clone());
+                return Some(actor_ref);
+            }
+
+            // An actor killed before it finished starting has no startup result
+            let failed = actor_ref
+                .startup_result
+                .get()
+                .is_some_and(|res| res.is_err());
+            self.start_failures.lock().unwrap().record(failed);
+        }
+
+        if self.start_failures.lock().unwrap().is_backing_off() {
+            return None;
+        }
+
+        // Register the singleton before it starts, so one which stops immediately still
+        // unregisters itself
+        let prepared_actor = A::prepare();
+        let actor_ref = prepared_actor.actor_ref().clone();
+        REMOTE_REGISTRY.lock().unwrap().insert(
+            actor_ref.id(),
+            RemoteRegistryActorRef::new(actor_ref.clone(), None),
+        );
+        prepared_actor.spawn((self.args)());
+        *instance = Some(actor_ref.clone());
+
+        Some(actor_ref)
+    }
+
+    /// Forgets the resolved singleton, if it's still the given actor.
+    fn invalidate(&self, actor_id: ActorId) {
+        let mut resolved = self.resolved.lock().unwrap();
+        if resolved
+            .as_ref()
+            .is_some_and(|actor_ref| actor_ref.id() == actor_id)
+        {
+            *resolved = None;
+        }
+    }
+}
+
+impl<A: Actor + RemoteActor> SingletonHost for Manager<A> {
+    fn resolve(self: Arc<Self>) -> BoxFuture<'static, Option<ActorId>> {
+        Box::pin(async move { self.ensure_running().await.map(|actor_ref| actor_ref.id()) })
+    }
+}
+
+/// Resolves a singleton hosted by the local node, started in the swarm with `local_peer_id`.
+pub(super) async fn resolve_local_singleton(
+    local_peer_id: PeerId,
+    actor_remote_id: Cow<'static, str>,
+) -> Result<ActorId, RemoteSendError> {
+    let host = SINGLETONS
+        .lock()
+        .unwrap()
+        .get(&(local_peer_id, &*actor_remote_id))
+        .map(|entry| entry.host.clone());
+    match host {
+        Some(host) => host
+            .resolve()
+            .await
+            .ok_or(RemoteSendError::SingletonUnavailable { actor_remote_id }),
+        None => Err(RemoteSendError::SingletonUnavailable { actor_remote_id }),
+    }
+}
+
+/// Returns the oldest member eligible to host the singleton.
+fn host<'a>(config: &Config, members: impl Iterator<Item = &'a Member>) -> Option<PeerId> {
+    members
+        .filter(|member| member.status == MemberStatus::Up)
+        .filter(|member| {
+            config
+                .role
+                .as_ref()
+                .is_none_or(|role| member.has_role(role))
+        })
+        .min_by_key(|member| (member.up_since, member.peer_id))
+        .map(|member| member.peer_id)
+}
+
+impl<A: Actor> Clone for ClusterSingleton<A> {
+    fn clone(&self) -> Self {
+        ClusterSingleton {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A: Actor> Clone for SingletonProxy<A> {
+    fn clone(&self) -> Self {
+        SingletonProxy {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A: Actor> fmt::Debug for ClusterSingleton<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClusterSingleton")
+            .field("config", &self.inner.config)
+            .field("host", &self.inner.host.lock().unwrap())
+            .finish()
+    }
+}
+
+impl<A: Actor> fmt::Debug for SingletonProxy<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingletonProxy")
+            .field("host", &self.inner.host.lock().unwrap())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use tokio::time;
+
+    use crate::remote::testing::{
+        Cluster, POLL_INTERVAL,
+        fixtures::{Get, Spawned},
+        register_remote_actor,
+    };
+
+    use super::*;
+
+    struct FailsToStart {
+        starts: Arc<AtomicUsize>,
+    }
+
+    impl Actor for FailsToStart {
+        type Args = Self;
+        type Error = String;
+
+        async fn on_start(state: Self::Args, _actor_ref: ActorRef<Self>) -> Result<Self, String> {
+            state.starts.fetch_add(1, Ordering::SeqCst);
+            Err("failed to start".to_string())
+        }
+    }
+
+    register_remote_actor!(FailsToStart);
+
+    fn member(status: MemberStatus, roles: &[&str], up_since: u64) -> Member {
+        Member {
+            peer_id: PeerId::random(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            status,
+            up_since,
+        }
+    }
+
+    #[test]
+    fn host_is_oldest_up_member_with_the_role() {
+        let members = [
+            member(MemberStatus::Leaving, &["scheduler"], 0),
+            member(MemberStatus::Up, &[], 1),
+            member(MemberStatus::Up, &["scheduler"], 2),
+            member(MemberStatus::Up, &["scheduler"], 3),
+            member(MemberStatus::Down, &["scheduler"], 0),
+        ];
+
+        let config = Config::default();
+        assert_eq!(host(&config, members.iter()), Some(members[1].peer_id));
+        let config = config.with_role("scheduler");
+        assert_eq!(host(&config, members.iter()), Some(members[2].peer_id));
+        let config = Config::default().with_role("other");
+        assert_eq!(host(&config, members.iter()), None);
+    }
+
+    #[tokio::test]
+    async fn singletons_are_started_per_swarm() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let mut singletons = Vec::new();
+        for i in 0..2 {
+            let value = 20 + i as u32;
+            let singleton = ClusterSingleton::<Spawned>::start_in(
+                cluster.node(i).swarm(),
+                Config::default(),
+                move || Spawned { value },
+            )
+            .await?;
+            singletons.push(singleton);
+        }
+        assert!(ClusterSingleton::<Spawned>::get().is_none());
+
+        time::timeout(Duration::from_secs(5), async {
+            while singletons[0].host().is_none() || singletons[0].host() != singletons[1].host() {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+
+        // Both proxies reach the singleton spawned by the host's swarm
+        let host = singletons[0].host().unwrap();
+        let expected = if host == cluster.node(0).peer_id() {
+            20
+        } else {
+            21
+        };
+        assert_eq!(singletons[0].is_local_host(), expected == 20);
+        assert_eq!(singletons[1].is_local_host(), expected == 21);
+        for singleton in &singletons {
+            assert_eq!(singleton.proxy().ask(&Get).await?, expected);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backs_off_after_repeated_start_failures() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(1).await?;
+
+        let starts = Arc::new(AtomicUsize::new(0));
+        let singleton = ClusterSingleton::<FailsToStart>::start_in(
+            cluster.node(0).swarm(),
+            Config::default(),
+            {
+                let starts = starts.clone();
+                move || FailsToStart {
+                    starts: starts.clone(),
+                }
+            },
+        )
+        .await?;
+        time::timeout(Duration::from_secs(5), async {
+            while !singleton.is_local_host() {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await?;
+
+        // Without backing off, every resolve would start the singleton again
+        for _ in 0..20 {
+            singleton.inner.ensure_running().await;
+            time::sleep(POLL_INTERVAL).await;
+        }
+        assert!(singleton.inner.ensure_running().await.is_none());
+        assert!(starts.load(Ordering::SeqCst) <= 3);
+
+        Ok(())
+    }
+}
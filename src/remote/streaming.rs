@@ -0,0 +1,799 @@
+//! Streaming of items to remote actors.
+//!
+//! [`RemoteActorRef::attach_stream`] forwards each item of a local stream to a remote actor as a
+//! [`StreamMessage`], the same way [`ActorRef::attach_stream`] does for local actors.
+//!
+//! Rather than sending one tell request per item, streams are sent over a dedicated protocol
+//! (`/kameo/streaming/1.0.0`). Items which are ready at the same time are grouped into batches of
+//! up to [`Config::with_batch_size`] items, and each batch is sent on its own substream.
+//!
+//! # Flow Control
+//!
+//! The receiving node delivers the items of a stream to the actor's mailbox one at a time and in
+//! order, acknowledging a batch only once all of its items have been delivered. The sender allows
+//! at most [`Config::with_window`] unacknowledged batches per stream, and stops pulling items from
+//! the stream until an acknowledgement arrives. A slow actor with a bounded mailbox therefore
+//! applies backpressure all the way to the stream on the sending node.
+//!
+//! [`RemoteActorRef::attach_stream`]: crate::actor::RemoteActorRef::attach_stream
+//! [`ActorRef::attach_stream`]: crate::actor::ActorRef::attach_stream
+//! [`StreamMessage`]: crate::message::StreamMessage
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task,
+    time::Duration,
+};
+
+use futures::{FutureExt, StreamExt, stream::FuturesOrdered};
+use libp2p::{
+    PeerId, StreamProtocol, request_response,
+    swarm::{
+        ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler,
+        THandlerInEvent, THandlerOutEvent, ToSwarm,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::oneshot, task::JoinSet};
+
+use crate::{actor::ActorId, error::RemoteSendError};
+
+use super::{
+    RemoteSender, SwarmCommand, SwarmSender, acl::AccessPolicy, codec::CodecId, messaging,
+    swarm::in_swarm,
+};
+
+const PROTO_NAME: StreamProtocol = StreamProtocol::new("/kameo/streaming/1.0.0");
+
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(0);
+
+type StreamResult = Result<(), RemoteSendError>;
+type Delivered = ((PeerId, u64), ActorId, bool, ReplyChannel, StreamResult);
+
+/// A batch of encoded stream items sent to a remote actor.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamRequest {
+    stream_id: u64,
+    seq: u64,
+    actor_id: ActorId,
+    actor_remote_id: Cow<'static, str>,
+    message_remote_id: Cow<'static, str>,
+    version: u32,
+    codec: CodecId,
+    payloads: Vec<Vec<u8>>,
+    last: bool,
+}
+
+/// The acknowledgement of a [`StreamRequest`], sent once its items have been delivered.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamResponse(StreamResult);
+
+/// Events emitted by the streaming behaviour.
+#[derive(Debug)]
+pub enum Event {
+    /// A stream was attached to a local actor.
+    Opened {
+        /// The peer which attached the stream.
+        peer: PeerId,
+        /// The ID of the stream, unique to the peer.
+        stream_id: u64,
+        /// The actor receiving the stream.
+        actor_id: ActorId,
+    },
+    /// A stream attached to a local actor has finished, or failed to be delivered.
+    Closed {
+        /// The peer which attached the stream.
+        peer: PeerId,
+        /// The ID of the stream, unique to the peer.
+        stream_id: u64,
+        /// The actor receiving the stream.
+        actor_id: ActorId,
+        /// The result of delivering the stream.
+        result: StreamResult,
+    },
+    /// An inbound stream batch failed.
+    InboundFailure {
+        /// The peer sending the batch.
+        peer: PeerId,
+        /// The connection used.
+        connection_id: ConnectionId,
+        /// The ID of the failed inbound request.
+        request_id: request_response::InboundRequestId,
+        /// The error that occurred.
+        error: request_response::InboundFailure,
+    },
+}
+
+/// The configuration for a `streaming::Behaviour` protocol.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    request_timeout: Duration,
+    max_concurrent_streams: usize,
+    request_size_maximum: u64,
+    window: usize,
+    batch_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(30),
+            max_concurrent_streams: 100,
+            request_size_maximum: 10 * 1024 * 1024,
+            window: 4,
+            batch_size: 64,
+        }
+    }
+}
+
+impl Config {
+    /// Sets the timeout for a batch to be delivered and acknowledged.
+    pub fn with_request_timeout(mut self, v: Duration) -> Self {
+        self.request_timeout = v;
+        self
+    }
+
+    /// Sets the upper bound for the number of concurrent inbound + outbound substreams.
+    pub fn with_max_concurrent_streams(mut self, num_streams: usize) -> Self {
+        self.max_concurrent_streams = num_streams;
+        self
+    }
+
+    /// Sets the limit for the size of a batch in bytes.
+    pub fn with_request_size_maximum(mut self, bytes: u64) -> Self {
+        self.request_size_maximum = bytes;
+        self
+    }
+
+    /// Sets the maximum number of unacknowledged batches per stream.
+    ///
+    /// Defaults to 4.
+    pub fn with_window(mut self, batches: usize) -> Self {
+        self.window = batches.max(1);
+        self
+    }
+
+    /// Sets the maximum number of items sent in a single batch.
+    ///
+    /// Defaults to 64.
+    pub fn with_batch_size(mut self, items: usize) -> Self {
+        self.batch_size = items.max(1);
+        self
+    }
+
+    /// Returns the maximum number of items sent in a single batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+}
+
+impl From<Config> for request_response::Config {
+    fn from(config: Config) -> Self {
+        request_response::Config::default()
+            .with_request_timeout(config.request_timeout)
+            .with_max_concurrent_streams(config.max_concurrent_streams)
+    }
+}
+
+impl<Req, Resp> From<Config> for request_response::cbor::codec::Codec<Req, Resp> {
+    fn from(config: Config) -> Self {
+        request_response::cbor::codec::Codec::default()
+            .set_request_size_maximum(config.request_size_maximum)
+    }
+}
+
+enum ReplyChannel {
+    Local(oneshot::Sender<StreamResult>),
+    Remote(request_response::ResponseChannel<StreamResponse>),
+}
+
+#[derive(Default)]
+struct InboundStream {
+    next_seq: u64,
+    delivering: bool,
+    failed: bool,
+    pending: BTreeMap<u64, (StreamRequest, ReplyChannel)>,
+}
+
+/// `Behaviour` is a `NetworkBehaviour` that delivers streams of items to actors
+/// on top of the request response protocol.
+#[allow(missing_debug_implementations)]
+pub struct Behaviour {
+    request_response: request_response::cbor::Behaviour<StreamRequest, StreamResponse>,
+    local_peer_id: PeerId,
+    config: Config,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+    requests: HashMap<request_response::OutboundRequestId, oneshot::Sender<StreamResult>>,
+    inbound: HashMap<(PeerId, u64), InboundStream>,
+    events: VecDeque<Event>,
+    join_set: JoinSet<Delivered>,
+    swarm: Option<SwarmSender>,
+}
+
+impl Behaviour {
+    /// Creates a new streaming behaviour.
+    pub fn new(local_peer_id: PeerId, config: Config) -> Self {
+        let request_response = request_response::cbor::Behaviour::with_codec(
+            config.into(),
+            [(PROTO_NAME, request_response::ProtocolSupport::Full)],
+            config.into(),
+        );
+
+        Behaviour {
+            request_response,
+            local_peer_id,
+            config,
+            access_policy: None,
+            requests: HashMap::new(),
+            inbound: HashMap::new(),
+            events: VecDeque::new(),
+            join_set: JoinSet::new(),
+            swarm: None,
+        }
+    }
+
+    pub(super) fn set_access_policy(&mut self, access_policy: Option<Arc<dyn AccessPolicy>>) {
+        self.access_policy = access_policy;
+    }
+
+    /// Sets the swarm actor refs within received items are bound to, instead of the global actor
+    /// swarm.
+    pub(super) fn set_swarm(&mut self, swarm: SwarmSender) {
+        self.swarm = Some(swarm);
+    }
+
+    /// Returns the streaming configuration.
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    pub(super) fn send_batch(
+        &mut self,
+        request: StreamRequest,
+        reply: oneshot::Sender<StreamResult>,
+    ) {
+        let peer_id = *request
+            .actor_id
+            .peer_id()
+            .expect("swarm should be bootstrapped");
+        if peer_id == self.local_peer_id {
+            if let Some(ev) = self.handle_batch(peer_id, request, ReplyChannel::Local(reply)) {
+                self.events.push_back(ev);
+            }
+        } else {
+            let request_id = self.request_response.send_request(&peer_id, request);
+            self.requests.insert(request_id, reply);
+        }
+    }
+
+    fn handle_batch(
+        &mut self,
+        peer: PeerId,
+        request: StreamRequest,
+        channel: ReplyChannel,
+    ) -> Option<Event> {
+        let key = (peer, request.stream_id);
+        let opened = (request.seq == 0).then_some(Event::Opened {
+            peer,
+            stream_id: request.stream_id,
+            actor_id: request.actor_id,
+        });
+        let stream = self.inbound.entry(key).or_default();
+        if stream.failed {
+            // Batches following a failed batch are discarded, the sender has already stopped
+            if request.last {
+                self.inbound.remove(&key);
+            }
+            return None;
+        }
+
+        stream.pending.insert(request.seq, (request, channel));
+        self.deliver_next(key);
+
+        opened
+    }
+
+    fn deliver_next(&mut self, key: (PeerId, u64)) {
+        let Some(stream) = self.inbound.get_mut(&key) else {
+            return;
+        };
+        if stream.delivering {
+            return;
+        }
+        let Some((request, channel)) = stream.pending.remove(&stream.next_seq) else {
+            return;
+        };
+
+        stream.delivering = true;
+        stream.next_seq += 1;
+        let actor_id = request.actor_id;
+        let last = request.last;
+        let sender = RemoteSender::new(key.0, None);
+        let access_policy = self.access_policy.clone();
+        self.join_set.spawn(
+            in_swarm(self.swarm.clone(), deliver(request, sender, access_policy))
+                .map(move |res| (key, actor_id, last, channel, res)),
+        );
+    }
+
+    fn handle_delivered(
+        &mut self,
+        key: (PeerId, u64),
+        actor_id: ActorId,
+        last: bool,
+        channel: ReplyChannel,
+        res: StreamResult,
+    ) -> Option<Event> {
+        let (peer, stream_id) = key;
+        let closed = match &res {
+            Ok(()) if !last => {
+                if let Some(stream) = self.inbound.get_mut(&key) {
+                    stream.delivering = false;
+                }
+                self.deliver_next(key);
+                None
+            }
+            Ok(()) => {
+                self.inbound.remove(&key);
+                Some(Event::Closed {
+                    peer,
+                    stream_id,
+                    actor_id,
+                    result: Ok(()),
+                })
+            }
+            Err(err) => {
+                if last {
+                    self.inbound.remove(&key);
+                } else {
+                    // Keep a tombstone so batches which are still in flight get discarded
+                    self.inbound.insert(
+                        key,
+                        InboundStream {
+                            failed: true,
+                            ..Default::default()
+                        },
+                    );
+                }
+                Some(Event::Closed {
+                    peer,
+                    stream_id,
+                    actor_id,
+                    result: Err(err.clone()),
+                })
+            }
+        };
+        self.send_reply(channel, res);
+
+        closed
+    }
+
+    fn send_reply(&mut self, channel: ReplyChannel, res: StreamResult) {
+        match channel {
+            ReplyChannel::Local(tx) => {
+                let _ = tx.send(res);
+            }
+            ReplyChannel::Remote(channel) => {
+                let _ = self
+                    .request_response
+                    .send_response(channel, StreamResponse(res));
+            }
+        }
+    }
+
+    fn handle_request_response_event(
+        &mut self,
+        ev: request_response::Event<StreamRequest, StreamResponse>,
+    ) -> Option<Event> {
+        match ev {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request, channel, ..
+                } => self.handle_batch(peer, request, ReplyChannel::Remote(channel)),
+                request_response::Message::Response {
+                    request_id,
+                    response: StreamResponse(res),
+                } => {
+                    if let Some(tx) = self.requests.remove(&request_id) {
+                        let _ = tx.send(res);
+                    }
+                    None
+                }
+            },
+            request_response::Event::OutboundFailure {
+                request_id, error, ..
+            } => {
+                if let Some(tx) = self.requests.remove(&request_id) {
+                    let _ = tx.send(Err(error.into()));
+                }
+                None
+            }
+            request_response::Event::InboundFailure {
+                peer,
+                connection_id,
+                request_id,
+                error,
+            } => Some(Event::InboundFailure {
+                peer,
+                connection_id,
+                request_id,
+                error,
+            }),
+            request_response::Event::ResponseSent { .. } => None,
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler =
+        THandler<request_response::cbor::Behaviour<StreamRequest, StreamResponse>>;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &libp2p::Multiaddr,
+        remote_addr: &libp2p::Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &libp2p::Multiaddr,
+        role_override: libp2p::core::Endpoint,
+        port_use: libp2p::core::transport::PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.request_response
+            .handle_established_outbound_connection(
+                connection_id,
+                peer,
+                addr,
+                role_override,
+                port_use,
+            )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+        if let FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            remaining_established: 0,
+            ..
+        }) = event
+        {
+            self.inbound
+                .retain(|(stream_peer_id, _), _| stream_peer_id != &peer_id);
+        }
+
+        self.request_response.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.request_response
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(ev) = self.events.pop_front() {
+            return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+        }
+
+        loop {
+            match self.join_set.poll_join_next(cx) {
+                task::Poll::Ready(Some(Ok((key, actor_id, last, channel, res)))) => {
+                    if let Some(ev) = self.handle_delivered(key, actor_id, last, channel, res) {
+                        return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+                    }
+                    continue;
+                }
+                task::Poll::Ready(Some(Err(err))) => {
+                    panic!("stream delivery futures should never fail: {err}");
+                }
+                task::Poll::Ready(None) | task::Poll::Pending => {}
+            }
+
+            match self.request_response.poll(cx) {
+                task::Poll::Ready(ToSwarm::GenerateEvent(ev)) => {
+                    if let Some(ev) = self.handle_request_response_event(ev) {
+                        return task::Poll::Ready(ToSwarm::GenerateEvent(ev));
+                    }
+                    continue;
+                }
+                task::Poll::Ready(other_ev) => {
+                    return task::Poll::Ready(
+                        other_ev.map_out(|_| unreachable!("we handled GenerateEvent above")),
+                    );
+                }
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Delivers each item in a batch to the actor's mailbox in order, waiting for mailbox capacity.
+async fn deliver(
+    request: StreamRequest,
+    sender: RemoteSender,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+) -> StreamResult {
+    let StreamRequest {
+        actor_id,
+        actor_remote_id,
+        message_remote_id,
+        version,
+        codec,
+        payloads,
+        ..
+    } = request;
+    if !messaging::is_allowed(
+        access_policy.as_deref(),
+        sender.peer_id(),
+        &actor_remote_id,
+        &message_remote_id,
+    ) {
+        return Err(RemoteSendError::Unauthorized {
+            actor_remote_id,
+            message_remote_id,
+        });
+    }
+    for payload in payloads {
+        messaging::tell(
+            actor_id,
+            actor_remote_id.clone(),
+            message_remote_id.clone(),
+            version,
+            payload,
+            codec,
+            None,
+            false,
+            sender,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Sends batches of a single stream to a remote actor, limiting the number of unacknowledged batches.
+pub(crate) struct StreamSender {
+    swarm_tx: SwarmSender,
+    stream_id: u64,
+    seq: u64,
+    actor_id: ActorId,
+    actor_remote_id: Cow<'static, str>,
+    message_remote_id: Cow<'static, str>,
+    version: u32,
+    codec: CodecId,
+    in_flight: FuturesOrdered<oneshot::Receiver<StreamResult>>,
+}
+
+impl StreamSender {
+    pub(crate) fn new(
+        swarm_tx: SwarmSender,
+        actor_id: ActorId,
+        actor_remote_id: &'static str,
+        message_remote_id: &'static str,
+        version: u32,
+        codec: CodecId,
+    ) -> Self {
+        StreamSender {
+            swarm_tx,
+            stream_id: NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed),
+            seq: 0,
+            actor_id,
+            actor_remote_id: Cow::Borrowed(actor_remote_id),
+            message_remote_id: Cow::Borrowed(message_remote_id),
+            version,
+            codec,
+            in_flight: FuturesOrdered::new(),
+        }
+    }
+
+    /// Sends a batch of encoded items, waiting for an acknowledgement first if the window is full.
+    pub(crate) async fn send(&mut self, payloads: Vec<Vec<u8>>, last: bool) -> StreamResult {
+        while self.in_flight.len() >= self.swarm_tx.stream_config().window {
+            self.recv_ack().await?;
+        }
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.swarm_tx.send(SwarmCommand::StreamBatch {
+            request: StreamRequest {
+                stream_id: self.stream_id,
+                seq: self.seq,
+                actor_id: self.actor_id,
+                actor_remote_id: self.actor_remote_id.clone(),
+                message_remote_id: self.message_remote_id.clone(),
+                version: self.version,
+                codec: self.codec,
+                payloads,
+                last,
+            },
+            reply: reply_tx,
+        })?;
+        self.seq += 1;
+        self.in_flight.push_back(reply_rx);
+
+        Ok(())
+    }
+
+    /// Waits for all batches sent to be acknowledged.
+    pub(crate) async fn flush(&mut self) -> StreamResult {
+        while !self.in_flight.is_empty() {
+            self.recv_ack().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn recv_ack(&mut self) -> StreamResult {
+        match self.in_flight.next().await {
+            Some(Ok(res)) => res,
+            Some(Err(_)) => Err(RemoteSendError::ConnectionClosed),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        error,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use tokio::{sync::Semaphore, time};
+
+    use crate::{
+        Actor,
+        actor::{ActorRef, Spawn},
+        mailbox,
+        message::{Context, Message, StreamMessage},
+        remote::testing::{Cluster, register_remote_actor, register_remote_message},
+    };
+
+    use super::*;
+
+    struct Collector {
+        items: Arc<Mutex<Vec<u32>>>,
+        gate: Option<Arc<Semaphore>>,
+    }
+
+    impl Actor for Collector {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Collector);
+
+    impl Message<StreamMessage<u32, (), ()>> for Collector {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            msg: StreamMessage<u32, (), ()>,
+            _ctx: &mut Context<Self, Self::Reply>,
+        ) {
+            if let StreamMessage::Next(item) = msg {
+                if let Some(gate) = &self.gate {
+                    gate.acquire().await.unwrap().forget();
+                }
+                self.items.lock().unwrap().push(item);
+            }
+        }
+    }
+
+    register_remote_message!(Collector, StreamMessage<u32, (), ()>);
+
+    #[tokio::test]
+    async fn stream_items_are_delivered_in_order() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::builder()
+            .configure(|behaviour| {
+                behaviour.with_streaming_config(Config::default().with_batch_size(8).with_window(2))
+            })
+            .start()
+            .await?;
+
+        let items = Arc::new(Mutex::new(Vec::new()));
+        let collector = Collector::spawn(Collector {
+            items: items.clone(),
+            gate: None,
+        });
+        cluster.node(0).register(&collector, "collector").await?;
+        let remote_collector = cluster
+            .wait_for_lookup::<Collector>(1, "collector", 1)
+            .await?
+            .remove(0);
+
+        let _ = remote_collector
+            .attach_stream(futures::stream::iter(0..500u32), (), ())
+            .await??;
+        cluster
+            .wait_until(|| items.lock().unwrap().len() == 500)
+            .await?;
+        assert_eq!(*items.lock().unwrap(), (0..500).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn slow_actor_applies_backpressure_to_stream() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::builder()
+            .configure(|behaviour| {
+                behaviour.with_streaming_config(Config::default().with_batch_size(4).with_window(2))
+            })
+            .start()
+            .await?;
+
+        let items = Arc::new(Mutex::new(Vec::new()));
+        let gate = Arc::new(Semaphore::new(0));
+        let collector = Collector::spawn_with_mailbox(
+            Collector {
+                items: items.clone(),
+                gate: Some(gate.clone()),
+            },
+            mailbox::bounded(1),
+        );
+        cluster.node(0).register(&collector, "collector").await?;
+        let remote_collector = cluster
+            .wait_for_lookup::<Collector>(1, "collector", 1)
+            .await?
+            .remove(0);
+
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let stream = futures::stream::iter(0..1000u32).inspect({
+            let pulled = pulled.clone();
+            move |_| {
+                pulled.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let handle = remote_collector.attach_stream(stream, (), ());
+
+        // The sender stops pulling once the window is full of batches the actor can't accept
+        time::sleep(Duration::from_millis(300)).await;
+        assert!(!handle.is_finished());
+        assert!(pulled.load(Ordering::SeqCst) < 50);
+        assert!(items.lock().unwrap().is_empty());
+
+        gate.add_permits(1000);
+        let _ = time::timeout(Duration::from_secs(10), handle).await???;
+        cluster
+            .wait_until(|| items.lock().unwrap().len() == 1000)
+            .await?;
+        assert_eq!(pulled.load(Ordering::SeqCst), 1000);
+        assert_eq!(*items.lock().unwrap(), (0..1000).collect::<Vec<_>>());
+
+        Ok(())
+    }
+}
@@ -1,14 +1,19 @@
 use core::task;
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     marker::PhantomData,
     pin, str,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Mutex, RwLock},
     task::Poll,
     time::Duration,
+    vec,
 };
 
-use futures::{Future, FutureExt, Stream, StreamExt, ready};
+use futures::{
+    Future, FutureExt, Stream, StreamExt, TryStreamExt, future::BoxFuture, ready,
+    stream::FuturesUnordered,
+};
 use libp2p::PeerId;
 use tokio::sync::{mpsc, oneshot};
 
@@ -16,18 +21,36 @@ use crate::{
     Actor,
     actor::{ActorId, ActorRef, RemoteActorRef},
     error::{ActorStopReason, Infallible, RegistryError, RemoteSendError},
+    message::Message,
 };
 
 use super::{
-    DowncastRegsiteredActorRefError, REMOTE_REGISTRY, RemoteActor, RemoteRegistryActorRef,
-    messaging::SwarmResponse,
+    DowncastRegsiteredActorRefError, REMOTE_REGISTRY, RegisteredName, RemoteActor,
+    RemoteRegistryActorRef,
+    codec::CodecId,
+    membership::{Member, MemberEvent},
+    messaging::{PeerCapabilities, SwarmResponse},
     registry::{
-        ActorRegistration, LookupLocalReply, LookupReply, LookupResult, RegisterReply,
-        UnregisterReply,
+        ActorRegistration, LookupCache, LookupLocalReply, LookupReply, LookupResult, RegisterReply,
+        UnregisterReply, WatchEvent, WatchReply,
     },
+    streaming::{self, StreamRequest},
 };
 
-static ACTOR_SWARM: OnceLock<ActorSwarm> = OnceLock::new();
+/// The global actor swarm, along with its interned peer ID.
+static ACTOR_SWARM: RwLock<Option<(Arc<ActorSwarm>, &'static PeerId)>> = RwLock::new(None);
+
+/// The peer IDs of every swarm which has been the global swarm.
+///
+/// IDs of local actors borrow the global swarm's peer ID, so the peer IDs are kept for the
+/// lifetime of the process rather than the swarms themselves.
+static GLOBAL_PEER_IDS: Mutex<Vec<&'static PeerId>> = Mutex::new(Vec::new());
+
+tokio::task_local! {
+    /// The swarm a message is being encoded for or was received through, used by actor refs
+    /// serialized within the message in place of the global swarm.
+    static CURRENT_SWARM: SwarmSender;
+}
 
 /// `ActorSwarm` is the core component for remote actors within Kameo.
 ///
@@ -57,19 +80,76 @@ impl ActorSwarm {
     /// needing to store the reference manually.
     ///
     /// ## Returns
-    /// An optional reference to the `ActorSwarm`, or `None` if it has not been bootstrapped.
-    pub fn get() -> Option<&'static Self> {
-        ACTOR_SWARM.get()
+    /// An optional reference to the `ActorSwarm`, or `None` if it has not been bootstrapped or
+    /// has been shut down.
+    pub fn get() -> Option<Arc<Self>> {
+        ACTOR_SWARM
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(swarm, _)| swarm.clone())
     }
 
-    pub(crate) fn set(
+    /// Returns the peer ID of the global swarm, if it has been bootstrapped.
+    pub(crate) fn global_peer_id() -> Option<&'static PeerId> {
+        ACTOR_SWARM
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(_, peer_id)| *peer_id)
+    }
+
+    pub(crate) fn new(
         swarm_tx: mpsc::UnboundedSender<SwarmCommand>,
         local_peer_id: PeerId,
-    ) -> Result<(), Self> {
-        ACTOR_SWARM.set(ActorSwarm {
-            swarm_tx: SwarmSender(swarm_tx),
+        codec: CodecId,
+        stream_config: streaming::Config,
+        lookup_cache: Option<LookupCache>,
+    ) -> Self {
+        ActorSwarm {
+            swarm_tx: SwarmSender {
+                tx: swarm_tx,
+                local_peer_id,
+                codec,
+                stream_config,
+                lookup_cache,
+            },
             local_peer_id,
-        })
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn set(swarm: ActorSwarm) -> Result<(), Self> {
+        let mut global = ACTOR_SWARM.write().unwrap();
+        if global.is_some() {
+            return Err(swarm);
+        }
+        let mut peer_ids = GLOBAL_PEER_IDS.lock().unwrap();
+        let peer_id = match peer_ids
+            .iter()
+            .find(|peer_id| ***peer_id == swarm.local_peer_id)
+        {
+            Some(peer_id) => *peer_id,
+            None => {
+                let peer_id = &*Box::leak(Box::new(swarm.local_peer_id));
+                peer_ids.push(peer_id);
+                peer_id
+            }
+        };
+        *global = Some((Arc::new(swarm), peer_id));
+        Ok(())
+    }
+
+    /// Clears the global actor swarm if it's the swarm with the given peer ID, allowing another
+    /// swarm to be initialized as the global swarm.
+    pub(crate) fn unset(local_peer_id: &PeerId) {
+        let mut global = ACTOR_SWARM.write().unwrap();
+        if global
+            .as_ref()
+            .is_some_and(|(swarm, _)| swarm.local_peer_id == *local_peer_id)
+        {
+            *global = None;
+        }
     }
 
     /// Returns the local peer ID, which uniquely identifies this node in the libp2p network.
@@ -93,7 +173,8 @@ impl ActorSwarm {
             let Some(ActorRegistration {
                 actor_id,
                 remote_id,
-            }) = reply_rx.await?
+                ..
+            }) = reply_rx.await??
             else {
                 return Ok(None);
             };
@@ -101,8 +182,8 @@ impl ActorSwarm {
                 return Err(RegistryError::BadActorType);
             }
 
-            let registry = REMOTE_REGISTRY.lock().await;
-            let Some(actor_ref_any) = registry.get(&actor_id) else {
+            let registry = REMOTE_REGISTRY.lock().unwrap();
+            let Some(actor_ref_any) = registry.get(&actor_id.into_local()) else {
                 return Ok(None);
             };
             match actor_ref_any.downcast() {
@@ -135,23 +216,59 @@ impl ActorSwarm {
                         "Multiple actors found for '{name_clone}'. Consider using lookup_all() for deterministic behavior when multiple actors may exist."
                     );
                 }
+                stream.finish_in_background();
             });
+            return Ok(first);
         }
 
+        stream.finish_in_background();
         Ok(first)
     }
 
+    /// Looks up the first actor with a given name in the swarm whose tags match a predicate.
+    pub(crate) async fn lookup_where<A: Actor + RemoteActor>(
+        &self,
+        name: Arc<str>,
+        mut predicate: impl FnMut(&BTreeMap<String, String>) -> bool,
+    ) -> Result<Option<RemoteActorRef<A>>, RegistryError> {
+        let mut stream = self.lookup_all(name);
+        while let Some(actor_ref) = stream.try_next().await? {
+            if predicate(actor_ref.tags()) {
+                stream.finish_in_background();
+                return Ok(Some(actor_ref));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Looks up all actors with a given name in the swarm.
     pub(crate) fn lookup_all<A: Actor + RemoteActor>(&self, name: Arc<str>) -> LookupStream<A> {
+        let fill = match &self.swarm_tx.lookup_cache {
+            Some(lookup_cache) => {
+                if let Some(registrations) = lookup_cache.get(&name) {
+                    return LookupStream::new_cached(self.swarm_tx.clone(), registrations);
+                }
+                Some(CacheFill {
+                    cache: lookup_cache.clone(),
+                    generation: lookup_cache.generation(),
+                    registrations: Vec::new(),
+                })
+            }
+            None => None,
+        };
+
         let (reply_tx, reply_rx) = mpsc::unbounded_channel();
         let cmd = SwarmCommand::Lookup {
-            name,
+            name: name.clone(),
             reply: reply_tx,
         };
-        self.swarm_tx.send(cmd);
+        if self.swarm_tx.send(cmd).is_err() {
+            return LookupStream::new_err();
+        }
 
         let swarm_tx = self.swarm_tx.clone();
-        LookupStream::new(swarm_tx, reply_rx)
+        LookupStream::new(swarm_tx, name, reply_rx, fill)
     }
 
     /// Registers an actor within the swarm.
@@ -159,8 +276,14 @@ impl ActorSwarm {
         &self,
         actor_ref: ActorRef<A>,
         name: Arc<str>,
+        tags: BTreeMap<String, String>,
     ) -> impl Future<Output = Result<(), RegistryError>> {
-        let registration = ActorRegistration::new(actor_ref.id(), Cow::Borrowed(A::REMOTE_ID));
+        let mut registration = ActorRegistration::new(
+            actor_ref.id().with_local_peer_id(self.local_peer_id),
+            Cow::Borrowed(A::REMOTE_ID),
+        );
+        registration.tags = tags;
+        let swarm = self.clone();
 
         let reply_rx = self
             .swarm_tx
@@ -171,13 +294,27 @@ impl ActorSwarm {
             });
 
         async move {
-            let res = reply_rx.await;
+            let res = reply_rx.await?;
             match res {
                 Ok(()) | Err(RegistryError::QuorumFailed { .. }) => {
-                    REMOTE_REGISTRY.lock().await.insert(
-                        actor_ref.id(),
-                        RemoteRegistryActorRef::new(actor_ref, Some(name)),
+                    let mut registry = REMOTE_REGISTRY.lock().unwrap();
+                    // The name now belongs to this actor, replacing any other local actor
+                    for entry in registry.values_mut() {
+                        entry
+                            .names
+                            .retain(|registered| !registered.matches(&name, &swarm));
+                    }
+                    let actor_id = actor_ref.id();
+                    let names = registry
+                        .remove(&actor_id)
+                        .map(|entry| entry.names)
+                        .unwrap_or_default();
+                    let mut entry = RemoteRegistryActorRef::new(
+                        actor_ref,
+                        Some(RegisteredName { name, swarm }),
                     );
+                    entry.names.extend(names);
+                    registry.insert(actor_id, entry);
 
                     Ok(())
                 }
@@ -196,25 +333,79 @@ impl ActorSwarm {
             .send_with_reply(|reply| SwarmCommand::Unregister { name, reply });
 
         async move {
-            reply_rx.await;
+            let _ = reply_rx.await;
+        }
+    }
+
+    /// Unregisters a name within the swarm, only if it's still registered to the actor.
+    pub(crate) fn unregister_actor(
+        &self,
+        name: Arc<str>,
+        actor_id: ActorId,
+    ) -> impl Future<Output = ()> {
+        let reply_rx = self
+            .swarm_tx
+            .send_with_reply(|reply| SwarmCommand::UnregisterActor {
+                name,
+                actor_id: actor_id.with_local_peer_id(self.local_peer_id),
+                reply,
+            });
+
+        async move {
+            let _ = reply_rx.await;
         }
     }
 
+    /// Watches for changes to the actors registered under a name.
+    pub(crate) fn watch(&self, name: Arc<str>) -> WatchStream {
+        let (reply_tx, reply_rx) = mpsc::unbounded_channel();
+        // The stream ends immediately if the swarm has stopped
+        let _ = self.swarm_tx.send(SwarmCommand::Watch {
+            name,
+            reply: reply_tx,
+        });
+
+        WatchStream { reply_rx }
+    }
+
+    /// Returns the members of the cluster.
+    pub(crate) fn members(&self) -> impl Future<Output = Vec<Member>> {
+        self.swarm_tx
+            .send_with_reply(|reply| SwarmCommand::Members { reply })
+            .map(Result::unwrap_or_default)
+    }
+
+    /// Subscribes to changes to the members of the cluster.
+    pub(crate) fn subscribe_members(&self) -> mpsc::UnboundedReceiver<MemberEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        // The receiver is closed immediately if the swarm has stopped
+        let _ = self.swarm_tx.send(SwarmCommand::SubscribeMembers { tx });
+
+        rx
+    }
+
+    /// Gracefully leaves the cluster.
+    pub(crate) fn leave(&self) -> impl Future<Output = ()> {
+        self.swarm_tx
+            .send_with_reply(|reply| SwarmCommand::Leave { reply })
+            .map(|_| ())
+    }
+
     pub(crate) fn link<A: Actor + RemoteActor, B: Actor + RemoteActor>(
         &self,
         actor_id: ActorId,
         sibbling_id: ActorId,
     ) -> impl Future<Output = Result<(), RemoteSendError<Infallible>>> {
         let reply_rx = self.swarm_tx.send_with_reply(|reply| SwarmCommand::Link {
-            actor_id,
+            actor_id: actor_id.with_local_peer_id(self.local_peer_id),
             actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
-            sibbling_id,
+            sibbling_id: sibbling_id.with_local_peer_id(self.local_peer_id),
             sibbling_remote_id: Cow::Borrowed(B::REMOTE_ID),
             reply,
         });
 
         async move {
-            match reply_rx.await {
+            match reply_rx.await? {
                 SwarmResponse::Link(result) => result,
                 SwarmResponse::OutboundFailure(err) => Err(err),
                 _ => panic!("got an unexpected swarm response"),
@@ -228,14 +419,14 @@ impl ActorSwarm {
         sibbling_id: ActorId,
     ) -> impl Future<Output = Result<(), RemoteSendError<Infallible>>> {
         let reply_rx = self.swarm_tx.send_with_reply(|reply| SwarmCommand::Unlink {
-            actor_id,
-            sibbling_id,
+            actor_id: actor_id.with_local_peer_id(self.local_peer_id),
+            sibbling_id: sibbling_id.with_local_peer_id(self.local_peer_id),
             sibbling_remote_id: Cow::Borrowed(B::REMOTE_ID),
             reply,
         });
 
         async move {
-            match reply_rx.await {
+            match reply_rx.await? {
                 SwarmResponse::Unlink(result) => result,
                 SwarmResponse::OutboundFailure(err) => Err(err),
                 _ => panic!("got an unexpected swarm response"),
@@ -253,15 +444,15 @@ impl ActorSwarm {
         let reply_rx = self
             .swarm_tx
             .send_with_reply(|reply| SwarmCommand::SignalLinkDied {
-                dead_actor_id,
-                notified_actor_id,
+                dead_actor_id: dead_actor_id.with_local_peer_id(self.local_peer_id),
+                notified_actor_id: notified_actor_id.with_local_peer_id(self.local_peer_id),
                 notified_actor_remote_id,
                 stop_reason,
                 reply,
             });
 
         async move {
-            match reply_rx.await {
+            match reply_rx.await? {
                 SwarmResponse::SignalLinkDied(result) => result,
                 SwarmResponse::OutboundFailure(err) => Err(err),
                 _ => panic!("got an unexpected swarm response"),
@@ -269,11 +460,225 @@ impl ActorSwarm {
         }
     }
 
+    /// Spawns an actor on a peer, optionally linking it to another actor.
+    pub(crate) fn spawn<A: Actor + RemoteActor>(
+        &self,
+        peer_id: PeerId,
+        args: Vec<u8>,
+        codec: CodecId,
+        link: Option<(ActorId, Cow<'static, str>)>,
+    ) -> impl Future<Output = Result<RemoteActorRef<A>, RemoteSendError>> {
+        let reply_rx = self.swarm_tx.send_with_reply(|reply| SwarmCommand::Spawn {
+            peer_id,
+            actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+            args,
+            codec,
+            link: link.map(|(sibbling_id, sibbling_remote_id)| {
+                (
+                    sibbling_id.with_local_peer_id(self.local_peer_id),
+                    sibbling_remote_id,
+                )
+            }),
+            reply,
+        });
+        let swarm_tx = self.swarm_tx.clone();
+
+        async move {
+            match reply_rx.await? {
+                SwarmResponse::Spawn(result) => {
+                    result.map(|actor_id| RemoteActorRef::new(actor_id, swarm_tx))
+                }
+                SwarmResponse::OutboundFailure(err) => Err(err),
+                _ => panic!("got an unexpected swarm response"),
+            }
+        }
+    }
+
+    /// Resolves a sharded entity on the peer owning its shard, spawning it if needed.
+    pub(crate) fn resolve_entity<A: Actor + RemoteActor>(
+        &self,
+        peer_id: PeerId,
+        entity_id: String,
+    ) -> impl Future<Output = Result<RemoteActorRef<A>, RemoteSendError>> {
+        let reply_rx = self
+            .swarm_tx
+            .send_with_reply(|reply| SwarmCommand::ResolveEntity {
+                peer_id,
+                actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+                entity_id,
+                reply,
+            });
+        let swarm_tx = self.swarm_tx.clone();
+
+        async move {
+            match reply_rx.await? {
+                SwarmResponse::ResolveEntity(result) => {
+                    result.map(|actor_id| RemoteActorRef::new(actor_id, swarm_tx))
+                }
+                SwarmResponse::OutboundFailure(err) => Err(err),
+                _ => panic!("got an unexpected swarm response"),
+            }
+        }
+    }
+
+    /// Resolves a cluster singleton hosted by a peer.
+    pub(crate) fn resolve_singleton<A: Actor + RemoteActor>(
+        &self,
+        peer_id: PeerId,
+    ) -> impl Future<Output = Result<RemoteActorRef<A>, RemoteSendError>> {
+        let reply_rx = self
+            .swarm_tx
+            .send_with_reply(|reply| SwarmCommand::ResolveSingleton {
+                peer_id,
+                actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+                reply,
+            });
+        let swarm_tx = self.swarm_tx.clone();
+
+        async move {
+            match reply_rx.await? {
+                SwarmResponse::ResolveSingleton(result) => {
+                    result.map(|actor_id| RemoteActorRef::new(actor_id, swarm_tx))
+                }
+                SwarmResponse::OutboundFailure(err) => Err(err),
+                _ => panic!("got an unexpected swarm response"),
+            }
+        }
+    }
+
+    /// Returns the capabilities of a connected peer, if a handshake with it has completed.
+    pub(crate) fn peer_capabilities(
+        &self,
+        peer_id: PeerId,
+    ) -> impl Future<Output = Option<PeerCapabilities>> {
+        self.swarm_tx
+            .send_with_reply(|reply| SwarmCommand::PeerCapabilities { peer_id, reply })
+            .map(|res| res.ok().flatten())
+    }
+
     pub(crate) fn sender(&self) -> &SwarmSender {
         &self.swarm_tx
     }
 }
 
+/// A handle to an actor swarm.
+///
+/// Remote functions such as [`RemoteActorRef::lookup`] and [`ActorRef::register`] use the global
+/// actor swarm, initialized with [`Behaviour::init_global`](super::Behaviour::init_global) or
+/// bootstrapping. A `SwarmHandle` is passed explicitly instead, allowing multiple independent
+/// swarms in one process, such as several nodes in a test or a node joining two separate
+/// clusters. Handles are obtained with [`Behaviour::swarm_handle`](super::Behaviour::swarm_handle)
+/// or [`BootstrapHandle::swarm`](super::BootstrapHandle::swarm).
+///
+/// Actor refs looked up through a handle send messages through its swarm.
+///
+/// # Example
+///
+/// ```no_run
+/// use kameo::actor::{RemoteActorRef, Spawn};
+/// use kameo::remote;
+/// # use kameo::{Actor, RemoteActor};
+/// #
+/// # #[derive(Actor, RemoteActor)]
+/// # struct MyActor;
+///
+/// # tokio_test::block_on(async {
+/// let node_a = remote::Bootstrap::new().global(false).start()?;
+/// let node_b = remote::Bootstrap::new().global(false).start()?;
+///
+/// let actor_ref = MyActor::spawn(MyActor);
+/// actor_ref.register_in(&node_a.swarm(), "my_actor").await?;
+///
+/// let remote_ref = RemoteActorRef::<MyActor>::lookup_in(&node_b.swarm(), "my_actor").await?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Clone, Debug)]
+pub struct SwarmHandle {
+    swarm: ActorSwarm,
+}
+
+impl SwarmHandle {
+    pub(crate) fn new(swarm: ActorSwarm) -> Self {
+        SwarmHandle { swarm }
+    }
+
+    /// Returns a handle to the global actor swarm, if it has been initialized.
+    pub fn global() -> Option<Self> {
+        ActorSwarm::get().map(|swarm| SwarmHandle::new(ActorSwarm::clone(&swarm)))
+    }
+
+    /// Returns the local peer ID of the swarm.
+    pub fn peer_id(&self) -> PeerId {
+        *self.swarm.local_peer_id()
+    }
+
+    /// Unregisters an actor within the swarm.
+    ///
+    /// This will only unregister an actor previously registered in this swarm by the current node.
+    pub async fn unregister(&self, name: impl Into<Arc<str>>) {
+        let name = name.into();
+        for entry in REMOTE_REGISTRY.lock().unwrap().values_mut() {
+            entry
+                .names
+                .retain(|registered| !registered.matches(&name, &self.swarm));
+        }
+        self.swarm.unregister(name).await;
+    }
+
+    /// Watches for changes to the actors registered under a name in the swarm.
+    ///
+    /// See [`remote::watch`](super::watch).
+    pub fn watch(&self, name: impl Into<Arc<str>>) -> WatchStream {
+        self.swarm.watch(name.into())
+    }
+
+    /// Returns the members of the swarm's cluster, including the local node, ordered from oldest
+    /// to youngest.
+    pub async fn members(&self) -> Vec<Member> {
+        self.swarm.members().await
+    }
+
+    /// Subscribes an actor to changes to the members of the swarm's cluster.
+    ///
+    /// See [`remote::subscribe_members`](super::subscribe_members).
+    pub fn subscribe_members<A>(&self, actor_ref: &ActorRef<A>)
+    where
+        A: Actor + Message<MemberEvent>,
+    {
+        let mut events = self.swarm.subscribe_members();
+        let actor_ref = actor_ref.downgrade();
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let Some(actor_ref) = actor_ref.upgrade() else {
+                    break;
+                };
+                if actor_ref.tell(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Gracefully leaves the swarm's cluster.
+    ///
+    /// See [`remote::leave`](super::leave).
+    pub async fn leave(&self) {
+        self.swarm.leave().await
+    }
+
+    /// Returns the remote actors and messages supported by a connected peer.
+    ///
+    /// See [`remote::peer_capabilities`](super::peer_capabilities).
+    pub async fn peer_capabilities(&self, peer_id: PeerId) -> Option<PeerCapabilities> {
+        self.swarm.peer_capabilities(peer_id).await
+    }
+
+    pub(crate) fn actor_swarm(&self) -> &ActorSwarm {
+        &self.swarm
+    }
+}
+
 /// A stream of remote actor references discovered during distributed lookup.
 ///
 /// This stream yields [`RemoteActorRef<A>`] instances as they are discovered across
@@ -311,9 +716,30 @@ pub struct LookupStream<A> {
 }
 
 impl<A> LookupStream<A> {
-    fn new(swarm_tx: SwarmSender, reply_rx: mpsc::UnboundedReceiver<LookupResult>) -> Self {
+    fn new(
+        swarm_tx: SwarmSender,
+        name: Arc<str>,
+        reply_rx: mpsc::UnboundedReceiver<LookupResult>,
+        fill: Option<CacheFill>,
+    ) -> Self {
+        LookupStream {
+            inner: LookupStreamInner::Stream {
+                swarm_tx,
+                name,
+                reply_rx: Some(reply_rx),
+                checks: FuturesUnordered::new(),
+                fill,
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    fn new_cached(swarm_tx: SwarmSender, registrations: Vec<ActorRegistration<'static>>) -> Self {
         LookupStream {
-            inner: LookupStreamInner::Stream { swarm_tx, reply_rx },
+            inner: LookupStreamInner::Cached {
+                swarm_tx,
+                registrations: registrations.into_iter(),
+            },
             _phantom: PhantomData,
         }
     }
@@ -333,8 +759,34 @@ enum LookupStreamInner {
     },
     Stream {
         swarm_tx: SwarmSender,
-        reply_rx: mpsc::UnboundedReceiver<Result<ActorRegistration<'static>, RegistryError>>,
+        name: Arc<str>,
+        reply_rx: Option<mpsc::UnboundedReceiver<LookupResult>>,
+        /// Registrations being checked with their hosting peer before being yielded.
+        checks: FuturesUnordered<BoxFuture<'static, (ActorRegistration<'static>, SwarmResponse)>>,
+        /// Registrations to cache once the lookup completes, if caching is enabled.
+        fill: Option<CacheFill>,
     },
+    Cached {
+        swarm_tx: SwarmSender,
+        registrations: vec::IntoIter<ActorRegistration<'static>>,
+    },
+}
+
+#[derive(Debug)]
+struct CacheFill {
+    cache: LookupCache,
+    generation: u64,
+    registrations: Vec<ActorRegistration<'static>>,
+}
+
+impl<A: Actor + RemoteActor> LookupStream<A> {
+    /// Continues the lookup in a background task so its results are cached, if it's
+    /// dropped before completing.
+    fn finish_in_background(mut self) {
+        if let LookupStreamInner::Stream { fill: Some(_), .. } = &self.inner {
+            tokio::spawn(async move { while self.next().await.is_some() {} });
+        }
+    }
 }
 
 impl<A: Actor + RemoteActor> Stream for LookupStream<A> {
@@ -354,34 +806,182 @@ impl<A: Actor + RemoteActor> Stream for LookupStream<A> {
                     Poll::Ready(Some(Err(RegistryError::SwarmNotBootstrapped)))
                 }
             }
-            LookupStreamInner::Stream { swarm_tx, reply_rx } => {
-                match ready!(reply_rx.poll_recv(cx)) {
-                    Some(Ok(registration)) => {
-                        if A::REMOTE_ID != registration.remote_id {
-                            Poll::Ready(Some(Err(RegistryError::BadActorType)))
-                        } else {
-                            Poll::Ready(Some(Ok(RemoteActorRef::new(
-                                registration.actor_id,
-                                swarm_tx.clone(),
-                            ))))
+            LookupStreamInner::Cached {
+                swarm_tx,
+                registrations,
+            } => Poll::Ready(registrations.next().map(|registration| {
+                if A::REMOTE_ID != registration.remote_id {
+                    return Err(RegistryError::BadActorType);
+                }
+                Ok(RemoteActorRef::new(registration.actor_id, swarm_tx.clone())
+                    .with_tags(registration.tags))
+            })),
+            LookupStreamInner::Stream {
+                swarm_tx,
+                name,
+                reply_rx,
+                checks,
+                fill,
+            } => loop {
+                // Registrations can outlive their actor in the registry until they expire,
+                // so each one is confirmed with the peer hosting it before being returned.
+                while let Some(rx) = reply_rx {
+                    match rx.poll_recv(cx) {
+                        Poll::Ready(Some(Ok(registration))) => {
+                            if A::REMOTE_ID != registration.remote_id {
+                                if let Some(fill) = fill {
+                                    fill.registrations.push(registration);
+                                }
+                                return Poll::Ready(Some(Err(RegistryError::BadActorType)));
+                            }
+                            let actor_id = registration.actor_id;
+                            let check =
+                                swarm_tx.send_with_reply(|reply| SwarmCommand::CheckRegistration {
+                                    actor_id,
+                                    actor_remote_id: Cow::Borrowed(A::REMOTE_ID),
+                                    name: name.to_string(),
+                                    reply,
+                                });
+                            checks.push(
+                                check
+                                    .map(move |res| {
+                                        let res = res.unwrap_or_else(|err| {
+                                            SwarmResponse::OutboundFailure(err.into())
+                                        });
+                                        (registration, res)
+                                    })
+                                    .boxed(),
+                            );
+                        }
+                        Poll::Ready(Some(Err(err))) => {
+                            // Incomplete results aren't cached
+                            *fill = None;
+                            return Poll::Ready(Some(Err(err)));
                         }
+                        Poll::Ready(None) => *reply_rx = None,
+                        Poll::Pending => break,
                     }
-                    Some(Err(err)) => Poll::Ready(Some(Err(err))),
-                    None => Poll::Ready(None),
                 }
-            }
+
+                match ready!(checks.poll_next_unpin(cx)) {
+                    // The hosting peer reported the actor is gone, or can no longer be dialed
+                    Some((
+                        _,
+                        SwarmResponse::CheckRegistration(false)
+                        | SwarmResponse::OutboundFailure(RemoteSendError::DialFailure),
+                    )) => continue,
+                    // Peers failing to respond for other reasons are given the benefit of the doubt
+                    Some((registration, _)) => {
+                        if let Some(fill) = fill {
+                            fill.registrations.push(registration.clone());
+                        }
+                        return Poll::Ready(Some(Ok(RemoteActorRef::new(
+                            registration.actor_id,
+                            swarm_tx.clone(),
+                        )
+                        .with_tags(registration.tags))));
+                    }
+                    None if reply_rx.is_none() => {
+                        if let Some(fill) = fill.take() {
+                            fill.cache
+                                .insert(name.clone(), fill.registrations, fill.generation);
+                        }
+                        return Poll::Ready(None);
+                    }
+                    None => return Poll::Pending,
+                }
+            },
         }
     }
 }
 
+/// A stream of changes to the actors registered under a name.
+///
+/// Created by [`remote::watch`](crate::remote::watch). The stream first yields a
+/// [`WatchEvent::Registered`] for each actor currently known to be registered under
+/// the name, followed by changes as they're observed by the registry.
+#[derive(Debug)]
+pub struct WatchStream {
+    reply_rx: mpsc::UnboundedReceiver<WatchEvent>,
+}
+
+impl Stream for WatchStream {
+    type Item = WatchEvent;
+
+    fn poll_next(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut().reply_rx.poll_recv(cx)
+    }
+}
+
 #[derive(Clone, Debug)]
-pub(crate) struct SwarmSender(mpsc::UnboundedSender<SwarmCommand>);
+pub(crate) struct SwarmSender {
+    tx: mpsc::UnboundedSender<SwarmCommand>,
+    local_peer_id: PeerId,
+    codec: CodecId,
+    stream_config: streaming::Config,
+    lookup_cache: Option<LookupCache>,
+}
 
 impl SwarmSender {
-    pub(crate) fn send(&self, cmd: SwarmCommand) {
-        self.0
-            .send(cmd)
-            .expect("the swarm should never stop running");
+    /// Returns the swarm messages are currently encoded for or were received through, falling
+    /// back to the global swarm.
+    pub(crate) fn current() -> Option<SwarmSender> {
+        CURRENT_SWARM
+            .try_with(Clone::clone)
+            .ok()
+            .or_else(|| ActorSwarm::get().map(|swarm| swarm.swarm_tx.clone()))
+    }
+
+    /// Runs `f` with actor refs serialized or deserialized within it using this swarm.
+    pub(crate) fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        CURRENT_SWARM.sync_scope(self.clone(), f)
+    }
+
+    /// Returns the local peer ID of the swarm.
+    pub(crate) fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// Sends a command to the swarm, failing if the swarm has stopped running.
+    pub(crate) fn send(&self, cmd: SwarmCommand) -> Result<(), SwarmStopped> {
+        self.tx.send(cmd).map_err(|_| SwarmStopped)
+    }
+
+    /// Returns the actor swarm commands are sent to.
+    pub(crate) fn swarm(&self) -> ActorSwarm {
+        ActorSwarm {
+            swarm_tx: self.clone(),
+            local_peer_id: self.local_peer_id,
+        }
+    }
+
+    /// Returns the codec messages are encoded with when they don't specify their own.
+    pub(crate) fn codec(&self) -> CodecId {
+        self.codec
+    }
+
+    /// Returns the configuration streams attached to remote actors are sent with.
+    pub(crate) fn stream_config(&self) -> streaming::Config {
+        self.stream_config
+    }
+
+    /// Discards cached lookups which a failed send to the actor shows are out of date.
+    pub(crate) fn invalidate_lookups<E>(&self, actor_id: ActorId, err: &RemoteSendError<E>) {
+        let Some(lookup_cache) = &self.lookup_cache else {
+            return;
+        };
+        match err {
+            RemoteSendError::ActorNotRunning => lookup_cache.invalidate_actor(actor_id),
+            RemoteSendError::DialFailure | RemoteSendError::ConnectionClosed => {
+                if let Some(peer_id) = actor_id.peer_id() {
+                    lookup_cache.invalidate_peer(peer_id);
+                }
+            }
+            _ => {}
+        }
     }
 
     fn send_with_reply<T>(
@@ -390,7 +990,8 @@ impl SwarmSender {
     ) -> SwarmFuture<T> {
         let (reply_tx, reply_rx) = oneshot::channel();
         let cmd = cmd_fn(reply_tx);
-        self.send(cmd);
+        // The reply sender is dropped with the command if the swarm has stopped, failing the future
+        let _ = self.send(cmd);
 
         SwarmFuture(reply_rx)
     }
@@ -429,6 +1030,22 @@ pub(crate) enum SwarmCommand {
         /// Reply sender.
         reply: UnregisterReply,
     },
+    /// Stop providing a key, if it's still registered to an actor.
+    UnregisterActor {
+        /// Actor name.
+        name: Arc<str>,
+        /// Actor ID.
+        actor_id: ActorId,
+        /// Reply sender.
+        reply: UnregisterReply,
+    },
+    /// Watch for changes to the actors registered under a name.
+    Watch {
+        /// Actor name.
+        name: Arc<str>,
+        /// Reply sender.
+        reply: WatchReply,
+    },
     /// An actor ask request.
     Ask {
         /// Actor ID.
@@ -437,14 +1054,20 @@ pub(crate) enum SwarmCommand {
         actor_remote_id: Cow<'static, str>,
         /// Message remote ID.
         message_remote_id: Cow<'static, str>,
+        /// Message version.
+        version: u32,
         /// Payload.
         payload: Vec<u8>,
+        /// Payload codec.
+        codec: CodecId,
         /// Mailbox timeout.
         mailbox_timeout: Option<Duration>,
         /// Reply timeout.
         reply_timeout: Option<Duration>,
         /// Fail if mailbox is full.
         immediate: bool,
+        /// Sender actor ID.
+        sender_id: Option<ActorId>,
         /// Reply sender.
         reply: oneshot::Sender<SwarmResponse>,
     },
@@ -456,12 +1079,39 @@ pub(crate) enum SwarmCommand {
         actor_remote_id: Cow<'static, str>,
         /// Message remote ID.
         message_remote_id: Cow<'static, str>,
+        /// Message version.
+        version: u32,
         /// Payload.
         payload: Vec<u8>,
+        /// Payload codec.
+        codec: CodecId,
         /// Mailbox timeout.
         mailbox_timeout: Option<Duration>,
         /// Fail if mailbox is full.
         immediate: bool,
+        /// Sender actor ID.
+        sender_id: Option<ActorId>,
+        /// Reply sender.
+        reply: Option<oneshot::Sender<SwarmResponse>>,
+    },
+    /// An actor tell request, resent until it is acknowledged by the receiving peer.
+    ReliableTell {
+        /// Actor ID.
+        actor_id: ActorId,
+        /// Actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        /// Message remote ID.
+        message_remote_id: Cow<'static, str>,
+        /// Message version.
+        version: u32,
+        /// Payload.
+        payload: Vec<u8>,
+        /// Payload codec.
+        codec: CodecId,
+        /// Mailbox timeout.
+        mailbox_timeout: Option<Duration>,
+        /// Sender actor ID.
+        sender_id: Option<ActorId>,
         /// Reply sender.
         reply: Option<oneshot::Sender<SwarmResponse>>,
     },
@@ -502,22 +1152,247 @@ pub(crate) enum SwarmCommand {
         /// Reply sender.
         reply: oneshot::Sender<SwarmResponse>,
     },
+    /// Spawn an actor on a peer.
+    Spawn {
+        /// Peer ID.
+        peer_id: PeerId,
+        /// Actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        /// Encoded actor args.
+        args: Vec<u8>,
+        /// Args codec.
+        codec: CodecId,
+        /// Actor to link the spawned actor to.
+        link: Option<(ActorId, Cow<'static, str>)>,
+        /// Reply sender.
+        reply: oneshot::Sender<SwarmResponse>,
+    },
+    /// Check whether an actor is still registered under a name on its peer.
+    CheckRegistration {
+        /// Registered actor ID.
+        actor_id: ActorId,
+        /// Registered actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        /// Registered name.
+        name: String,
+        /// Reply sender.
+        reply: oneshot::Sender<SwarmResponse>,
+    },
+    /// Resolve a sharded entity on a peer, spawning it if needed.
+    ResolveEntity {
+        /// Peer ID.
+        peer_id: PeerId,
+        /// Entity actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        /// Entity ID.
+        entity_id: String,
+        /// Reply sender.
+        reply: oneshot::Sender<SwarmResponse>,
+    },
+    /// Resolve a cluster singleton hosted by a peer.
+    ResolveSingleton {
+        /// Peer ID.
+        peer_id: PeerId,
+        /// Singleton actor remote ID.
+        actor_remote_id: Cow<'static, str>,
+        /// Reply sender.
+        reply: oneshot::Sender<SwarmResponse>,
+    },
+    /// Get the capabilities of a connected peer.
+    PeerCapabilities {
+        /// Peer ID.
+        peer_id: PeerId,
+        /// Reply sender.
+        reply: oneshot::Sender<Option<PeerCapabilities>>,
+    },
+    /// A batch of items in a stream attached to an actor.
+    StreamBatch {
+        /// The batch request.
+        request: StreamRequest,
+        /// Reply sender, notified once the batch has been delivered.
+        reply: oneshot::Sender<Result<(), RemoteSendError>>,
+    },
+    /// Get the members of the cluster.
+    Members {
+        /// Reply sender.
+        reply: oneshot::Sender<Vec<Member>>,
+    },
+    /// Subscribe to changes to the members of the cluster.
+    SubscribeMembers {
+        /// Event sender.
+        tx: mpsc::UnboundedSender<MemberEvent>,
+    },
+    /// Gracefully leave the cluster.
+    Leave {
+        /// Reply sender, notified once members have acknowledged the departure.
+        reply: oneshot::Sender<()>,
+    },
+}
+
+/// Runs `fut` with actor refs serialized or deserialized within it using `swarm`, or the global
+/// swarm if `None`.
+pub(crate) async fn in_swarm<F: Future>(swarm: Option<SwarmSender>, fut: F) -> F::Output {
+    match swarm {
+        Some(swarm) => CURRENT_SWARM.scope(swarm, fut).await,
+        None => fut.await,
+    }
 }
 
 /// `SwarmFuture` represents a future that contains the response from a remote actor.
 ///
 /// This future is returned when sending a message to a remote actor via the actor swarm.
 /// If the response is not needed, the future can simply be dropped without awaiting it.
+///
+/// The future fails with [`SwarmStopped`] if the swarm stops running before replying.
 #[derive(Debug)]
 struct SwarmFuture<T>(oneshot::Receiver<T>);
 
 impl<T> Future for SwarmFuture<T> {
-    type Output = T;
+    type Output = Result<T, SwarmStopped>;
 
     fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
-        task::Poll::Ready(
-            ready!(self.0.poll_unpin(cx))
-                .expect("the oneshot sender should never be dropped before being sent to"),
-        )
+        task::Poll::Ready(ready!(self.0.poll_unpin(cx)).map_err(|_| SwarmStopped))
+    }
+}
+
+/// The actor swarm has stopped running, and can no longer handle commands.
+///
+/// Converted into the `SwarmNotBootstrapped` variant of remote errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SwarmStopped;
+
+impl<E> From<SwarmStopped> for RemoteSendError<E> {
+    fn from(_: SwarmStopped) -> Self {
+        RemoteSendError::SwarmNotBootstrapped
+    }
+}
+
+impl From<SwarmStopped> for RegistryError {
+    fn from(_: SwarmStopped) -> Self {
+        RegistryError::SwarmNotBootstrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        error,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        actor::Spawn,
+        message::{Context, Message},
+        remote::{
+            RemoteMessage,
+            testing::{
+                Cluster,
+                fixtures::{Counter, Inc, Target},
+                register_remote_actor, register_remote_message,
+            },
+        },
+    };
+
+    use super::*;
+
+    struct Forwarder;
+
+    impl Actor for Forwarder {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Forwarder);
+
+    /// Increments a counter on another node, as received from the node hosting it.
+    #[derive(Deserialize)]
+    struct IncVia(RemoteActorRef<Counter>);
+
+    impl Message<IncVia> for Forwarder {
+        type Reply = Option<String>;
+
+        async fn handle(
+            &mut self,
+            IncVia(counter): IncVia,
+            _ctx: &mut Context<Self, Self::Reply>,
+        ) -> Option<String> {
+            counter.tell(&Inc).send_ack().await.ok()?;
+            counter.id().peer_id().map(ToString::to_string)
+        }
+    }
+
+    register_remote_message!(Forwarder, IncVia);
+
+    /// [`IncVia`] as sent by the node hosting the counter.
+    #[derive(Serialize)]
+    struct IncViaLocal(ActorRef<Counter>);
+
+    impl Message<IncViaLocal> for Forwarder {
+        type Reply = Option<String>;
+
+        async fn handle(
+            &mut self,
+            _msg: IncViaLocal,
+            _ctx: &mut Context<Self, Self::Reply>,
+        ) -> Option<String> {
+            unreachable!("only sent to remote forwarders")
+        }
+    }
+
+    impl RemoteMessage<IncViaLocal> for Forwarder {
+        const REMOTE_ID: &'static str = <Forwarder as RemoteMessage<IncVia>>::REMOTE_ID;
+    }
+
+    #[tokio::test]
+    async fn actor_refs_in_messages_are_bound_to_the_sending_swarm()
+    -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let forwarder = Forwarder::spawn(Forwarder);
+        cluster.node(0).register(&forwarder, "forwarder").await?;
+        let remote_forwarder = cluster
+            .wait_for_lookup::<Forwarder>(1, "forwarder", 1)
+            .await?
+            .remove(0);
+
+        // Neither swarm is global, so the counter is only known through the swarm sending it
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Counter::spawn(Counter {
+            count: count.clone(),
+        });
+        let peer_id = remote_forwarder.ask(&IncViaLocal(counter.clone())).await?;
+        assert_eq!(peer_id, Some(cluster.node(1).peer_id().to_string()));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(REMOTE_REGISTRY.lock().unwrap().contains_key(&counter.id()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lookup_skips_stopped_actors() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(2).await?;
+
+        let target = Target::spawn(Target);
+        cluster.node(0).register(&target, "target").await?;
+        cluster.wait_for_lookup::<Target>(1, "target", 1).await?;
+
+        target.kill();
+        target.wait_for_shutdown().await;
+        assert!(cluster.node(1).lookup::<Target>("target").await?.is_none());
+
+        Ok(())
     }
 }
@@ -0,0 +1,991 @@
+//! An in-process cluster of nodes for testing remote actors.
+//!
+//! Exercising remote behaviour over real sockets and mDNS discovery is slow and prone to flaky
+//! failures. A [`Cluster`] instead runs any number of nodes in the current tokio runtime, each
+//! with its own [`SwarmHandle`], connected to each other over libp2p's in-memory transport.
+//! Nodes are not initialized as the global actor swarm, so actors are registered and looked up
+//! on a chosen node with [`Node::register`] and [`Node::lookup`], or with the `*_in` methods on
+//! [`ActorRef`] and [`RemoteActorRef`].
+//!
+//! Network failures are injected with [`Cluster::disconnect`], which drops the connections
+//! between two nodes, and [`Cluster::partition`], which additionally denies any new connections
+//! between two groups of nodes until [`Cluster::heal`] is called. Remote links observe these as
+//! [`ActorStopReason::PeerDisconnected`](crate::error::ActorStopReason::PeerDisconnected).
+//!
+//! This module is available with the `testing` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use kameo::{actor::Spawn, remote::testing::Cluster, Actor, RemoteActor};
+//!
+//! #[derive(Actor, RemoteActor)]
+//! struct MyActor;
+//!
+//! # tokio_test::block_on(async {
+//! let cluster = Cluster::new(3).await?;
+//!
+//! let actor_ref = MyActor::spawn(MyActor);
+//! cluster.node(0).register(&actor_ref, "my_actor").await?;
+//!
+//! // Wait for the registration to be visible from another node
+//! let found = cluster.wait_for_lookup::<MyActor>(2, "my_actor", 1).await?;
+//! assert_eq!(found[0].id(), cluster.node(0).actor_id(&actor_ref));
+//!
+//! // Cut node 0 off from the rest of the cluster
+//! cluster.partition(&[0], &[1, 2]).await?;
+//! assert!(!cluster.node(2).is_connected(&cluster.node(0).peer_id()));
+//!
+//! cluster.heal().await?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! # });
+//! ```
+
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    error, fmt,
+    sync::{Arc, Mutex},
+    task,
+    time::Duration,
+};
+
+use futures::{StreamExt, TryStreamExt};
+use libp2p::{
+    Multiaddr, PeerId, Swarm, SwarmBuilder, Transport,
+    core::{
+        Endpoint,
+        muxing::StreamMuxerBox,
+        transport::{Boxed, MemoryTransport, PortUse},
+        upgrade,
+    },
+    identity::Keypair,
+    noise,
+    swarm::{
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, SwarmEvent, THandler,
+        THandlerInEvent, THandlerOutEvent, ToSwarm, dial_opts::DialOpts, dummy,
+    },
+    yamux,
+};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::{self, error::Elapsed},
+};
+
+use crate::{
+    Actor,
+    actor::{ActorId, ActorRef, RemoteActorRef},
+    error::RegistryError,
+};
+
+use super::{Behaviour, RemoteActor, SwarmHandle, messaging, registry};
+
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const IDLE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+type ConfigureFn = Arc<dyn Fn(Behaviour) -> Behaviour + Send + Sync>;
+
+/// A builder for an in-process [`Cluster`].
+#[derive(Clone)]
+pub struct ClusterBuilder {
+    nodes: usize,
+    messaging_config: messaging::Config,
+    registry_backend: registry::Backend,
+    configure: Option<ConfigureFn>,
+    keypairs: Vec<Keypair>,
+    connected: bool,
+    timeout: Duration,
+}
+
+impl ClusterBuilder {
+    /// Sets the number of nodes in the cluster.
+    ///
+    /// Defaults to 2.
+    pub fn nodes(mut self, nodes: usize) -> Self {
+        self.nodes = nodes;
+        self
+    }
+
+    /// Sets the messaging configuration used by every node.
+    pub fn messaging_config(mut self, config: messaging::Config) -> Self {
+        self.messaging_config = config;
+        self
+    }
+
+    /// Sets the registry backend used by every node.
+    pub fn registry_backend(mut self, backend: registry::Backend) -> Self {
+        self.registry_backend = backend;
+        self
+    }
+
+    /// Sets the identities of the first nodes, in order, so that their peer IDs are known before
+    /// the cluster is started, such as for a [`registry::StaticConfig`].
+    ///
+    /// Nodes without a given keypair are assigned a random identity.
+    pub fn keypairs(mut self, keypairs: impl IntoIterator<Item = Keypair>) -> Self {
+        self.keypairs = keypairs.into_iter().collect();
+        self
+    }
+
+    /// Applies additional configuration to each node's [`Behaviour`], such as access control
+    /// policies or heartbeat settings.
+    pub fn configure(mut self, f: impl Fn(Behaviour) -> Behaviour + Send + Sync + 'static) -> Self {
+        self.configure = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets whether every pair of nodes is connected when the cluster is started.
+    ///
+    /// Defaults to `true`. Disconnected nodes can be connected with [`Cluster::connect`].
+    pub fn connected(mut self, connected: bool) -> Self {
+        self.connected = connected;
+        self
+    }
+
+    /// Sets how long the cluster's `wait_for_*` methods wait before giving up.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Starts the nodes and waits for every node to be connected to every other node, unless
+    /// disabled with [`ClusterBuilder::connected`].
+    pub async fn start(self) -> Result<Cluster, Box<dyn error::Error>> {
+        let network = Arc::new(Network::default());
+        let mut nodes = Vec::with_capacity(self.nodes);
+        for i in 0..self.nodes {
+            let key = self
+                .keypairs
+                .get(i)
+                .cloned()
+                .unwrap_or_else(Keypair::generate_ed25519);
+            nodes.push(Node::start(&self, key, network.clone()).await?);
+        }
+
+        let cluster = Cluster {
+            nodes,
+            network,
+            timeout: self.timeout,
+        };
+        if self.connected {
+            cluster.heal().await?;
+        }
+
+        Ok(cluster)
+    }
+}
+
+impl Default for ClusterBuilder {
+    fn default() -> Self {
+        ClusterBuilder {
+            nodes: 2,
+            messaging_config: messaging::Config::default(),
+            registry_backend: registry::Backend::default(),
+            configure: None,
+            keypairs: Vec::new(),
+            connected: true,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl fmt::Debug for ClusterBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClusterBuilder")
+            .field("nodes", &self.nodes)
+            .field("messaging_config", &self.messaging_config)
+            .field("registry_backend", &self.registry_backend)
+            .field("connected", &self.connected)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A set of nodes running in the current tokio runtime, connected over an in-memory transport.
+///
+/// Nodes are shut down when the cluster is dropped. See the [module docs](self) for an example.
+#[derive(Debug)]
+pub struct Cluster {
+    nodes: Vec<Node>,
+    network: Arc<Network>,
+    timeout: Duration,
+}
+
+impl Cluster {
+    /// Starts a cluster of `nodes` nodes with the default configuration.
+    pub async fn new(nodes: usize) -> Result<Self, Box<dyn error::Error>> {
+        Cluster::builder().nodes(nodes).start().await
+    }
+
+    /// Returns a builder for configuring a cluster.
+    pub fn builder() -> ClusterBuilder {
+        ClusterBuilder::default()
+    }
+
+    /// Returns the node at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn node(&self, index: usize) -> &Node {
+        &self.nodes[index]
+    }
+
+    /// Returns all nodes in the cluster.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Closes all connections between nodes `a` and `b`, and waits for both nodes to observe it.
+    ///
+    /// The nodes may reconnect, for example when one of them sends a message to the other. Use
+    /// [`Cluster::partition`] to keep them apart.
+    pub async fn disconnect(&self, a: usize, b: usize) -> Result<(), Elapsed> {
+        self.nodes[a].send(NodeCommand::Disconnect(self.nodes[b].peer_id()));
+        self.wait_for_disconnected(a, b).await
+    }
+
+    /// Splits the cluster, closing all connections between the nodes in `left` and the nodes in
+    /// `right` and denying new ones until [`Cluster::heal`] is called.
+    ///
+    /// Waits until every node has observed the partition.
+    pub async fn partition(&self, left: &[usize], right: &[usize]) -> Result<(), Elapsed> {
+        {
+            let mut blocked = self.network.blocked.lock().unwrap();
+            for &a in left {
+                for &b in right {
+                    let (a, b) = (self.nodes[a].peer_id(), self.nodes[b].peer_id());
+                    blocked.insert((a, b));
+                    blocked.insert((b, a));
+                }
+            }
+        }
+
+        for &a in left {
+            for &b in right {
+                self.nodes[a].send(NodeCommand::Disconnect(self.nodes[b].peer_id()));
+            }
+        }
+        for &a in left {
+            for &b in right {
+                self.wait_for_disconnected(a, b).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Isolates a node from every other node in the cluster.
+    pub async fn isolate(&self, node: usize) -> Result<(), Elapsed> {
+        let rest: Vec<_> = (0..self.nodes.len()).filter(|&i| i != node).collect();
+        self.partition(&[node], &rest).await
+    }
+
+    /// Connects nodes `a` and `b` if they aren't already connected, and waits for both nodes to
+    /// observe the connection.
+    ///
+    /// Any partition between the two nodes is lifted.
+    pub async fn connect(&self, a: usize, b: usize) -> Result<(), Elapsed> {
+        let (node_a, node_b) = (&self.nodes[a], &self.nodes[b]);
+        {
+            let mut blocked = self.network.blocked.lock().unwrap();
+            blocked.remove(&(node_a.peer_id(), node_b.peer_id()));
+            blocked.remove(&(node_b.peer_id(), node_a.peer_id()));
+        }
+
+        node_b.send(NodeCommand::AddAddress(
+            node_a.peer_id(),
+            node_a.addr.clone(),
+        ));
+        node_a.send(NodeCommand::Dial(node_b.peer_id(), node_b.addr.clone()));
+        self.wait_until(|| {
+            node_a.is_connected(&node_b.peer_id()) && node_b.is_connected(&node_a.peer_id())
+        })
+        .await
+    }
+
+    /// Lifts all partitions and reconnects every pair of nodes.
+    pub async fn heal(&self) -> Result<(), Elapsed> {
+        self.network.blocked.lock().unwrap().clear();
+        for a in 0..self.nodes.len() {
+            for b in a + 1..self.nodes.len() {
+                self.connect(a, b).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits until nodes `a` and `b` are no longer connected to each other.
+    pub async fn wait_for_disconnected(&self, a: usize, b: usize) -> Result<(), Elapsed> {
+        let (node_a, node_b) = (&self.nodes[a], &self.nodes[b]);
+        self.wait_until(|| {
+            !node_a.is_connected(&node_b.peer_id()) && !node_b.is_connected(&node_a.peer_id())
+        })
+        .await
+    }
+
+    /// Waits until looking up `name` from `node` returns exactly `count` actors, returning them.
+    ///
+    /// This is useful for asserting that registrations and unregistrations have propagated
+    /// through the registry. Lookup errors are treated as the registry not having converged yet.
+    pub async fn wait_for_lookup<A>(
+        &self,
+        node: usize,
+        name: &str,
+        count: usize,
+    ) -> Result<Vec<RemoteActorRef<A>>, Elapsed>
+    where
+        A: Actor + RemoteActor + 'static,
+    {
+        let swarm = self.nodes[node].swarm();
+        time::timeout(self.timeout, async {
+            loop {
+                let found: Result<Vec<_>, _> = RemoteActorRef::<A>::lookup_all_in(swarm, name)
+                    .try_collect()
+                    .await;
+                if let Ok(found) = found
+                    && found.len() == count
+                {
+                    return found;
+                }
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+    }
+
+    /// Waits until `f` returns `true`, checking it periodically.
+    ///
+    /// This is useful for waiting on effects of remote messages, such as an actor's state being
+    /// updated.
+    pub async fn wait_until(&self, mut f: impl FnMut() -> bool) -> Result<(), Elapsed> {
+        time::timeout(self.timeout, async {
+            while !f() {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+    }
+}
+
+/// A node in a [`Cluster`].
+#[derive(Debug)]
+pub struct Node {
+    swarm: SwarmHandle,
+    addr: Multiaddr,
+    commands: mpsc::UnboundedSender<NodeCommand>,
+    connected: watch::Receiver<HashSet<PeerId>>,
+    task: JoinHandle<()>,
+}
+
+impl Node {
+    async fn start(
+        config: &ClusterBuilder,
+        key: Keypair,
+        network: Arc<Network>,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let mut swarm = SwarmBuilder::with_existing_identity(key)
+            .with_tokio()
+            .with_other_transport(transport)?
+            .with_behaviour(|key| {
+                let local_peer_id = key.public().to_peer_id();
+                let mut kameo = Behaviour::new(local_peer_id, config.messaging_config.clone())
+                    .with_registry_backend(config.registry_backend.clone())?;
+                if let Some(configure) = &config.configure {
+                    kameo = configure(kameo);
+                }
+                Ok(NodeBehaviour {
+                    kameo,
+                    firewall: Firewall {
+                        local_peer_id,
+                        network,
+                    },
+                })
+            })?
+            .with_swarm_config(|config| {
+                config.with_idle_connection_timeout(IDLE_CONNECTION_TIMEOUT)
+            })
+            .build();
+
+        swarm.listen_on("/memory/0".parse()?)?;
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+                break address;
+            }
+        };
+
+        let handle = swarm.behaviour().kameo.swarm_handle();
+        let (commands, commands_rx) = mpsc::unbounded_channel();
+        let (connected_tx, connected) = watch::channel(HashSet::new());
+        let task = tokio::spawn(run_node(swarm, commands_rx, connected_tx));
+
+        Ok(Node {
+            swarm: handle,
+            addr,
+            commands,
+            connected,
+            task,
+        })
+    }
+
+    /// Returns the node's peer ID.
+    pub fn peer_id(&self) -> PeerId {
+        self.swarm.peer_id()
+    }
+
+    /// Returns the in-memory address the node is listening on.
+    pub fn addr(&self) -> &Multiaddr {
+        &self.addr
+    }
+
+    /// Returns a handle to the node's actor swarm.
+    pub fn swarm(&self) -> &SwarmHandle {
+        &self.swarm
+    }
+
+    /// Returns whether the node has an open connection to a peer.
+    pub fn is_connected(&self, peer_id: &PeerId) -> bool {
+        self.connected.borrow().contains(peer_id)
+    }
+
+    /// Returns the ID of a local actor as seen by other nodes when it is registered on this node.
+    ///
+    /// Nodes aren't the global actor swarm, so the IDs of local actors don't include a peer ID
+    /// and compare unequal to the IDs of remote actor refs looked up from other nodes.
+    pub fn actor_id<A: Actor>(&self, actor_ref: &ActorRef<A>) -> ActorId {
+        actor_ref.id().with_local_peer_id(self.peer_id())
+    }
+
+    /// Registers an actor under a name on this node.
+    ///
+    /// See [`ActorRef::register_in`].
+    pub async fn register<A>(
+        &self,
+        actor_ref: &ActorRef<A>,
+        name: &str,
+    ) -> Result<(), RegistryError>
+    where
+        A: Actor + RemoteActor + 'static,
+    {
+        actor_ref.register_in(&self.swarm, name).await
+    }
+
+    /// Looks up an actor by name from this node.
+    ///
+    /// See [`RemoteActorRef::lookup_in`].
+    pub async fn lookup<A>(&self, name: &str) -> Result<Option<RemoteActorRef<A>>, RegistryError>
+    where
+        A: Actor + RemoteActor + 'static,
+    {
+        RemoteActorRef::lookup_in(&self.swarm, name).await
+    }
+
+    fn send(&self, command: NodeCommand) {
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn transport(
+    key: &Keypair,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn error::Error + Send + Sync>> {
+    Ok(MemoryTransport::default()
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(key)?)
+        .multiplex(yamux::Config::default())
+        .boxed())
+}
+
+#[derive(Debug)]
+enum NodeCommand {
+    AddAddress(PeerId, Multiaddr),
+    Dial(PeerId, Multiaddr),
+    Disconnect(PeerId),
+}
+
+async fn run_node(
+    mut swarm: Swarm<NodeBehaviour>,
+    mut commands: mpsc::UnboundedReceiver<NodeCommand>,
+    connected: watch::Sender<HashSet<PeerId>>,
+) {
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(NodeCommand::AddAddress(peer_id, addr)) => {
+                    swarm.add_peer_address(peer_id, addr);
+                }
+                Some(NodeCommand::Dial(peer_id, addr)) => {
+                    swarm.add_peer_address(peer_id, addr.clone());
+                    if !swarm.is_connected(&peer_id) {
+                        let _ = swarm.dial(DialOpts::peer_id(peer_id).addresses(vec![addr]).build());
+                    }
+                }
+                Some(NodeCommand::Disconnect(peer_id)) => {
+                    let _ = swarm.disconnect_peer_id(peer_id);
+                }
+                None => break,
+            },
+            event = swarm.select_next_some() => match event {
+                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    connected.send_modify(|connected| {
+                        connected.insert(peer_id);
+                    });
+                }
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                    connected.send_modify(|connected| {
+                        connected.remove(&peer_id);
+                    });
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+#[derive(NetworkBehaviour)]
+struct NodeBehaviour {
+    kameo: Behaviour,
+    firewall: Firewall,
+}
+
+#[derive(Debug, Default)]
+struct Network {
+    blocked: Mutex<HashSet<(PeerId, PeerId)>>,
+}
+
+/// Denies connections between partitioned nodes.
+struct Firewall {
+    local_peer_id: PeerId,
+    network: Arc<Network>,
+}
+
+impl Firewall {
+    fn check(&self, peer: PeerId) -> Result<THandler<Self>, ConnectionDenied> {
+        if self
+            .network
+            .blocked
+            .lock()
+            .unwrap()
+            .contains(&(self.local_peer_id, peer))
+        {
+            return Err(ConnectionDenied::new(Partitioned));
+        }
+
+        Ok(dummy::ConnectionHandler)
+    }
+}
+
+impl NetworkBehaviour for Firewall {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = Infallible;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.check(peer)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+        _port_use: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.check(peer)
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm<'_>) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        match event {}
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        task::Poll::Pending
+    }
+}
+
+#[derive(Debug)]
+struct Partitioned;
+
+impl fmt::Display for Partitioned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nodes are partitioned")
+    }
+}
+
+impl error::Error for Partitioned {}
+
+/// Registers a test actor's remote functions, as `#[derive(RemoteActor)]` does.
+#[cfg(test)]
+macro_rules! register_remote_actor {
+    ($ty:ty) => {
+        $crate::remote::testing::register_remote_actor!($ty, spawn_unsupported);
+    };
+    ($ty:ty, spawnable) => {
+        $crate::remote::testing::register_remote_actor!($ty, spawn);
+    };
+    ($ty:ty, $spawn:ident) => {
+        impl $crate::remote::RemoteActor for $ty {
+            const REMOTE_ID: &'static str = concat!(module_path!(), "::", stringify!($ty));
+        }
+
+        const _: () = {
+            use $crate::remote::_internal::*;
+
+            #[linkme::distributed_slice(REMOTE_ACTORS)]
+            static REG: (&'static str, RemoteActorFns) = (
+                <$ty as $crate::remote::RemoteActor>::REMOTE_ID,
+                RemoteActorFns {
+                    link: |actor_id, sibbling_id, sibbling_remote_id| {
+                        Box::pin(link::<$ty>(actor_id, sibbling_id, sibbling_remote_id))
+                    },
+                    unlink: |actor_id, sibbling_id| Box::pin(unlink::<$ty>(actor_id, sibbling_id)),
+                    signal_link_died: |dead_actor_id, notified_actor_id, stop_reason| {
+                        Box::pin(signal_link_died::<$ty>(
+                            dead_actor_id,
+                            notified_actor_id,
+                            stop_reason,
+                        ))
+                    },
+                    spawn: |args, codec, link| Box::pin($spawn::<$ty>(args, codec, link)),
+                },
+            );
+        };
+    };
+}
+
+/// Registers a test message's remote functions, as `#[remote_message]` does.
+#[cfg(test)]
+macro_rules! register_remote_message {
+    ($actor:ty, $msg:ty) => {
+        impl $crate::remote::RemoteMessage<$msg> for $actor {
+            const REMOTE_ID: &'static str = concat!(
+                module_path!(),
+                "::",
+                stringify!($actor),
+                "::",
+                stringify!($msg)
+            );
+        }
+
+        const _: () = {
+            use $crate::remote::_internal::*;
+
+            #[linkme::distributed_slice(REMOTE_MESSAGES)]
+            static REG: (RemoteMessageRegistrationID<'static>, RemoteMessageFns) = (
+                RemoteMessageRegistrationID {
+                    actor_remote_id: <$actor as $crate::remote::RemoteActor>::REMOTE_ID,
+                    message_remote_id: <$actor as $crate::remote::RemoteMessage<$msg>>::REMOTE_ID,
+                },
+                RemoteMessageFns {
+                    version: <$actor as $crate::remote::RemoteMessage<$msg>>::VERSION,
+                    upcast_versions:
+                        <$actor as $crate::remote::RemoteMessage<$msg>>::UPCAST_VERSIONS,
+                    ask: |actor_id, sender, msg, codec, version, mailbox_timeout, reply_timeout| {
+                        Box::pin(ask::<$actor, $msg>(
+                            actor_id,
+                            sender,
+                            msg,
+                            codec,
+                            version,
+                            mailbox_timeout,
+                            reply_timeout,
+                        ))
+                    },
+                    try_ask: |actor_id, sender, msg, codec, version, reply_timeout| {
+                        Box::pin(try_ask::<$actor, $msg>(
+                            actor_id,
+                            sender,
+                            msg,
+                            codec,
+                            version,
+                            reply_timeout,
+                        ))
+                    },
+                    tell: |actor_id, sender, msg, codec, version, mailbox_timeout| {
+                        Box::pin(tell::<$actor, $msg>(
+                            actor_id,
+                            sender,
+                            msg,
+                            codec,
+                            version,
+                            mailbox_timeout,
+                        ))
+                    },
+                    try_tell: |actor_id, sender, msg, codec, version| {
+                        Box::pin(try_tell::<$actor, $msg>(
+                            actor_id, sender, msg, codec, version,
+                        ))
+                    },
+                },
+            );
+        };
+    };
+}
+
+#[cfg(test)]
+pub(crate) use {register_remote_actor, register_remote_message};
+
+/// Actors shared by the cluster tests of the remote modules.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::{
+        convert::Infallible,
+        ops::ControlFlow,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use serde::{Deserialize, Serialize};
+    use tokio::{sync::mpsc, time};
+
+    use crate::{
+        Actor,
+        actor::{ActorId, ActorRef, WeakActorRef},
+        error::ActorStopReason,
+        message::{Context, Message},
+    };
+
+    pub(crate) struct Target;
+
+    impl Actor for Target {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Target);
+
+    pub(crate) struct Watcher {
+        pub(crate) link_died: Option<mpsc::UnboundedSender<ActorStopReason>>,
+    }
+
+    impl Actor for Watcher {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+
+        async fn on_link_died(
+            &mut self,
+            _actor_ref: WeakActorRef<Self>,
+            _id: ActorId,
+            reason: ActorStopReason,
+        ) -> Result<ControlFlow<ActorStopReason>, Self::Error> {
+            if let Some(tx) = &self.link_died {
+                let _ = tx.send(reason);
+            }
+            // The link is only removed once handled, so a slow handler would see any duplicate
+            // notification of the same link dying
+            time::sleep(Duration::from_millis(100)).await;
+            Ok(ControlFlow::Continue(()))
+        }
+    }
+
+    register_remote_actor!(Watcher);
+
+    pub(crate) struct Counter {
+        pub(crate) count: Arc<AtomicUsize>,
+    }
+
+    impl Actor for Counter {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Counter);
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct Inc;
+
+    impl Message<Inc> for Counter {
+        type Reply = ();
+
+        async fn handle(&mut self, _msg: Inc, _ctx: &mut Context<Self, Self::Reply>) {
+            self.count.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    register_remote_message!(Counter, Inc);
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct Spawned {
+        pub(crate) value: u32,
+    }
+
+    impl Actor for Spawned {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(Spawned, spawnable);
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct Get;
+
+    impl Message<Get> for Spawned {
+        type Reply = u32;
+
+        async fn handle(&mut self, _msg: Get, _ctx: &mut Context<Self, Self::Reply>) -> u32 {
+            self.value
+        }
+    }
+
+    register_remote_message!(Spawned, Get);
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct Stop;
+
+    impl Message<Stop> for Spawned {
+        type Reply = ();
+
+        async fn handle(&mut self, _msg: Stop, ctx: &mut Context<Self, Self::Reply>) {
+            ctx.stop();
+        }
+    }
+
+    register_remote_message!(Spawned, Stop);
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct NotSpawnable;
+
+    impl Actor for NotSpawnable {
+        type Args = Self;
+        type Error = Infallible;
+
+        async fn on_start(
+            state: Self::Args,
+            _actor_ref: ActorRef<Self>,
+        ) -> Result<Self, Self::Error> {
+            Ok(state)
+        }
+    }
+
+    register_remote_actor!(NotSpawnable);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{actor::Spawn, error::ActorStopReason};
+
+    use super::{
+        fixtures::{Target, Watcher},
+        *,
+    };
+
+    #[tokio::test]
+    async fn registry_converges_across_nodes() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(3).await?;
+
+        let first = Target::spawn(Target);
+        let second = Target::spawn(Target);
+        cluster.node(0).register(&first, "target").await?;
+        cluster.node(1).register(&second, "target").await?;
+
+        for node in 0..3 {
+            let found = cluster.wait_for_lookup::<Target>(node, "target", 2).await?;
+            let ids: HashSet<_> = found.iter().map(|actor_ref| actor_ref.id()).collect();
+            assert_eq!(
+                ids,
+                HashSet::from([
+                    cluster.node(0).actor_id(&first),
+                    cluster.node(1).actor_id(&second)
+                ])
+            );
+        }
+
+        cluster.node(0).swarm().unregister("target").await;
+        let found = cluster.wait_for_lookup::<Target>(2, "target", 1).await?;
+        assert_eq!(found[0].id(), cluster.node(1).actor_id(&second));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn partition_signals_peer_disconnected() -> Result<(), Box<dyn error::Error>> {
+        let cluster = Cluster::new(3).await?;
+
+        let target = Target::spawn(Target);
+        cluster.node(0).register(&target, "target").await?;
+        let remote_target = cluster
+            .wait_for_lookup::<Target>(1, "target", 1)
+            .await?
+            .remove(0);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = Watcher::spawn(Watcher {
+            link_died: Some(tx),
+        });
+        watcher
+            .register_in(cluster.node(1).swarm(), "watcher")
+            .await?;
+        watcher.link_remote(&remote_target).await?;
+
+        cluster.partition(&[0], &[1, 2]).await?;
+        let reason = time::timeout(Duration::from_secs(5), rx.recv()).await?;
+        assert!(matches!(reason, Some(ActorStopReason::PeerDisconnected)));
+
+        // Partitioned nodes can't reconnect until the partition is healed
+        let (node_0, node_1) = (cluster.node(0), cluster.node(1));
+        node_0.send(NodeCommand::Dial(node_1.peer_id(), node_1.addr().clone()));
+        time::sleep(Duration::from_millis(200)).await;
+        assert!(!node_0.is_connected(&node_1.peer_id()));
+
+        cluster.heal().await?;
+        assert!(cluster.node(0).is_connected(&cluster.node(1).peer_id()));
+
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
     Actor, Reply,
     actor::{ActorRef, ReplyRecipient},
     error::{self, SendError},
-    mailbox::Signal,
+    mailbox::{MessageMetadata, Signal},
     message::Message,
     reply::{ReplyError, ReplySender},
 };
@@ -32,6 +32,7 @@ where
     msg: M,
     mailbox_timeout: Tm,
     reply_timeout: Tr,
+    metadata: MessageMetadata,
     #[cfg(all(debug_assertions, feature = "tracing"))]
     called_at: &'static std::panic::Location<'static>,
 }
@@ -57,11 +58,19 @@ where
             msg,
             mailbox_timeout: Tm::default(),
             reply_timeout: Tr::default(),
+            metadata: MessageMetadata::default(),
             #[cfg(all(debug_assertions, feature = "tracing"))]
             called_at,
         }
     }
 
+    /// Marks the message as received from a remote peer.
+    #[cfg(feature = "remote")]
+    pub(crate) fn remote_sender(mut self, sender: remote::RemoteSender) -> Self {
+        self.metadata.remote_sender = Some(Box::new(sender));
+        self
+    }
+
     /// Sets the timeout for waiting for the actors mailbox to have capacity.
     pub fn mailbox_timeout(
         self,
@@ -79,6 +88,7 @@ where
             msg: self.msg,
             mailbox_timeout: WithRequestTimeout(duration),
             reply_timeout: self.reply_timeout,
+            metadata: self.metadata,
             #[cfg(all(debug_assertions, feature = "tracing"))]
             called_at: self.called_at,
         }
@@ -98,6 +108,7 @@ where
             msg: self.msg,
             mailbox_timeout: self.mailbox_timeout,
             reply_timeout: WithRequestTimeout(duration),
+            metadata: self.metadata,
             #[cfg(all(debug_assertions, feature = "tracing"))]
             called_at: self.called_at,
         }
@@ -124,6 +135,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -187,6 +199,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -236,6 +249,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(sender.boxed()),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -272,6 +286,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(sender.boxed()),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -299,6 +314,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -355,6 +371,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -392,6 +409,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -417,6 +435,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(sender.boxed()),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -468,6 +487,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: Some(reply),
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -777,7 +797,12 @@ where
     }
 
     /// Enqueues the message into the remote actors mailbox, returning a pending reply which needs to be awaited.
-    pub fn enqueue(self) -> Result<RemotePendingReply<A::Reply>, rmp_serde::encode::Error>
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`](remote::codec::CodecError) if the message can't be encoded with
+    /// its codec.
+    pub fn enqueue(self) -> Result<RemotePendingReply<A::Reply>, remote::codec::CodecError>
     where
         M: serde::Serialize,
         Tm: Into<Option<Duration>>,
@@ -822,7 +847,12 @@ where
 
     /// Tries to enqueue the message into the actors mailbox without waiting for mailbox capacity,
     /// returning a pending reply which needs to be awaited.
-    pub fn try_enqueue(self) -> Result<RemotePendingReply<A::Reply>, rmp_serde::encode::Error>
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`](remote::codec::CodecError) if the message can't be encoded with
+    /// its codec.
+    pub fn try_enqueue(self) -> Result<RemotePendingReply<A::Reply>, remote::codec::CodecError>
     where
         M: serde::Serialize,
         Tr: Into<Option<Duration>>,
@@ -865,50 +895,19 @@ fn remote_ask_enqueue<'a, A, M>(
     mailbox_timeout: Option<Duration>,
     reply_timeout: Option<Duration>,
     immediate: bool,
-) -> Result<RemotePendingReply<A::Reply>, rmp_serde::encode::Error>
+) -> Result<RemotePendingReply<A::Reply>, remote::codec::CodecError>
 where
     A: Actor + Message<M> + remote::RemoteActor + remote::RemoteMessage<M>,
     M: serde::Serialize + Send + 'static,
     <A::Reply as Reply>::Ok: serde::de::DeserializeOwned,
     <A::Reply as Reply>::Error: serde::de::DeserializeOwned,
 {
-    use remote::*;
-    use std::borrow::Cow;
-
-    let actor_id = actor_ref.id();
-    let (reply_tx, reply_rx) = oneshot::channel();
-    actor_ref.send_to_swarm(remote::SwarmCommand::Ask {
-        actor_id,
-        actor_remote_id: Cow::Borrowed(<A as remote::RemoteActor>::REMOTE_ID),
-        message_remote_id: Cow::Borrowed(<A as remote::RemoteMessage<M>>::REMOTE_ID),
-        payload: rmp_serde::to_vec_named(msg)?,
+    let fut = remote::MessageTarget::new::<A, M>(actor_ref).enqueue_ask(
+        msg,
         mailbox_timeout,
         reply_timeout,
         immediate,
-        reply: reply_tx,
-    });
-
-    let fut = async move {
-        match reply_rx.await.unwrap() {
-            messaging::SwarmResponse::Ask(res) => match res {
-                Ok(payload) => Ok(rmp_serde::decode::from_slice(&payload)
-                    .map_err(|err| error::RemoteSendError::DeserializeMessage(err.to_string()))?),
-                Err(err) => Err(err
-                    .map_err(|err| match rmp_serde::decode::from_slice(&err) {
-                        Ok(err) => error::RemoteSendError::HandlerError(err),
-                        Err(err) => {
-                            error::RemoteSendError::DeserializeHandlerError(err.to_string())
-                        }
-                    })
-                    .flatten()),
-            },
-            messaging::SwarmResponse::OutboundFailure(err) => {
-                Err(err
-                    .map_err(|_| unreachable!("outbound failure doesn't contain handler errors")))
-            }
-            _ => panic!("unexpected response"),
-        }
-    };
+    )?;
 
     Ok(RemotePendingReply { fut: Box::pin(fut) })
 }
@@ -927,39 +926,9 @@ where
     <A::Reply as Reply>::Ok: serde::de::DeserializeOwned,
     <A::Reply as Reply>::Error: serde::de::DeserializeOwned,
 {
-    use remote::*;
-    use std::borrow::Cow;
-
-    let actor_id = actor_ref.id();
-    let (reply_tx, reply_rx) = oneshot::channel();
-    actor_ref.send_to_swarm(remote::SwarmCommand::Ask {
-        actor_id,
-        actor_remote_id: Cow::Borrowed(<A as remote::RemoteActor>::REMOTE_ID),
-        message_remote_id: Cow::Borrowed(<A as remote::RemoteMessage<M>>::REMOTE_ID),
-        payload: rmp_serde::to_vec_named(msg)
-            .map_err(|err| error::RemoteSendError::SerializeMessage(err.to_string()))?,
-        mailbox_timeout,
-        reply_timeout,
-        immediate,
-        reply: reply_tx,
-    });
-
-    match reply_rx.await.unwrap() {
-        messaging::SwarmResponse::Ask(res) => match res {
-            Ok(payload) => Ok(rmp_serde::decode::from_slice(&payload)
-                .map_err(|err| error::RemoteSendError::DeserializeMessage(err.to_string()))?),
-            Err(err) => Err(err
-                .map_err(|err| match rmp_serde::decode::from_slice(&err) {
-                    Ok(err) => error::RemoteSendError::HandlerError(err),
-                    Err(err) => error::RemoteSendError::DeserializeHandlerError(err.to_string()),
-                })
-                .flatten()),
-        },
-        messaging::SwarmResponse::OutboundFailure(err) => {
-            Err(err.map_err(|_| unreachable!("outbound failure doesn't contain handler errors")))
-        }
-        _ => panic!("unexpected response"),
-    }
+    remote::MessageTarget::new::<A, M>(actor_ref)
+        .ask(msg, mailbox_timeout, reply_timeout, immediate)
+        .await
 }
 
 #[cfg(all(debug_assertions, feature = "tracing"))]
@@ -6,7 +6,7 @@ use crate::{
     Actor,
     actor::{ActorRef, Recipient, ReplyRecipient},
     error::SendError,
-    mailbox::Signal,
+    mailbox::{MessageMetadata, Signal},
     message::Message,
     reply::ReplyError,
 };
@@ -26,6 +26,7 @@ where
     actor_ref: &'a ActorRef<A>,
     msg: M,
     mailbox_timeout: Tm,
+    metadata: MessageMetadata,
     #[cfg(all(debug_assertions, feature = "tracing"))]
     called_at: &'static std::panic::Location<'static>,
 }
@@ -49,11 +50,19 @@ where
             actor_ref,
             msg,
             mailbox_timeout: Tm::default(),
+            metadata: MessageMetadata::default(),
             #[cfg(all(debug_assertions, feature = "tracing"))]
             called_at,
         }
     }
 
+    /// Marks the message as received from a remote peer.
+    #[cfg(feature = "remote")]
+    pub(crate) fn remote_sender(mut self, sender: crate::remote::RemoteSender) -> Self {
+        self.metadata.remote_sender = Some(Box::new(sender));
+        self
+    }
+
     /// Sets the timeout for waiting for the actors mailbox to have capacity.
     pub fn mailbox_timeout(self, duration: Duration) -> TellRequest<'a, A, M, WithRequestTimeout> {
         self.mailbox_timeout_opt(Some(duration))
@@ -67,6 +76,7 @@ where
             actor_ref: self.actor_ref,
             msg: self.msg,
             mailbox_timeout: WithRequestTimeout(duration),
+            metadata: self.metadata,
             #[cfg(all(debug_assertions, feature = "tracing"))]
             called_at: self.called_at,
         }
@@ -82,6 +92,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: None,
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -113,6 +124,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: None,
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         Ok(self.actor_ref.mailbox_sender().try_send(signal)?)
@@ -125,6 +137,7 @@ where
             actor_ref: self.actor_ref.clone(),
             reply: None,
             sent_within_actor: self.actor_ref.is_current(),
+            metadata: self.metadata,
         };
 
         let tx = self.actor_ref.mailbox_sender();
@@ -368,17 +381,16 @@ pub use remote::RemoteTellRequest;
 
 #[cfg(feature = "remote")]
 mod remote {
-    use std::{borrow::Cow, time::Duration};
+    use std::time::Duration;
 
     use serde::Serialize;
-    use tokio::sync::oneshot;
 
     use crate::{
         Actor,
         actor::RemoteActorRef,
         error::RemoteSendError,
         message::Message,
-        remote::{RemoteActor, RemoteMessage, SwarmCommand, messaging},
+        remote::{MessageTarget, RemoteActor, RemoteMessage},
         request::{WithRequestTimeout, WithoutRequestTimeout},
     };
 
@@ -456,12 +468,39 @@ mod remote {
     {
         /// Sends the message fire-and-forget style (fast, no delivery confirmation).
         pub fn send(self) -> Result<(), RemoteSendError> {
-            remote_tell(self.actor_ref, self.msg, self.mailbox_timeout.into(), false)
+            MessageTarget::new::<A, M>(self.actor_ref).tell(
+                self.msg,
+                self.mailbox_timeout.into(),
+                false,
+            )
         }
 
         /// Sends the message and waits for delivery acknowledgment (reliable, slower).
         pub async fn send_ack(self) -> Result<(), RemoteSendError> {
-            remote_tell_ack(self.actor_ref, self.msg, self.mailbox_timeout.into(), false).await
+            MessageTarget::new::<A, M>(self.actor_ref)
+                .tell_ack(self.msg, self.mailbox_timeout.into(), false)
+                .await
+        }
+
+        /// Sends the message with at-least-once delivery, waiting for it to be acknowledged.
+        ///
+        /// The message is kept in an outbox and resent after failures such as timeouts and closed
+        /// connections, until the receiving peer acknowledges it. Receivers discard copies of
+        /// messages they've already delivered, so the actor handles the message once per sender
+        /// even if it's resent. Delivery continues in the background if the returned future is
+        /// dropped.
+        ///
+        /// An error is returned if the message can't be delivered by retrying, such as when the
+        /// actor is no longer running, or if the configured number of attempts is exhausted.
+        /// See [`messaging::Config::with_redelivery_backoff`] and
+        /// [`messaging::Config::with_max_delivery_attempts`].
+        ///
+        /// [`messaging::Config::with_redelivery_backoff`]: crate::remote::messaging::Config::with_redelivery_backoff
+        /// [`messaging::Config::with_max_delivery_attempts`]: crate::remote::messaging::Config::with_max_delivery_attempts
+        pub async fn send_reliable(self) -> Result<(), RemoteSendError> {
+            MessageTarget::new::<A, M>(self.actor_ref)
+                .tell_reliable(self.msg, self.mailbox_timeout.into())
+                .await
         }
     }
 
@@ -472,73 +511,18 @@ mod remote {
     {
         /// Tries to send the message fire-and-forget style, failing immediately if mailbox is full.
         pub async fn try_send(self) -> Result<(), RemoteSendError> {
-            remote_tell(self.actor_ref, self.msg, self.mailbox_timeout.into(), true)
+            MessageTarget::new::<A, M>(self.actor_ref).tell(
+                self.msg,
+                self.mailbox_timeout.into(),
+                true,
+            )
         }
 
         /// Tries to send the message with acknowledgment, failing immediately if mailbox is full.
         pub async fn try_send_ack(self) -> Result<(), RemoteSendError> {
-            remote_tell_ack(self.actor_ref, self.msg, self.mailbox_timeout.into(), true).await
-        }
-    }
-
-    fn remote_tell<A, M>(
-        actor_ref: &RemoteActorRef<A>,
-        msg: &M,
-        mailbox_timeout: Option<Duration>,
-        immediate: bool,
-    ) -> Result<(), RemoteSendError>
-    where
-        A: Actor + Message<M> + RemoteActor + RemoteMessage<M>,
-        M: Serialize + Send + 'static,
-    {
-        let actor_id = actor_ref.id();
-        actor_ref.send_to_swarm(SwarmCommand::Tell {
-            actor_id,
-            actor_remote_id: Cow::Borrowed(<A as RemoteActor>::REMOTE_ID),
-            message_remote_id: Cow::Borrowed(<A as RemoteMessage<M>>::REMOTE_ID),
-            payload: rmp_serde::to_vec_named(msg)
-                .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))?,
-            mailbox_timeout,
-            immediate,
-            reply: None,
-        });
-
-        Ok(())
-    }
-
-    async fn remote_tell_ack<A, M>(
-        actor_ref: &RemoteActorRef<A>,
-        msg: &M,
-        mailbox_timeout: Option<Duration>,
-        immediate: bool,
-    ) -> Result<(), RemoteSendError>
-    where
-        A: Actor + Message<M> + RemoteActor + RemoteMessage<M>,
-        M: Serialize + Send + 'static,
-    {
-        let actor_id = actor_ref.id();
-        let (reply_tx, reply_rx) = oneshot::channel();
-        actor_ref.send_to_swarm(SwarmCommand::Tell {
-            actor_id,
-            actor_remote_id: Cow::Borrowed(<A as RemoteActor>::REMOTE_ID),
-            message_remote_id: Cow::Borrowed(<A as RemoteMessage<M>>::REMOTE_ID),
-            payload: rmp_serde::to_vec_named(msg)
-                .map_err(|err| RemoteSendError::SerializeMessage(err.to_string()))?,
-            mailbox_timeout,
-            immediate,
-            reply: Some(reply_tx),
-        });
-
-        match reply_rx.await.unwrap() {
-            messaging::SwarmResponse::Tell(res) => match res {
-                Ok(()) => Ok(()),
-                Err(err) => Err(err),
-            },
-            messaging::SwarmResponse::OutboundFailure(err) => {
-                Err(err
-                    .map_err(|_| unreachable!("outbound failure doesn't contain handler errors")))
-            }
-            _ => panic!("unexpected response"),
+            MessageTarget::new::<A, M>(self.actor_ref)
+                .tell_ack(self.msg, self.mailbox_timeout.into(), true)
+                .await
         }
     }
 }